@@ -0,0 +1,76 @@
+//! Reading REPL input, with line editing where the terminal supports it.
+
+pub enum Line {
+    Text(String),
+    /// The user discarded the line being typed.
+    Interrupted,
+    Eof,
+}
+
+#[cfg(not(target_os = "wasi"))]
+mod imp {
+    use std::io;
+
+    use rustyline::error::ReadlineError;
+    use rustyline::DefaultEditor;
+
+    use super::Line;
+
+    pub struct Reader {
+        editor: DefaultEditor,
+    }
+
+    impl Reader {
+        pub fn new() -> io::Result<Reader> {
+            let editor = DefaultEditor::new().map_err(to_io)?;
+            Ok(Reader { editor })
+        }
+
+        pub fn read(&mut self, prompt: &str) -> io::Result<Line> {
+            match self.editor.readline(prompt) {
+                Ok(line) => {
+                    self.editor.add_history_entry(line.as_str()).map_err(to_io)?;
+                    Ok(Line::Text(line))
+                }
+                Err(ReadlineError::Interrupted) => Ok(Line::Interrupted),
+                Err(ReadlineError::Eof) => Ok(Line::Eof),
+                Err(e) => Err(to_io(e)),
+            }
+        }
+    }
+
+    fn to_io(e: ReadlineError) -> io::Error {
+        match e {
+            ReadlineError::Io(e) => e,
+            e => io::Error::other(e),
+        }
+    }
+}
+
+/// WASI hosts hand us plain stdio, so lines are read without editing.
+#[cfg(target_os = "wasi")]
+mod imp {
+    use std::io::{self, Write};
+
+    use super::Line;
+
+    pub struct Reader;
+
+    impl Reader {
+        pub fn new() -> io::Result<Reader> {
+            Ok(Reader)
+        }
+
+        pub fn read(&mut self, prompt: &str) -> io::Result<Line> {
+            print!("{}", prompt);
+            io::stdout().flush()?;
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line)? == 0 {
+                return Ok(Line::Eof);
+            }
+            Ok(Line::Text(line.trim_end_matches(&['\n', '\r'][..]).to_string()))
+        }
+    }
+}
+
+pub use self::imp::Reader;
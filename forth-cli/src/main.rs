@@ -0,0 +1,170 @@
+//! Interactive REPL for the Forth interpreter.
+//!
+//! Usage: `forth-cli [FILE]...`
+//!
+//! Each FILE is evaluated in order before the prompt appears. At the prompt,
+//! `include FILE` loads another file, and `undo` reverts what the last line
+//! or file did. A colon definition may span several lines; the prompt
+//! changes while one is open, and Ctrl-C abandons it.
+//!
+//! EMIT, TYPE and friends write to stdout, and KEY and ACCEPT read from
+//! stdin, while LOG and LOG-TYPE write to stderr. MS sleeps, and TIME&DATE
+//! tells the time in UTC. Words AFTER and EVERY schedule run before the
+//! next prompt once they are due, and PAUSE lets the tasks TASK started
+//! take a turn. BLOCK and friends keep blocks in `blocks.fb` in the
+//! current directory. The same binary builds for `wasm32-wasip1` and runs
+//! under a WASI runtime such as wasmtime, without line editing.
+
+extern crate forth_core;
+#[cfg(not(target_os = "wasi"))]
+extern crate rustyline;
+
+mod line;
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+
+use forth_core::{Error, FileBlocks, Forth, ForthResult, Span, StdIo, SystemClock};
+use line::{Line, Reader};
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = "| ";
+
+/// Prints `result`, pointing at the failing word within `source` if any.
+/// `origin` names where `source` came from in error messages.
+fn report(forth: &Forth, result: ForthResult, source: &str, origin: &str) -> bool {
+    for warning in forth.warnings() {
+        eprintln!("{}: warning: {}", origin, warning);
+    }
+    let error = match result {
+        Ok(()) => return true,
+        Err(e) => e,
+    };
+    match forth.error_location() {
+        Some(location) => {
+            let span = Span { start: location.offset, end: location.offset + location.word.len() };
+            let (line, column) = span.line_column(source);
+            eprintln!("{}:{}:{}: {}", origin, line, column, error);
+        }
+        None => eprintln!("{}: {}", origin, error),
+    }
+    for frame in forth.error_trace() {
+        match frame.defined_at {
+            Some((line, column)) => eprintln!("  in {} at {} (defined at {}:{})", frame.word, frame.token, line, column),
+            None => eprintln!("  in {} at {}", frame.word, frame.token),
+        }
+    }
+    false
+}
+
+/// Carries on an evaluation that ended in `result` for as long as PAUSE
+/// asks for the tasks to take a turn.
+fn run(forth: &mut Forth, mut result: ForthResult) -> ForthResult {
+    while result == Err(Error::PauseRequested) && forth.is_paused() {
+        forth.run_tasks(None)?;
+        result = forth.resume();
+    }
+    result
+}
+
+fn load(forth: &mut Forth, path: &str) -> bool {
+    match fs::read_to_string(path) {
+        Ok(source) => {
+            let result = forth.eval(&source);
+            let mut result = run(forth, result);
+            if result.is_ok() && forth.unfinished_definition().is_some() {
+                forth.cancel_definition();
+                result = Err(Error::UnterminatedDefinition);
+            }
+            report(forth, result, &source, path)
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            false
+        }
+    }
+}
+
+fn print_stack(forth: &Forth) {
+    let stack = forth.stack_slice();
+    print!(" ok");
+    if !stack.is_empty() {
+        print!(" <{}>", stack.len());
+        for value in stack {
+            print!(" {}", value);
+        }
+    }
+    println!();
+}
+
+fn repl(forth: &mut Forth) -> io::Result<()> {
+    let mut reader = Reader::new()?;
+    loop {
+        if let Err(e) = forth.run_timers(None) {
+            eprintln!("timer: {}", e);
+        }
+        let open = forth.unfinished_definition().is_some();
+        let prompt = if open { CONTINUATION_PROMPT } else { PROMPT };
+        let line = match reader.read(prompt)? {
+            Line::Text(line) => line,
+            Line::Interrupted => {
+                forth.cancel_definition();
+                continue;
+            }
+            Line::Eof => return Ok(()),
+        };
+
+        if !open {
+            let mut words = line.split_whitespace();
+            match words.next().map(str::to_lowercase).as_deref() {
+                Some("include") => {
+                    for path in words {
+                        load(forth, path);
+                    }
+                    print_stack(forth);
+                    continue;
+                }
+                Some("undo") if words.next().is_none() => {
+                    if !forth.undo() {
+                        eprintln!("nothing to undo");
+                    }
+                    print_stack(forth);
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        let result = forth.eval(&line);
+        let result = run(forth, result);
+        io::stdout().flush()?;
+        if report(forth, result, &line, "input") && forth.unfinished_definition().is_none() {
+            print_stack(forth);
+        }
+    }
+}
+
+fn main() {
+    let mut forth = Forth::builder().undo(true).build();
+    forth.set_io(StdIo);
+    forth.set_source_provider(|name: &str| fs::read_to_string(name).ok());
+    forth.set_clock(SystemClock::new());
+    forth.set_block_storage(FileBlocks::new("blocks.fb"));
+    forth.set_log_sink(|message, word| match word {
+        Some(word) => eprintln!("[{}] {}", word, message),
+        None => eprintln!("{}", message),
+    });
+
+    for path in env::args().skip(1) {
+        if !load(&mut forth, &path) {
+            process::exit(1);
+        }
+    }
+
+    if let Err(e) = repl(&mut forth) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
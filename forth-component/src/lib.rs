@@ -0,0 +1,95 @@
+//! The interpreter as a WebAssembly component, for hosts such as wasmtime,
+//! or other languages, that don't run the JavaScript glue `forth-wasm`
+//! needs. `wit/forth.wit` describes its interface.
+//!
+//! Build it with `cargo build -p forth-component --target wasm32-wasip2
+//! --release`, which leaves the component in
+//! `target/wasm32-wasip2/release/forth_component.wasm`.
+
+extern crate forth_core;
+extern crate wit_bindgen;
+
+use std::cell::RefCell;
+
+use forth_core::{Error, Forth, ForthResult, Value};
+
+wit_bindgen::generate!({ world: "forth" });
+
+use exports::disasm::forth::interpreter::{EvalError, EvalResult, Guest, GuestForth};
+
+struct Component;
+
+impl Guest for Component {
+    type Forth = Interpreter;
+}
+
+/// An interpreter, exported as the `forth` resource.
+struct Interpreter {
+    forth: RefCell<Forth>,
+}
+
+/// The name of `e`'s variant, as its `Debug` output starts.
+fn error_kind(e: &Error) -> String {
+    let debug = format!("{:?}", e);
+    debug.split('(').next().unwrap_or_default().to_owned()
+}
+
+impl Interpreter {
+    fn outcome(&self, result: ForthResult) -> EvalResult {
+        let forth = self.forth.borrow();
+        let error = result.err().map(|e| {
+            let location = forth.error_location();
+            EvalError {
+                kind: error_kind(&e),
+                message: e.to_string(),
+                word: location.map(|l| l.word.clone()),
+                offset: location.map(|l| l.offset as u32),
+            }
+        });
+        EvalResult { stack: self.stack(), output: forth.output().to_owned(), error }
+    }
+}
+
+impl GuestForth for Interpreter {
+    fn new() -> Self {
+        Interpreter { forth: RefCell::new(Forth::new()) }
+    }
+
+    fn eval(&self, code: String) -> EvalResult {
+        let result = self.forth.borrow_mut().eval(&code);
+        self.outcome(result)
+    }
+
+    fn resume(&self) -> EvalResult {
+        let result = self.forth.borrow_mut().resume();
+        self.outcome(result)
+    }
+
+    fn is_paused(&self) -> bool {
+        self.forth.borrow().is_paused()
+    }
+
+    fn set_fuel(&self, fuel: Option<u64>) {
+        self.forth.borrow_mut().set_fuel(fuel);
+    }
+
+    fn stack(&self) -> Vec<i64> {
+        self.forth.borrow().stack().into_iter().map(i64::from).collect()
+    }
+
+    fn push(&self, value: i64) {
+        self.forth.borrow_mut().push(value as Value);
+    }
+
+    fn words(&self) -> Vec<String> {
+        self.forth.borrow().words().into_iter().map(|word| word.name).collect()
+    }
+
+    fn reset(&self) {
+        *self.forth.borrow_mut() = Forth::new();
+    }
+}
+
+// Exporting defines symbols only a wasm linker accepts.
+#[cfg(target_arch = "wasm32")]
+export!(Component);
@@ -0,0 +1,67 @@
+//! Times define-heavy and loop-heavy evaluations, and counts the heap
+//! allocations each makes, which are slow under small allocators such as
+//! `wee_alloc`. Run with `cargo bench -p forth-core --bench allocations`.
+
+extern crate forth_core;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use forth_core::Forth;
+
+const EVALS: usize = 1_000;
+
+/// The system allocator, counting allocations and reallocations.
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Evaluates each of `inputs` in `f`, printing the time and allocations
+/// per evaluation.
+fn measure(label: &str, f: &mut Forth, inputs: &[String]) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for input in inputs {
+        f.eval(input).unwrap();
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    println!("{}: {:?} and {:.1} allocations per eval", label, elapsed / inputs.len() as u32,
+        allocations as f64 / inputs.len() as f64);
+}
+
+fn main() {
+    // A word of its own per evaluation, as when pasting a long program.
+    let mut f = Forth::new();
+    let inputs: Vec<String> = (0..EVALS).map(|i| format!(": W{} DUP DUP * * 1+ {} + ;", i, i)).collect();
+    measure("define", &mut f, &inputs);
+
+    let inputs: Vec<String> = (0..EVALS).map(|i| format!("{} CONSTANT C{} VARIABLE V{}", i, i, i)).collect();
+    measure("define constants and variables", &mut f, &inputs);
+
+    // Calls nested eight deep, 8^4 of them per evaluation.
+    f.eval(": L1 1+ 1+ 1+ 1+ 1+ 1+ 1+ 1+ ; : L2 L1 L1 L1 L1 L1 L1 L1 L1 ;").unwrap();
+    f.eval(": L3 L2 L2 L2 L2 L2 L2 L2 L2 ; : L4 L3 L3 L3 L3 L3 L3 L3 L3 ;").unwrap();
+    let inputs = vec![String::from("0 L4 DROP"); EVALS];
+    measure("loop", &mut f, &inputs);
+}
@@ -0,0 +1,53 @@
+//! Times calls of small compiled words, called and inlined, evaluation of
+//! source that uses them, and forks of a dictionary full of them. Run with
+//! `cargo bench -p forth-core`.
+
+extern crate forth_core;
+
+use std::time::Instant;
+
+use forth_core::Forth;
+
+const CALLS: usize = 100_000;
+const EVALS: usize = 10_000;
+const FORKS: usize = 1_000;
+
+fn main() {
+    let mut f = Forth::new();
+    f.eval(": SQ DUP * ; : QUAD SQ SQ ; : W 2 QUAD DROP ;").unwrap();
+    // The host runs the word in a tight loop.
+    let program = f.compile_program("W").unwrap();
+    let start = Instant::now();
+    for _ in 0..CALLS {
+        f.run(&program).unwrap();
+    }
+    let elapsed = start.elapsed();
+    println!("call W: {:?} per call", elapsed / CALLS as u32);
+
+    let mut inlined = Forth::builder().inline_words(8).build();
+    inlined.eval(": SQ DUP * ; : QUAD SQ SQ ; : W 2 QUAD DROP ;").unwrap();
+    let program = inlined.compile_program("W").unwrap();
+    let start = Instant::now();
+    for _ in 0..CALLS {
+        inlined.run(&program).unwrap();
+    }
+    let elapsed = start.elapsed();
+    println!("call W, inlined: {:?} per call", elapsed / CALLS as u32);
+
+    let start = Instant::now();
+    for _ in 0..EVALS {
+        f.eval("2 quad drop 3 sq dup + drop w").unwrap();
+    }
+    let elapsed = start.elapsed();
+    println!("eval: {:?} per eval", elapsed / EVALS as u32);
+
+    for i in 0..200 {
+        f.eval(&format!(": W{} W W W W W W W W W W W W W W W W ;", i)).unwrap();
+    }
+    let start = Instant::now();
+    for _ in 0..FORKS {
+        f.fork();
+    }
+    let elapsed = start.elapsed();
+    println!("fork: {:?} per fork", elapsed / FORKS as u32);
+}
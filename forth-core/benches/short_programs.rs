@@ -0,0 +1,46 @@
+//! Times the short programs typical of a REPL or a playground, where the
+//! cost is mostly in setting up rather than running: creating an
+//! interpreter, a first evaluation that fills its stacks, defining a word
+//! and tokenizing a line. Run with `cargo bench -p forth-core --bench
+//! short_programs`.
+
+#[macro_use]
+extern crate criterion;
+extern crate forth_core;
+
+use criterion::{black_box, Criterion};
+
+use forth_core::Forth;
+
+const PROGRAM: &str = "1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 + + + + + + + + + + + + + + + .";
+
+fn new(c: &mut Criterion) {
+    c.bench_function("new", |b| b.iter(Forth::new));
+}
+
+fn first_eval(c: &mut Criterion) {
+    c.bench_function("first eval", |b| {
+        b.iter_with_setup(Forth::new, |mut f| {
+            f.eval(black_box(PROGRAM)).unwrap();
+            f
+        })
+    });
+}
+
+fn define(c: &mut Criterion) {
+    let f = Forth::new();
+    c.bench_function("define", |b| {
+        b.iter_with_setup(|| f.fork(), |mut f| {
+            f.eval(black_box(": CUBE DUP DUP * * ;")).unwrap();
+            f
+        })
+    });
+}
+
+fn tokenize(c: &mut Criterion) {
+    let f = Forth::new();
+    c.bench_function("tokenize", |b| b.iter(|| f.tokenize(black_box(PROGRAM))));
+}
+
+criterion_group!(benches, new, first_eval, define, tokenize);
+criterion_main!(benches);
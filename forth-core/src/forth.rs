@@ -0,0 +1,5130 @@
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::collections::VecDeque;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::convert::TryFrom;
+use core::mem;
+use core::ops::{Index, Range};
+use core::slice;
+use core::str;
+
+use smallvec::SmallVec;
+
+use heap::Heap;
+
+mod arena;
+mod blocks;
+mod channels;
+mod check;
+mod clock;
+mod coverage;
+mod debug;
+mod disassemble;
+mod effects;
+mod events;
+mod execution;
+mod format;
+mod frames;
+mod gc;
+mod help;
+mod hooks;
+mod image;
+mod include;
+mod intern;
+mod io;
+mod jit;
+mod log;
+mod messages;
+mod modules;
+mod optimize;
+mod profile;
+mod protect;
+mod quotations;
+mod random;
+mod recognizers;
+mod recording;
+mod recover;
+mod stats;
+mod store;
+mod strings;
+mod structures;
+mod tasks;
+mod terminal;
+mod tester;
+mod timers;
+mod tokenize;
+mod trace;
+#[cfg(feature = "typed")]
+mod typed;
+mod xchar;
+#[cfg(feature = "serde")]
+mod snapshot;
+
+pub use self::blocks::{BlockStorage, MemoryBlocks, BLOCK_SIZE};
+#[cfg(feature = "std")]
+pub use self::blocks::FileBlocks;
+pub use self::channels::{Channels, MemoryChannels};
+pub use self::check::Diagnostic;
+pub use self::clock::{Clock, TimeAndDate};
+#[cfg(feature = "std")]
+pub use self::clock::SystemClock;
+pub use self::coverage::WordCoverage;
+pub use self::debug::{CallFrame, ErrorFrame, WatchHit, Watchpoint};
+pub use self::effects::StackEffect;
+pub use self::events::Key;
+pub use self::execution::{Execution, Step};
+pub use self::format::{Case, FormatOptions};
+pub use self::gc::GcStats;
+pub use self::hooks::ExecutionHook;
+pub use self::include::SourceProvider;
+pub use self::io::{Io, MemoryIo};
+#[cfg(feature = "std")]
+pub use self::io::StdIo;
+pub use self::jit::{CompiledWord, Instruction, WordCompiler};
+pub use self::messages::{Language, Messages};
+pub use self::profile::WordProfile;
+pub use self::recognizers::Recognizer;
+pub use self::recording::{MemoryWrite, RecordedStep, Recording};
+pub use self::recover::Recovery;
+pub use self::stats::{DefinedWord, EvalStats};
+pub use self::terminal::{GridCell, TerminalGrid};
+pub use self::tokenize::{SourceToken, TokenKind, Tokens};
+pub use self::trace::{Trace, TraceOptions, TraceStep};
+#[cfg(feature = "typed")]
+pub use self::typed::{Float, TypedValue};
+use self::arena::{Arena, Slot};
+use self::blocks::{
+    do_block, do_buffer, do_empty_buffers, do_flush, do_list, do_load, do_save_buffers, do_update, BlockBuffer,
+};
+use self::channels::{
+    do_receive, do_receive_question, do_send, do_string_receive, do_string_receive_question, do_string_send,
+};
+use self::clock::{do_counter, do_elapsed, do_ms, do_ticks, do_time_and_date};
+use self::coverage::Coverage;
+use self::debug::{Debugger, Watch};
+use self::events::{
+    define_key_constants, do_button_question, do_ekey, do_ekey_question, do_ekey_to_char, do_mouse_x, do_mouse_y,
+    is_key_event, Pointer,
+};
+use self::frames::{do_every_frame, do_stop_frames};
+use self::help::do_help;
+use self::hooks::Hooks;
+use self::include::{do_include, do_included, do_require, do_required};
+use self::intern::{Names, Symbol};
+use self::log::{do_log, do_log_type, LogSink};
+use self::modules::{do_end_module, do_module, do_private, do_public, OpenModule};
+use self::profile::Profiler;
+use self::quotations::{do_dip, do_end_quotation, do_start_quotation, do_times, Enclosing};
+use self::random::{do_random, do_seed, Random};
+use self::recognizers::{do_add_recognizer, RecognizerEntry};
+use self::recording::Recorder;
+use self::stats::Counters;
+use self::store::{do_load_dict, do_save_dict, DictionaryStore};
+use self::strings::{
+    do_number_to_string, do_string_depth, do_string_dot, do_string_drop, do_string_dup, do_string_equals,
+    do_string_fetch, do_string_len, do_string_plus, do_string_quote, do_string_search, do_string_sub,
+    do_string_swap, do_string_to_number, do_to_string, Handle,
+};
+use self::structures::{do_begin_structure, do_cfield, do_end_structure, do_field, do_plus_field};
+use self::tasks::{do_pause, do_stop_task, do_task, Tasks};
+use self::terminal::{do_at_xy, do_bold, do_colour, do_normal, do_page};
+use self::tester::{do_arrow, do_t_close, do_t_open, Tester};
+use self::timers::{do_after, do_cancel, do_every, Timers};
+use self::tokenize::Lexed;
+use self::trace::Tracer;
+#[cfg(feature = "typed")]
+use self::typed::{do_bits_to_f, do_f_to_s, do_s_to_f, do_string_literal, do_to_string_value, parse_float, Types};
+use self::xchar::{
+    do_bracket_char, do_char, do_x_size, do_xc_fetch_plus, do_xc_size, do_xc_store_plus_question, do_xchar_plus,
+    to_char,
+};
+#[cfg(feature = "serde")]
+pub use self::snapshot::Snapshot;
+
+type WordExecutor = dyn Fn(&mut Forth, usize) -> ForthResult;
+
+type NativeWord = dyn Fn(&mut Stack) -> ForthResult;
+
+type OutputSink = dyn FnMut(&str) -> ForthResult;
+
+type InputSource = dyn FnMut() -> Result<Option<String>, Error>;
+
+type DataSpaceObserver = dyn FnMut(&[u8]);
+
+/// Map for name tables: hashed with `std`, ordered without it.
+#[cfg(feature = "std")]
+type Map<K, V> = std::collections::HashMap<K, V>;
+#[cfg(not(feature = "std"))]
+type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+
+/// The data stack, as seen by native words.
+pub type Stack = Vec<Value>;
+
+/// Identifier of the wordlist every built-in word lives in, save for the
+/// extensions in a standard interpreter.
+pub const FORTH_WORDLIST: Value = 0;
+/// Wordlist, outside the search order, that a standard interpreter keeps
+/// the built-in words outside the standard's word sets in.
+const EXTENSIONS_WORDLIST: usize = 1;
+/// The built-in words outside the standard's word sets.
+const EXTENSIONS: [&str; 36] = [
+    "MOUSE-X", "MOUSE-Y", "BUTTON?", "SAVE-DICT", "LOAD-DICT", "TICKS", "COUNTER", "ELAPSED", "RANDOM", "SEED",
+    "LOG", "LOG-TYPE", "EVERY-FRAME", "STOP-FRAMES", "AFTER", "EVERY", "CANCEL", "ALIAS", "HELP", "TASK", "PAUSE",
+    "STOP-TASK", "SEND", "RECV?", "RECV", "MODULE", "PRIVATE", "PUBLIC", "END-MODULE", "+RECOGNIZER", "TIMES",
+    "DIP", "FG", "BG", "BOLD", "NORMAL",
+];
+
+/// Common words defined in Forth rather than Rust, such as NIP and `0=`.
+const STANDARD_PRELUDE: &str = include_str!("forth/prelude.fs");
+
+/// Default limit on words run from within other words, as by EVALUATE.
+const CALL_DEPTH: usize = 128;
+/// Room made on the stacks up front, enough that short programs never
+/// have them grow.
+const STACK_CAPACITY: usize = 64;
+const RETURN_STACK_CAPACITY: usize = 32;
+/// Room made after the data space and heap for the terminal input buffer.
+const TIB_CAPACITY: usize = 1024;
+/// Widest field .R and U.R pad a number to.
+const MAX_FIELD_WIDTH: usize = 64 * 1024;
+/// Size of each of the two transient buffers, between the heap and the
+/// terminal input buffer, that S" and S\" leave their strings in when
+/// interpreting, taking turns.
+const STRING_BUFFER_SIZE: usize = 512;
+/// Default size of the data space in bytes.
+const DATA_SPACE_SIZE: usize = 64 * 1024;
+/// Default limit on the data space an image or snapshot may bring.
+const MAX_DATA_SPACE_SIZE: usize = 16 * 1024 * 1024;
+/// Size of the region following the data space that ALLOCATE serves. The
+/// terminal input buffer follows the heap.
+const HEAP_SIZE: usize = 64 * 1024;
+/// I/O result codes reported by the memory-allocation words.
+const IOR_ALLOCATE: Value = -59;
+const IOR_FREE: Value = -60;
+const IOR_RESIZE: Value = -61;
+/// Address of the `>IN` cell.
+const TO_IN: usize = 0;
+/// Address of the `BASE` cell.
+const BASE: usize = CELL;
+/// Transient buffer `WORD` leaves its counted string in.
+const WORD_BUFFER: usize = 2 * CELL;
+const WORD_BUFFER_SIZE: usize = 256;
+/// Buffer pictured numeric output is built in, from the end backwards.
+const HOLD_BUFFER: usize = WORD_BUFFER + WORD_BUFFER_SIZE;
+const HOLD_BUFFER_SIZE: usize = 128;
+/// First data-space address available to the program.
+const DATA_START: usize = HOLD_BUFFER + HOLD_BUFFER_SIZE;
+
+#[derive(Clone)]
+struct Word {
+    name: Symbol,
+    /// Compiled body, kept in `bodies`.
+    data: Slot,
+    /// What running the word does; `do_exec`, running `data`, if `None`.
+    exec: Option<Rc<WordExecutor>>,
+    wordlist: usize,
+    immediate: bool,
+    /// Word this entry is a synonym for; lookups resolve to the target.
+    alias_of: Option<usize>,
+    /// Span in `source` of the name each instruction of `data` came from,
+    /// kept in `body_spans`.
+    spans: Slot,
+    /// Text of the colon definition that compiled `data`.
+    source: String,
+    /// Whether `exec` is Rust code, built in or supplied by the host, rather
+    /// than the interpreter running `data`.
+    native: bool,
+    /// One-based line and column at which the colon definition began in
+    /// the text it was compiled from, if known. Not kept by snapshots.
+    defined_at: Option<(usize, usize)>,
+    /// Whether a call of the word that ends a body keeps its caller's
+    /// frame rather than taking it over: EXIT needs the frame to leave it,
+    /// and RECV to run again from it should it wait.
+    keeps_frame: bool,
+}
+
+impl Word {
+    fn new(name: Symbol, exec: &'static WordExecutor) -> Self {
+        Self {
+            name,
+            data: Slot::default(),
+            exec: Some(Rc::new(exec)),
+            wordlist: FORTH_WORDLIST as usize,
+            immediate: false,
+            alias_of: None,
+            spans: Slot::default(),
+            source: String::new(),
+            native: true,
+            defined_at: None,
+            keeps_frame: false,
+        }
+    }
+
+    fn new_immediate(name: Symbol, exec: &'static WordExecutor) -> Self {
+        Self {
+            immediate: true,
+            ..Self::new(name, exec)
+        }
+    }
+
+    fn new_keeping_frame(name: Symbol, exec: &'static WordExecutor) -> Self {
+        Self {
+            keeps_frame: true,
+            ..Self::new(name, exec)
+        }
+    }
+
+    fn new_compiled(name: Symbol, data: Slot, wordlist: usize) -> Self {
+        Self {
+            name,
+            data,
+            exec: None,
+            wordlist,
+            immediate: false,
+            alias_of: None,
+            spans: Slot::default(),
+            source: String::new(),
+            native: false,
+            defined_at: None,
+            keeps_frame: false,
+        }
+    }
+
+    fn new_alias(name: Symbol, target: usize, wordlist: usize) -> Self {
+        Self {
+            alias_of: Some(target),
+            ..Self::new_compiled(name, Slot::default(), wordlist)
+        }
+    }
+}
+
+/// A dictionary entry as kept by snapshots and images. The code of native
+/// words can't be saved; the interpreter they are revived in must already
+/// have them.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct SavedWord {
+    name: String,
+    native: bool,
+    data: Vec<Op>,
+    wordlist: usize,
+    immediate: bool,
+    alias_of: Option<usize>,
+    spans: Vec<Span>,
+    source: String,
+}
+
+/// Whether the current wordlist, those in the search order and those of
+/// `words` are all among the first `wordlists`, as in a saved dictionary
+/// that hangs together.
+fn valid_wordlists(wordlists: usize, current: usize, order: &[usize], words: &[SavedWord]) -> bool {
+    current < wordlists && order.iter().all(|&wordlist| wordlist < wordlists)
+        && words.iter().all(|word| word.wordlist < wordlists)
+}
+
+fn do_nop(_forth: &mut Forth, _index: usize) -> ForthResult {
+    Ok(())
+}
+
+fn do_colon(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.compile()
+}
+
+fn do_arithmetic(forth: &mut Forth, index: usize) -> ForthResult {
+    let stack = &mut forth.stack;
+    if stack.len() < 2 {
+        return Err(Error::StackUnderflow);
+    }
+    let v2 = i128::from(stack.pop().unwrap());
+    let v1 = i128::from(stack.pop().unwrap());
+    let v = match forth.names.resolve(forth.words[index].name) {
+        "+" => v1 + v2,
+        "-" => v1 - v2,
+        "*" => v1 * v2,
+        "/" => {
+            if v2 == 0 {
+                return Err(Error::DivisionByZero);
+            }
+            forth.division.divide(v1, v2).0
+        },
+        _ => unreachable!(),
+    };
+    let v = forth.overflow.narrow(v)?;
+    forth.stack.push(v);
+    Ok(())
+}
+
+fn do_divide(forth: &mut Forth, index: usize) -> ForthResult {
+    let divisor = forth.pop()?;
+    let (dividend, division) = match forth.word_name(index) {
+        "MOD" | "/MOD" => (Double::from(forth.pop()?), forth.division),
+        "FM/MOD" => (forth.pop_double()?, Division::Floored),
+        "SM/REM" => (forth.pop_double()?, Division::Symmetric),
+        _ => unreachable!(),
+    };
+    if divisor == 0 {
+        return Err(Error::DivisionByZero);
+    }
+    #[allow(clippy::useless_conversion)]
+    let (quotient, remainder) = division.divide(dividend.into(), divisor.into());
+    // The remainder is smaller than the divisor, so only the quotient can
+    // overflow.
+    let quotient = forth.overflow.narrow(quotient)?;
+    forth.stack.push(remainder as Value);
+    if forth.word_name(index) != "MOD" {
+        forth.stack.push(quotient);
+    }
+    Ok(())
+}
+
+fn do_unary(forth: &mut Forth, index: usize) -> ForthResult {
+    let v = i128::from(forth.pop()?);
+    let v = match forth.word_name(index) {
+        "1+" | "CHAR+" => v + 1,
+        "1-" => v - 1,
+        "2*" => v * 2,
+        "2/" => v >> 1,
+        "CELL+" => v + CELL as i128,
+        "CELLS" => v * CELL as i128,
+        "CHARS" => v,
+        _ => unreachable!(),
+    };
+    let v = forth.overflow.narrow(v)?;
+    forth.stack.push(v);
+    Ok(())
+}
+
+fn do_within(forth: &mut Forth, _index: usize) -> ForthResult {
+    let hi = forth.pop()?;
+    let lo = forth.pop()?;
+    let n = forth.pop()?;
+    let within = (n.wrapping_sub(lo) as Unsigned) < (hi.wrapping_sub(lo) as Unsigned);
+    forth.stack.push(if within { -1 } else { 0 });
+    Ok(())
+}
+
+/// `=` and `<>`, which compare cells without going through the overflow
+/// policy.
+fn do_equals(forth: &mut Forth, index: usize) -> ForthResult {
+    let v2 = forth.pop()?;
+    let v1 = forth.pop()?;
+    let equal = (v1 == v2) == (forth.word_name(index) == "=");
+    forth.stack.push(if equal { -1 } else { 0 });
+    Ok(())
+}
+
+fn do_invert(forth: &mut Forth, _index: usize) -> ForthResult {
+    let v = forth.pop()?;
+    forth.stack.push(!v);
+    Ok(())
+}
+
+fn do_dup(forth: &mut Forth, _index: usize) -> ForthResult {
+    let stack = &mut forth.stack;
+    if stack.is_empty() {
+        return Err(Error::StackUnderflow);
+    }
+    let v = *stack.last().unwrap();
+    stack.push(v);
+    Ok(())
+}
+
+fn do_drop(forth: &mut Forth, _index: usize) -> ForthResult {
+    let stack = &mut forth.stack;
+    if stack.is_empty() {
+        return Err(Error::StackUnderflow);
+    }
+    stack.pop().unwrap();
+    Ok(())
+}
+
+fn do_swap(forth: &mut Forth, _index: usize) -> ForthResult {
+    let stack = &mut forth.stack;
+    if stack.len() < 2 {
+        return Err(Error::StackUnderflow);
+    }
+    let v1 = stack.pop().unwrap();
+    let v2 = stack.pop().unwrap();
+    stack.push(v1);
+    stack.push(v2);
+    Ok(())
+}
+
+fn do_over(forth: &mut Forth, _index: usize) -> ForthResult {
+    let stack = &mut forth.stack;
+    if stack.len() < 2 {
+        return Err(Error::StackUnderflow);
+    }
+    let v = stack[stack.len() - 2];
+    stack.push(v);
+    Ok(())
+}
+
+fn do_exec(forth: &mut Forth, index: usize) -> ForthResult {
+    if forth.return_stack_depth.is_some_and(|depth| forth.return_stack.len() >= depth) {
+        return Err(Error::ReturnStackOverflow);
+    }
+    forth.return_stack.push(Frame { word: index, pc: 0 });
+    Ok(())
+}
+
+fn do_exit(forth: &mut Forth, _index: usize) -> ForthResult {
+    // Only frames of the innermost `execute` may be left; EXIT in input
+    // that a word EVALUATEs must not return from that word.
+    if forth.return_stack.len() <= forth.frame_base {
+        return Err(Error::InvalidWord);
+    }
+    forth.return_stack.pop();
+    Ok(())
+}
+
+fn do_recurse(forth: &mut Forth, _index: usize) -> ForthResult {
+    let span = forth.last_span;
+    let body = forth.definition.as_mut().ok_or(Error::InvalidWord)?;
+    let word = body.word.ok_or(Error::InvalidWord)?;
+    body.push(Op::Call(word), span);
+    Ok(())
+}
+
+fn do_wordlist(forth: &mut Forth, _index: usize) -> ForthResult {
+    let wid = forth.wordlists;
+    forth.wordlists += 1;
+    forth.stack.push(wid as Value);
+    Ok(())
+}
+
+fn do_forth_wordlist(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.stack.push(FORTH_WORDLIST);
+    Ok(())
+}
+
+fn do_get_order(forth: &mut Forth, _index: usize) -> ForthResult {
+    for &wid in forth.order.iter() {
+        forth.stack.push(wid as Value);
+    }
+    forth.stack.push(forth.order.len() as Value);
+    Ok(())
+}
+
+fn do_set_order(forth: &mut Forth, _index: usize) -> ForthResult {
+    let n = forth.pop()?;
+    if n == -1 {
+        forth.order = vec![FORTH_WORDLIST as usize];
+        return Ok(());
+    }
+    if n < 0 {
+        return Err(Error::InvalidWordlist);
+    }
+    let mut order = Vec::new();
+    for _ in 0..n {
+        let wid = forth.pop()?;
+        order.push(forth.wordlist_id(wid)?);
+    }
+    order.reverse();
+    forth.order = order;
+    Ok(())
+}
+
+fn do_get_current(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.stack.push(forth.current as Value);
+    Ok(())
+}
+
+fn do_set_current(forth: &mut Forth, _index: usize) -> ForthResult {
+    let wid = forth.pop()?;
+    forth.current = forth.wordlist_id(wid)?;
+    Ok(())
+}
+
+fn do_definitions(forth: &mut Forth, _index: usize) -> ForthResult {
+    if let Some(&wid) = forth.order.last() {
+        forth.current = wid;
+    }
+    Ok(())
+}
+
+fn do_forth(forth: &mut Forth, _index: usize) -> ForthResult {
+    match forth.order.last_mut() {
+        Some(top) => *top = FORTH_WORDLIST as usize,
+        None => forth.order.push(FORTH_WORDLIST as usize),
+    }
+    Ok(())
+}
+
+fn do_only(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.order = vec![FORTH_WORDLIST as usize];
+    Ok(())
+}
+
+fn do_also(forth: &mut Forth, _index: usize) -> ForthResult {
+    if let Some(&wid) = forth.order.last() {
+        forth.order.push(wid);
+    }
+    Ok(())
+}
+
+fn do_previous(forth: &mut Forth, _index: usize) -> ForthResult {
+    if forth.order.pop().is_none() {
+        return Err(Error::InvalidWordlist);
+    }
+    Ok(())
+}
+
+fn do_bracket_if(forth: &mut Forth, _index: usize) -> ForthResult {
+    if forth.pop()? == 0 {
+        forth.skip_conditional(true);
+    }
+    Ok(())
+}
+
+fn do_bracket_else(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.skip_conditional(false);
+    Ok(())
+}
+
+fn do_bracket_defined(forth: &mut Forth, index: usize) -> ForthResult {
+    let defined = match forth.parse_word() {
+        Ok(_) => true,
+        Err(Error::UnknownWord(_)) => false,
+        Err(e) => return Err(e),
+    };
+    let negate = forth.word_name(index) == "[UNDEFINED]";
+    forth.stack.push(if defined != negate { -1 } else { 0 });
+    Ok(())
+}
+
+fn do_evaluate(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    forth.check_range(addr, len)?;
+    forth.evaluate(addr as usize, len as usize)
+}
+
+fn do_s_quote(forth: &mut Forth, _index: usize) -> ForthResult {
+    let (start, len) = forth.parse(b'"');
+    let bytes = forth.memory[start..start + len].to_vec();
+    forth.string_literal(&bytes)
+}
+
+fn do_s_backslash_quote(forth: &mut Forth, _index: usize) -> ForthResult {
+    let bytes = forth.parse_escaped();
+    forth.string_literal(&bytes)
+}
+
+fn do_paren(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.load_lexed_text();
+    let (start, len) = forth.source;
+    let mut offset = forth.to_in();
+    let mut depth = 1;
+    while offset < len && depth > 0 {
+        match forth.memory[start + offset] {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            _ => {}
+        }
+        offset += 1;
+    }
+    forth.set_to_in(offset);
+    Ok(())
+}
+
+fn do_backslash(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.parse(b'\n');
+    Ok(())
+}
+
+fn do_fetch(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    let v = forth.fetch(addr)?;
+    forth.stack.push(v);
+    Ok(())
+}
+
+fn do_store(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    let v = forth.pop()?;
+    forth.store(addr, v)
+}
+
+fn do_c_fetch(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    forth.check_range(addr, 1)?;
+    forth.stack.push(Value::from(forth.memory[addr as usize]));
+    Ok(())
+}
+
+fn do_c_store(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    let c = forth.pop()?;
+    forth.check_range(addr, 1)?;
+    forth.memory[addr as usize] = c as u8;
+    Ok(())
+}
+
+/// 2@ and 2! keep the cell on top of the stack at the lower address, so
+/// a double-cell number is stored most significant cell first.
+fn do_two_fetch(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    let hi = forth.fetch(addr)?;
+    let lo = forth.fetch(addr.wrapping_add(CELL as Value))?;
+    forth.stack.push(lo);
+    forth.stack.push(hi);
+    Ok(())
+}
+
+fn do_two_store(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    let hi = forth.pop()?;
+    let lo = forth.pop()?;
+    // Checked up front so that a failure stores neither cell.
+    forth.check_range(addr, 2 * CELL as Value)?;
+    forth.store(addr, hi)?;
+    forth.store(addr + CELL as Value, lo)
+}
+
+fn do_bl(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.stack.push(Value::from(b' '));
+    Ok(())
+}
+
+fn do_source(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.stack.push(forth.source.0 as Value);
+    forth.stack.push(forth.source.1 as Value);
+    Ok(())
+}
+
+fn do_to_in(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.stack.push(TO_IN as Value);
+    Ok(())
+}
+
+fn do_word(forth: &mut Forth, _index: usize) -> ForthResult {
+    let delimiter = forth.pop()? as u8;
+    forth.skip_delimiters(delimiter);
+    let (start, len) = forth.parse(delimiter);
+    let len = len.min(WORD_BUFFER_SIZE - 1);
+    forth.memory[WORD_BUFFER] = len as u8;
+    forth.memory.copy_within(start..start + len, WORD_BUFFER + 1);
+    forth.stack.push(WORD_BUFFER as Value);
+    Ok(())
+}
+
+fn do_parse(forth: &mut Forth, _index: usize) -> ForthResult {
+    let delimiter = forth.pop()? as u8;
+    let (start, len) = forth.parse(delimiter);
+    forth.stack.push(start as Value);
+    forth.stack.push(len as Value);
+    Ok(())
+}
+
+fn do_parse_name(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.skip_delimiters(b' ');
+    let (start, len) = forth.parse(b' ');
+    forth.stack.push(start as Value);
+    forth.stack.push(len as Value);
+    Ok(())
+}
+
+fn do_base(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.stack.push(BASE as Value);
+    Ok(())
+}
+
+fn do_decimal(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.store(BASE as Value, 10)
+}
+
+fn do_hex(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.store(BASE as Value, 16)
+}
+
+fn do_s_to_d(forth: &mut Forth, _index: usize) -> ForthResult {
+    let v = forth.pop()?;
+    forth.push_double(Double::from(v));
+    Ok(())
+}
+
+fn do_d_plus(forth: &mut Forth, _index: usize) -> ForthResult {
+    let d2 = forth.pop_double()?;
+    let d1 = forth.pop_double()?;
+    forth.push_double(d1.wrapping_add(d2));
+    Ok(())
+}
+
+fn do_m_star(forth: &mut Forth, _index: usize) -> ForthResult {
+    let v2 = forth.pop()?;
+    let v1 = forth.pop()?;
+    forth.push_double(Double::from(v1) * Double::from(v2));
+    Ok(())
+}
+
+/// `>NUMBER` ( ud1 c-addr1 u1 -- ud2 c-addr2 u2 ) adds the digits in the
+/// current base at the start of the string to `ud1`, leaving the rest of
+/// the string from the first character that isn't one.
+fn do_to_number(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    forth.check_range(addr, len)?;
+    let mut ud = forth.pop_double()? as UDouble;
+    let base = forth.base()?;
+    let bytes = &forth.memory[addr as usize..(addr + len) as usize];
+    let count = bytes.iter().take_while(|&&byte| (byte as char).is_digit(base)).count();
+    for &byte in &bytes[..count] {
+        let digit = (byte as char).to_digit(base).unwrap();
+        ud = ud.wrapping_mul(UDouble::from(base)).wrapping_add(UDouble::from(digit));
+    }
+    forth.push_double(ud as Double);
+    forth.push(addr + count as Value);
+    forth.push(len - count as Value);
+    Ok(())
+}
+
+/// NUMBER? ( c-addr u -- n true | false ) reads the string as the
+/// interpreter reads a number, prefixes and all.
+fn do_number_question(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    forth.check_range(addr, len)?;
+    let text = String::from_utf8_lossy(&forth.memory[addr as usize..(addr + len) as usize]).into_owned();
+    match parse_number(text.trim(), forth.base()?) {
+        Ok(Some(n)) => {
+            forth.push(n);
+            forth.push(-1);
+        }
+        _ => forth.push(0),
+    }
+    Ok(())
+}
+
+fn do_less_number_sign(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.hold = HOLD_BUFFER + HOLD_BUFFER_SIZE;
+    Ok(())
+}
+
+fn do_number_sign(forth: &mut Forth, _index: usize) -> ForthResult {
+    let ud = forth.pop_double()? as UDouble;
+    let ud = forth.hold_digit(ud)?;
+    forth.push_double(ud as Double);
+    Ok(())
+}
+
+fn do_number_sign_s(forth: &mut Forth, _index: usize) -> ForthResult {
+    let mut ud = forth.pop_double()? as UDouble;
+    loop {
+        ud = forth.hold_digit(ud)?;
+        if ud == 0 {
+            break;
+        }
+    }
+    forth.push_double(0);
+    Ok(())
+}
+
+fn do_hold(forth: &mut Forth, _index: usize) -> ForthResult {
+    let c = to_char(forth.pop()?);
+    let mut buf = [0; 4];
+    // Held from the end backwards, so the last byte goes first.
+    for &b in c.encode_utf8(&mut buf).as_bytes().iter().rev() {
+        forth.hold_char(b)?;
+    }
+    Ok(())
+}
+
+fn do_sign(forth: &mut Forth, _index: usize) -> ForthResult {
+    if forth.pop()? < 0 {
+        forth.hold_char(b'-')?;
+    }
+    Ok(())
+}
+
+fn do_number_sign_greater(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.pop_double()?;
+    forth.stack.push(forth.hold as Value);
+    forth.stack.push((HOLD_BUFFER + HOLD_BUFFER_SIZE - forth.hold) as Value);
+    Ok(())
+}
+
+fn do_type(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    forth.check_range(addr, len)?;
+    let bytes = &forth.memory[addr as usize..(addr + len) as usize];
+    let text = String::from_utf8_lossy(bytes).into_owned();
+    forth.emit(&text)
+}
+
+/// Lists memory sixteen bytes to a line: the address, the bytes in hex and
+/// then as text, with a dot for each byte that isn't printable ASCII.
+fn do_dump(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    forth.check_range(addr, len)?;
+    let mut text = String::new();
+    for (i, line) in forth.memory[addr as usize..(addr + len) as usize].chunks(16).enumerate() {
+        text.push_str(&format!("{:08X} ", addr as usize + 16 * i));
+        for byte in line {
+            text.push_str(&format!(" {:02X}", byte));
+        }
+        text.push_str(&" ".repeat(3 * (16 - line.len()) + 2));
+        let printable = |byte: u8| byte == b' ' || byte.is_ascii_graphic();
+        text.extend(line.iter().map(|&byte| if printable(byte) { byte as char } else { '.' }));
+        text.push('\n');
+    }
+    forth.emit(&text)
+}
+
+fn do_emit(forth: &mut Forth, _index: usize) -> ForthResult {
+    let c = to_char(forth.pop()?);
+    forth.emit(c.encode_utf8(&mut [0; 4]))
+}
+
+fn do_cr(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.emit("\n")
+}
+
+fn do_dot(forth: &mut Forth, _index: usize) -> ForthResult {
+    let v = forth.pop()?;
+    let text = forth.number_text(v.unsigned_abs(), v < 0)?;
+    forth.emit(&format!("{} ", text))
+}
+
+/// .R ( n width -- ) prints `n` right-aligned in `width` characters, at
+/// most `MAX_FIELD_WIDTH`, or in as many as it takes.
+fn do_dot_r(forth: &mut Forth, _index: usize) -> ForthResult {
+    let width = forth.pop()?;
+    let v = forth.pop()?;
+    let text = forth.number_text(v.unsigned_abs(), v < 0)?;
+    forth.emit_right(&text, width)
+}
+
+/// U.R ( u width -- ) is .R for an unsigned number.
+fn do_u_dot_r(forth: &mut Forth, _index: usize) -> ForthResult {
+    let width = forth.pop()?;
+    let u = forth.pop()? as Unsigned;
+    let text = forth.number_text(u, false)?;
+    forth.emit_right(&text, width)
+}
+
+fn do_here(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.stack.push(forth.here as Value);
+    Ok(())
+}
+
+fn do_allot(forth: &mut Forth, _index: usize) -> ForthResult {
+    let n = forth.pop()?;
+    forth.allot(n)?;
+    Ok(())
+}
+
+fn do_comma(forth: &mut Forth, _index: usize) -> ForthResult {
+    let v = forth.pop()?;
+    let addr = forth.allot(CELL as Value)?;
+    forth.store(addr, v)
+}
+
+fn do_create(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.here as Value;
+    forth.define_constant(addr)
+}
+
+fn do_variable(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.check_dictionary()?;
+    let addr = forth.allot(CELL as Value)?;
+    forth.define_constant(addr)
+}
+
+fn do_constant(forth: &mut Forth, _index: usize) -> ForthResult {
+    let v = forth.pop()?;
+    forth.define_constant(v)
+}
+
+fn do_two_variable(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.check_dictionary()?;
+    let addr = forth.allot(2 * CELL as Value)?;
+    forth.define_constant(addr)
+}
+
+fn do_two_constant(forth: &mut Forth, _index: usize) -> ForthResult {
+    let hi = forth.pop()?;
+    let lo = forth.pop()?;
+    forth.define_compiled(&[Op::Literal(lo), Op::Literal(hi)])
+}
+
+fn do_move(forth: &mut Forth, index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let dst = forth.pop()?;
+    let src = forth.pop()?;
+    let src = forth.memory_range(src, len)?;
+    let dst = forth.memory_range(dst, len)?;
+    match forth.word_name(index) {
+        // CMOVE copies byte by byte from low addresses, so an overlapping
+        // destination above the source repeats the leading bytes.
+        "CMOVE" => {
+            for (s, d) in src.zip(dst) {
+                forth.memory[d] = forth.memory[s];
+            }
+        }
+        "CMOVE>" => {
+            for (s, d) in src.zip(dst).rev() {
+                forth.memory[d] = forth.memory[s];
+            }
+        }
+        _ => forth.memory.copy_within(src, dst.start),
+    }
+    Ok(())
+}
+
+fn do_fill(forth: &mut Forth, _index: usize) -> ForthResult {
+    let c = forth.pop()?;
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    let range = forth.memory_range(addr, len)?;
+    for b in &mut forth.memory[range] {
+        *b = c as u8;
+    }
+    Ok(())
+}
+
+fn do_erase(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    let range = forth.memory_range(addr, len)?;
+    for b in &mut forth.memory[range] {
+        *b = 0;
+    }
+    Ok(())
+}
+
+fn do_count(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    let range = forth.memory_range(addr, 1)?;
+    let len = forth.memory[range.start];
+    forth.stack.push(addr + 1);
+    forth.stack.push(Value::from(len));
+    Ok(())
+}
+
+fn do_compare(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len2 = forth.pop()?;
+    let addr2 = forth.pop()?;
+    let len1 = forth.pop()?;
+    let addr1 = forth.pop()?;
+    let s1 = forth.memory_range(addr1, len1)?;
+    let s2 = forth.memory_range(addr2, len2)?;
+    let ordering = forth.memory[s1].cmp(&forth.memory[s2]);
+    forth.stack.push(ordering as Value);
+    Ok(())
+}
+
+fn do_allocate(forth: &mut Forth, _index: usize) -> ForthResult {
+    let size = forth.pop()?;
+    let addr = usize::try_from(size).ok().and_then(|size| forth.heap.allocate(size));
+    match addr {
+        Some(addr) => {
+            forth.stack.push(addr as Value);
+            forth.stack.push(0);
+        }
+        None => {
+            forth.stack.push(0);
+            forth.stack.push(IOR_ALLOCATE);
+        }
+    }
+    Ok(())
+}
+
+fn do_free(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    let freed = usize::try_from(addr).is_ok_and(|addr| forth.heap.free(addr));
+    forth.stack.push(if freed { 0 } else { IOR_FREE });
+    Ok(())
+}
+
+fn do_resize(forth: &mut Forth, _index: usize) -> ForthResult {
+    let size = forth.pop()?;
+    let addr = forth.pop()?;
+    let old = usize::try_from(addr).ok().and_then(|addr| forth.heap.size_of(addr).map(|size| (addr, size)));
+    let resized = match (old, usize::try_from(size)) {
+        (Some((addr, old_size)), Ok(size)) => {
+            forth.heap.resize(addr, size).inspect(|&new_addr| {
+                if new_addr != addr {
+                    let len = old_size.min(size);
+                    forth.memory.copy_within(addr..addr + len, new_addr);
+                }
+            })
+        }
+        _ => None,
+    };
+    match resized {
+        Some(new_addr) => {
+            forth.stack.push(new_addr as Value);
+            forth.stack.push(0);
+        }
+        None => {
+            forth.stack.push(addr);
+            forth.stack.push(IOR_RESIZE);
+        }
+    }
+    Ok(())
+}
+
+fn do_key(forth: &mut Forth, _index: usize) -> ForthResult {
+    let c = loop {
+        forth.fill_input()?;
+        match forth.input.pop_front() {
+            Some(c) if is_key_event(c) => continue,
+            Some(c) => break c,
+            None => return Err(Error::NoInput),
+        }
+    };
+    forth.stack.push(c as Value);
+    Ok(())
+}
+
+fn do_key_question(forth: &mut Forth, _index: usize) -> ForthResult {
+    let ready = forth.input.iter().any(|&c| !is_key_event(c));
+    forth.stack.push(if ready { -1 } else { 0 });
+    Ok(())
+}
+
+fn do_accept(forth: &mut Forth, _index: usize) -> ForthResult {
+    let max = forth.pop()?;
+    let addr = forth.pop()?;
+    let range = forth.memory_range(addr, max)?;
+    forth.fill_input()?;
+    let mut len = 0;
+    while let Some(&c) = forth.input.front() {
+        if c == '\n' {
+            forth.input.pop_front();
+            break;
+        }
+        if is_key_event(c) {
+            forth.input.pop_front();
+            continue;
+        }
+        let mut buf = [0; 4];
+        let bytes = c.encode_utf8(&mut buf).as_bytes();
+        if len + bytes.len() > range.len() {
+            break;
+        }
+        let start = range.start + len;
+        forth.memory[start..start + bytes.len()].copy_from_slice(bytes);
+        len += bytes.len();
+        forth.input.pop_front();
+    }
+    forth.stack.push(len as Value);
+    Ok(())
+}
+
+fn do_tick(forth: &mut Forth, _index: usize) -> ForthResult {
+    let xt = forth.parse_word()?;
+    forth.stack.push(xt as Value);
+    Ok(())
+}
+
+fn do_execute(forth: &mut Forth, _index: usize) -> ForthResult {
+    let xt = forth.pop()?;
+    let xt = forth.xt(xt)?;
+    forth.call(xt)
+}
+
+/// CATCH ( i*x xt -- j*x 0 | i*x n ) runs `xt`, leaving 0 if it finishes
+/// or, if it fails, the error's throw code with the stack back at its
+/// depth before. Requests to the host, such as MS waiting or running out of
+/// fuel, aren't caught.
+fn do_catch(forth: &mut Forth, _index: usize) -> ForthResult {
+    let xt = forth.pop()?;
+    let xt = forth.xt(xt)?;
+    let depth = forth.stack.len();
+    let traced = forth.error_trace.len();
+    match forth.execute(xt) {
+        Ok(()) => forth.stack.push(0),
+        Err(e @ Error::OutOfFuel)
+        | Err(e @ Error::Stopped)
+        | Err(e @ Error::IncludeRequested(_))
+        | Err(e @ Error::WaitRequested(_))
+        | Err(e @ Error::PauseRequested) => return Err(e),
+        Err(e) => {
+            forth.error_trace.truncate(traced);
+            forth.stack.resize(depth, 0);
+            forth.stack.push(Value::from(e.throw_code()));
+        }
+    }
+    Ok(())
+}
+
+fn do_synonym(forth: &mut Forth, _index: usize) -> ForthResult {
+    let name = forth.parse_new_name()?;
+    let target = forth.parse_word()?;
+    forth.define_alias(&name, target)
+}
+
+fn do_alias(forth: &mut Forth, _index: usize) -> ForthResult {
+    let xt = forth.pop()?;
+    let target = forth.xt(xt)?;
+    let name = forth.parse_new_name()?;
+    forth.define_alias(&name, target)
+}
+
+/// A single cell: 32 bits, or 64 with the `cell64` feature.
+#[cfg(not(feature = "cell64"))]
+pub type Value = i32;
+#[cfg(feature = "cell64")]
+pub type Value = i64;
+/// A cell taken as unsigned.
+#[cfg(not(feature = "cell64"))]
+type Unsigned = u32;
+#[cfg(feature = "cell64")]
+type Unsigned = u64;
+/// A double cell, as S>D makes and `#` takes apart.
+#[cfg(not(feature = "cell64"))]
+type Double = i64;
+#[cfg(feature = "cell64")]
+type Double = i128;
+#[cfg(not(feature = "cell64"))]
+type UDouble = u64;
+#[cfg(feature = "cell64")]
+type UDouble = u128;
+
+/// Size of a cell in bytes.
+pub(crate) const CELL: usize = mem::size_of::<Value>();
+pub type ForthResult = Result<(), Error>;
+
+/// How division rounds quotients that aren't whole.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Division {
+    /// Toward zero, as SM/REM does. Remainders take the sign of the
+    /// dividend.
+    Symmetric,
+    /// Toward negative infinity, as FM/MOD does. Remainders take the sign
+    /// of the divisor.
+    Floored,
+}
+
+impl Division {
+    /// Quotient and remainder of `n / d`, for nonzero `d`. The one
+    /// quotient too large for `i128`, from a 64-bit double cell divided by
+    /// -1, wraps.
+    fn divide(self, n: i128, d: i128) -> (i128, i128) {
+        let (q, r) = (n.wrapping_div(d), n.wrapping_rem(d));
+        match self {
+            Division::Floored if r != 0 && (r < 0) != (d < 0) => (q - 1, r + d),
+            _ => (q, r),
+        }
+    }
+}
+
+/// What arithmetic words do with results that don't fit in a cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+    /// Keep the low bits, as two's complement hardware does.
+    Wrapping,
+    /// Fail with `Error::Overflow`.
+    Checked,
+    /// Clamp to the largest or smallest cell.
+    Saturating,
+}
+
+impl Overflow {
+    /// Fits the exact result `v` of an arithmetic word into a cell.
+    fn narrow(self, v: i128) -> Result<Value, Error> {
+        let min = i128::from(Value::MIN);
+        let max = i128::from(Value::MAX);
+        match self {
+            Overflow::Wrapping => Ok(v as Value),
+            Overflow::Checked if v < min || v > max => Err(Error::Overflow),
+            Overflow::Checked => Ok(v as Value),
+            Overflow::Saturating => Ok(v.clamp(min, max) as Value),
+        }
+    }
+}
+
+/// A Forth interpreter: data stack, dictionary, data space and the state of
+/// the input being interpreted.
+pub struct Forth {
+    stack: Vec<Value>,
+    /// Compiled words being run, innermost last.
+    return_stack: Vec<Frame>,
+    /// Number of frames below those of the innermost `execute`.
+    frame_base: usize,
+    words: Vec<Word>,
+    /// The compiled bodies of `words`, and the spans of their instructions.
+    bodies: Arena<Op>,
+    body_spans: Arena<Span>,
+    names: Names,
+    /// Where in `words` each name is defined, oldest first, by symbol; kept
+    /// in step with `words`.
+    index: Vec<SmallVec<[usize; 1]>>,
+    wordlists: usize,
+    order: Vec<usize>,
+    current: usize,
+    memory: Vec<u8>,
+    here: usize,
+    /// Address and length of the input buffer currently being interpreted.
+    source: (usize, usize),
+    /// Body of the definition being compiled, if any.
+    definition: Option<Definition>,
+    /// A definition left waiting for `;` by the last evaluation.
+    unfinished: Option<Unfinished>,
+    heap: Heap,
+    /// Characters supplied by the host for KEY and ACCEPT.
+    input: VecDeque<char>,
+    /// Start of the pictured numeric output built so far.
+    hold: usize,
+    output: String,
+    /// Span of the last name parsed from the current input source.
+    last_span: Span,
+    /// Span of the last name parsed from the top-level input, and the number
+    /// of names parsed from it so far.
+    input_span: Span,
+    names_parsed: usize,
+    error_location: Option<Location>,
+    /// The compiled words running when the last evaluation failed,
+    /// innermost first.
+    error_trace: Vec<ErrorFrame>,
+    stack_depth: Option<usize>,
+    return_stack_depth: Option<usize>,
+    call_depth: Option<usize>,
+    overflow: Overflow,
+    division: Division,
+    case_sensitive: bool,
+    data_space_size: usize,
+    /// Largest data space `load_image` and `restore` accept.
+    max_data_space_size: usize,
+    /// Most words that may be defined after the built-in ones.
+    max_words: Option<usize>,
+    /// Most bytes of output a single evaluation may produce, and how many
+    /// the current one has.
+    max_output: Option<usize>,
+    output_len: usize,
+    /// Limits on what evaluations are given to read: bytes of input, names
+    /// read from it, bytes in a name and instructions in a definition.
+    max_input_len: Option<usize>,
+    max_tokens: Option<usize>,
+    max_name_len: Option<usize>,
+    max_definition_len: Option<usize>,
+    /// Whether output past `max_output` is dropped rather than failing, and
+    /// whether the current evaluation's was.
+    truncate_output: bool,
+    output_truncated: bool,
+    /// Whether `eval` starts the output with the input and ends it with
+    /// ` ok` or the error, as a terminal session shows them.
+    echo_input: bool,
+    ok_prompt: bool,
+    /// The grid output is also written to, as the builder's `terminal`
+    /// asked.
+    terminal: Option<TerminalGrid>,
+    /// What errors and warnings are shown as.
+    messages: Messages,
+    /// Address of the terminal input buffer, past the data space, heap and
+    /// transient string buffers.
+    tib: usize,
+    /// Which transient buffer the next interpreted string goes in.
+    string_buffer: usize,
+    /// Number of built-in words at the start of the dictionary.
+    builtins: usize,
+    /// Number of words at the start of the dictionary that are the
+    /// interpreter's own primitives, before host words and the prelude.
+    primitives: usize,
+    /// Whether colon definitions are passed through `peephole`.
+    optimize: bool,
+    /// Longest body, in instructions, that is inlined rather than called.
+    inline_limit: Option<usize>,
+    /// Receives output as it is produced instead of `output`.
+    output_sink: Option<Box<OutputSink>>,
+    /// Receives what LOG and LOG-TYPE log.
+    log_sink: Option<Box<LogSink>>,
+    /// Asked for more text when KEY or ACCEPT find `input` empty.
+    input_source: Option<Box<InputSource>>,
+    /// Where SAVE-DICT and LOAD-DICT keep definitions.
+    dictionary_store: Option<DictionaryStore>,
+    /// Where the block words keep blocks, and the buffer they use.
+    block_storage: Option<Box<dyn BlockStorage>>,
+    block_buffer: BlockBuffer,
+    /// Where INCLUDED and REQUIRED read files, and the names of those read.
+    source_provider: Option<Box<dyn SourceProvider>>,
+    included_files: Vec<String>,
+    /// Offered colon definitions to run natively.
+    word_compiler: Option<Box<dyn WordCompiler>>,
+    /// Where the pointer MOUSE-X, MOUSE-Y and BUTTON? read is.
+    pointer: Pointer,
+    /// The word EVERY-FRAME chose for `run_frame`.
+    frame_word: Option<usize>,
+    /// Parsers for literals tried on names that aren't words or numbers.
+    recognizers: Vec<RecognizerEntry>,
+    /// Definitions set aside while quotations inside them are compiled,
+    /// innermost last.
+    quotations: Vec<Enclosing>,
+    /// The words AFTER and EVERY scheduled for `run_timers`.
+    timers: Timers,
+    /// The words TASK started for `run_tasks`.
+    tasks: Tasks,
+    /// The string stack of the words the builder's `strings` adds.
+    strings: Vec<Handle>,
+    /// What each item on the data stack is, with typed cells.
+    #[cfg(feature = "typed")]
+    typed: Option<Types>,
+    /// The modules MODULE has begun and END-MODULE not yet ended,
+    /// innermost last.
+    modules: Vec<OpenModule>,
+    /// The test T{ began, and how many have failed.
+    tester: Tester,
+    /// What the last evaluation's definitions were warned of.
+    warnings: Vec<Warning>,
+    /// Where RANDOM draws numbers from.
+    random: Random,
+    /// What TICKS and TIME&DATE read, and MS waits on.
+    clock: Option<Box<dyn Clock>>,
+    channels: Box<dyn Channels>,
+    /// Whether the host's clock, input source and dictionary store are
+    /// ignored, so that the same program and input always do the same.
+    deterministic: bool,
+    /// Milliseconds MS has waited in a deterministic interpreter, which
+    /// stand in for the clock's ticks.
+    virtual_ticks: u64,
+    /// Shown the data space whenever an evaluation stops.
+    data_space_observer: Option<Box<DataSpaceObserver>>,
+    /// Counts calls and time per word between `start_profiling` and
+    /// `stop_profiling`.
+    profiler: Option<Profiler>,
+    /// Records what runs between `start_coverage` and `stop_coverage`.
+    coverage: Option<Coverage>,
+    /// Called as each word starts and finishes.
+    hooks: Hooks,
+    /// What the last evaluation has cost so far.
+    counters: Counters,
+    /// Records the stack after each token during `trace`.
+    tracer: Option<Tracer>,
+    recorder: Option<Recorder>,
+    /// The session `debug` started, while it can still be carried on.
+    debugger: Option<Debugger>,
+    /// Canonical names of the words `debug` sessions stop at.
+    breakpoints: Vec<String>,
+    /// Canonical names of the words programs may not define, and whether
+    /// they may define any.
+    protected: Vec<String>,
+    frozen: bool,
+    watchpoints: Vec<Watch>,
+    /// Why the `debug` session last stopped, if a watchpoint stopped it.
+    watch_hit: Option<WatchHit>,
+    /// Tokens read in place of the input buffer during `eval_tokens`.
+    lexed: Option<Lexed>,
+    /// Reused by `next_token` so that names of known words aren't
+    /// allocated.
+    name_buffer: String,
+    /// Instructions left to run before evaluation stops with `OutOfFuel`,
+    /// or `None` for no limit.
+    fuel: Option<u64>,
+    /// Whether the last evaluation ran out of fuel somewhere `resume` can
+    /// carry on from. Its frames stay on the return stack.
+    paused: bool,
+    /// Instructions of the paused evaluation still to run at the top level
+    /// once its frames are done.
+    pending: VecDeque<Op>,
+    /// Number of `execute` calls in progress. Only the outermost can pause.
+    executing: usize,
+    /// Whether `eval` keeps a copy of the interpreter from before it for
+    /// `undo`, and that copy.
+    keep_undo: bool,
+    undo: Option<Box<Forth>>,
+}
+
+/// Reasons evaluation can stop. `Forth::error_location` tells where.
+///
+/// ```
+/// use forth_core::{Error, Forth};
+///
+/// let mut f = Forth::new();
+/// assert_eq!(f.eval(": SQ DUP * ; ;"), Err(Error::SemicolonOutsideDefinition));
+/// assert_eq!(f.eval(": A 1 : B 2 ;"), Err(Error::NestedDefinition));
+/// assert_eq!(f.error_location().unwrap().offset, 6);
+/// assert_eq!(f.eval("VARIABLE"), Err(Error::MissingName));
+/// assert_eq!(f.eval(": 10 20 ;"), Err(Error::NumericName(String::from("10"))));
+/// assert_eq!(f.eval("S\" : CUBE DUP\" EVALUATE"), Err(Error::UnterminatedDefinition));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    DivisionByZero,
+    Overflow,
+    StackUnderflow,
+    /// A string word found too few strings on the string stack.
+    StringStackUnderflow,
+    UnknownWord(String),
+    InvalidWord,
+    InvalidWordlist,
+    InvalidAddress,
+    InvalidBase,
+    /// A block number below 1, or too large for the block storage.
+    InvalidBlock,
+    NoInput,
+    NoStorage,
+    NoClock,
+    NumberOutOfRange(String),
+    StackOverflow,
+    ReturnStackOverflow,
+    CallDepthExceeded,
+    OutOfFuel,
+    Stopped,
+    DictionaryFull,
+    /// A program tried to define this word, which the host protected.
+    ProtectedWord(String),
+    /// A program tried to define a word once the dictionary was frozen.
+    DictionaryFrozen,
+    DataSpaceFull,
+    OutputLimitExceeded,
+    /// The input is longer, or has more names, than the builder's
+    /// `max_input_len` or `max_tokens` allow.
+    InputTooLarge,
+    /// A name is longer than the builder's `max_name_len` allows.
+    NameTooLong,
+    /// A definition has more instructions than the builder's
+    /// `max_definition_len` allows.
+    DefinitionTooLong,
+    InvalidSnapshot,
+    InvalidImage,
+    UnterminatedDefinition,
+    /// `;` outside a colon definition.
+    SemicolonOutsideDefinition,
+    /// `:` inside a colon definition, which must end with `;` first.
+    NestedDefinition,
+    /// A defining word such as `:` or VARIABLE found no name after it.
+    MissingName,
+    /// A colon definition was given this name, which reads as a number.
+    NumericName(String),
+    UnbalancedControl(String),
+    /// `[IF]` branches that leave the stack at different depths, ended by
+    /// this word, as `Forth::lint` reports.
+    UnbalancedStack(String),
+    /// INCLUDE or INCLUDED asked for this file, which the host is to pass
+    /// to `Forth::include`.
+    IncludeRequested(String),
+    /// MS asked the host to wait this many milliseconds before calling
+    /// `Forth::resume`.
+    WaitRequested(u64),
+    /// PAUSE asked the host to call `Forth::run_tasks` before
+    /// `Forth::resume`, or RECV to pass a message along first.
+    PauseRequested,
+    /// The source provider has no file of this name.
+    FileNotFound(String),
+    /// With typed cells, this word was given a float or a string where it
+    /// takes something else.
+    TypeMismatch(String),
+    /// Failure reported by a word the host defined, in its own words.
+    Native(String),
+    /// A callback the host handed over, such as a JavaScript function
+    /// taking output, threw or gave back something it shouldn't have, with
+    /// what went wrong.
+    Callback(String),
+    /// A word the host defined failed with this THROW code, which
+    /// `throw_code` passes on.
+    Thrown(String, i32),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().error(self))
+    }
+}
+
+impl Error {
+    /// A stable identifier for the kind of error, the name of its variant,
+    /// such as `StackUnderflow`, for hosts to match on without parsing
+    /// messages.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let e = Forth::new().eval("FOO").unwrap_err();
+    /// assert_eq!(e.kind(), "UnknownWord");
+    /// assert_eq!(e.detail(), Some("FOO"));
+    /// assert_eq!(Error::StackUnderflow.detail(), None);
+    /// ```
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            Error::DivisionByZero => "DivisionByZero",
+            Error::Overflow => "Overflow",
+            Error::StackUnderflow => "StackUnderflow",
+            Error::StringStackUnderflow => "StringStackUnderflow",
+            Error::UnknownWord(_) => "UnknownWord",
+            Error::InvalidWord => "InvalidWord",
+            Error::InvalidWordlist => "InvalidWordlist",
+            Error::InvalidAddress => "InvalidAddress",
+            Error::InvalidBase => "InvalidBase",
+            Error::InvalidBlock => "InvalidBlock",
+            Error::NoInput => "NoInput",
+            Error::NoStorage => "NoStorage",
+            Error::NoClock => "NoClock",
+            Error::NumberOutOfRange(_) => "NumberOutOfRange",
+            Error::StackOverflow => "StackOverflow",
+            Error::ReturnStackOverflow => "ReturnStackOverflow",
+            Error::CallDepthExceeded => "CallDepthExceeded",
+            Error::OutOfFuel => "OutOfFuel",
+            Error::Stopped => "Stopped",
+            Error::DictionaryFull => "DictionaryFull",
+            Error::ProtectedWord(_) => "ProtectedWord",
+            Error::DictionaryFrozen => "DictionaryFrozen",
+            Error::DataSpaceFull => "DataSpaceFull",
+            Error::OutputLimitExceeded => "OutputLimitExceeded",
+            Error::InputTooLarge => "InputTooLarge",
+            Error::NameTooLong => "NameTooLong",
+            Error::DefinitionTooLong => "DefinitionTooLong",
+            Error::InvalidSnapshot => "InvalidSnapshot",
+            Error::InvalidImage => "InvalidImage",
+            Error::UnterminatedDefinition => "UnterminatedDefinition",
+            Error::SemicolonOutsideDefinition => "SemicolonOutsideDefinition",
+            Error::NestedDefinition => "NestedDefinition",
+            Error::MissingName => "MissingName",
+            Error::NumericName(_) => "NumericName",
+            Error::UnbalancedControl(_) => "UnbalancedControl",
+            Error::UnbalancedStack(_) => "UnbalancedStack",
+            Error::IncludeRequested(_) => "IncludeRequested",
+            Error::WaitRequested(_) => "WaitRequested",
+            Error::PauseRequested => "PauseRequested",
+            Error::FileNotFound(_) => "FileNotFound",
+            Error::TypeMismatch(_) => "TypeMismatch",
+            Error::Native(_) => "Native",
+            Error::Callback(_) => "Callback",
+            Error::Thrown(..) => "Thrown",
+        }
+    }
+
+    /// The code THROW would give the error in a standard system, such as -4
+    /// for stack underflow or -13 for an undefined word. Errors the
+    /// standard has no code for get codes from -256 down, which it leaves
+    /// to systems.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// assert_eq!(f.eval("1 0 /").unwrap_err().throw_code(), -10);
+    /// assert_eq!(f.eval("DROP DROP DROP").unwrap_err().throw_code(), -4);
+    /// ```
+    pub fn throw_code(&self) -> i32 {
+        match *self {
+            Error::StackOverflow => -3,
+            Error::StackUnderflow => -4,
+            Error::ReturnStackOverflow | Error::CallDepthExceeded => -5,
+            Error::DictionaryFull | Error::DataSpaceFull => -8,
+            Error::InvalidAddress => -9,
+            Error::DivisionByZero => -10,
+            Error::Overflow => -11,
+            Error::UnknownWord(_) => -13,
+            Error::SemicolonOutsideDefinition => -14,
+            Error::InvalidWord | Error::MissingName => -16,
+            Error::NameTooLong => -19,
+            Error::UnbalancedControl(_) => -22,
+            Error::InvalidBase | Error::NumberOutOfRange(_) => -24,
+            Error::Stopped => -28,
+            Error::NestedDefinition => -29,
+            Error::NumericName(_) => -32,
+            Error::InvalidBlock => -35,
+            Error::FileNotFound(_) => -38,
+            Error::UnterminatedDefinition => -39,
+            Error::InvalidWordlist => -50,
+            Error::NoInput | Error::OutputLimitExceeded => -57,
+            Error::OutOfFuel => -256,
+            Error::NoStorage => -257,
+            Error::NoClock => -258,
+            Error::InvalidSnapshot => -259,
+            Error::InvalidImage => -260,
+            Error::IncludeRequested(_) => -261,
+            Error::WaitRequested(_) => -262,
+            Error::Native(_) => -263,
+            Error::UnbalancedStack(_) => -264,
+            Error::ProtectedWord(_) => -265,
+            Error::DictionaryFrozen => -266,
+            Error::InputTooLarge => -267,
+            Error::DefinitionTooLong => -268,
+            Error::PauseRequested => -269,
+            Error::StringStackUnderflow => -270,
+            Error::TypeMismatch(_) => -271,
+            Error::Callback(_) => -272,
+            Error::Thrown(_, code) => code,
+        }
+    }
+
+    /// The text the error carries, such as the unknown word, the file not
+    /// found or a native word's message, if any.
+    pub fn detail(&self) -> Option<&str> {
+        match *self {
+            Error::UnknownWord(ref text)
+            | Error::NumberOutOfRange(ref text)
+            | Error::UnbalancedControl(ref text)
+            | Error::UnbalancedStack(ref text)
+            | Error::ProtectedWord(ref text)
+            | Error::NumericName(ref text)
+            | Error::IncludeRequested(ref text)
+            | Error::FileNotFound(ref text)
+            | Error::TypeMismatch(ref text)
+            | Error::Native(ref text)
+            | Error::Callback(ref text)
+            | Error::Thrown(ref text, _) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Why `ForthBuilder::try_build` failed: the error the prelude stopped with,
+/// and where in the prelude.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreludeError {
+    pub error: Error,
+    pub location: Option<Location>,
+}
+
+impl fmt::Display for PreludeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.location {
+            Some(ref location) => write!(f, "prelude failed at {} (offset {}): {}",
+                location.word, location.offset, self.error),
+            None => write!(f, "prelude failed: {}", self.error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PreludeError {}
+
+/// Something allowed that is likely a mistake, noted in `Forth::warnings`
+/// without stopping evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// A word of this name was already defined, and is now hidden by the
+    /// new one.
+    Redefined(String),
+    /// A word was defined whose name reads as a number, which the word now
+    /// stands for instead.
+    NumericName(String),
+}
+
+impl Warning {
+    /// A stable identifier for the kind of warning, the name of its
+    /// variant, such as `Redefined`.
+    pub fn kind(&self) -> &'static str {
+        match *self {
+            Warning::Redefined(_) => "Redefined",
+            Warning::NumericName(_) => "NumericName",
+        }
+    }
+
+    /// The name of the word defined.
+    pub fn name(&self) -> &str {
+        match *self {
+            Warning::Redefined(ref name) | Warning::NumericName(ref name) => name,
+        }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&Messages::default().warning(self))
+    }
+}
+
+/// Position in the evaluated input of the name being interpreted when an
+/// error occurred.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    /// The name as it appears in the input.
+    pub word: String,
+    /// Byte offset of the name in the input.
+    pub offset: usize,
+    /// Offset of the name in the input, in characters.
+    pub char_offset: usize,
+    /// Index of the name among all names parsed from the input.
+    pub token_index: usize,
+}
+
+/// Metadata about a dictionary entry, as listed by `Forth::words`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WordInfo {
+    pub name: String,
+    /// Whether the word was defined by the program or the host rather than
+    /// built into the interpreter.
+    pub user_defined: bool,
+    pub immediate: bool,
+    /// Number of compiled instructions in the word's body; zero for words
+    /// implemented natively.
+    pub length: usize,
+    pub wordlist: Value,
+    /// The stack comment after the name in the word's definition, as
+    /// given by `Forth::word_doc`.
+    pub doc: Option<String>,
+}
+
+/// A colon definition by name and source text of its body, as exchanged by
+/// `Forth::user_words` and `Forth::define_words`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WordDefinition {
+    pub name: String,
+    pub body: String,
+}
+
+impl WordDefinition {
+    /// The definition as source text, with its `;` on a line of its own so
+    /// that a body ending in a `\` comment doesn't swallow it.
+    fn source(&self) -> String {
+        format!(": {} {}\n;", self.name, self.body)
+    }
+}
+
+/// Byte range of a token in the source it was parsed from.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// One-based line and column (in characters) at which the span starts
+    /// in `source`.
+    pub fn line_column(&self, source: &str) -> (usize, usize) {
+        let before = &source[..self.start];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (line, before[line_start..].chars().count() + 1)
+    }
+}
+
+/// Input compiled ahead of time by `Forth::compile_program`.
+#[derive(Debug, Clone)]
+pub struct Program {
+    ops: Vec<Op>,
+    /// Size of the dictionary the program was compiled against.
+    words: usize,
+}
+
+/// Instructions of a definition being compiled, or their spans. Most
+/// bodies are short enough to be built up without allocating.
+type Body<T> = SmallVec<[T; 16]>;
+
+/// A colon definition under construction.
+#[derive(Clone)]
+struct Definition {
+    ops: Body<Op>,
+    spans: Body<Span>,
+    /// Index the word will have in the dictionary, for RECURSE; `None`
+    /// when compiling a `Program`.
+    word: Option<usize>,
+    /// Line and column at which the definition began, if known.
+    defined_at: Option<(usize, usize)>,
+}
+
+/// A colon definition an evaluation ran out of input in, kept for the
+/// next to finish.
+#[derive(Clone)]
+struct Unfinished {
+    name: Symbol,
+    /// Text of the definition so far, each input's part on its own line.
+    source: String,
+    definition: Definition,
+}
+
+/// A compiled word being run, and where in its body it is.
+#[derive(Clone)]
+struct Frame {
+    word: usize,
+    pc: usize,
+}
+
+impl Definition {
+    fn push(&mut self, op: Op, span: Span) {
+        self.ops.push(op);
+        self.spans.push(span);
+    }
+}
+
+/// A name parsed from the input, resolved as far as possible.
+enum Token {
+    Word(String),
+    /// `;`, which isn't in the dictionary; colon definitions look for it.
+    Semicolon,
+    WordIndex(usize),
+    Number(Value),
+}
+
+/// An instruction in the body of a compiled word: run the word at a
+/// dictionary index, or push a number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+enum Op {
+    Call(usize),
+    Literal(Value),
+}
+
+/// Finds the next space-delimited name in `input` at or after `offset`.
+fn scan_name(input: &[u8], offset: usize) -> Option<Span> {
+    let start = offset + input[offset..].iter().position(|&c| !is_delimiter(c, b' '))?;
+    let end = input[start..].iter().position(|&c| is_delimiter(c, b' ')).map_or(input.len(), |n| start + n);
+    Some(Span { start, end })
+}
+
+/// The spans of the space-delimited names in some source text, found one
+/// at a time, as returned by `lex`.
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Span;
+
+    fn next(&mut self) -> Option<Span> {
+        let span = scan_name(self.input, self.offset)?;
+        self.offset = span.end;
+        Some(span)
+    }
+}
+
+/// Splits `source` into space-delimited names the way the interpreter's
+/// lexer does, without interpreting parsing words such as `S"` or `(`.
+/// Names are found as they are asked for, so stopping early leaves the
+/// rest of `source` unread.
+///
+/// ```
+/// use forth_core::{lex, Span};
+///
+/// let mut names = lex("  1 DUP\n+");
+/// assert_eq!(names.next(), Some(Span { start: 2, end: 3 }));
+/// assert_eq!(names.count(), 2);
+/// ```
+pub fn lex<'a>(source: &'a str) -> Lexer<'a> {
+    Lexer { input: source.as_bytes(), offset: 0 }
+}
+
+/// Whether `c` separates names when parsing with `delimiter`. A space
+/// delimiter also matches any control character, so tabs and newlines
+/// separate names too.
+fn is_delimiter(c: u8, delimiter: u8) -> bool {
+    c == delimiter || (delimiter == b' ' && c.is_ascii_control())
+}
+
+/// Recognizes `text` as a number: an optional `$`, `#` or `%` base prefix
+/// followed by an optionally negative run of digits in that base (`base`
+/// without a prefix), or a `'c'` character literal.
+fn parse_number(text: &str, base: u32) -> Result<Option<Value>, Error> {
+    let mut chars = text.chars();
+    if let (Some('\''), Some(c), Some('\''), None) = (chars.next(), chars.next(), chars.next(), chars.next()) {
+        return Ok(Some(c as Value));
+    }
+
+    let (base, digits) = match text.chars().next() {
+        Some('$') => (16, &text[1..]),
+        Some('#') => (10, &text[1..]),
+        Some('%') => (2, &text[1..]),
+        _ => (base, text),
+    };
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, digits),
+    };
+    if digits.is_empty() || !digits.chars().all(|c| c.is_digit(base)) {
+        return Ok(None);
+    }
+
+    let mut magnitude: Double = 0;
+    for c in digits.chars() {
+        magnitude = magnitude * Double::from(base) + Double::from(c.to_digit(base).unwrap());
+        if magnitude > Double::from(Unsigned::MAX) {
+            return Err(Error::NumberOutOfRange(String::from(text)));
+        }
+    }
+    if negative {
+        if magnitude > -Double::from(Value::MIN) {
+            return Err(Error::NumberOutOfRange(String::from(text)));
+        }
+        Ok(Some(-magnitude as Value))
+    } else {
+        // Values past the signed range denote unsigned cells, as in $FFFFFFFF.
+        Ok(Some(magnitude as Unsigned as Value))
+    }
+}
+
+/// Address of the terminal input buffer for a data space of `size` bytes,
+/// past the heap and the transient string buffers that follow it, or
+/// `None` if it would be past the end of the address space.
+fn tib_address(size: usize) -> Option<usize> {
+    size.checked_add(HEAP_SIZE + 2 * STRING_BUFFER_SIZE)
+}
+
+/// Memory holding `contents`, zero-filled up to the terminal input buffer
+/// at `tib`, with room past it for a line of input so that evaluating one
+/// doesn't move all of it.
+fn new_memory(contents: &[u8], tib: usize) -> Vec<u8> {
+    let len = contents.len().max(tib);
+    let mut memory = Vec::with_capacity(len + TIB_CAPACITY);
+    memory.extend_from_slice(contents);
+    memory.resize(len, 0);
+    memory
+}
+
+/// Configures and creates a `Forth`.
+///
+/// ```
+/// use forth_core::{Error, Forth};
+///
+/// let mut f = Forth::builder().stack_depth(2).build();
+/// assert_eq!(f.eval("1 2 3"), Err(Error::StackOverflow));
+/// ```
+pub struct ForthBuilder {
+    stack_depth: Option<usize>,
+    return_stack_depth: Option<usize>,
+    call_depth: Option<usize>,
+    overflow: Overflow,
+    division: Division,
+    case_sensitive: bool,
+    data_space_size: usize,
+    max_data_space_size: usize,
+    max_words: Option<usize>,
+    max_output: Option<usize>,
+    max_input_len: Option<usize>,
+    max_tokens: Option<usize>,
+    max_name_len: Option<usize>,
+    max_definition_len: Option<usize>,
+    truncate_output: bool,
+    optimize: bool,
+    inline_limit: Option<usize>,
+    standard: bool,
+    deterministic: bool,
+    frozen: bool,
+    undo: bool,
+    strings: bool,
+    #[cfg(feature = "typed")]
+    typed: bool,
+    echo_input: bool,
+    ok_prompt: bool,
+    terminal: Option<(usize, usize)>,
+    language: Language,
+    natives: Vec<(String, Rc<NativeWord>)>,
+    standard_prelude: bool,
+    prelude: Option<String>,
+}
+
+impl ForthBuilder {
+    pub fn new() -> Self {
+        ForthBuilder {
+            stack_depth: None,
+            return_stack_depth: None,
+            call_depth: Some(CALL_DEPTH),
+            overflow: Overflow::Wrapping,
+            division: Division::Symmetric,
+            case_sensitive: false,
+            data_space_size: DATA_SPACE_SIZE,
+            max_data_space_size: MAX_DATA_SPACE_SIZE,
+            max_words: None,
+            max_output: None,
+            max_input_len: None,
+            max_tokens: None,
+            max_name_len: None,
+            max_definition_len: None,
+            truncate_output: false,
+            optimize: false,
+            inline_limit: None,
+            standard: false,
+            deterministic: false,
+            frozen: false,
+            undo: false,
+            strings: false,
+            #[cfg(feature = "typed")]
+            typed: false,
+            echo_input: false,
+            ok_prompt: false,
+            terminal: None,
+            language: Language::English,
+            natives: Vec::new(),
+            standard_prelude: true,
+            prelude: None,
+        }
+    }
+
+    /// Maximum number of items on the data stack; exceeding it fails with
+    /// `StackOverflow`. Unlimited by default.
+    pub fn stack_depth(mut self, depth: usize) -> Self {
+        self.stack_depth = Some(depth);
+        self
+    }
+
+    /// Maximum number of nested calls of compiled words; exceeding it, as
+    /// by runaway RECURSE, fails with `ReturnStackOverflow`. Unlimited by
+    /// default. A call that ends a word's body replaces the word's call
+    /// rather than nesting in it, so tail recursion doesn't count.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().return_stack_depth(8).build();
+    /// f.eval(": DEEPER RECURSE 1 ;").unwrap();
+    /// assert_eq!(f.eval("DEEPER"), Err(Error::ReturnStackOverflow));
+    ///
+    /// // Counts down to zero, choosing what to EXECUTE next from a table
+    /// // of itself and DONE.
+    /// f.eval(": DONE ; CREATE NEXT 0 , ' DONE ,").unwrap();
+    /// f.eval(": COUNTDOWN 1- DUP 1 2147483647 WITHIN CELLS NEXT CELL+ + @ EXECUTE ;").unwrap();
+    /// f.eval("' COUNTDOWN NEXT ! 3000000 COUNTDOWN").unwrap();
+    /// assert_eq!(f.stack(), vec![0]);
+    /// ```
+    pub fn return_stack_depth(mut self, depth: usize) -> Self {
+        self.return_stack_depth = Some(depth);
+        self
+    }
+
+    /// Maximum nesting of words run from inside other words, as when a
+    /// word EVALUATEs text that calls it again. Unlike calls between
+    /// compiled words, which `return_stack_depth` limits, these nest on the
+    /// host's own stack, so exceeding the limit fails with
+    /// `CallDepthExceeded` before it runs out. Defaults to 128; `None`
+    /// lifts the limit.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().call_depth(Some(16)).build();
+    /// f.eval(": AGAIN S\" AGAIN\" EVALUATE ;").unwrap();
+    /// assert_eq!(f.eval("AGAIN"), Err(Error::CallDepthExceeded));
+    /// ```
+    pub fn call_depth(mut self, depth: Option<usize>) -> Self {
+        self.call_depth = depth;
+        self
+    }
+
+    /// How `+`, `-`, `*`, `/` and the other arithmetic words handle results
+    /// that don't fit in a cell. Wrapping by default.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth, Overflow, Value};
+    ///
+    /// let mut f = Forth::builder().overflow(Overflow::Checked).build();
+    /// f.push(Value::MAX);
+    /// assert_eq!(f.eval("1+"), Err(Error::Overflow));
+    ///
+    /// let mut f = Forth::builder().overflow(Overflow::Saturating).build();
+    /// f.push(Value::MAX);
+    /// f.eval("1+ 3 *").unwrap();
+    /// assert_eq!(f.stack(), vec![Value::MAX]);
+    /// ```
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// How `/`, MOD and /MOD round. Symmetric by default; FM/MOD and SM/REM
+    /// are always floored and symmetric respectively.
+    ///
+    /// ```
+    /// use forth_core::{Division, Forth};
+    ///
+    /// let mut f = Forth::builder().division(Division::Floored).build();
+    /// f.eval("-7 2 /MOD  -7 S>D 2 SM/REM").unwrap();
+    /// assert_eq!(f.stack(), vec![1, -4, -1, -3]);
+    /// ```
+    pub fn division(mut self, division: Division) -> Self {
+        self.division = division;
+        self
+    }
+
+    /// Whether word names are matched case-sensitively. Built-in words are
+    /// upper case, so they must be written that way in case-sensitive mode.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Size of the data space in bytes, including the interpreter's own
+    /// buffers at its start. Reserving space past its end, as by ALLOT, `,`
+    /// or CREATE, fails with `DataSpaceFull`.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().data_space_size(4096).build();
+    /// assert_eq!(f.eval("8192 ALLOT"), Err(Error::DataSpaceFull));
+    /// ```
+    pub fn data_space_size(mut self, size: usize) -> Self {
+        self.data_space_size = size.max(DATA_START);
+        self
+    }
+
+    /// Largest data space an image or snapshot may bring with it when
+    /// `load_image` or `restore` puts it in place of this interpreter's;
+    /// larger ones are refused as invalid rather than allocated. 16 MiB by
+    /// default, and never less than `data_space_size`.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let image = Forth::builder().data_space_size(1 << 20).build().save_image();
+    /// let mut f = Forth::builder().max_data_space_size(1 << 16).build();
+    /// assert_eq!(f.load_image(&image), Err(Error::InvalidImage));
+    /// ```
+    pub fn max_data_space_size(mut self, size: usize) -> Self {
+        self.max_data_space_size = size;
+        self
+    }
+
+    /// Maximum number of words that may be defined once the interpreter is
+    /// built, by `:`, CREATE, CONSTANT and the like; defining more fails
+    /// with `DictionaryFull`. Built-in words, the prelude's and those the
+    /// host defines don't count. Unlimited by default.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().max_words(Some(2)).build();
+    /// f.eval(": A 1 ; VARIABLE B").unwrap();
+    /// assert_eq!(f.eval(": C 3 ;"), Err(Error::DictionaryFull));
+    /// ```
+    pub fn max_words(mut self, max: Option<usize>) -> Self {
+        self.max_words = max;
+        self
+    }
+
+    /// Maximum number of bytes of output a single `eval` or `resume` may
+    /// produce, whether collected or sent to an output sink; output past
+    /// it fails with `OutputLimitExceeded`, keeping what fit. Unlimited by
+    /// default.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().max_output(Some(4)).build();
+    /// assert_eq!(f.eval("1 . 2 . 3 ."), Err(Error::OutputLimitExceeded));
+    /// assert_eq!(f.output(), "1 2 ");
+    /// ```
+    pub fn max_output(mut self, max: Option<usize>) -> Self {
+        self.max_output = max;
+        self
+    }
+
+    /// Maximum number of bytes of input `eval` and the like take, so that
+    /// a huge paste fails with `InputTooLarge` before it is copied or
+    /// read. Text EVALUATE and INCLUDED interpret doesn't count.
+    /// Unlimited by default.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().max_input_len(Some(16)).build();
+    /// assert_eq!(f.eval(&"1 ".repeat(100)), Err(Error::InputTooLarge));
+    /// assert!(f.is_empty());
+    /// ```
+    pub fn max_input_len(mut self, max: Option<usize>) -> Self {
+        self.max_input_len = max;
+        self
+    }
+
+    /// Maximum number of names and numbers a single evaluation may read
+    /// from its input; reading more fails with `InputTooLarge`. Unlimited
+    /// by default.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().max_tokens(Some(3)).build();
+    /// f.eval("1 2 +").unwrap();
+    /// assert_eq!(f.eval("1 2 3 4"), Err(Error::InputTooLarge));
+    /// ```
+    pub fn max_tokens(mut self, max: Option<usize>) -> Self {
+        self.max_tokens = max;
+        self
+    }
+
+    /// Maximum length in bytes of a name read from the input, whether of a
+    /// word to run or one being defined; longer names fail with
+    /// `NameTooLong`. Unlimited by default.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().max_name_len(Some(8)).build();
+    /// assert_eq!(f.eval(": SHORT 1 ; VARIABLE MUCH-TOO-LONG"), Err(Error::NameTooLong));
+    /// ```
+    pub fn max_name_len(mut self, max: Option<usize>) -> Self {
+        self.max_name_len = max;
+        self
+    }
+
+    /// Maximum number of instructions a colon definition or `Program` may
+    /// compile to; longer ones fail with `DefinitionTooLong`. Unlimited by
+    /// default.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().max_definition_len(Some(4)).build();
+    /// f.eval(": FOUR 1 2 3 4 ;").unwrap();
+    /// assert_eq!(f.eval(": FIVE 1 2 3 4 5 ;"), Err(Error::DefinitionTooLong));
+    /// ```
+    pub fn max_definition_len(mut self, max: Option<usize>) -> Self {
+        self.max_definition_len = max;
+        self
+    }
+
+    /// Whether output past `max_output` is dropped, ending at a character
+    /// boundary, rather than failing the evaluation, so a program printing
+    /// without end can carry on. `Forth::output_truncated` tells whether
+    /// any was. Off by default.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::builder().max_output(Some(4)).truncate_output(true).build();
+    /// f.eval("1 . 2 . 3 . 4").unwrap();
+    /// assert_eq!(f.output(), "1 2 ");
+    /// assert!(f.output_truncated());
+    /// assert_eq!(f.stack(), vec![4]);
+    /// ```
+    pub fn truncate_output(mut self, truncate: bool) -> Self {
+        self.truncate_output = truncate;
+        self
+    }
+
+    /// Whether colon definitions are optimized as they are compiled:
+    /// arithmetic on literals is done at compile time, and values pushed
+    /// only to be dropped are left out. The source text kept for
+    /// `definition_source` is unaffected. Off by default.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::builder().optimize(true).build();
+    /// f.eval(": TEN 2 3 + 2 * ; : NOTHING DUP DROP 1 DROP ;").unwrap();
+    /// let words = f.words();
+    /// assert_eq!(words.iter().find(|w| w.name == "TEN").unwrap().length, 1);
+    /// assert_eq!(words.iter().find(|w| w.name == "NOTHING").unwrap().length, 0);
+    /// ```
+    pub fn optimize(mut self, optimize: bool) -> Self {
+        self.optimize = optimize;
+        self
+    }
+
+    /// Compiles calls of compiled words of up to `max_len` instructions by
+    /// copying in their bodies, saving the cost of the call. Words that use
+    /// EXIT are still called. Nothing is inlined by default.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::builder().inline_words(2).build();
+    /// f.eval(": SQ DUP * ; : QUAD SQ SQ ; 3 QUAD").unwrap();
+    /// assert_eq!(f.stack(), vec![81]);
+    /// assert_eq!(f.words().last().unwrap().length, 4);
+    /// ```
+    pub fn inline_words(mut self, max_len: usize) -> Self {
+        self.inline_limit = Some(max_len);
+        self
+    }
+
+    /// Whether to behave as a standard system rather than offer this
+    /// interpreter's conveniences: arithmetic wraps, names match whatever
+    /// their case, and the built-in words outside the standard's word sets,
+    /// such as TICKS, RANDOM, LOG, EVERY-FRAME and ALIAS, are kept out of
+    /// the search order, so that programs may define words of those names
+    /// and find them undefined. Setting arithmetic or case afterwards still
+    /// takes effect. Off by default.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().standard(true).build();
+    /// f.eval("[DEFINED] RANDOM [DEFINED] dup").unwrap();
+    /// assert_eq!(f.stack(), vec![0, -1]);
+    /// assert_eq!(f.eval("TICKS"), Err(Error::UnknownWord("TICKS".into())));
+    /// ```
+    pub fn standard(mut self, standard: bool) -> Self {
+        self.standard = standard;
+        if standard {
+            self.overflow = Overflow::Wrapping;
+            self.case_sensitive = false;
+        }
+        self
+    }
+
+    /// Whether to make the interpreter deterministic, so that the same
+    /// program given the same input always has the same results, as when
+    /// grading exercises. The host's clock, input source and dictionary
+    /// store are ignored: MS returns at once, TICKS counts the
+    /// milliseconds it has waited, TIME&DATE tells the time that long
+    /// after midnight at the start of 2000, KEY and ACCEPT read only the
+    /// input queued with `push_input`, and SAVE-DICT and LOAD-DICT fail
+    /// with `NoStorage`. RANDOM starts from the same seed in any
+    /// interpreter. Off by default.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().deterministic(true).build();
+    /// f.set_input_source(|| Some(String::from("typed")));
+    /// f.eval("TICKS 1500 MS TICKS TIME&DATE").unwrap();
+    /// assert_eq!(f.stack(), vec![0, 1500, 1, 0, 0, 1, 1, 2000]);
+    /// assert_eq!(f.eval("KEY"), Err(Error::NoInput));
+    /// ```
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Whether to freeze the dictionary once the prelude has run, as
+    /// `Forth::freeze` does, so that programs can only use the words it
+    /// and the host defined. Off by default.
+    pub fn frozen(mut self, frozen: bool) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
+    /// Whether `eval` keeps what it changes for `Forth::undo` to revert, as
+    /// `Forth::set_undo` sets. Off by default.
+    pub fn undo(mut self, undo: bool) -> Self {
+        self.undo = undo;
+        self
+    }
+
+    /// Whether to add the words that keep strings on a string stack, such
+    /// as `$"`, `$+` and `$.`, which `Forth::strings` describes. Off by
+    /// default.
+    pub fn strings(mut self, strings: bool) -> Self {
+        self.strings = strings;
+        self
+    }
+
+    /// Whether items on the data stack are integers, floats or strings,
+    /// which `+`, `-`, `*`, `/` and `.` go by, with literals such as `2.5`
+    /// and `" text"` and the words `>STRING`, `S>F`, `F>S` and `BITS>F`, as
+    /// `Forth::typed_stack` describes. Off by default.
+    #[cfg(feature = "typed")]
+    pub fn typed(mut self, typed: bool) -> Self {
+        self.typed = typed;
+        self
+    }
+
+    /// Whether `eval` starts the output with the input it was given, as a
+    /// terminal echoes what is typed, so that the output reads as a session
+    /// log. Off by default.
+    pub fn echo_input(mut self, echo: bool) -> Self {
+        self.echo_input = echo;
+        self
+    }
+
+    /// Whether `eval` ends the output as a Forth terminal ends each line:
+    /// with ` ok`, with ` compiled` inside a colon definition, or with the
+    /// error on a line of its own. An evaluation that pauses gets its
+    /// prompt when `resume` finishes it. Off by default.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::builder().echo_input(true).ok_prompt(true).build();
+    /// f.eval("1 2 + .").unwrap();
+    /// assert_eq!(f.output(), "1 2 + . 3  ok\n");
+    /// f.eval(": SQ").unwrap();
+    /// assert_eq!(f.output(), ": SQ  compiled\n");
+    /// f.eval("DUP * ; NOPE").unwrap_err();
+    /// assert_eq!(f.output(), "DUP * ; NOPE \nunknown word NOPE\n");
+    /// ```
+    pub fn ok_prompt(mut self, prompt: bool) -> Self {
+        self.ok_prompt = prompt;
+        self
+    }
+
+    /// Columns and rows of a grid of characters that output is also written
+    /// to once the prelude has run, for screen-oriented programs, as
+    /// `Forth::terminal` shows it. AT-XY, PAGE, FG, BG, BOLD and NORMAL
+    /// then act on the grid rather than sending ANSI escape sequences. None
+    /// by default.
+    pub fn terminal(mut self, size: Option<(usize, usize)>) -> Self {
+        self.terminal = size;
+        self
+    }
+
+    /// The language `Forth::messages` shows errors and warnings in, as at
+    /// the ok prompt. English by default.
+    ///
+    /// ```
+    /// use forth_core::{Forth, Language};
+    ///
+    /// let mut f = Forth::builder().language(Language::German).ok_prompt(true).build();
+    /// let e = f.eval("1 0 /").unwrap_err();
+    /// assert_eq!(f.output(), "\nDivision durch null\n");
+    /// assert_eq!(f.messages().error(&e), "Division durch null");
+    /// assert_eq!(e.to_string(), "division by zero");
+    /// ```
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Defines a native word, as `Forth::define_native` does, before the
+    /// prelude runs.
+    pub fn native<F>(mut self, name: &str, f: F) -> Self
+        where F: Fn(&mut Stack) -> ForthResult + 'static
+    {
+        self.natives.push((String::from(name), Rc::new(f)));
+        self
+    }
+
+    /// Whether to define the common words the interpreter writes in Forth
+    /// over its Rust primitives, such as NIP, TUCK, NEGATE and `0=`. On by
+    /// default; turning it off leaves only the primitives.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval("1 2 NIP 3 NEGATE 0 0=").unwrap();
+    /// assert_eq!(f.stack(), vec![2, -3, -1]);
+    ///
+    /// let mut f = Forth::builder().standard_prelude(false).build();
+    /// assert_eq!(f.eval("1 2 NIP"), Err(Error::UnknownWord("NIP".into())));
+    /// ```
+    pub fn standard_prelude(mut self, standard_prelude: bool) -> Self {
+        self.standard_prelude = standard_prelude;
+        self
+    }
+
+    /// Source evaluated once the interpreter is created, after the
+    /// standard prelude, replacing any given earlier. Words it defines
+    /// count as built in rather than user defined.
+    pub fn prelude(mut self, source: &str) -> Self {
+        self.prelude = Some(String::from(source));
+        self
+    }
+
+    /// Creates the interpreter.
+    ///
+    /// # Panics
+    ///
+    /// If the prelude fails; `try_build` reports that instead.
+    pub fn build(self) -> Forth {
+        match self.try_build() {
+            Ok(forth) => forth,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Creates the interpreter, failing if the prelude does.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().prelude(": SQUARE DUP * ;").try_build().unwrap();
+    /// f.eval("3 SQUARE").unwrap();
+    /// assert_eq!(f.stack(), vec![9]);
+    ///
+    /// let e = Forth::builder().prelude("1 NOPE").try_build().err().unwrap();
+    /// assert_eq!(e.error, Error::UnknownWord("NOPE".to_string()));
+    /// ```
+    pub fn try_build(self) -> Result<Forth, PreludeError> {
+        let mut names = Names::default();
+        let mut words = Vec::new();
+        let mut bodies = Arena::new();
+        for name in ["+", "-", "*", "/"].iter() {
+            words.push(Word::new(names.intern(name), &do_arithmetic));
+        }
+        for name in ["1+", "1-", "2*", "2/", "CELL+", "CELLS", "CHAR+", "CHARS"].iter() {
+            words.push(Word::new(names.intern(name), &do_unary));
+        }
+        for name in ["MOD", "/MOD", "FM/MOD", "SM/REM"].iter() {
+            words.push(Word::new(names.intern(name), &do_divide));
+        }
+        words.push(Word::new(names.intern("WITHIN"), &do_within));
+        for name in ["=", "<>"].iter() {
+            words.push(Word::new(names.intern(name), &do_equals));
+        }
+        words.push(Word::new(names.intern("INVERT"), &do_invert));
+        words.push(Word::new(names.intern("DUP"), &do_dup));
+        words.push(Word::new(names.intern("DROP"), &do_drop));
+        words.push(Word::new(names.intern("SWAP"), &do_swap));
+        words.push(Word::new(names.intern("OVER"), &do_over));
+        words.push(Word::new(names.intern(":"), &do_colon));
+        words.push(Word::new(names.intern("WORDLIST"), &do_wordlist));
+        words.push(Word::new(names.intern("FORTH-WORDLIST"), &do_forth_wordlist));
+        words.push(Word::new(names.intern("GET-ORDER"), &do_get_order));
+        words.push(Word::new(names.intern("SET-ORDER"), &do_set_order));
+        words.push(Word::new(names.intern("GET-CURRENT"), &do_get_current));
+        words.push(Word::new(names.intern("SET-CURRENT"), &do_set_current));
+        words.push(Word::new(names.intern("DEFINITIONS"), &do_definitions));
+        words.push(Word::new(names.intern("FORTH"), &do_forth));
+        words.push(Word::new(names.intern("ONLY"), &do_only));
+        words.push(Word::new(names.intern("ALSO"), &do_also));
+        words.push(Word::new(names.intern("PREVIOUS"), &do_previous));
+        words.push(Word::new_immediate(names.intern("[IF]"), &do_bracket_if));
+        words.push(Word::new_immediate(names.intern("[ELSE]"), &do_bracket_else));
+        words.push(Word::new_immediate(names.intern("[THEN]"), &do_nop));
+        words.push(Word::new_immediate(names.intern("[DEFINED]"), &do_bracket_defined));
+        words.push(Word::new_immediate(names.intern("[UNDEFINED]"), &do_bracket_defined));
+        words.push(Word::new(names.intern("EVALUATE"), &do_evaluate));
+        words.push(Word::new_immediate(names.intern("S\""), &do_s_quote));
+        words.push(Word::new_immediate(names.intern("S\\\""), &do_s_backslash_quote));
+        words.push(Word::new_immediate(names.intern("("), &do_paren));
+        words.push(Word::new_immediate(names.intern("\\"), &do_backslash));
+        words.push(Word::new(names.intern("@"), &do_fetch));
+        words.push(Word::new(names.intern("!"), &do_store));
+        words.push(Word::new(names.intern("C@"), &do_c_fetch));
+        words.push(Word::new(names.intern("C!"), &do_c_store));
+        words.push(Word::new(names.intern("2@"), &do_two_fetch));
+        words.push(Word::new(names.intern("2!"), &do_two_store));
+        words.push(Word::new(names.intern("BL"), &do_bl));
+        words.push(Word::new(names.intern("SOURCE"), &do_source));
+        words.push(Word::new(names.intern(">IN"), &do_to_in));
+        words.push(Word::new(names.intern("WORD"), &do_word));
+        words.push(Word::new(names.intern("PARSE"), &do_parse));
+        words.push(Word::new(names.intern("PARSE-NAME"), &do_parse_name));
+        words.push(Word::new(names.intern("BASE"), &do_base));
+        words.push(Word::new(names.intern("DECIMAL"), &do_decimal));
+        words.push(Word::new(names.intern("HEX"), &do_hex));
+        words.push(Word::new(names.intern("S>D"), &do_s_to_d));
+        words.push(Word::new(names.intern("D+"), &do_d_plus));
+        words.push(Word::new(names.intern("M*"), &do_m_star));
+        words.push(Word::new(names.intern("<#"), &do_less_number_sign));
+        words.push(Word::new(names.intern("#"), &do_number_sign));
+        words.push(Word::new(names.intern("#S"), &do_number_sign_s));
+        words.push(Word::new(names.intern("HOLD"), &do_hold));
+        words.push(Word::new(names.intern("SIGN"), &do_sign));
+        words.push(Word::new(names.intern("#>"), &do_number_sign_greater));
+        words.push(Word::new(names.intern("TYPE"), &do_type));
+        words.push(Word::new(names.intern("DUMP"), &do_dump));
+        words.push(Word::new(names.intern("EMIT"), &do_emit));
+        words.push(Word::new(names.intern("XEMIT"), &do_emit));
+        words.push(Word::new(names.intern("XHOLD"), &do_hold));
+        words.push(Word::new(names.intern("CHAR"), &do_char));
+        words.push(Word::new_immediate(names.intern("[CHAR]"), &do_bracket_char));
+        words.push(Word::new(names.intern("XC-SIZE"), &do_xc_size));
+        words.push(Word::new(names.intern("X-SIZE"), &do_x_size));
+        words.push(Word::new(names.intern("XC@+"), &do_xc_fetch_plus));
+        words.push(Word::new(names.intern("XC!+?"), &do_xc_store_plus_question));
+        words.push(Word::new(names.intern("XCHAR+"), &do_xchar_plus));
+        words.push(Word::new(names.intern("CR"), &do_cr));
+        words.push(Word::new(names.intern("AT-XY"), &do_at_xy));
+        words.push(Word::new(names.intern("PAGE"), &do_page));
+        words.push(Word::new(names.intern("FG"), &do_colour));
+        words.push(Word::new(names.intern("BG"), &do_colour));
+        words.push(Word::new(names.intern("BOLD"), &do_bold));
+        words.push(Word::new(names.intern("NORMAL"), &do_normal));
+        words.push(Word::new(names.intern("."), &do_dot));
+        words.push(Word::new(names.intern(".R"), &do_dot_r));
+        words.push(Word::new(names.intern("U.R"), &do_u_dot_r));
+        words.push(Word::new(names.intern(">NUMBER"), &do_to_number));
+        words.push(Word::new(names.intern("NUMBER?"), &do_number_question));
+        words.push(Word::new(names.intern("HERE"), &do_here));
+        words.push(Word::new(names.intern("ALLOT"), &do_allot));
+        words.push(Word::new(names.intern(","), &do_comma));
+        words.push(Word::new(names.intern("CREATE"), &do_create));
+        words.push(Word::new(names.intern("VARIABLE"), &do_variable));
+        words.push(Word::new(names.intern("CONSTANT"), &do_constant));
+        words.push(Word::new(names.intern("2VARIABLE"), &do_two_variable));
+        words.push(Word::new(names.intern("2CONSTANT"), &do_two_constant));
+        words.push(Word::new(names.intern("MODULE"), &do_module));
+        words.push(Word::new(names.intern("PRIVATE"), &do_private));
+        words.push(Word::new(names.intern("PUBLIC"), &do_public));
+        words.push(Word::new(names.intern("END-MODULE"), &do_end_module));
+        words.push(Word::new(names.intern("BEGIN-STRUCTURE"), &do_begin_structure));
+        words.push(Word::new(names.intern("+FIELD"), &do_plus_field));
+        words.push(Word::new(names.intern("FIELD:"), &do_field));
+        words.push(Word::new(names.intern("CFIELD:"), &do_cfield));
+        words.push(Word::new(names.intern("END-STRUCTURE"), &do_end_structure));
+        words.push(Word::new(names.intern("MOVE"), &do_move));
+        words.push(Word::new(names.intern("CMOVE"), &do_move));
+        words.push(Word::new(names.intern("CMOVE>"), &do_move));
+        words.push(Word::new(names.intern("FILL"), &do_fill));
+        words.push(Word::new(names.intern("ERASE"), &do_erase));
+        words.push(Word::new(names.intern("COUNT"), &do_count));
+        words.push(Word::new(names.intern("COMPARE"), &do_compare));
+        words.push(Word::new(names.intern("ALLOCATE"), &do_allocate));
+        words.push(Word::new(names.intern("FREE"), &do_free));
+        words.push(Word::new(names.intern("RESIZE"), &do_resize));
+        words.push(Word::new(names.intern("KEY"), &do_key));
+        words.push(Word::new(names.intern("XKEY"), &do_key));
+        words.push(Word::new(names.intern("KEY?"), &do_key_question));
+        words.push(Word::new(names.intern("EKEY"), &do_ekey));
+        words.push(Word::new(names.intern("EKEY?"), &do_ekey_question));
+        words.push(Word::new(names.intern("EKEY>CHAR"), &do_ekey_to_char));
+        define_key_constants(&mut words, &mut bodies, &mut names);
+        words.push(Word::new(names.intern("MOUSE-X"), &do_mouse_x));
+        words.push(Word::new(names.intern("MOUSE-Y"), &do_mouse_y));
+        words.push(Word::new(names.intern("BUTTON?"), &do_button_question));
+        words.push(Word::new(names.intern("SAVE-DICT"), &do_save_dict));
+        words.push(Word::new(names.intern("LOAD-DICT"), &do_load_dict));
+        words.push(Word::new(names.intern("BLOCK"), &do_block));
+        words.push(Word::new(names.intern("BUFFER"), &do_buffer));
+        words.push(Word::new(names.intern("UPDATE"), &do_update));
+        words.push(Word::new(names.intern("SAVE-BUFFERS"), &do_save_buffers));
+        words.push(Word::new(names.intern("EMPTY-BUFFERS"), &do_empty_buffers));
+        words.push(Word::new(names.intern("FLUSH"), &do_flush));
+        words.push(Word::new(names.intern("LIST"), &do_list));
+        words.push(Word::new(names.intern("LOAD"), &do_load));
+        words.push(Word::new(names.intern("INCLUDED"), &do_included));
+        words.push(Word::new(names.intern("INCLUDE"), &do_include));
+        words.push(Word::new(names.intern("REQUIRED"), &do_required));
+        words.push(Word::new(names.intern("REQUIRE"), &do_require));
+        words.push(Word::new(names.intern("ACCEPT"), &do_accept));
+        words.push(Word::new(names.intern("MS"), &do_ms));
+        words.push(Word::new(names.intern("TICKS"), &do_ticks));
+        words.push(Word::new(names.intern("COUNTER"), &do_counter));
+        words.push(Word::new(names.intern("ELAPSED"), &do_elapsed));
+        words.push(Word::new(names.intern("TIME&DATE"), &do_time_and_date));
+        words.push(Word::new(names.intern("RANDOM"), &do_random));
+        words.push(Word::new(names.intern("SEED"), &do_seed));
+        words.push(Word::new(names.intern("LOG"), &do_log));
+        words.push(Word::new(names.intern("LOG-TYPE"), &do_log_type));
+        words.push(Word::new(names.intern("EVERY-FRAME"), &do_every_frame));
+        words.push(Word::new(names.intern("STOP-FRAMES"), &do_stop_frames));
+        words.push(Word::new(names.intern("+RECOGNIZER"), &do_add_recognizer));
+        words.push(Word::new(names.intern("AFTER"), &do_after));
+        words.push(Word::new(names.intern("EVERY"), &do_every));
+        words.push(Word::new(names.intern("CANCEL"), &do_cancel));
+        words.push(Word::new(names.intern("TASK"), &do_task));
+        words.push(Word::new(names.intern("PAUSE"), &do_pause));
+        words.push(Word::new(names.intern("STOP-TASK"), &do_stop_task));
+        words.push(Word::new(names.intern("SEND"), &do_send));
+        words.push(Word::new(names.intern("RECV?"), &do_receive_question));
+        words.push(Word::new_keeping_frame(names.intern("RECV"), &do_receive));
+        words.push(Word::new(names.intern("'"), &do_tick));
+        words.push(Word::new(names.intern("EXECUTE"), &do_execute));
+        words.push(Word::new(names.intern("CATCH"), &do_catch));
+        words.push(Word::new_keeping_frame(names.intern("EXIT"), &do_exit));
+        words.push(Word::new_immediate(names.intern("RECURSE"), &do_recurse));
+        words.push(Word::new_immediate(names.intern("[:"), &do_start_quotation));
+        words.push(Word::new_immediate(names.intern(";]"), &do_end_quotation));
+        words.push(Word::new(names.intern("TIMES"), &do_times));
+        words.push(Word::new(names.intern("DIP"), &do_dip));
+        words.push(Word::new(names.intern("SYNONYM"), &do_synonym));
+        words.push(Word::new(names.intern("ALIAS"), &do_alias));
+        words.push(Word::new(names.intern("HELP"), &do_help));
+        words.push(Word::new(names.intern("T{"), &do_t_open));
+        words.push(Word::new(names.intern("->"), &do_arrow));
+        words.push(Word::new(names.intern("}T"), &do_t_close));
+        if self.strings {
+            words.push(Word::new_immediate(names.intern("$\""), &do_string_quote));
+            words.push(Word::new(names.intern(">$"), &do_to_string));
+            words.push(Word::new(names.intern("$@"), &do_string_fetch));
+            words.push(Word::new(names.intern("$LEN"), &do_string_len));
+            words.push(Word::new(names.intern("$DEPTH"), &do_string_depth));
+            words.push(Word::new(names.intern("$."), &do_string_dot));
+            words.push(Word::new(names.intern("$DROP"), &do_string_drop));
+            words.push(Word::new(names.intern("$DUP"), &do_string_dup));
+            words.push(Word::new(names.intern("$SWAP"), &do_string_swap));
+            words.push(Word::new(names.intern("$+"), &do_string_plus));
+            words.push(Word::new(names.intern("$SUB"), &do_string_sub));
+            words.push(Word::new(names.intern("$SEARCH"), &do_string_search));
+            words.push(Word::new(names.intern("$="), &do_string_equals));
+            words.push(Word::new(names.intern("$>N"), &do_string_to_number));
+            words.push(Word::new(names.intern("N>$"), &do_number_to_string));
+            words.push(Word::new(names.intern("$SEND"), &do_string_send));
+            words.push(Word::new(names.intern("$RECV?"), &do_string_receive_question));
+            words.push(Word::new_keeping_frame(names.intern("$RECV"), &do_string_receive));
+        }
+        #[cfg(feature = "typed")]
+        {
+            if self.typed {
+                words.push(Word::new_immediate(names.intern("\""), &do_string_literal));
+                words.push(Word::new(names.intern(">STRING"), &do_to_string_value));
+                words.push(Word::new(names.intern("S>F"), &do_s_to_f));
+                words.push(Word::new(names.intern("F>S"), &do_f_to_s));
+                words.push(Word::new(names.intern("BITS>F"), &do_bits_to_f));
+            }
+        }
+        if self.standard {
+            let extensions: Vec<Symbol> = EXTENSIONS.iter().map(|name| names.intern(name)).collect();
+            for word in words.iter_mut().filter(|word| extensions.contains(&word.name)) {
+                word.wordlist = EXTENSIONS_WORDLIST;
+            }
+        }
+        let tib = tib_address(self.data_space_size).expect("data space too large");
+        let builtins = words.len();
+        let mut forth = Forth {
+            stack: Vec::with_capacity(STACK_CAPACITY),
+            return_stack: Vec::with_capacity(RETURN_STACK_CAPACITY),
+            frame_base: 0,
+            words,
+            bodies,
+            body_spans: Arena::new(),
+            names,
+            index: Vec::new(),
+            wordlists: if self.standard { EXTENSIONS_WORDLIST + 1 } else { 1 },
+            order: vec![FORTH_WORDLIST as usize],
+            current: FORTH_WORDLIST as usize,
+            memory: new_memory(&[], tib),
+            here: DATA_START,
+            source: (tib, 0),
+            heap: Heap::new(self.data_space_size, HEAP_SIZE),
+            input: VecDeque::new(),
+            definition: None,
+            unfinished: None,
+            hold: HOLD_BUFFER + HOLD_BUFFER_SIZE,
+            output: String::new(),
+            output_sink: None,
+            log_sink: None,
+            profiler: None,
+            coverage: None,
+            hooks: Hooks::default(),
+            counters: Counters::default(),
+            tracer: None,
+            recorder: None,
+            debugger: None,
+            breakpoints: Vec::new(),
+            protected: Vec::new(),
+            frozen: false,
+            watchpoints: Vec::new(),
+            watch_hit: None,
+            input_source: None,
+            dictionary_store: None,
+            block_storage: Some(Box::new(MemoryBlocks::default())),
+            block_buffer: BlockBuffer::default(),
+            source_provider: None,
+            word_compiler: None,
+            pointer: Pointer::default(),
+            frame_word: None,
+            recognizers: Vec::new(),
+            quotations: Vec::new(),
+            timers: Timers::default(),
+            tasks: Tasks::default(),
+            strings: Vec::new(),
+            #[cfg(feature = "typed")]
+            typed: None,
+            modules: Vec::new(),
+            tester: Tester::default(),
+            warnings: Vec::new(),
+            random: Random::default(),
+            clock: None,
+            channels: Box::new(MemoryChannels::default()),
+            deterministic: self.deterministic,
+            virtual_ticks: 0,
+            data_space_observer: None,
+            included_files: Vec::new(),
+            last_span: Span::default(),
+            input_span: Span::default(),
+            names_parsed: 0,
+            error_location: None,
+            error_trace: Vec::new(),
+            stack_depth: None,
+            return_stack_depth: None,
+            call_depth: None,
+            overflow: self.overflow,
+            division: self.division,
+            case_sensitive: self.case_sensitive,
+            data_space_size: self.data_space_size,
+            max_data_space_size: self.max_data_space_size.max(self.data_space_size),
+            max_words: None,
+            max_output: None,
+            max_input_len: None,
+            max_tokens: None,
+            max_name_len: None,
+            max_definition_len: None,
+            output_len: 0,
+            truncate_output: self.truncate_output,
+            output_truncated: false,
+            echo_input: false,
+            ok_prompt: false,
+            terminal: None,
+            messages: Messages::default(),
+            tib,
+            string_buffer: 0,
+            builtins,
+            primitives: builtins,
+            optimize: self.optimize,
+            inline_limit: self.inline_limit,
+            lexed: None,
+            name_buffer: String::new(),
+            fuel: None,
+            paused: false,
+            pending: VecDeque::new(),
+            executing: 0,
+            keep_undo: false,
+            undo: None,
+        };
+        forth.store(BASE as Value, 10).unwrap();
+        forth.reindex();
+        // The limits are for programs, so the standard prelude isn't held
+        // to them.
+        if self.standard_prelude {
+            if let Err(error) = forth.eval(STANDARD_PRELUDE) {
+                let location = forth.error_location.take();
+                return Err(PreludeError { error, location });
+            }
+        }
+        forth.stack_depth = self.stack_depth;
+        forth.return_stack_depth = self.return_stack_depth;
+        forth.call_depth = self.call_depth;
+        forth.max_output = self.max_output;
+        forth.max_input_len = self.max_input_len;
+        forth.max_tokens = self.max_tokens;
+        forth.max_name_len = self.max_name_len;
+        forth.max_definition_len = self.max_definition_len;
+        for (name, f) in self.natives {
+            forth.define_native(&name, move |stack| f(stack));
+        }
+        if let Some(prelude) = self.prelude {
+            if let Err(error) = forth.eval(&prelude) {
+                let location = forth.error_location.take();
+                return Err(PreludeError { error, location });
+            }
+            if forth.unfinished.is_some() {
+                return Err(PreludeError { error: Error::UnterminatedDefinition, location: None });
+            }
+        }
+        forth.builtins = forth.words.len();
+        forth.max_words = self.max_words;
+        forth.frozen = self.frozen;
+        forth.keep_undo = self.undo;
+        forth.echo_input = self.echo_input;
+        forth.ok_prompt = self.ok_prompt;
+        forth.terminal = self.terminal.map(|(columns, rows)| TerminalGrid::new(columns, rows));
+        forth.messages = Messages::new(self.language);
+        #[cfg(feature = "typed")]
+        {
+            forth.typed = if self.typed { Some(Types::default()) } else { None };
+        }
+        Ok(forth)
+    }
+}
+
+impl Default for ForthBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Forth {
+    pub fn new() -> Forth {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> ForthBuilder {
+        ForthBuilder::new()
+    }
+
+    /// An independent copy of the interpreter: stack, dictionary, data
+    /// space, pending input and any evaluation paused for lack of fuel.
+    /// Changes to either don't affect the other, so the copy can run code
+    /// that may fail and simply be dropped. The output sink and input
+    /// source are not carried over; the copy collects its output for
+    /// `output` and reads only queued input.
+    pub fn fork(&self) -> Forth {
+        Forth {
+            stack: self.stack.clone(),
+            return_stack: self.return_stack.clone(),
+            frame_base: self.frame_base,
+            words: self.words.clone(),
+            bodies: self.bodies.clone(),
+            body_spans: self.body_spans.clone(),
+            names: self.names.clone(),
+            index: self.index.clone(),
+            wordlists: self.wordlists,
+            order: self.order.clone(),
+            current: self.current,
+            memory: new_memory(&self.memory, self.tib),
+            here: self.here,
+            source: self.source,
+            definition: self.definition.clone(),
+            unfinished: self.unfinished.clone(),
+            heap: self.heap.clone(),
+            input: self.input.clone(),
+            hold: self.hold,
+            output: self.output.clone(),
+            last_span: self.last_span,
+            input_span: self.input_span,
+            names_parsed: self.names_parsed,
+            error_location: self.error_location.clone(),
+            error_trace: self.error_trace.clone(),
+            stack_depth: self.stack_depth,
+            return_stack_depth: self.return_stack_depth,
+            call_depth: self.call_depth,
+            overflow: self.overflow,
+            division: self.division,
+            case_sensitive: self.case_sensitive,
+            data_space_size: self.data_space_size,
+            max_data_space_size: self.max_data_space_size,
+            max_words: self.max_words,
+            max_output: self.max_output,
+            max_input_len: self.max_input_len,
+            max_tokens: self.max_tokens,
+            max_name_len: self.max_name_len,
+            max_definition_len: self.max_definition_len,
+            output_len: self.output_len,
+            truncate_output: self.truncate_output,
+            output_truncated: self.output_truncated,
+            echo_input: self.echo_input,
+            ok_prompt: self.ok_prompt,
+            terminal: self.terminal.clone(),
+            messages: self.messages.clone(),
+            tib: self.tib,
+            string_buffer: self.string_buffer,
+            builtins: self.builtins,
+            primitives: self.primitives,
+            optimize: self.optimize,
+            inline_limit: self.inline_limit,
+            output_sink: None,
+            log_sink: None,
+            profiler: None,
+            coverage: self.coverage.clone(),
+            hooks: Hooks::default(),
+            counters: self.counters,
+            tracer: None,
+            recorder: None,
+            debugger: self.debugger,
+            breakpoints: self.breakpoints.clone(),
+            protected: self.protected.clone(),
+            frozen: self.frozen,
+            watchpoints: self.watchpoints.clone(),
+            watch_hit: self.watch_hit.clone(),
+            input_source: None,
+            dictionary_store: None,
+            block_storage: None,
+            block_buffer: self.block_buffer,
+            source_provider: None,
+            word_compiler: None,
+            pointer: self.pointer,
+            frame_word: self.frame_word,
+            recognizers: self.recognizers.clone(),
+            quotations: self.quotations.clone(),
+            timers: self.timers.clone(),
+            tasks: self.tasks.clone(),
+            strings: self.strings.clone(),
+            #[cfg(feature = "typed")]
+            typed: self.typed.clone(),
+            modules: self.modules.clone(),
+            tester: self.tester.clone(),
+            warnings: Vec::new(),
+            random: self.random,
+            clock: None,
+            channels: Box::new(MemoryChannels::default()),
+            deterministic: self.deterministic,
+            virtual_ticks: self.virtual_ticks,
+            data_space_observer: None,
+            included_files: self.included_files.clone(),
+            lexed: self.lexed.clone(),
+            name_buffer: String::new(),
+            fuel: self.fuel,
+            paused: self.paused,
+            pending: self.pending.clone(),
+            executing: 0,
+            keep_undo: self.keep_undo,
+            undo: None,
+        }
+    }
+
+    pub fn stack(&self) -> Vec<Value> {
+        self.stack.clone()
+    }
+
+    /// The data stack, bottom first, without copying it.
+    pub fn stack_slice(&self) -> &[Value] {
+        &self.stack
+    }
+
+    /// Pushes `v` onto the data stack, e.g. to pass arguments to the next
+    /// `eval`.
+    pub fn push(&mut self, v: Value) {
+        self.stack.push(v);
+    }
+
+    /// Removes and returns the top of the data stack.
+    pub fn pop(&mut self) -> Result<Value, Error> {
+        self.stack.pop().ok_or(Error::StackUnderflow)
+    }
+
+    /// The item `n` places below the top of the data stack; `peek(0)` is the
+    /// top.
+    pub fn peek(&self, n: usize) -> Option<Value> {
+        self.stack.len().checked_sub(n + 1).map(|i| self.stack[i])
+    }
+
+    /// Number of items on the data stack.
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// Iterates over the data stack, bottom first.
+    pub fn iter(&self) -> slice::Iter<'_, Value> {
+        self.stack.iter()
+    }
+
+    /// Text printed by the program so far.
+    pub fn output(&self) -> &str {
+        &self.output
+    }
+
+    /// Whether the last evaluation's output was cut short at `max_output`,
+    /// as `truncate_output` allows.
+    pub fn output_truncated(&self) -> bool {
+        self.output_truncated
+    }
+
+    /// What the definitions made by the last evaluation, including any
+    /// resumed or included parts, were warned of: hiding a word already
+    /// defined, or taking a name that reads as a number. Host-defined
+    /// words aren't checked.
+    ///
+    /// ```
+    /// use forth_core::{Forth, Warning};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": DUP 1 ; VARIABLE X : SQ DUP * ; 20 CONSTANT 10").unwrap();
+    /// assert_eq!(f.warnings(), [
+    ///     Warning::Redefined("DUP".into()),
+    ///     Warning::NumericName("10".into()),
+    /// ]);
+    /// assert_eq!(f.warnings()[0].to_string(), "redefined DUP");
+    /// f.eval("CREATE X").unwrap();
+    /// assert_eq!(f.warnings(), [Warning::Redefined("X".into())]);
+    /// ```
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Adds a word implemented by `f` to the current compilation wordlist,
+    /// shadowing any earlier word of the same name.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.define_native("TRIPLE", |stack| {
+    ///     let v = stack.pop().ok_or(Error::StackUnderflow)?;
+    ///     stack.push(v * 3);
+    ///     Ok(())
+    /// });
+    /// f.eval("5 TRIPLE").unwrap();
+    /// assert_eq!(f.stack(), vec![15]);
+    /// ```
+    pub fn define_native<F>(&mut self, name: &str, f: F)
+        where F: Fn(&mut Stack) -> ForthResult + 'static
+    {
+        let mut word = Word::new_compiled(self.intern(name), Slot::default(), self.current);
+        word.exec = Some(Rc::new(move |forth: &mut Forth, _index| f(&mut forth.stack)));
+        word.native = true;
+        self.add_word(word);
+    }
+
+    /// Like `define_native`, but `f` also gets the data space and heap, so
+    /// that the word can take strings and buffers as address and length.
+    /// The slice is indexed by address.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.define_native_with_memory("VOWELS", |stack, memory| {
+    ///     let len = stack.pop().ok_or(Error::StackUnderflow)? as usize;
+    ///     let addr = stack.pop().ok_or(Error::StackUnderflow)? as usize;
+    ///     let text = memory.get(addr..addr + len).ok_or(Error::InvalidAddress)?;
+    ///     stack.push(text.iter().filter(|c| b"AEIOU".contains(c)).count() as _);
+    ///     Ok(())
+    /// });
+    /// f.eval(": GREETING S\" HELLO WORLD\" ; GREETING VOWELS").unwrap();
+    /// assert_eq!(f.stack(), vec![3]);
+    /// ```
+    pub fn define_native_with_memory<F>(&mut self, name: &str, f: F)
+        where F: Fn(&mut Stack, &mut [u8]) -> ForthResult + 'static
+    {
+        let mut word = Word::new_compiled(self.intern(name), Slot::default(), self.current);
+        word.exec = Some(Rc::new(move |forth: &mut Forth, _index| {
+            let tib = forth.tib;
+            f(&mut forth.stack, &mut forth.memory[..tib])
+        }));
+        word.native = true;
+        self.add_word(word);
+    }
+
+    /// Sends program output to `sink` as it is produced rather than
+    /// collecting it for `output`.
+    pub fn set_output_sink<F>(&mut self, mut sink: F)
+        where F: FnMut(&str) + 'static
+    {
+        self.set_fallible_output_sink(move |text| {
+            sink(text);
+            Ok(())
+        });
+    }
+
+    /// Like `set_output_sink`, for a sink that can fail. Its error is that
+    /// of the word that printed.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.set_fallible_output_sink(|text| match text.contains('!') {
+    ///     true => Err(Error::Native("no shouting".into())),
+    ///     false => Ok(()),
+    /// });
+    /// f.eval(r#": HI S" hi" TYPE ; : HEY S" hey!" TYPE ;"#).unwrap();
+    /// assert_eq!(f.eval("HI HEY 1"), Err(Error::Native("no shouting".into())));
+    /// assert_eq!(f.eval("HEY").unwrap_err().throw_code(), -263);
+    /// ```
+    pub fn set_fallible_output_sink<F>(&mut self, sink: F)
+        where F: FnMut(&str) -> ForthResult + 'static
+    {
+        self.output_sink = Some(Box::new(sink));
+    }
+
+    /// Goes back to collecting program output for `output`.
+    pub fn clear_output_sink(&mut self) {
+        self.output_sink = None;
+    }
+
+    fn emit(&mut self, text: &str) -> ForthResult {
+        let room = self.max_output.map_or(usize::MAX, |max| max.saturating_sub(self.output_len));
+        self.output_len += text.len();
+        let text = match text.len() > room {
+            false => text,
+            true if self.truncate_output => {
+                self.output_truncated = true;
+                let mut end = room;
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                if end == 0 {
+                    return Ok(());
+                }
+                &text[..end]
+            }
+            true => return Err(Error::OutputLimitExceeded),
+        };
+        self.write_terminal(text);
+        match self.output_sink {
+            Some(ref mut sink) => sink(text),
+            None => {
+                self.output.push_str(text);
+                Ok(())
+            }
+        }
+    }
+
+    /// Outputs `text` right-aligned in `width` characters, at most
+    /// `MAX_FIELD_WIDTH`, a run of spaces at a time, so that a field wider
+    /// than `max_output` allows fails or is cut short as any other output
+    /// would.
+    fn emit_right(&mut self, text: &str, width: Value) -> ForthResult {
+        const SPACES: &str = "                                                                ";
+        let width = usize::try_from(width.max(0)).map_or(MAX_FIELD_WIDTH, |width| width.min(MAX_FIELD_WIDTH));
+        let mut padding = width.saturating_sub(text.chars().count());
+        while padding > 0 {
+            let n = padding.min(SPACES.len());
+            self.emit(&SPACES[..n])?;
+            if self.output_truncated {
+                return Ok(());
+            }
+            padding -= n;
+        }
+        self.emit(text)
+    }
+
+    /// The data space, where HERE, ALLOT and `,` place data and VARIABLE and
+    /// CREATE put their bodies, as `data_space_size` sized it.
+    pub fn data_space(&self) -> &[u8] {
+        &self.memory[..self.data_space_size]
+    }
+
+    /// The data space, for the host to change.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval("VARIABLE X X").unwrap();
+    /// let x = f.pop().unwrap() as usize;
+    /// f.data_space_mut()[x] = 7;
+    /// f.eval("X @").unwrap();
+    /// assert_eq!(f.stack(), vec![7]);
+    /// ```
+    pub fn data_space_mut(&mut self) -> &mut [u8] {
+        &mut self.memory[..self.data_space_size]
+    }
+
+    /// Shows `observer` the data space each time an evaluation, `resume`
+    /// or debugging step stops, whether it finished, failed or paused, and
+    /// after restoring a snapshot or image, so the host can mirror it
+    /// somewhere without polling.
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use forth_core::Forth;
+    ///
+    /// let mirror = Rc::new(RefCell::new(Vec::new()));
+    /// let copy = mirror.clone();
+    /// let mut f = Forth::new();
+    /// f.set_data_space_observer(move |data| *copy.borrow_mut() = data.to_vec());
+    /// f.eval("VARIABLE X 42 X ! X").unwrap();
+    /// let x = f.stack()[0] as usize;
+    /// assert_eq!(mirror.borrow()[x], 42);
+    /// ```
+    pub fn set_data_space_observer<F>(&mut self, observer: F)
+        where F: FnMut(&[u8]) + 'static
+    {
+        self.data_space_observer = Some(Box::new(observer));
+    }
+
+    pub fn clear_data_space_observer(&mut self) {
+        self.data_space_observer = None;
+    }
+
+    fn observe_data_space(&mut self) {
+        if let Some(ref mut observer) = self.data_space_observer {
+            observer(&self.memory[..self.data_space_size]);
+        }
+    }
+
+    /// Queues `text` to be read by KEY and ACCEPT.
+    pub fn push_input(&mut self, text: &str) {
+        self.input.extend(text.chars());
+    }
+
+    /// Has KEY and ACCEPT call `source` for more text once the queued input
+    /// runs out; it returns `None` at end of input. KEY? only reports input
+    /// that is already queued. A deterministic interpreter ignores it.
+    pub fn set_input_source<F>(&mut self, mut source: F)
+        where F: FnMut() -> Option<String> + 'static
+    {
+        self.set_fallible_input_source(move || Ok(source()));
+    }
+
+    /// Like `set_input_source`, for a source that can fail, as one calling
+    /// out to another language might. Its error is that of the KEY or
+    /// ACCEPT that asked for more.
+    pub fn set_fallible_input_source<F>(&mut self, source: F)
+        where F: FnMut() -> Result<Option<String>, Error> + 'static
+    {
+        if self.deterministic {
+            return;
+        }
+        self.input_source = Some(Box::new(source));
+    }
+
+    /// Goes back to reading only the input queued with `push_input`.
+    pub fn clear_input_source(&mut self) {
+        self.input_source = None;
+    }
+
+    fn fill_input(&mut self) -> ForthResult {
+        if !self.input.is_empty() {
+            return Ok(());
+        }
+        if let Some(ref mut source) = self.input_source {
+            if let Some(text) = source()? {
+                self.input.extend(text.chars());
+            }
+        }
+        Ok(())
+    }
+
+    /// Pops a double-cell value, most significant cell on top.
+    fn pop_double(&mut self) -> Result<Double, Error> {
+        let hi = self.pop()?;
+        let lo = self.pop()?;
+        Ok((Double::from(hi) << Value::BITS) | Double::from(lo as Unsigned))
+    }
+
+    fn push_double(&mut self, d: Double) {
+        self.stack.push(d as Value);
+        self.stack.push((d >> Value::BITS) as Value);
+    }
+
+    fn base(&self) -> Result<u32, Error> {
+        match self.fetch(BASE as Value)? {
+            b @ 2..=36 => Ok(b as u32),
+            _ => Err(Error::InvalidBase),
+        }
+    }
+
+    fn hold_char(&mut self, c: u8) -> ForthResult {
+        if self.hold == HOLD_BUFFER {
+            return Err(Error::InvalidAddress);
+        }
+        self.hold -= 1;
+        self.memory[self.hold] = c;
+        Ok(())
+    }
+
+    /// Holds the least significant digit of `ud` in the current base and
+    /// returns the remaining quotient.
+    fn hold_digit(&mut self, ud: UDouble) -> Result<UDouble, Error> {
+        let base = self.base()?;
+        let digit = core::char::from_digit((ud % UDouble::from(base)) as u32, base).unwrap();
+        self.hold_char(digit.to_ascii_uppercase() as u8)?;
+        Ok(ud / UDouble::from(base))
+    }
+
+    /// Writes `magnitude` in the current base, after a minus sign if
+    /// `negative`, through the pictured numeric output buffer.
+    fn number_text(&mut self, magnitude: Unsigned, negative: bool) -> Result<String, Error> {
+        let end = HOLD_BUFFER + HOLD_BUFFER_SIZE;
+        self.hold = end;
+        let mut ud = UDouble::from(magnitude);
+        loop {
+            ud = self.hold_digit(ud)?;
+            if ud == 0 {
+                break;
+            }
+        }
+        if negative {
+            self.hold_char(b'-')?;
+        }
+        Ok(String::from_utf8_lossy(&self.memory[self.hold..end]).into_owned())
+    }
+
+    /// Pushes `v` when interpreting, or compiles it into the current
+    /// definition as a literal.
+    fn literal(&mut self, v: Value) {
+        let span = self.last_span;
+        match self.definition {
+            Some(ref mut body) => body.push(Op::Literal(v), span),
+            None => self.stack.push(v),
+        }
+    }
+
+    fn check_range(&self, addr: Value, len: Value) -> ForthResult {
+        self.memory_range(addr, len).map(|_| ())
+    }
+
+    /// The bytes of memory from `addr` for `len`, failing with
+    /// `InvalidAddress` unless all are there, however wide cells are.
+    fn memory_range(&self, addr: Value, len: Value) -> Result<Range<usize>, Error> {
+        let start = usize::try_from(addr).map_err(|_| Error::InvalidAddress)?;
+        let len = usize::try_from(len).map_err(|_| Error::InvalidAddress)?;
+        match start.checked_add(len) {
+            Some(end) if end <= self.memory.len() => Ok(start..end),
+            _ => Err(Error::InvalidAddress),
+        }
+    }
+
+    /// Reserves `n` bytes of data space (releasing them if `n` is
+    /// negative) and returns the address of the reserved region.
+    fn allot(&mut self, n: Value) -> Result<Value, Error> {
+        let addr = self.here as Value;
+        let here = match addr.checked_add(n) {
+            Some(here) => here,
+            None if n > 0 => return Err(Error::DataSpaceFull),
+            None => return Err(Error::InvalidAddress),
+        };
+        if here < DATA_START as Value {
+            return Err(Error::InvalidAddress);
+        }
+        if here as usize > self.data_space_size {
+            return Err(Error::DataSpaceFull);
+        }
+        self.here = here as usize;
+        Ok(addr)
+    }
+
+    /// The address of the terminal input buffer past a data space of `size`
+    /// bytes being loaded, with the data-space pointer at `here` and `used`
+    /// bytes of memory saved, or `None` if those don't fit together: the
+    /// space is larger than `max_data_space_size` allows or than cells can
+    /// address, or `here` or the memory lies outside it.
+    fn loaded_tib(&self, size: usize, here: usize, used: usize) -> Option<usize> {
+        if size > self.max_data_space_size || here < DATA_START || here > size {
+            return None;
+        }
+        let tib = tib_address(size)?;
+        let end = tib.checked_add(TIB_CAPACITY)?;
+        Value::try_from(end).ok()?;
+        if used > tib {
+            return None;
+        }
+        Some(tib)
+    }
+
+    /// Rebuilds a dictionary from saved entries, taking the code of native
+    /// words from the entries at the same index in the current dictionary.
+    /// Returns `None` if those don't match, or if a saved entry refers to a
+    /// word outside the dictionary. The entries' bodies and spans are in the
+    /// arenas returned with them.
+    fn revive_words(&mut self, saved: Vec<SavedWord>) -> Option<(Vec<Word>, Arena<Op>, Arena<Span>)> {
+        let count = saved.len();
+        let mut words = Vec::with_capacity(count);
+        let (mut bodies, mut body_spans) = (Arena::new(), Arena::new());
+        for (index, saved) in saved.into_iter().enumerate() {
+            let name = self.names.intern(&saved.name);
+            let mut word = Word::new_compiled(name, bodies.alloc(&saved.data), saved.wordlist);
+            if saved.native {
+                let native = self.words.get(index).filter(|native| native.native && native.name == name)?;
+                word.exec = native.exec.clone();
+                word.native = true;
+                word.keeps_frame = native.keeps_frame;
+            }
+            let in_range = |op: &Op| match *op {
+                Op::Call(target) => target < count,
+                _ => true,
+            };
+            if !saved.data.iter().all(in_range) || saved.alias_of.is_some_and(|target| target >= count) {
+                return None;
+            }
+            word.immediate = saved.immediate;
+            word.alias_of = saved.alias_of;
+            word.spans = body_spans.alloc(&saved.spans);
+            word.source = saved.source;
+            words.push(word);
+        }
+        Some((words, bodies, body_spans))
+    }
+
+    /// The form of `name` stored in and looked up from the dictionary,
+    /// borrowed when `name` is already in that form.
+    fn canonical_name<'n>(&self, name: &'n str) -> Cow<'n, str> {
+        if self.is_canonical(name) {
+            Cow::Borrowed(name)
+        } else {
+            Cow::Owned(name.to_uppercase())
+        }
+    }
+
+    fn is_canonical(&self, name: &str) -> bool {
+        self.case_sensitive || name.is_ascii() && !name.bytes().any(|b| b.is_ascii_lowercase())
+    }
+
+    /// `UnknownWord` for `name`, as the dictionary would have had it.
+    fn unknown_word(&self, name: &str) -> Error {
+        Error::UnknownWord(self.canonical_name(name).into_owned())
+    }
+
+    /// The symbol of `name`'s canonical form, if any word has had it.
+    /// Short ASCII names are folded on the stack rather than allocated.
+    fn canonical_symbol(&self, name: &str) -> Option<Symbol> {
+        if self.is_canonical(name) {
+            return self.names.get(name);
+        }
+        let mut buffer = [0; 32];
+        if name.is_ascii() && name.len() <= buffer.len() {
+            let upper = &mut buffer[..name.len()];
+            upper.copy_from_slice(name.as_bytes());
+            upper.make_ascii_uppercase();
+            return self.names.get(str::from_utf8(upper).unwrap());
+        }
+        self.names.get(&name.to_uppercase())
+    }
+
+    /// Validates an execution token, which is a dictionary index.
+    fn xt(&self, xt: Value) -> Result<usize, Error> {
+        usize::try_from(xt).ok().filter(|&index| index < self.words.len()).ok_or(Error::InvalidWord)
+    }
+
+    /// Fails with `DictionaryFrozen` or `DictionaryFull` if no more words
+    /// may be defined.
+    fn check_dictionary(&self) -> ForthResult {
+        if self.frozen {
+            return Err(Error::DictionaryFrozen);
+        }
+        match self.max_words {
+            Some(max) if self.words.len() - self.builtins >= max => Err(Error::DictionaryFull),
+            _ => Ok(()),
+        }
+    }
+
+    /// Notes a warning if defining a word named `name` hides another or
+    /// takes a number's place.
+    fn check_name(&mut self, name: Symbol) {
+        let text = self.names.resolve(name);
+        let warning = if self.lookup_symbol(name).is_some() {
+            Warning::Redefined(String::from(text))
+        } else if let Ok(Some(_)) = parse_number(text, self.base().unwrap_or(10)) {
+            Warning::NumericName(String::from(text))
+        } else {
+            return;
+        };
+        self.warnings.push(warning);
+    }
+
+    fn define_alias(&mut self, name: &str, target: usize) -> ForthResult {
+        self.check_dictionary()?;
+        let wordlist = self.current;
+        let name = self.intern(name);
+        self.check_protected(name)?;
+        self.check_name(name);
+        let alias = Word::new_alias(name, target, wordlist);
+        self.add_word(alias);
+        Ok(())
+    }
+
+    /// Defines the next name in the input as a word pushing `v`.
+    fn define_constant(&mut self, v: Value) -> ForthResult {
+        self.define_compiled(&[Op::Literal(v)])
+    }
+
+    /// Defines the next name in the input as a word running `ops`.
+    fn define_compiled(&mut self, ops: &[Op]) -> ForthResult {
+        self.check_dictionary()?;
+        let name = self.parse_new_name()?;
+        let wordlist = self.current;
+        let name = self.intern(&name);
+        self.check_protected(name)?;
+        self.check_name(name);
+        let word = Word::new_compiled(name, self.bodies.alloc(ops), wordlist);
+        self.add_word(word);
+        Ok(())
+    }
+
+    fn fetch(&self, addr: Value) -> Result<Value, Error> {
+        self.check_range(addr, CELL as Value)?;
+        let addr = addr as usize;
+        let mut bytes = [0; CELL];
+        bytes.copy_from_slice(&self.memory[addr..addr + CELL]);
+        Ok(Value::from_le_bytes(bytes))
+    }
+
+    fn store(&mut self, addr: Value, v: Value) -> ForthResult {
+        self.check_range(addr, CELL as Value)?;
+        let addr = addr as usize;
+        self.memory[addr..addr + CELL].copy_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    /// Copies `bytes` to the end of the data space, returning the address
+    /// and length of the copy.
+    fn store_bytes(&mut self, bytes: &[u8]) -> Result<(Value, Value), Error> {
+        let addr = self.here;
+        if addr + bytes.len() > self.data_space_size {
+            return Err(Error::DataSpaceFull);
+        }
+        self.memory[addr..addr + bytes.len()].copy_from_slice(bytes);
+        self.here += bytes.len();
+        Ok((addr as Value, bytes.len() as Value))
+    }
+
+    /// Compiles `bytes` into data space as the string a definition leaves,
+    /// or when interpreting copies them to the transient buffer whose turn
+    /// it is, which the next string but one overwrites.
+    fn string_literal(&mut self, bytes: &[u8]) -> ForthResult {
+        let (addr, len) = match self.definition {
+            Some(_) => self.store_bytes(bytes)?,
+            None => {
+                if bytes.len() > STRING_BUFFER_SIZE {
+                    return Err(Error::DataSpaceFull);
+                }
+                let addr = self.data_space_size + HEAP_SIZE + self.string_buffer * STRING_BUFFER_SIZE;
+                self.string_buffer = 1 - self.string_buffer;
+                self.memory[addr..addr + bytes.len()].copy_from_slice(bytes);
+                (addr as Value, bytes.len() as Value)
+            }
+        };
+        self.literal(addr);
+        self.literal(len);
+        Ok(())
+    }
+
+    fn to_in(&self) -> usize {
+        let offset = self.fetch(TO_IN as Value).unwrap_or(0).max(0) as usize;
+        offset.min(self.source.1)
+    }
+
+    fn set_to_in(&mut self, offset: usize) {
+        self.store(TO_IN as Value, offset as Value).unwrap();
+    }
+
+    fn skip_delimiters(&mut self, delimiter: u8) {
+        let (start, len) = self.source;
+        let mut offset = self.to_in();
+        while offset < len && is_delimiter(self.memory[start + offset], delimiter) {
+            offset += 1;
+        }
+        self.set_to_in(offset);
+    }
+
+    /// Parses the input up to `delimiter`, returning the address and length
+    /// of the parsed text and leaving `>IN` past the delimiter.
+    fn parse(&mut self, delimiter: u8) -> (usize, usize) {
+        self.load_lexed_text();
+        let (start, len) = self.source;
+        let begin = self.to_in();
+        let mut end = begin;
+        while end < len && !is_delimiter(self.memory[start + end], delimiter) {
+            end += 1;
+        }
+        self.set_to_in((end + 1).min(len));
+        (start + begin, end - begin)
+    }
+
+    /// Parses a string up to an unescaped `"`, translating the escape
+    /// sequences defined for `S\"`.
+    fn parse_escaped(&mut self) -> Vec<u8> {
+        self.load_lexed_text();
+        let (start, len) = self.source;
+        let input = &self.memory[start..start + len];
+        let mut offset = self.to_in();
+        let mut bytes = Vec::new();
+        while offset < len && input[offset] != b'"' {
+            let c = input[offset];
+            offset += 1;
+            if c != b'\\' || offset == len {
+                bytes.push(c);
+                continue;
+            }
+            let escape = input[offset];
+            offset += 1;
+            match escape {
+                b'a' => bytes.push(7),
+                b'b' => bytes.push(8),
+                b'e' => bytes.push(27),
+                b'f' => bytes.push(12),
+                b'l' | b'n' => bytes.push(b'\n'),
+                b'm' => bytes.extend_from_slice(b"\r\n"),
+                b'q' => bytes.push(b'"'),
+                b'r' => bytes.push(b'\r'),
+                b't' => bytes.push(b'\t'),
+                b'v' => bytes.push(11),
+                b'z' => bytes.push(0),
+                b'x' => {
+                    let hex = input.get(offset..offset + 2)
+                        .and_then(|hex| str::from_utf8(hex).ok())
+                        .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                    match hex {
+                        Some(b) => {
+                            bytes.push(b);
+                            offset += 2;
+                        }
+                        None => bytes.push(b'x'),
+                    }
+                }
+                other => bytes.push(other),
+            }
+        }
+        self.set_to_in((offset + 1).min(len));
+        bytes
+    }
+
+    /// Parses the next name from the input and finds the word it names,
+    /// failing with `InvalidWord` if there is none and `UnknownWord` if
+    /// nothing has that name.
+    fn parse_word(&mut self) -> Result<usize, Error> {
+        let mut name = mem::take(&mut self.name_buffer);
+        let found = match self.parse_name_into(&mut name) {
+            true => self.check_name_len(&name)
+                .and_then(|()| self.lookup_word(&name).ok_or_else(|| self.unknown_word(&name))),
+            false => Err(Error::InvalidWord),
+        };
+        self.name_buffer = name;
+        found
+    }
+
+    /// Parses the name a defining word gives the word it defines, failing
+    /// with `MissingName` at the end of the input.
+    fn parse_new_name(&mut self) -> Result<String, Error> {
+        let name = self.parse_name().ok_or(Error::MissingName)?;
+        self.check_name_len(&name)?;
+        Ok(name)
+    }
+
+    /// Fails with `NameTooLong` if `name` is longer than names may be.
+    fn check_name_len(&self, name: &str) -> ForthResult {
+        match self.max_name_len {
+            Some(max) if name.len() > max => Err(Error::NameTooLong),
+            _ => Ok(()),
+        }
+    }
+
+    /// Parses the next space-delimited name from the input.
+    fn parse_name(&mut self) -> Option<String> {
+        let mut name = String::new();
+        if self.parse_name_into(&mut name) {
+            Some(name)
+        } else {
+            None
+        }
+    }
+
+    /// Like `parse_name`, but replaces the contents of `name` instead of
+    /// allocating. Returns false, leaving `name` empty, at the end of the
+    /// input.
+    fn parse_name_into(&mut self, name: &mut String) -> bool {
+        name.clear();
+        if let Some(ref mut lexed) = self.lexed {
+            let token = match lexed.next_name() {
+                Some(token) => token,
+                None => return false,
+            };
+            self.last_span = token.span;
+            self.input_span = token.span;
+            self.names_parsed += 1;
+            name.push_str(&token.text);
+            return true;
+        }
+        let (start, len) = self.source;
+        let span = scan_name(&self.memory[start..start + len], self.to_in());
+        let span = match span {
+            Some(span) => span,
+            None => {
+                self.set_to_in(len);
+                return false;
+            }
+        };
+        self.set_to_in((span.end + 1).min(len));
+        self.last_span = span;
+        if start == self.tib {
+            self.input_span = span;
+            self.names_parsed += 1;
+        }
+        let bytes = &self.memory[start + span.start..start + span.end];
+        match str::from_utf8(bytes) {
+            Ok(text) => name.push_str(text),
+            Err(_) => name.push_str(&String::from_utf8_lossy(bytes)),
+        }
+        true
+    }
+
+    /// During `eval_tokens`, makes the text of the next comment or string
+    /// token the input source, for a parsing word to read as usual.
+    fn load_lexed_text(&mut self) {
+        let text = match self.lexed {
+            Some(ref mut lexed) => lexed.next_text(),
+            None => return,
+        };
+        self.memory.truncate(self.tib);
+        self.memory.extend_from_slice(text.as_bytes());
+        self.source = (self.tib, text.len());
+        self.set_to_in(0);
+    }
+
+    /// Parses the next name from the input, resolving it to a dictionary
+    /// entry or, failing that, a number. Names that are neither are left as
+    /// `Token::Word`, as written, for the recognizer chain to try.
+    fn next_token(&mut self) -> Result<Option<Token>, Error> {
+        let mut name = mem::take(&mut self.name_buffer);
+        let token = if !self.parse_name_into(&mut name) {
+            Ok(None)
+        } else if self.max_tokens.is_some_and(|max| self.names_parsed > max) {
+            Err(Error::InputTooLarge)
+        } else {
+            self.check_name_len(&name).and_then(|()| self.resolve_name(&name).map(Some))
+        };
+        self.name_buffer = name;
+        token
+    }
+
+    fn resolve_name(&self, name: &str) -> Result<Token, Error> {
+        if let Some(symbol) = self.canonical_symbol(name) {
+            if let Some(index) = self.lookup_symbol(symbol) {
+                return Ok(Token::WordIndex(index));
+            }
+        }
+        if let Some(index) = self.lookup_qualified(name) {
+            return Ok(Token::WordIndex(index));
+        }
+        Ok(match parse_number(name, self.base()?)? {
+            Some(v) => Token::Number(v),
+            None if name == ";" => Token::Semicolon,
+            None => Token::Word(String::from(name)),
+        })
+    }
+
+    fn wordlist_id(&self, wid: Value) -> Result<usize, Error> {
+        if wid < 0 || wid as usize >= self.wordlists {
+            return Err(Error::InvalidWordlist);
+        }
+        Ok(wid as usize)
+    }
+
+    /// Finds the most recent definition of `name`, in any case unless
+    /// case-sensitive, searching the wordlists in search order starting
+    /// from the top.
+    fn lookup_word(&self, name: &str) -> Option<usize> {
+        // Quotations go by the empty name, and aren't to be found by it.
+        if name.is_empty() {
+            return None;
+        }
+        self.canonical_symbol(name)
+            .and_then(|symbol| self.lookup_symbol(symbol))
+            .or_else(|| self.lookup_qualified(name))
+    }
+
+    fn lookup_symbol(&self, symbol: Symbol) -> Option<usize> {
+        let entries = self.index.get(symbol.index())?;
+        for &wid in self.order.iter().rev() {
+            for &i in entries.iter().rev() {
+                let w = &self.words[i];
+                if w.wordlist == wid {
+                    return Some(w.alias_of.unwrap_or(i))
+                }
+            }
+        }
+        None
+    }
+
+    /// Appends `word` to the dictionary.
+    fn add_word(&mut self, word: Word) {
+        let symbol = word.name.index();
+        if self.index.len() <= symbol {
+            self.index.resize(symbol + 1, SmallVec::new());
+        }
+        self.index[symbol].push(self.words.len());
+        self.words.push(word);
+    }
+
+    /// Interns the canonical form of `name`.
+    fn intern(&mut self, name: &str) -> Symbol {
+        let name = self.canonical_name(name);
+        self.names.intern(&name)
+    }
+
+    fn word_name(&self, index: usize) -> &str {
+        self.names.resolve(self.words[index].name)
+    }
+
+    /// Rebuilds the name index after `words` is replaced wholesale.
+    fn reindex(&mut self) {
+        self.index.clear();
+        self.index.resize(self.names.len(), SmallVec::new());
+        for (i, word) in self.words.iter().enumerate() {
+            self.index[word.name.index()].push(i);
+        }
+    }
+
+    /// Discards input up to the matching `[THEN]`, or up to the matching
+    /// `[ELSE]` when `stop_at_else` is set. Nested `[IF]`s are skipped whole.
+    fn skip_conditional(&mut self, stop_at_else: bool) {
+        let mut depth = 0;
+        while let Some(name) = self.parse_name() {
+            match name.to_uppercase().as_str() {
+                "[IF]" => depth += 1,
+                "[ELSE]" if depth == 0 && stop_at_else => return,
+                "[THEN]" if depth == 0 => return,
+                "[THEN]" => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    /// Starts the word at `index`: native words run to completion, while
+    /// compiled ones push a frame for `execute` to step through.
+    fn call(&mut self, index: usize) -> ForthResult {
+        let exec = self.words[index].exec.clone();
+        let exec: &WordExecutor = match exec {
+            Some(ref exec) => &**exec,
+            None => &do_exec,
+        };
+        #[cfg(feature = "typed")]
+        {
+            if self.typed.is_some() {
+                return self.call_typed(index, exec);
+            }
+        }
+        if self.profiler.is_none() && self.coverage.is_none() && !self.hooked() {
+            return exec(self, index);
+        }
+        self.call_observed(index, exec)
+    }
+
+    /// Runs `exec` for the word at `index`, recording it for the profiler,
+    /// coverage and execution hooks.
+    fn call_observed(&mut self, index: usize, exec: &WordExecutor) -> ForthResult {
+        self.cover_word(index);
+        self.profile_call(index);
+        self.hook_before(index);
+        let depth = self.return_stack.len();
+        let caller = self.return_stack.last().map(|frame| frame.word);
+        let result = exec(self, index);
+        self.profile_return();
+        // Compiled words finish when `step_frames` leaves their frame.
+        if result.is_ok() && self.return_stack.len() <= depth {
+            self.hook_after(index);
+            if let (true, Some(caller)) = (self.return_stack.len() < depth, caller) {
+                // EXIT left the frame of the word it ended.
+                self.hook_after(caller);
+            }
+        }
+        result
+    }
+
+    /// Runs the word at `index` to completion, stepping through the bodies
+    /// of compiled words until the return stack is back where it started.
+    fn execute(&mut self, index: usize) -> ForthResult {
+        let base = self.return_stack.len();
+        self.run_frames(base, |forth| forth.call(index))
+    }
+
+    /// Runs `start`, then steps through compiled words until the return
+    /// stack is back to `base`. Frames are left for `resume` if the
+    /// evaluation pauses.
+    fn run_frames<F>(&mut self, base: usize, start: F) -> ForthResult
+        where F: FnOnce(&mut Forth) -> ForthResult
+    {
+        if self.call_depth.is_some_and(|depth| self.executing >= depth) {
+            return Err(Error::CallDepthExceeded);
+        }
+        let saved_base = mem::replace(&mut self.frame_base, base);
+        self.executing += 1;
+        let result = start(self).and_then(|()| self.step_frames(base));
+        self.executing -= 1;
+        if !self.paused {
+            if result.is_err() {
+                self.trace_error(base);
+            }
+            self.return_stack.truncate(base);
+        }
+        if self.profiler.is_some() {
+            self.profile_return();
+        }
+        self.frame_base = saved_base;
+        result
+    }
+
+    fn step_frames(&mut self, base: usize) -> ForthResult {
+        while self.return_stack.len() > base {
+            let frame = self.return_stack.last().unwrap();
+            let data = self.bodies.get(self.words[frame.word].data);
+            let op = match data.get(frame.pc) {
+                Some(&op) => op,
+                None => {
+                    let word = self.return_stack.pop().unwrap().word;
+                    self.hook_after(word);
+                    if self.profiler.is_some() {
+                        self.profile_return();
+                    }
+                    continue;
+                }
+            };
+            let tail = frame.pc + 1 == data.len();
+            if self.debugger.is_some() {
+                self.debug_stop(op, 1)?;
+            }
+            self.burn(1)?;
+            let frame = self.return_stack.last_mut().unwrap();
+            let (word, pc) = (frame.word, frame.pc);
+            frame.pc += 1;
+            if self.coverage.is_some() {
+                self.cover_instruction(word, pc);
+            }
+            match op {
+                Op::Literal(v) => self.stack.push(v),
+                Op::Call(index) => {
+                    // A call that ends the body takes over its frame, so
+                    // tail recursion runs in constant return stack space.
+                    let caller = match tail && !self.words[index].keeps_frame {
+                        true => self.return_stack.pop(),
+                        false => None,
+                    };
+                    if let Some(ref caller) = caller {
+                        self.hook_after(caller.word);
+                    }
+                    if let Err(e) = self.call(index) {
+                        // Put the frame back for error_trace to list.
+                        self.return_stack.extend(caller);
+                        return Err(e);
+                    }
+                }
+            }
+            self.check_stack_depth()?;
+        }
+        Ok(())
+    }
+
+    /// Takes the fuel for one instruction, run with `depth` calls of
+    /// `execute` in progress. With none left, fails with `OutOfFuel`,
+    /// pausing the evaluation if the instruction belongs to the outermost
+    /// interpreter rather than to EVALUATE or a definition being compiled.
+    fn burn(&mut self, depth: usize) -> ForthResult {
+        self.count_operation();
+        match self.fuel {
+            Some(0) => {
+                self.paused = self.executing == depth;
+                Err(Error::OutOfFuel)
+            }
+            Some(ref mut fuel) => {
+                *fuel -= 1;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn check_stack_depth(&mut self) -> ForthResult {
+        self.count_stack_depth();
+        if self.stack_depth.is_some_and(|depth| self.stack.len() > depth) {
+            return Err(Error::StackOverflow);
+        }
+        Ok(())
+    }
+
+    fn compile(&mut self) -> ForthResult {
+        self.check_dictionary()?;
+        let start = self.last_span.start;
+        // Tokens lexed ahead of time don't keep the text between them.
+        let defined_at = match self.lexed {
+            Some(_) => None,
+            None => {
+                let (source, len) = self.source;
+                let text = String::from_utf8_lossy(&self.memory[source..source + start.min(len)]);
+                Some(Span { start: text.len(), end: text.len() }.line_column(&text))
+            }
+        };
+        let word_name = self.parse_new_name()?;
+        if let Ok(Some(_)) = parse_number(&word_name, self.base().unwrap_or(10)) {
+            return Err(Error::NumericName(word_name));
+        }
+        let word_name = self.intern(&word_name);
+        self.check_protected(word_name)?;
+
+        let word = Some(self.words.len());
+        self.definition = Some(Definition { ops: Body::new(), spans: Body::new(), word, defined_at });
+        self.compile_rest(word_name, String::new(), start)
+    }
+
+    /// Carries on with the definition an earlier evaluation left
+    /// unfinished, if any, from the start of the input.
+    fn continue_definition(&mut self) -> ForthResult {
+        match self.unfinished.take() {
+            Some(unfinished) => {
+                self.definition = Some(unfinished.definition);
+                self.compile_rest(unfinished.name, unfinished.source, 0)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Compiles the current definition, of `name`, up to `;` and adds it
+    /// to the dictionary, its source being `text` from earlier inputs and
+    /// the input from `start` on. Should the outermost input run out first,
+    /// the definition is kept for the next evaluation to finish.
+    fn compile_rest(&mut self, name: Symbol, mut text: String, start: usize) -> ForthResult {
+        let compiled = self.definition.as_ref().unwrap().spans.len();
+        let result = self.compile_tokens();
+        let mut definition = self.definition.take().unwrap();
+        let in_quotation = !mem::take(&mut self.quotations).is_empty();
+        let finished = result?;
+        // Only the outermost input can leave a definition for the next, and
+        // only outside a quotation.
+        if !finished && (self.source.0 != self.tib || in_quotation) {
+            return Err(Error::UnterminatedDefinition);
+        }
+        // Unfinished, the definition takes the rest of the input.
+        let end = match finished {
+            true => self.last_span.end,
+            false => usize::MAX,
+        };
+        let offset = text.len();
+        match self.lexed {
+            Some(ref lexed) => text.push_str(&lexed.source(start, end)),
+            None => {
+                let (source, len) = self.source;
+                text.push_str(&String::from_utf8_lossy(&self.memory[source + start..source + end.min(len)]));
+            }
+        }
+        // Spans become offsets in the text of the definition.
+        for span in &mut definition.spans[compiled..] {
+            span.start = (span.start + offset).saturating_sub(start);
+            span.end = (span.end + offset).saturating_sub(start);
+        }
+        if !finished {
+            text.truncate(text.trim_end().len());
+            text.push('\n');
+            self.unfinished = Some(Unfinished { name, source: text, definition });
+            return Ok(());
+        }
+        let (ops, spans) = match self.optimize {
+            true => self.peephole(&definition.ops, &definition.spans),
+            false => (definition.ops, definition.spans),
+        };
+        self.check_name(name);
+        let mut word = Word::new_compiled(name, self.bodies.alloc(&ops), self.current);
+        word.spans = self.body_spans.alloc(&spans);
+        word.source = text;
+        word.defined_at = definition.defined_at;
+        self.add_word(word);
+        self.compile_natively(self.words.len() - 1);
+        Ok(())
+    }
+
+    /// The name of the colon definition the last evaluation ran out of
+    /// input in, before its `;`. The next evaluation carries on compiling
+    /// it, so that a definition can span several.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQ DUP").unwrap();
+    /// assert_eq!(f.unfinished_definition(), Some("SQ"));
+    /// f.eval("* ; 3 SQ").unwrap();
+    /// assert_eq!(f.unfinished_definition(), None);
+    /// assert_eq!(f.stack(), vec![9]);
+    /// assert_eq!(f.definition_source("SQ").unwrap().0, ": SQ DUP\n* ;");
+    /// ```
+    pub fn unfinished_definition(&self) -> Option<&str> {
+        self.unfinished.as_ref().map(|unfinished| self.names.resolve(unfinished.name))
+    }
+
+    /// Abandons the definition the last evaluation left unfinished, if any.
+    pub fn cancel_definition(&mut self) {
+        self.unfinished = None;
+    }
+
+    /// Compiles the input into the current definition up to `;`, returning
+    /// whether one was found before the input ran out.
+    fn compile_tokens(&mut self) -> Result<bool, Error> {
+        while let Some(token) = self.next_token()? {
+            match token {
+                Token::Semicolon if !self.quotations.is_empty() => {
+                    return Err(Error::UnbalancedControl(String::from("[:")));
+                }
+                Token::Semicolon => return Ok(true),
+                #[cfg(feature = "typed")]
+                Token::Word(ref name) if self.typed.is_some() && parse_float(name).is_some() => {
+                    self.float_literal(parse_float(name).unwrap())?;
+                }
+                Token::Word(name) => self.recognize(&name)?,
+                Token::WordIndex(index) if self.primitive(Op::Call(index)) == Some(":") => {
+                    return Err(Error::NestedDefinition);
+                }
+                Token::WordIndex(index) if self.words[index].immediate => {
+                    self.execute(index)?;
+                }
+                Token::WordIndex(index) => {
+                    let span = self.last_span;
+                    let body = self.inline_body(index);
+                    let definition = self.definition.as_mut().unwrap();
+                    match body {
+                        Some(body) => {
+                            for &op in self.bodies.get(body) {
+                                definition.push(op, span);
+                            }
+                        }
+                        None => definition.push(Op::Call(index), span),
+                    }
+                }
+                Token::Number(v) => self.literal(v),
+            }
+            let len = self.definition.as_ref().map_or(0, |definition| definition.ops.len());
+            if self.max_definition_len.is_some_and(|max| len > max) {
+                return Err(Error::DefinitionTooLong);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Interprets the current input source, running each name as it is
+    /// parsed.
+    fn interpret(&mut self) -> ForthResult {
+        if self.unfinished.is_some() {
+            self.continue_definition()?;
+        }
+        while let Some(token) = self.next_token()? {
+            let span = self.input_span;
+            self.interpret_token(token)?;
+            if self.tracer.is_some() {
+                self.trace_step(span);
+            }
+            if self.recorder.is_some() {
+                self.record_step(span);
+            }
+        }
+        Ok(())
+    }
+
+    fn interpret_token(&mut self, token: Token) -> ForthResult {
+        match token {
+            Token::Semicolon => Err(Error::SemicolonOutsideDefinition),
+            #[cfg(feature = "typed")]
+            Token::Word(ref name) if self.typed.is_some() && parse_float(name).is_some() => {
+                self.float_literal(parse_float(name).unwrap())
+            }
+            Token::Word(name) => self.recognize(&name),
+            Token::WordIndex(index) => self.interpret_op(Op::Call(index)),
+            Token::Number(v) => self.interpret_op(Op::Literal(v)),
+        }
+    }
+
+    fn interpret_op(&mut self, op: Op) -> ForthResult {
+        let ready = match self.debugger {
+            Some(_) => self.debug_stop(op, 0),
+            None => Ok(()),
+        };
+        if let Err(e) = ready.and_then(|()| self.burn(0)) {
+            if self.paused {
+                self.pending.push_front(op);
+            }
+            return Err(e);
+        }
+        match op {
+            Op::Call(index) => self.execute(index)?,
+            Op::Literal(v) => self.stack.push(v),
+        }
+        self.check_stack_depth()
+    }
+
+    /// Interprets `len` bytes at `addr` as a nested input source, restoring
+    /// the interrupted source afterwards whether or not the nested
+    /// evaluation succeeded.
+    fn evaluate(&mut self, addr: usize, len: usize) -> ForthResult {
+        let saved_to_in = self.to_in();
+        let saved_source = mem::replace(&mut self.source, (addr, len));
+        let saved_lexed = self.lexed.take();
+        self.set_to_in(0);
+        let result = self.interpret();
+        self.source = saved_source;
+        self.set_to_in(saved_to_in);
+        self.lexed = saved_lexed;
+        result
+    }
+
+    /// Interprets `text` as EVALUATE would, from a copy placed after the
+    /// input being interpreted.
+    fn evaluate_text(&mut self, text: &str) -> ForthResult {
+        let start = self.memory.len();
+        self.memory.extend_from_slice(text.as_bytes());
+        let result = self.evaluate(start, text.len());
+        self.memory.truncate(start);
+        result
+    }
+
+    pub fn eval(&mut self, input: &str) -> ForthResult {
+        if self.keep_undo && self.executing == 0 {
+            self.undo = Some(Box::new(self.fork()));
+        }
+        let echo = self.echo_input && self.executing == 0;
+        let result = self.with_input(input, |forth| {
+            if echo {
+                forth.emit(input.trim_end())?;
+                forth.emit(" ")?;
+            }
+            forth.interpret()
+        });
+        self.prompt(&result);
+        result
+    }
+
+    /// Ends the output of an evaluation that has finished with what a Forth
+    /// terminal shows after each line, if the builder's `ok_prompt` asked
+    /// for it.
+    fn prompt(&mut self, result: &ForthResult) {
+        if !self.ok_prompt || self.paused || self.executing != 0 {
+            return;
+        }
+        let text = match *result {
+            Ok(()) if self.unfinished.is_some() => String::from(" compiled\n"),
+            Ok(()) => String::from(" ok\n"),
+            Err(ref e) => format!("\n{}\n", self.messages.error(e)),
+        };
+        // Output already at its limit goes without.
+        let _ = self.emit(&text);
+    }
+
+    /// Evaluates `input` as `eval` does, but if it fails puts the stacks,
+    /// dictionary and data space back as they were, rather than leaving
+    /// whatever it did before the error. The output and where the error
+    /// happened are kept, as is input KEY and ACCEPT took and fuel used.
+    /// An evaluation paused for lack of fuel isn't rolled back, since
+    /// `resume` may yet finish it.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval("1 2").unwrap();
+    /// let result = f.eval_transactional(": SQ DUP * ; 3 SQ NOPE");
+    /// assert_eq!(result, Err(Error::UnknownWord(String::from("NOPE"))));
+    /// assert_eq!(f.stack(), vec![1, 2]);
+    /// assert_eq!(f.eval("2 SQ"), Err(Error::UnknownWord(String::from("SQ"))));
+    /// ```
+    pub fn eval_transactional(&mut self, input: &str) -> ForthResult {
+        let saved = self.fork();
+        let result = self.eval(input);
+        if result.is_err() && !self.paused {
+            self.roll_back(saved);
+        }
+        result
+    }
+
+    /// Goes back to the state `saved` was forked from, keeping what the
+    /// host has seen or supplied since.
+    fn roll_back(&mut self, mut saved: Forth) {
+        saved.output = mem::take(&mut self.output);
+        saved.output_truncated = self.output_truncated;
+        saved.error_location = self.error_location.take();
+        saved.error_trace = mem::take(&mut self.error_trace);
+        saved.warnings = mem::take(&mut self.warnings);
+        saved.input = mem::take(&mut self.input);
+        self.go_back(saved);
+    }
+
+    /// Goes back to the state `saved` was forked from, output and all,
+    /// keeping only the host's hooks and the fuel and time used.
+    fn go_back(&mut self, mut saved: Forth) {
+        saved.fuel = self.fuel;
+        saved.virtual_ticks = self.virtual_ticks;
+        saved.output_sink = self.output_sink.take();
+        saved.log_sink = self.log_sink.take();
+        saved.input_source = self.input_source.take();
+        saved.dictionary_store = self.dictionary_store.take();
+        saved.block_storage = self.block_storage.take();
+        saved.source_provider = self.source_provider.take();
+        saved.word_compiler = self.word_compiler.take();
+        saved.clock = self.clock.take();
+        saved.channels = mem::replace(&mut self.channels, Box::new(MemoryChannels::default()));
+        saved.data_space_observer = self.data_space_observer.take();
+        saved.profiler = self.profiler.take();
+        saved.coverage = self.coverage.take();
+        saved.hooks = mem::take(&mut self.hooks);
+        saved.keep_undo = self.keep_undo;
+        saved.undo = self.undo.take();
+        *self = saved;
+        self.observe_data_space();
+    }
+
+    /// Whether `eval` keeps what it changes for `undo` to revert. Keeping it
+    /// costs a copy of the interpreter each time. Turning it off forgets
+    /// what `undo` would have reverted.
+    pub fn set_undo(&mut self, undo: bool) {
+        self.keep_undo = undo;
+        if !undo {
+            self.undo = None;
+        }
+    }
+
+    /// Reverts the most recent `eval`: the stacks, dictionary, data space,
+    /// queued input and collected `output` go back to what they were before
+    /// it, as does any evaluation it paused. Returns whether there was
+    /// one to revert; only the last can be, and only while `set_undo` is
+    /// on.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::builder().undo(true).build();
+    /// f.eval(": SQ DUP * ; 3 SQ").unwrap();
+    /// f.eval("DROP 42 CONSTANT SQ 1 .").unwrap();
+    /// assert!(f.undo());
+    /// assert_eq!((f.stack(), f.output()), (vec![9], ""));
+    /// f.eval("2 SQ").unwrap();
+    /// assert_eq!(f.stack(), vec![9, 4]);
+    /// assert!(f.undo());
+    /// assert!(!f.undo());
+    /// ```
+    pub fn undo(&mut self) -> bool {
+        match self.undo.take() {
+            Some(saved) => {
+                self.go_back(*saved);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Compiles `input` into a `Program` that `run` can execute repeatedly
+    /// without parsing it again. Immediate words such as `S"` and `(` act
+    /// while compiling, as in a colon definition; names are resolved
+    /// against the dictionary as it is now, so later redefinitions don't
+    /// affect the program. `:` can't be compiled, and words that parse the
+    /// input when run, like `VARIABLE`, find none and fail.
+    pub fn compile_program(&mut self, input: &str) -> Result<Program, Error> {
+        self.definition = Some(Definition { ops: Body::new(), spans: Body::new(), word: None, defined_at: None });
+        // Compiling can't be resumed, so it counts as nested and running out
+        // of fuel in an immediate word ends it.
+        self.executing += 1;
+        let result = self.with_input(input, |forth| match forth.compile_tokens()? {
+            true => Err(Error::SemicolonOutsideDefinition),
+            false => Ok(()),
+        });
+        self.executing -= 1;
+        let definition = self.definition.take();
+        let in_quotation = !mem::take(&mut self.quotations).is_empty();
+        result?;
+        match definition {
+            Some(definition) if !in_quotation => Ok(Program { ops: definition.ops.into_vec(), words: self.words.len() }),
+            _ => Err(Error::UnbalancedControl(String::from("[:"))),
+        }
+    }
+
+    /// Runs a program made by `compile_program` on this interpreter or one
+    /// forked from it. Output is collected as by `eval`.
+    pub fn run(&mut self, program: &Program) -> ForthResult {
+        if program.words > self.words.len() {
+            return Err(Error::InvalidWord);
+        }
+        self.with_input("", |forth| forth.interpret_ops(&program.ops))
+    }
+
+    /// Runs `ops` at the top level, keeping those not yet run for `resume`
+    /// if it pauses.
+    fn interpret_ops(&mut self, ops: &[Op]) -> ForthResult {
+        for (i, &op) in ops.iter().enumerate() {
+            if let Err(e) = self.interpret_op(op) {
+                if self.paused {
+                    self.pending.extend(&ops[i + 1..]);
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Limits evaluation to `fuel` more instructions, counting each name
+    /// or number interpreted and each instruction of a compiled word run.
+    /// Evaluation that runs out fails with `OutOfFuel`, and can be carried
+    /// on with `resume` after adding fuel, unless it ran out inside
+    /// EVALUATE or while compiling. `None`, the default, removes the limit.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQ DUP * ;").unwrap();
+    /// f.set_fuel(Some(4));
+    /// assert_eq!(f.eval("3 SQ 4 SQ +"), Err(Error::OutOfFuel));
+    /// assert_eq!(f.stack(), vec![9]);
+    /// assert!(f.is_paused());
+    ///
+    /// f.set_fuel(Some(100));
+    /// f.resume().unwrap();
+    /// assert_eq!(f.stack(), vec![25]);
+    /// assert_eq!(f.fuel(), Some(95));
+    /// ```
+    pub fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.fuel = fuel;
+    }
+
+    /// The fuel left, if limited.
+    pub fn fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    /// Whether the last evaluation ran out of fuel and can be resumed.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Carries on with an evaluation that ran out of fuel, from where it
+    /// stopped. Output is collected afresh, and errors are located in the
+    /// original input, as by `eval`. Does nothing if no evaluation is
+    /// paused.
+    pub fn resume(&mut self) -> ForthResult {
+        if !self.paused {
+            return Ok(());
+        }
+        self.paused = false;
+        self.output.clear();
+        self.output_len = 0;
+        self.output_truncated = false;
+        self.error_location = None;
+        self.error_trace.clear();
+        let result = self.run_frames(0, |_| Ok(()))
+            .and_then(|()| {
+                let pending: Vec<Op> = self.pending.drain(..).collect();
+                self.interpret_ops(&pending)
+            })
+            .and_then(|()| self.interpret());
+        let (start, len) = self.source;
+        let input = String::from_utf8_lossy(&self.memory[start..start + len]).into_owned();
+        self.locate_error(&result, &input);
+        if !self.paused {
+            self.lexed = None;
+        }
+        self.prompt(&result);
+        self.observe_data_space();
+        result
+    }
+
+    /// Forgets an evaluation paused by running out of fuel.
+    fn discard_paused(&mut self) {
+        if self.paused {
+            self.paused = false;
+            self.pending.clear();
+            self.lexed = None;
+        }
+    }
+
+    /// Makes `input` the current input source, runs `f` and records where
+    /// in `input` it failed, if it did.
+    fn with_input<F>(&mut self, input: &str, f: F) -> ForthResult
+        where F: FnOnce(&mut Forth) -> ForthResult
+    {
+        // Input too large is turned away before it is copied.
+        let too_large = self.max_input_len.is_some_and(|max| input.len() > max);
+        let input = if too_large { "" } else { input };
+        self.discard_paused();
+        self.debugger = None;
+        // The terminal input buffer lives right after the data space.
+        self.memory.truncate(self.tib);
+        self.memory.extend_from_slice(input.as_bytes());
+        self.return_stack.clear();
+        self.output.clear();
+        self.output_len = 0;
+        self.output_truncated = false;
+        self.warnings.clear();
+        self.source = (self.tib, input.len());
+        self.set_to_in(0);
+        self.names_parsed = 0;
+        self.error_location = None;
+        self.error_trace.clear();
+        self.reset_counters();
+        let result = if too_large { Err(Error::InputTooLarge) } else { f(self) };
+        self.locate_error(&result, input);
+        self.observe_data_space();
+        result
+    }
+
+    /// Records where in `input` evaluation stopped, if `result` is an
+    /// error.
+    fn locate_error(&mut self, result: &ForthResult, input: &str) {
+        if result.is_err() && self.names_parsed > 0 {
+            let token_index = self.names_parsed - 1;
+            self.error_location = match self.lexed {
+                Some(ref lexed) => lexed.location(token_index),
+                None => {
+                    let Span { start: offset, end } = self.input_span;
+                    Some(Location {
+                        word: String::from(&input[offset..end]),
+                        offset,
+                        char_offset: input[..offset].chars().count(),
+                        token_index,
+                    })
+                }
+            };
+        }
+    }
+
+    /// The source text of the colon definition `name` was compiled from,
+    /// and the span within that text of each instruction of the compiled body.
+    pub fn definition_source(&self, name: &str) -> Option<(&str, &[Span])> {
+        let index = self.lookup_word(name)?;
+        let word = &self.words[index];
+        Some((&word.source, self.body_spans.get(word.spans)))
+    }
+
+    /// Where in the input the last failed `eval` stopped.
+    pub fn error_location(&self) -> Option<&Location> {
+        self.error_location.as_ref()
+    }
+
+    /// Describes every named dictionary entry, oldest first, including
+    /// entries shadowed by later definitions of the same name. Quotations
+    /// have no name and are left out.
+    pub fn words(&self) -> Vec<WordInfo> {
+        let named = self.words.iter().enumerate().filter(|(_, word)| !self.names.resolve(word.name).is_empty());
+        named.map(|(index, word)| WordInfo {
+            name: String::from(self.names.resolve(word.name)),
+            user_defined: index >= self.builtins,
+            immediate: word.immediate,
+            length: word.data.len(),
+            wordlist: word.wordlist as Value,
+            doc: self.doc_at(index).map(String::from),
+        }).collect()
+    }
+
+    /// Names of the words visible in the search order that start with
+    /// `prefix`, most recently defined first, each listed once.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        let prefix = self.canonical_name(prefix);
+        let mut symbols = Vec::new();
+        for word in self.words.iter().rev() {
+            let name = self.names.resolve(word.name);
+            if self.order.contains(&word.wordlist)
+                && !name.is_empty()
+                && name.starts_with(&*prefix)
+                && !symbols.contains(&word.name)
+            {
+                symbols.push(word.name);
+            }
+        }
+        symbols.into_iter().map(|symbol| String::from(self.names.resolve(symbol))).collect()
+    }
+
+    /// The colon definitions made so far, oldest first, so that
+    /// `define_words` can recreate them in another interpreter.
+    pub fn user_words(&self) -> Vec<WordDefinition> {
+        (self.builtins..self.words.len())
+            .filter(|&index| self.is_colon_definition(index))
+            .map(|index| self.word_definition(index))
+            .collect()
+    }
+
+    /// The colon definitions of `names` and of the colon definitions they
+    /// use, oldest first, so that `define_words` can recreate just those in
+    /// another interpreter. Fails with `UnknownWord` for a name with no
+    /// colon definition.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut teacher = Forth::new();
+    /// teacher.eval(": SQ DUP * ; : CUBE DUP SQ * ; : UNRELATED 1 ;").unwrap();
+    /// let names: Vec<_> = teacher.export_words(&["cube"]).unwrap().into_iter().map(|word| word.name).collect();
+    /// assert_eq!(names, ["SQ", "CUBE"]);
+    ///
+    /// let mut student = Forth::new();
+    /// student.import_words(&teacher, &["CUBE"]).unwrap();
+    /// student.eval("3 CUBE").unwrap();
+    /// assert_eq!(student.stack(), vec![27]);
+    /// assert_eq!(student.eval("UNRELATED"), Err(Error::UnknownWord("UNRELATED".into())));
+    /// assert_eq!(student.import_words(&teacher, &["DUP"]), Err(Error::UnknownWord("DUP".into())));
+    /// ```
+    pub fn export_words(&self, names: &[&str]) -> Result<Vec<WordDefinition>, Error> {
+        let mut wanted = Vec::new();
+        for &name in names {
+            match self.lookup_word(name) {
+                Some(index) if self.is_colon_definition(index) => wanted.push(index),
+                _ => return Err(Error::UnknownWord(String::from(name))),
+            }
+        }
+        let mut needed = Vec::new();
+        while let Some(index) = wanted.pop() {
+            if !needed.contains(&index) {
+                needed.push(index);
+                wanted.extend(self.colon_dependencies(index));
+            }
+        }
+        needed.sort_unstable();
+        Ok(needed.into_iter().map(|index| self.word_definition(index)).collect())
+    }
+
+    /// Defines here the colon definitions `export_words` gives of `names`
+    /// in `other`, so that a word written in one session can be handed to
+    /// another along with the words it uses.
+    pub fn import_words(&mut self, other: &Forth, names: &[&str]) -> ForthResult {
+        let words = other.export_words(names)?;
+        self.define_words(&words)
+    }
+
+    /// Whether the entry at `index` is a colon definition made after the
+    /// interpreter was built, whose source `user_words` can give.
+    fn is_colon_definition(&self, index: usize) -> bool {
+        let word = &self.words[index];
+        index >= self.builtins && !word.native && word.alias_of.is_none() && !word.source.is_empty()
+    }
+
+    fn word_definition(&self, index: usize) -> WordDefinition {
+        let word = &self.words[index];
+        let spans: Vec<Span> = lex(&word.source).collect();
+        // Drop the leading `: name` and the closing `;`.
+        let body = match (spans.get(2), spans.last()) {
+            (Some(first), Some(last)) if spans.len() > 3 => &word.source[first.start..last.start],
+            _ => "",
+        };
+        WordDefinition {
+            name: String::from(self.names.resolve(word.name)),
+            body: String::from(body.trim_end()),
+        }
+    }
+
+    /// The colon definitions the body of the one at `index` names, each the
+    /// latest of its name made before it, as compiling it found them.
+    fn colon_dependencies(&self, index: usize) -> Vec<usize> {
+        let source = &self.words[index].source;
+        lex(source).skip(2)
+            .filter_map(|span| {
+                let symbol = self.canonical_symbol(&source[span.start..span.end])?;
+                self.index.get(symbol.index())?.iter().rev().cloned()
+                    .find(|&i| i < index && self.is_colon_definition(i))
+            })
+            .collect()
+    }
+
+    /// Compiles each of `words` as a colon definition, in order, stopping
+    /// at the first one that fails. One whose body leaves it open, as an
+    /// unclosed `(` comment would, fails with `UnterminatedDefinition` and
+    /// is abandoned.
+    pub fn define_words(&mut self, words: &[WordDefinition]) -> ForthResult {
+        for word in words {
+            self.eval(&word.source())?;
+            if self.unfinished.take().is_some() {
+                return Err(Error::UnterminatedDefinition);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Indexes the data stack from the bottom, like `stack_slice`.
+impl Index<usize> for Forth {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        &self.stack[index]
+    }
+}
+
+impl<'a> IntoIterator for &'a Forth {
+    type Item = &'a Value;
+    type IntoIter = slice::Iter<'a, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl Default for Forth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn right_aligned_fields_wider_than_format_allows() {
+        let mut f = Forth::builder().max_output(Some(100)).build();
+        assert_eq!(f.eval("1 65536 .R"), Err(Error::OutputLimitExceeded));
+        assert_eq!(f.eval("1 70000 U.R"), Err(Error::OutputLimitExceeded));
+
+        let mut f = Forth::builder().max_output(Some(10)).truncate_output(true).build();
+        f.eval("1 70000 .R").unwrap();
+        assert_eq!(f.output(), " ".repeat(10));
+        assert!(f.output_truncated());
+
+        let mut f = Forth::new();
+        f.eval("-12 70000 .R").unwrap();
+        assert_eq!(f.output().len(), MAX_FIELD_WIDTH);
+        assert!(f.output().ends_with(" -12"));
+        f.eval(&format!("5 {} U.R", Value::MAX)).unwrap();
+        assert_eq!(f.output().len(), MAX_FIELD_WIDTH);
+    }
+
+    #[test]
+    fn interpreted_strings_do_not_use_data_space() {
+        let mut f = Forth::builder().data_space_size(4096).build();
+        for _ in 0..10_000 {
+            f.eval(r#"S" a string typed at the prompt" 2DROP S\" and\tanother" 2DROP"#).unwrap();
+        }
+        f.eval(r#"HERE S" x" 2DROP HERE ="#).unwrap();
+        assert_eq!(f.stack(), vec![-1]);
+    }
+
+    #[test]
+    fn interpreted_strings_take_turns_in_two_buffers() {
+        let mut f = Forth::new();
+        f.eval(r#"S" abc" S" abd" COMPARE  S" abc" S\" abc" COMPARE"#).unwrap();
+        assert_eq!(f.stack(), vec![-1, 0]);
+    }
+
+    #[test]
+    fn compiled_strings_are_kept() {
+        let mut f = Forth::new();
+        f.eval(r#": GREET S" hello" ; S" other" 2DROP S" more" 2DROP GREET TYPE"#).unwrap();
+        assert_eq!(f.output(), "hello");
+    }
+
+    #[test]
+    fn channels_outlive_rollback() {
+        let channels = MemoryChannels::new();
+        let mut alice = Forth::builder().undo(true).build();
+        let mut bob = Forth::new();
+        alice.set_channels(channels.clone());
+        bob.set_channels(channels);
+
+        assert_eq!(alice.eval_transactional("1 0 SEND FOO"), Err(Error::UnknownWord("FOO".into())));
+        alice.eval("2 0 SEND").unwrap();
+        assert!(alice.undo());
+        alice.eval("3 0 SEND").unwrap();
+        // What was sent before the failure stays sent.
+        bob.eval("0 RECV 0 RECV 0 RECV 0 RECV?").unwrap();
+        assert_eq!(bob.stack(), vec![1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn receiving_last_in_a_definition_waits_in_its_frame() {
+        let channels = MemoryChannels::new();
+        let mut alice = Forth::new();
+        let mut bob = Forth::new();
+        alice.set_channels(channels.clone());
+        bob.set_channels(channels);
+
+        assert_eq!(bob.eval(": NEXT 1 RECV ; : TWICE NEXT NEXT + ; TWICE"), Err(Error::PauseRequested));
+        alice.eval("5 1 SEND").unwrap();
+        assert_eq!(bob.resume(), Err(Error::PauseRequested));
+        alice.eval("6 1 SEND").unwrap();
+        bob.resume().unwrap();
+        assert_eq!(bob.stack(), vec![11]);
+    }
+
+
+    #[test]
+    fn unterminated_quotations_leave_the_interpreter_usable() {
+        let mut f = Forth::new();
+        assert_eq!(f.compile_program("[:").err(), Some(Error::UnbalancedControl("[:".into())));
+        assert_eq!(f.compile_program("1 [: 2 [: 3 ;]").err(), Some(Error::UnbalancedControl("[:".into())));
+        assert_eq!(f.compile_program("1 ;]").err(), Some(Error::UnbalancedControl(";]".into())));
+        assert_eq!(f.eval(": Y [: 1 "), Err(Error::UnterminatedDefinition));
+        assert_eq!(f.eval(";]"), Err(Error::UnbalancedControl(";]".into())));
+        f.eval(": X 1 ; X").unwrap();
+        let program = f.compile_program("[: 2 ;] EXECUTE X").unwrap();
+        f.run(&program).unwrap();
+        assert_eq!(f.stack(), vec![1, 2, 1]);
+    }
+
+
+    #[test]
+    fn addresses_out_of_range_are_invalid() {
+        let mut f = Forth::new();
+        assert_eq!(f.eval("-8 @"), Err(Error::InvalidAddress));
+        assert_eq!(f.eval("HERE -1 TYPE"), Err(Error::InvalidAddress));
+        assert_eq!(f.eval("-1 EXECUTE"), Err(Error::InvalidWord));
+        f.eval("-1 FREE").unwrap();
+        assert_eq!(f.stack(), vec![IOR_FREE]);
+    }
+
+    #[cfg(feature = "cell64")]
+    #[test]
+    fn addresses_past_the_address_space_are_invalid() {
+        // On a 32-bit target these would wrap around to small addresses.
+        let mut f = Forth::new();
+        assert_eq!(f.eval("$100000008 @"), Err(Error::InvalidAddress));
+        assert_eq!(f.eval("8 $100000000 DUMP"), Err(Error::InvalidAddress));
+        assert_eq!(f.eval("$100000000 EXECUTE"), Err(Error::InvalidWord));
+        f.eval("$100000001 ALLOCATE NIP").unwrap();
+        assert_eq!(f.stack(), vec![IOR_ALLOCATE]);
+    }
+
+    #[test]
+    fn host_callback_failures_end_the_word_that_called_them() {
+        struct Failing;
+
+        impl SourceProvider for Failing {
+            fn source(&mut self, _name: &str) -> Option<String> {
+                None
+            }
+
+            fn try_source(&mut self, name: &str) -> Result<Option<String>, Error> {
+                Err(Error::Callback(format!("can't read {}", name)))
+            }
+        }
+
+        let mut f = Forth::new();
+        f.set_source_provider(Failing);
+        assert_eq!(f.eval("S\" a.fs\" INCLUDED"), Err(Error::Callback("can't read a.fs".into())));
+        f.set_fallible_log_sink(|_, _| Err(Error::Callback("console gone".into())));
+        assert_eq!(f.eval("1 2 LOG 3"), Err(Error::Callback("console gone".into())));
+        assert_eq!(f.stack(), vec![1]);
+        f.set_fallible_input_source(|| Err(Error::Callback("no keyboard".into())));
+        assert_eq!(f.eval("KEY"), Err(Error::Callback("no keyboard".into())));
+    }
+
+    #[test]
+    fn catch_gives_the_throw_code_of_failed_callbacks() {
+        let mut f = Forth::new();
+        f.set_fallible_output_sink(|_| Err(Error::Callback("page closed".into())));
+        // The stack is back at its depth, though what `.` took is gone.
+        f.eval(": SHOW ( n -- ) 1+ . ; 10 20 7 ' SHOW CATCH").unwrap();
+        assert_eq!(f.stack(), vec![10, 20, 0, -272]);
+        assert!(f.error_trace().is_empty());
+        f.stack.clear();
+
+        f.clear_output_sink();
+        f.eval("5 ' SHOW CATCH").unwrap();
+        assert_eq!(f.stack(), vec![0]);
+        assert_eq!(f.output(), "6 ");
+        f.eval("DROP ' DROP CATCH").unwrap();
+        assert_eq!(f.stack(), vec![-4]);
+    }
+
+    #[test]
+    fn an_error_inside_a_definition_goes_back_to_interpreting() {
+        let mut f = Forth::new();
+        assert_eq!(f.eval(": BAD 1 NOPE ; 2"), Err(Error::UnknownWord("NOPE".into())));
+        assert_eq!(f.unfinished_definition(), None);
+        assert_eq!(f.eval("BAD"), Err(Error::UnknownWord("BAD".into())));
+        // The rest of the input, ; and all, went with the definition.
+        f.eval("3 4 +").unwrap();
+        assert_eq!(f.stack(), vec![7]);
+
+        // Likewise when the definition carried on from an earlier input.
+        f.eval(": HALF 1").unwrap();
+        assert_eq!(f.unfinished_definition(), Some("HALF"));
+        assert_eq!(f.eval("NOPE ;"), Err(Error::UnknownWord("NOPE".into())));
+        assert_eq!(f.unfinished_definition(), None);
+        assert_eq!(f.eval("HALF"), Err(Error::UnknownWord("HALF".into())));
+        assert_eq!(f.eval(";"), Err(Error::SemicolonOutsideDefinition));
+        assert_eq!(f.eval(": A : B ;"), Err(Error::NestedDefinition));
+        f.eval(": C 5 ; C").unwrap();
+        assert_eq!(f.stack(), vec![7, 5]);
+    }
+
+    #[test]
+    fn words_act_while_compiling_and_interpreting_nests() {
+        let mut f = Forth::new();
+        // Immediate words run in the middle of a definition, and parse
+        // from the same input.
+        f.eval(": LETTER ( -- c ) [CHAR] A ( a comment ) ; LETTER").unwrap();
+        f.eval(": EITHER [DEFINED] LETTER [IF] 1 [ELSE] 2 [THEN] ; EITHER").unwrap();
+        // EVALUATE run by a definition interprets, and compiles, anew.
+        f.eval(r#": DEFINE S" : SQ DUP * ; 3 SQ" EVALUATE ; DEFINE 4 SQ"#).unwrap();
+        assert_eq!(f.stack(), vec![65, 1, 9, 16]);
+        // The definition and the outer input carry on where they were once
+        // EVALUATE is done.
+        f.stack.clear();
+        f.eval(r#": RUN EVALUATE 4 ; 1 S" 2 3" RUN 5"#).unwrap();
+        assert_eq!(f.stack(), vec![1, 2, 3, 4, 5]);
+        // A definition EVALUATE leaves unfinished isn't carried on.
+        assert_eq!(f.eval(r#"S" : PART 1" EVALUATE"#), Err(Error::UnterminatedDefinition));
+        assert_eq!(f.unfinished_definition(), None);
+    }
+
+    #[test]
+    fn pausing_keeps_the_rest_of_the_input_for_resume() {
+        let mut f = Forth::new();
+        assert_eq!(f.eval("1 PAUSE 2"), Err(Error::PauseRequested));
+        assert!(f.is_paused());
+        assert_eq!(f.stack(), vec![1]);
+        f.resume().unwrap();
+        assert!(!f.is_paused());
+        assert_eq!(f.stack(), vec![1, 2]);
+
+        // Inside a definition, the rest of it runs first, then the input.
+        f.eval(": STEP 3 PAUSE 4 ; : STEPS STEP PAUSE STEP ;").unwrap();
+        assert_eq!(f.eval("STEPS 5"), Err(Error::PauseRequested));
+        assert_eq!(f.resume(), Err(Error::PauseRequested));
+        assert_eq!(f.resume(), Err(Error::PauseRequested));
+        f.resume().unwrap();
+        assert_eq!(f.stack(), vec![1, 2, 3, 4, 3, 4, 5]);
+
+        // A new evaluation drops what was left.
+        assert_eq!(f.eval("6 PAUSE 7"), Err(Error::PauseRequested));
+        f.eval("8").unwrap();
+        assert!(!f.is_paused());
+        assert_eq!(f.resume(), Ok(()));
+        assert_eq!(f.stack()[7..], [6, 8]);
+    }
+
+
+    #[test]
+    fn tail_calls_run_in_constant_return_stack_space() {
+        let mut f = Forth::builder().return_stack_depth(4).build();
+        f.eval(": SPIN 1 DROP RECURSE ; : OUTER 2 DROP SPIN ;").unwrap();
+        f.set_fuel(Some(100_000));
+        assert_eq!(f.eval("OUTER"), Err(Error::OutOfFuel));
+        assert_eq!(f.return_stack().iter().map(|frame| frame.word.as_str()).collect::<Vec<_>>(), ["SPIN"]);
+        assert!(f.eval_stats().max_return_stack_depth <= 1);
+
+        // Calls elsewhere in the body still take a frame each.
+        f.set_fuel(None);
+        f.eval(": DEEP RECURSE 1 ;").unwrap();
+        assert!(matches!(f.eval("DEEP"), Err(Error::ReturnStackOverflow) | Err(Error::CallDepthExceeded)));
+    }
+
+
+    #[test]
+    fn bodies_hold_calls_bound_when_compiled() {
+        let mut f = Forth::builder().optimize(false).build();
+        f.eval(": ONE 1 ; : TWO ONE DUP + ; ' ONE").unwrap();
+        let one = f.lookup_word("ONE").unwrap();
+        let dup = f.lookup_word("DUP").unwrap();
+        let plus = f.lookup_word("+").unwrap();
+        assert_eq!(f.body(one), [Op::Literal(1)]);
+        assert_eq!(f.body(f.lookup_word("TWO").unwrap()), [Op::Call(one), Op::Call(dup), Op::Call(plus)]);
+        assert_eq!(f.stack(), vec![one as Value]);
+
+        // Redefining a word, even a primitive, leaves those compiled
+        // before it calling the old one.
+        f.eval(": ONE 10 ; : DUP 0 ; TWO SWAP EXECUTE ONE").unwrap();
+        assert_eq!(f.stack(), vec![2, 1, 10]);
+    }
+
+
+    #[test]
+    fn the_standard_prelude_is_not_held_to_the_limits() {
+        let mut f = Forth::builder()
+            .call_depth(Some(0))
+            .stack_depth(0)
+            .return_stack_depth(0)
+            .max_output(Some(0))
+            .try_build()
+            .unwrap();
+        assert_eq!(f.eval("1"), Err(Error::StackOverflow));
+        assert_eq!(f.eval(": ONE 1 ; ONE"), Err(Error::CallDepthExceeded));
+        let e = Forth::builder().call_depth(Some(0)).prelude(": ONE 1 ; ONE").try_build().err().unwrap();
+        assert_eq!(e.error, Error::CallDepthExceeded);
+    }
+
+    #[test]
+    fn comparing_and_inverting_never_overflow() {
+        let mut f = Forth::builder().overflow(Overflow::Checked).build();
+        let (min, max) = (Value::MIN, Value::MAX);
+        f.eval(&format!("{} 1 = {} -1 = {} {} = {} 1 <> {} -1 <>", min, max, min, min, min, max)).unwrap();
+        assert_eq!(f.stack(), vec![0, 0, -1, -1, -1]);
+        f.stack.clear();
+        f.eval(&format!("{} INVERT {} INVERT 0 INVERT {} 0=", min, max, min)).unwrap();
+        assert_eq!(f.stack(), vec![max, min, -1, 0]);
+        f.stack.clear();
+        f.eval(&format!("{} NEGATE", max)).unwrap();
+        assert_eq!(f.stack(), vec![-max]);
+        assert_eq!(f.eval(&format!("{} NEGATE", min)), Err(Error::Overflow));
+    }
+
+    #[test]
+    fn defined_words_may_end_in_a_line_comment() {
+        let mut f = Forth::new();
+        f.eval(": SQ DUP * \\ square it\n;").unwrap();
+        let words = f.user_words();
+        assert_eq!(words[0].body, "DUP * \\ square it");
+
+        let mut g = Forth::new();
+        g.define_words(&words).unwrap();
+        assert_eq!(g.unfinished_definition(), None);
+        g.eval("3 SQ").unwrap();
+        assert_eq!(g.stack(), vec![9]);
+
+        let open = WordDefinition { name: String::from("OPEN"), body: String::from("1 ( never closed") };
+        assert_eq!(g.define_words(&[open]), Err(Error::UnterminatedDefinition));
+        assert_eq!(g.unfinished_definition(), None);
+        g.eval("4 SQ").unwrap();
+        assert_eq!(g.stack(), vec![9, 16]);
+    }
+
+    #[test]
+    fn imported_words_may_end_in_a_line_comment() {
+        let mut f = Forth::new();
+        f.eval(": SQ DUP * \\ square it\n; : CUBE DUP SQ * \\ uses SQ\n;").unwrap();
+        let mut g = Forth::new();
+        g.import_words(&f, &["CUBE"]).unwrap();
+        assert_eq!(g.unfinished_definition(), None);
+        g.eval("2 CUBE 3 SQ").unwrap();
+        assert_eq!(g.stack(), vec![8, 9]);
+    }
+
+    #[test]
+    fn allotting_past_the_largest_cell_fills_data_space() {
+        let mut f = Forth::new();
+        let here = f.here;
+        assert_eq!(f.eval(&format!("{} ALLOT", Value::MAX)), Err(Error::DataSpaceFull));
+        assert_eq!(f.eval(&format!("{} ALLOT", Value::MIN)), Err(Error::InvalidAddress));
+        assert_eq!(f.here, here);
+    }
+
+    #[test]
+    fn quotations_cannot_be_found_by_name() {
+        let mut f = Forth::new();
+        f.eval(": Q [: 2 ;] ; Q DROP").unwrap();
+        assert_eq!(f.lookup_word(""), None);
+        assert_eq!(f.definition_source(""), None);
+        assert_eq!(f.disassemble(""), None);
+        assert!(!f.complete("").iter().any(String::is_empty));
+        assert!(!f.words().iter().any(|word| word.name.is_empty()));
+        assert_eq!(f.complete("Q"), ["Q"]);
+    }
+
+    #[test]
+    fn redefinitions_share_a_symbol_and_shadow_in_order() {
+        let mut f = Forth::new();
+        f.eval(": sq dup * ; : SQ 0 ; : Sq 1 ;").unwrap();
+        let entries: Vec<usize> = (0..f.words.len()).filter(|&index| f.word_name(index) == "SQ").collect();
+        assert_eq!(entries.len(), 3);
+        assert!(entries.iter().all(|&index| f.words[index].name == f.words[entries[0]].name));
+        assert_eq!(f.lookup_word("sQ"), Some(entries[2]));
+        f.eval("SQ").unwrap();
+        assert_eq!(f.stack(), vec![1]);
+
+        // Names interned but never defined, or whose definition failed,
+        // are still unknown.
+        assert_eq!(f.eval(": NEVER NOPE ;"), Err(Error::UnknownWord("NOPE".into())));
+        assert_eq!(f.eval("NEVER"), Err(Error::UnknownWord("NEVER".into())));
+        // With case kept, differently cased names are different words.
+        let mut f = Forth::builder().case_sensitive(true).build();
+        f.eval(": sq 1 ; : SQ 2 ; sq SQ").unwrap();
+        assert_eq!(f.stack(), vec![1, 2]);
+        assert_ne!(f.names.get("sq"), f.names.get("SQ"));
+    }
+
+}
@@ -0,0 +1,144 @@
+//! Storage for the bodies of compiled words and the spans they came from,
+//! kept together in large chunks rather than one allocation per word, so
+//! that defining a word rarely allocates at all.
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+
+use super::{Forth, Op, Span};
+
+/// Items a chunk makes room for at once. Larger bodies get a chunk of
+/// their own.
+const CHUNK: usize = 1024;
+
+/// Where a body is kept in an `Arena`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(super) struct Slot {
+    chunk: usize,
+    start: usize,
+    len: usize,
+}
+
+impl Slot {
+    pub(super) fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Append-only storage for slices of `T`. Chunks are shared between forks
+/// of an interpreter; only the last, which new bodies go in, is copied
+/// when a fork first adds to it. Bodies stay until the arena is rebuilt,
+/// as `Forth::gc` does.
+#[derive(Debug, Clone)]
+pub(super) struct Arena<T> {
+    chunks: Vec<Rc<Vec<T>>>,
+}
+
+impl<T: Clone> Arena<T> {
+    pub(super) fn new() -> Arena<T> {
+        Arena { chunks: Vec::new() }
+    }
+
+    /// Copies `items` into the arena.
+    pub(super) fn alloc(&mut self, items: &[T]) -> Slot {
+        if items.is_empty() {
+            return Slot::default();
+        }
+        if self.chunks.last().is_none_or(|chunk| chunk.len() + items.len() > CHUNK) {
+            self.chunks.push(Rc::new(Vec::with_capacity(CHUNK.max(items.len()))));
+        }
+        let index = self.chunks.len() - 1;
+        let last = &mut self.chunks[index];
+        if Rc::get_mut(last).is_none() {
+            // Shared with a fork, so copied, with the room a new chunk has.
+            let mut copy = Vec::with_capacity(CHUNK);
+            copy.extend_from_slice(last);
+            *last = Rc::new(copy);
+        }
+        let chunk = Rc::get_mut(last).unwrap();
+        let start = chunk.len();
+        chunk.extend_from_slice(items);
+        Slot { chunk: index, start, len: items.len() }
+    }
+
+    pub(super) fn get(&self, slot: Slot) -> &[T] {
+        match slot.len {
+            0 => &[],
+            len => &self.chunks[slot.chunk][slot.start..slot.start + len],
+        }
+    }
+}
+
+impl Forth {
+    /// The compiled body of the word at `index`.
+    pub(super) fn body(&self, index: usize) -> &[Op] {
+        self.bodies.get(self.words[index].data)
+    }
+
+    /// The span in its source of each instruction of the body of the word
+    /// at `index`.
+    pub(super) fn instruction_spans(&self, index: usize) -> &[Span] {
+        self.body_spans.get(self.words[index].spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Forth;
+    use super::*;
+
+    impl<T> Arena<T> {
+        /// Items kept, whether or not a word still uses them.
+        fn len(&self) -> usize {
+            self.chunks.iter().map(|chunk| chunk.len()).sum()
+        }
+    }
+
+    #[test]
+    fn bodies_share_chunks() {
+        let mut arena = Arena::new();
+        let small = arena.alloc(&[1, 2, 3]);
+        let empty = arena.alloc(&[]);
+        let large = arena.alloc(&[7; CHUNK + 1]);
+        let after = arena.alloc(&[4]);
+        assert_eq!(arena.get(small), [1, 2, 3]);
+        assert_eq!(arena.get(empty), [] as [i32; 0]);
+        assert_eq!(arena.get(large).len(), CHUNK + 1);
+        assert_eq!(arena.get(after), [4]);
+        assert_eq!(arena.chunks.len(), 3);
+    }
+
+    #[test]
+    fn gc_reclaims_the_space_of_dropped_bodies() {
+        let mut f = Forth::builder().optimize(false).build();
+        f.eval(": BIG 1 2 3 4 5 6 7 8 ;").unwrap();
+        let before = f.bodies.len();
+        for _ in 0..200 {
+            f.eval(": BIG 1 2 3 4 5 6 7 8 ;").unwrap();
+        }
+        assert_eq!(f.bodies.len(), before + 200 * 8);
+        assert_eq!(f.gc().words, 200);
+        assert_eq!(f.bodies.len(), before);
+        f.eval("BIG").unwrap();
+        assert_eq!(f.stack(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn forks_copy_the_chunk_they_add_to() {
+        let mut f = Forth::new();
+        f.eval(": A 1 ;").unwrap();
+        let mut fork = f.fork();
+        fork.eval(": A 2 ; : B A A ;").unwrap();
+        f.eval(": B 3 ; A B").unwrap();
+        fork.eval("A B").unwrap();
+        assert_eq!(f.stack(), vec![1, 3]);
+        assert_eq!(fork.stack(), vec![2, 2, 2]);
+        // Both added to the last chunk, so each has its own copy of it,
+        // while any before it are still shared.
+        let (ours, theirs) = (&f.bodies.chunks, &fork.bodies.chunks);
+        assert_eq!(ours.len(), theirs.len());
+        let last = ours.len() - 1;
+        assert!((0..last).all(|i| Rc::ptr_eq(&ours[i], &theirs[i])));
+        assert!(!Rc::ptr_eq(&ours[last], &theirs[last]));
+    }
+}
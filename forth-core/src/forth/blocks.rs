@@ -0,0 +1,252 @@
+//! BLOCK, BUFFER, UPDATE, SAVE-BUFFERS, EMPTY-BUFFERS, FLUSH, LIST and
+//! LOAD, which work on 1 KiB blocks the host's block storage keeps.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+use super::{Error, Forth, ForthResult, Value};
+
+/// Bytes in a block.
+pub const BLOCK_SIZE: usize = 1024;
+/// Characters in each of the lines LIST shows a block as.
+const LINE_LEN: usize = 64;
+
+/// Keeps the blocks BLOCK reads and FLUSH writes back, as the host
+/// decides. Blocks are numbered from 1.
+pub trait BlockStorage {
+    /// Reads block `n` into `block`, which holds spaces beforehand and is
+    /// left alone for blocks never written.
+    fn read(&mut self, n: u32, block: &mut [u8]) -> ForthResult;
+
+    /// Writes `block` as block `n`.
+    fn write(&mut self, n: u32, block: &[u8]) -> ForthResult;
+}
+
+/// Blocks kept in memory for as long as the storage lasts, which is what
+/// an interpreter uses until the host gives it other storage.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBlocks {
+    blocks: BTreeMap<u32, Vec<u8>>,
+}
+
+impl BlockStorage for MemoryBlocks {
+    fn read(&mut self, n: u32, block: &mut [u8]) -> ForthResult {
+        if let Some(saved) = self.blocks.get(&n) {
+            block.copy_from_slice(saved);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, n: u32, block: &[u8]) -> ForthResult {
+        self.blocks.insert(n, block.to_vec());
+        Ok(())
+    }
+}
+
+/// Blocks kept one after another in a file, block 1 first, as is
+/// traditional. The file is created when a block is first written.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct FileBlocks {
+    path: std::path::PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl FileBlocks {
+    pub fn new<P: Into<std::path::PathBuf>>(path: P) -> FileBlocks {
+        FileBlocks { path: path.into() }
+    }
+
+    fn offset(n: u32) -> u64 {
+        u64::from(n - 1) * BLOCK_SIZE as u64
+    }
+}
+
+#[cfg(feature = "std")]
+fn io_error(e: std::io::Error) -> Error {
+    use alloc::string::ToString;
+
+    Error::Native(e.to_string())
+}
+
+#[cfg(feature = "std")]
+impl BlockStorage for FileBlocks {
+    fn read(&mut self, n: u32, block: &mut [u8]) -> ForthResult {
+        use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+        let mut file = match std::fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(io_error(e)),
+        };
+        file.seek(SeekFrom::Start(Self::offset(n))).map_err(io_error)?;
+        // Past the end of the file, or partly, the block is unwritten.
+        let mut read = 0;
+        while read < block.len() {
+            match file.read(&mut block[read..]).map_err(io_error)? {
+                0 => break,
+                len => read += len,
+            }
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, n: u32, block: &[u8]) -> ForthResult {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let mut file = std::fs::OpenOptions::new().write(true).create(true).truncate(false)
+            .open(&self.path).map_err(io_error)?;
+        // Blocks skipped over read back as spaces rather than zeros.
+        let len = file.metadata().map_err(io_error)?.len();
+        if len < Self::offset(n) {
+            file.seek(SeekFrom::Start(len)).map_err(io_error)?;
+            let blank = alloc::vec![b' '; (Self::offset(n) - len) as usize];
+            file.write_all(&blank).map_err(io_error)?;
+        }
+        file.seek(SeekFrom::Start(Self::offset(n))).map_err(io_error)?;
+        file.write_all(block).map_err(io_error)
+    }
+}
+
+/// The block buffer: where it is in the heap once made, the block it
+/// holds, if any, and whether UPDATE marked it for writing back.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct BlockBuffer {
+    addr: Option<usize>,
+    block: Option<u32>,
+    updated: bool,
+}
+
+fn block_number(n: Value) -> Result<u32, Error> {
+    u32::try_from(n).ok().filter(|&n| n > 0).ok_or(Error::InvalidBlock)
+}
+
+pub(super) fn do_block(forth: &mut Forth, _index: usize) -> ForthResult {
+    let n = block_number(forth.pop()?)?;
+    let addr = forth.assign_buffer(n, true)?;
+    forth.push(addr as Value);
+    Ok(())
+}
+
+pub(super) fn do_buffer(forth: &mut Forth, _index: usize) -> ForthResult {
+    let n = block_number(forth.pop()?)?;
+    let addr = forth.assign_buffer(n, false)?;
+    forth.push(addr as Value);
+    Ok(())
+}
+
+pub(super) fn do_update(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.block_buffer.updated = forth.block_buffer.block.is_some();
+    Ok(())
+}
+
+pub(super) fn do_save_buffers(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.save_buffers()
+}
+
+pub(super) fn do_empty_buffers(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.block_buffer.block = None;
+    forth.block_buffer.updated = false;
+    Ok(())
+}
+
+pub(super) fn do_flush(forth: &mut Forth, index: usize) -> ForthResult {
+    forth.save_buffers()?;
+    do_empty_buffers(forth, index)
+}
+
+pub(super) fn do_list(forth: &mut Forth, _index: usize) -> ForthResult {
+    let n = block_number(forth.pop()?)?;
+    let addr = forth.assign_buffer(n, true)?;
+    let block = &forth.memory[addr..addr + BLOCK_SIZE];
+    let mut text = format!("Screen {}\n", n);
+    for (i, line) in block.chunks(LINE_LEN).enumerate() {
+        text.push_str(&format!("{:2} {}\n", i, String::from_utf8_lossy(line).trim_end()));
+    }
+    forth.emit(&text)
+}
+
+pub(super) fn do_load(forth: &mut Forth, _index: usize) -> ForthResult {
+    let n = block_number(forth.pop()?)?;
+    let addr = forth.assign_buffer(n, true)?;
+    // Blocks the loaded one uses take over the buffer, so it runs from a
+    // copy.
+    let text = String::from_utf8_lossy(&forth.memory[addr..addr + BLOCK_SIZE]).into_owned();
+    forth.evaluate_text(&text)
+}
+
+impl Forth {
+    /// Has the block words keep blocks in `storage`, in place of the
+    /// `MemoryBlocks` each interpreter starts with, such as `FileBlocks`
+    /// on native hosts. A deterministic interpreter ignores it.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth, MemoryBlocks};
+    ///
+    /// let mut f = Forth::new();
+    /// f.set_block_storage(MemoryBlocks::default());
+    /// f.eval(": SQ DUP * ; S\" 7 SQ\" 1 BLOCK SWAP MOVE UPDATE FLUSH").unwrap();
+    /// f.eval("1 LOAD 2 LIST").unwrap();
+    /// assert_eq!(f.stack(), vec![49]);
+    /// assert!(f.output().starts_with("Screen 2\n 0 \n 1 \n"));
+    ///
+    /// f.eval("1 LIST").unwrap();
+    /// assert!(f.output().starts_with("Screen 1\n 0 7 SQ\n"));
+    /// assert_eq!(f.eval("0 BLOCK"), Err(Error::InvalidBlock));
+    /// ```
+    pub fn set_block_storage<S>(&mut self, storage: S)
+        where S: BlockStorage + 'static
+    {
+        if self.deterministic {
+            return;
+        }
+        self.block_storage = Some(Box::new(storage));
+    }
+
+    /// Writes the block buffer back to the block storage if UPDATE marked
+    /// it, as SAVE-BUFFERS does.
+    pub fn save_buffers(&mut self) -> ForthResult {
+        let buffer = self.block_buffer;
+        let (addr, n) = match (buffer.addr, buffer.block) {
+            (Some(addr), Some(n)) if buffer.updated => (addr, n),
+            _ => return Ok(()),
+        };
+        let storage = self.block_storage.as_mut().ok_or(Error::NoStorage)?;
+        storage.write(n, &self.memory[addr..addr + BLOCK_SIZE])?;
+        self.block_buffer.updated = false;
+        Ok(())
+    }
+
+    /// Makes the block buffer hold block `n`, writing back the block it
+    /// held if updated, and reading `n` in if `read`, and returns where
+    /// it is.
+    fn assign_buffer(&mut self, n: u32, read: bool) -> Result<usize, Error> {
+        if self.block_storage.is_none() {
+            return Err(Error::NoStorage);
+        }
+        let addr = match self.block_buffer.addr {
+            Some(addr) => addr,
+            None => {
+                let addr = self.heap.allocate(BLOCK_SIZE).ok_or(Error::DataSpaceFull)?;
+                self.block_buffer.addr = Some(addr);
+                addr
+            }
+        };
+        if self.block_buffer.block == Some(n) {
+            return Ok(addr);
+        }
+        self.save_buffers()?;
+        self.block_buffer.block = None;
+        if read {
+            let block = &mut self.memory[addr..addr + BLOCK_SIZE];
+            block.fill(b' ');
+            self.block_storage.as_mut().unwrap().read(n, block)?;
+        }
+        self.block_buffer.block = Some(n);
+        Ok(addr)
+    }
+}
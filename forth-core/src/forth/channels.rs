@@ -0,0 +1,185 @@
+//! SEND and RECV, which pass cells and strings between interpreters over
+//! numbered channels the host provides, so that several can work on an
+//! exercise together.
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::cell::RefCell;
+
+use super::{Error, Forth, ForthResult, Op, Value};
+
+/// Carries the messages of SEND and `$SEND` to RECV and `$RECV`, as the
+/// host decides. Each channel keeps the cells and strings sent on it in
+/// order, apart from each other.
+pub trait Channels {
+    /// Queues `v` on `channel`.
+    fn send(&mut self, channel: Value, v: Value);
+
+    /// Takes the oldest cell waiting on `channel`, if any.
+    fn receive(&mut self, channel: Value) -> Option<Value>;
+
+    /// Queues `text` on `channel`.
+    fn send_string(&mut self, channel: Value, text: String);
+
+    /// Takes the oldest string waiting on `channel`, if any.
+    fn receive_string(&mut self, channel: Value) -> Option<String>;
+}
+
+#[derive(Debug, Clone, Default)]
+struct Queue {
+    cells: VecDeque<Value>,
+    strings: VecDeque<String>,
+}
+
+/// Channels that clones share, so that interpreters given clones can
+/// talk to each other, and the host can keep one to send and receive on
+/// too. Each interpreter starts with channels of its own.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryChannels {
+    queues: Rc<RefCell<BTreeMap<Value, Queue>>>,
+}
+
+impl MemoryChannels {
+    pub fn new() -> MemoryChannels {
+        MemoryChannels::default()
+    }
+}
+
+impl Channels for MemoryChannels {
+    fn send(&mut self, channel: Value, v: Value) {
+        self.queues.borrow_mut().entry(channel).or_default().cells.push_back(v);
+    }
+
+    fn receive(&mut self, channel: Value) -> Option<Value> {
+        self.queues.borrow_mut().get_mut(&channel)?.cells.pop_front()
+    }
+
+    fn send_string(&mut self, channel: Value, text: String) {
+        self.queues.borrow_mut().entry(channel).or_default().strings.push_back(text);
+    }
+
+    fn receive_string(&mut self, channel: Value) -> Option<String> {
+        self.queues.borrow_mut().get_mut(&channel)?.strings.pop_front()
+    }
+}
+
+/// SEND ( x ch -- )
+pub(super) fn do_send(forth: &mut Forth, _index: usize) -> ForthResult {
+    let channel = forth.pop()?;
+    let v = forth.pop()?;
+    forth.channels.send(channel, v);
+    Ok(())
+}
+
+/// RECV? ( ch -- x true | false )
+pub(super) fn do_receive_question(forth: &mut Forth, _index: usize) -> ForthResult {
+    let channel = forth.pop()?;
+    match forth.channels.receive(channel) {
+        Some(v) => {
+            forth.push(v);
+            forth.push(-1);
+        }
+        None => forth.push(0),
+    }
+    Ok(())
+}
+
+/// RECV ( ch -- x ) waits for a cell.
+pub(super) fn do_receive(forth: &mut Forth, index: usize) -> ForthResult {
+    let channel = forth.pop()?;
+    match forth.channels.receive(channel) {
+        Some(v) => {
+            forth.push(v);
+            Ok(())
+        }
+        None => wait(forth, index, channel),
+    }
+}
+
+/// `$SEND` ( ch -- ) ( $: s -- )
+pub(super) fn do_string_send(forth: &mut Forth, _index: usize) -> ForthResult {
+    let channel = forth.pop()?;
+    let bytes = forth.pop_string()?;
+    forth.channels.send_string(channel, String::from_utf8_lossy(&bytes).into_owned());
+    Ok(())
+}
+
+/// `$RECV?` ( ch -- flag ) ( $: -- s | )
+pub(super) fn do_string_receive_question(forth: &mut Forth, _index: usize) -> ForthResult {
+    let channel = forth.pop()?;
+    match forth.channels.receive_string(channel) {
+        Some(text) => {
+            forth.push_string(text.as_bytes())?;
+            forth.push(-1);
+        }
+        None => forth.push(0),
+    }
+    Ok(())
+}
+
+/// `$RECV` ( ch -- ) ( $: -- s ) waits for a string.
+pub(super) fn do_string_receive(forth: &mut Forth, index: usize) -> ForthResult {
+    let channel = forth.pop()?;
+    match forth.channels.receive_string(channel) {
+        Some(text) => forth.push_string(text.as_bytes()),
+        None => wait(forth, index, channel),
+    }
+}
+
+/// Fails with `PauseRequested`, as PAUSE does, arranging for `resume` to
+/// run the word at `index` on `channel` again rather than carry on after
+/// it.
+fn wait(forth: &mut Forth, index: usize, channel: Value) -> ForthResult {
+    forth.push(channel);
+    // The word asking is run by the outermost `execute`.
+    forth.paused = forth.executing == 1;
+    if forth.paused {
+        // RECV and `$RECV` are defined as keeping their caller's frame
+        // when called last in a body, so it is the one to step back in.
+        match forth.return_stack.last_mut() {
+            Some(frame) => frame.pc -= 1,
+            None => forth.pending.push_front(Op::Call(index)),
+        }
+    }
+    Err(Error::PauseRequested)
+}
+
+impl Forth {
+    /// Has SEND ( x ch -- ) and RECV ( ch -- x ) pass cells over
+    /// `channels`, and, with the builder's `strings`, `$SEND` ( ch -- )
+    /// and `$RECV` ( ch -- ) pass strings from and to the string stack.
+    /// RECV? ( ch -- x true | false ) and `$RECV?` ( ch -- flag ) take a
+    /// message only if one is waiting. With none, RECV and `$RECV` fail
+    /// with `PauseRequested`, as PAUSE does, and try again once resumed,
+    /// so that the host can pass messages along in between.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth, MemoryChannels};
+    ///
+    /// let channels = MemoryChannels::new();
+    /// let mut alice = Forth::builder().strings(true).build();
+    /// let mut bob = Forth::builder().strings(true).build();
+    /// alice.set_channels(channels.clone());
+    /// bob.set_channels(channels);
+    ///
+    /// assert_eq!(bob.eval("1 RECV? 1 RECV 10 *"), Err(Error::PauseRequested));
+    /// assert_eq!(bob.stack(), vec![0, 1]);
+    /// alice.eval("42 1 SEND $\" hi\" 2 $SEND").unwrap();
+    /// bob.resume().unwrap();
+    /// assert_eq!(bob.stack(), vec![0, 420]);
+    /// bob.eval("2 $RECV $.").unwrap();
+    /// assert_eq!(bob.output(), "hi");
+    ///
+    /// assert_eq!(bob.eval(": NEXT 1 RECV 1+ ; NEXT"), Err(Error::PauseRequested));
+    /// alice.eval("7 1 SEND").unwrap();
+    /// bob.resume().unwrap();
+    /// assert_eq!(bob.stack(), vec![0, 420, 8]);
+    /// ```
+    pub fn set_channels<C>(&mut self, channels: C)
+        where C: Channels + 'static
+    {
+        self.channels = Box::new(channels);
+    }
+}
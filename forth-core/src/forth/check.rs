@@ -0,0 +1,273 @@
+//! Static checking of source text without running it.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem;
+
+use super::{parse_number, Error, Forth, SourceToken, Span, TokenKind};
+
+/// A problem `Forth::check` found, and where in the source it is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub error: Error,
+    pub span: Span,
+}
+
+/// A control-flow word still waiting for its closing word.
+struct Open {
+    name: &'static str,
+    span: Span,
+}
+
+/// For each word that closes or continues a control structure, the words it
+/// may follow and the word it leaves open in their place, if any.
+fn control_rule(name: &str) -> Option<(&'static [&'static str], Option<&'static str>)> {
+    Some(match name {
+        "ELSE" => (&["IF"], Some("ELSE")),
+        "THEN" => (&["IF", "ELSE"], None),
+        "WHILE" => (&["BEGIN"], Some("WHILE")),
+        "REPEAT" => (&["WHILE"], None),
+        "UNTIL" | "AGAIN" => (&["BEGIN"], None),
+        "LOOP" | "+LOOP" => (&["DO"], None),
+        "ENDOF" => (&["OF"], None),
+        "ENDCASE" => (&["CASE"], None),
+        ";]" => (&["[:"], None),
+        _ => return None,
+    })
+}
+
+/// Control-flow words that start a structure.
+fn control_opener(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "IF" => "IF",
+        "BEGIN" => "BEGIN",
+        "DO" | "?DO" => "DO",
+        "CASE" => "CASE",
+        "OF" => "OF",
+        "[:" => "[:",
+        _ => return None,
+    })
+}
+
+struct Checker {
+    /// Whether to look only at how definitions and control structures
+    /// nest, leaving names alone.
+    structure_only: bool,
+    /// The colon definition being checked, and its open control structures.
+    definition: Option<Span>,
+    control: Vec<Open>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checker {
+    fn report(&mut self, error: Error, span: Span) {
+        self.diagnostics.push(Diagnostic { error, span });
+    }
+
+    fn control(&mut self, name: &str, span: Span) {
+        if let Some(opener) = control_opener(name) {
+            self.control.push(Open { name: opener, span });
+            return;
+        }
+        let (after, leaves) = match control_rule(name) {
+            Some(rule) => rule,
+            None => return,
+        };
+        match self.control.last() {
+            Some(open) if after.contains(&open.name) => {
+                self.control.pop();
+            }
+            _ => {
+                self.report(Error::UnbalancedControl(String::from(name)), span);
+                return;
+            }
+        }
+        match leaves {
+            Some(open) => self.control.push(Open { name: open, span }),
+            // REPEAT closes the BEGIN its WHILE continued.
+            None if name == "REPEAT" && self.control.last().is_some_and(|open| open.name == "BEGIN") => {
+                self.control.pop();
+            }
+            None => {}
+        }
+    }
+
+    /// Checks a control-flow word, which outside a definition can only
+    /// close or continue a structure that was never opened.
+    fn structure(&mut self, name: &str, span: Span) {
+        if self.definition.is_some() || control_opener(name).is_none() {
+            self.control(name, span);
+        }
+    }
+
+    fn end_definition(&mut self) {
+        for open in mem::take(&mut self.control) {
+            self.report(Error::UnbalancedControl(String::from(open.name)), open.span);
+        }
+        self.definition = None;
+    }
+
+    fn run(&mut self, forth: &Forth, tokens: &[SourceToken]) {
+        for (i, token) in tokens.iter().enumerate() {
+            let name = forth.canonical_name(&token.text);
+            // The tokenizer gives parsing words every name they can take,
+            // so one short of names simply ends the source early.
+            let missing_names = |needed: usize| tokens.len() - i - 1 < needed;
+            match token.kind {
+                TokenKind::Word | TokenKind::Defining => match &*name {
+                    ":" if self.definition.is_none() => match tokens.get(i + 1) {
+                        Some(next) => {
+                            self.definition = Some(next.span);
+                            if let Ok(Some(_)) = parse_number(&next.text, forth.base().unwrap_or(10)) {
+                                self.report(Error::NumericName(next.text.clone()), next.span);
+                            }
+                        }
+                        None => self.report(Error::MissingName, token.span),
+                    },
+                    ":" => self.report(Error::NestedDefinition, token.span),
+                    ";" if self.definition.is_some() => self.end_definition(),
+                    ";" if self.structure_only => self.report(Error::SemicolonOutsideDefinition, token.span),
+                    _ if self.structure_only => self.structure(&name, token.span),
+                    "SYNONYM" if missing_names(1) => self.report(Error::MissingName, token.span),
+                    "SYNONYM" if missing_names(2) => self.report(Error::InvalidWord, token.span),
+                    "CREATE" | "VARIABLE" | "CONSTANT" | "2VARIABLE" | "2CONSTANT" | "ALIAS" | "BEGIN-STRUCTURE"
+                    | "+FIELD" | "FIELD:" | "CFIELD:" | "MODULE"
+                        if missing_names(1) =>
+                    {
+                        self.report(Error::MissingName, token.span)
+                    }
+                    "'" | "[DEFINED]" | "[UNDEFINED]" | "CHAR" | "[CHAR]" | "INCLUDE" | "REQUIRE" | "HELP"
+                        if missing_names(1) =>
+                    {
+                        self.report(Error::InvalidWord, token.span)
+                    }
+                    _ if self.definition.is_some() => self.control(&name, token.span),
+                    _ => {}
+                },
+                TokenKind::Unknown | TokenKind::OutOfRange if self.structure_only => match &*name {
+                    ";" => self.report(Error::SemicolonOutsideDefinition, token.span),
+                    _ => self.structure(&name, token.span),
+                },
+                TokenKind::Unknown | TokenKind::OutOfRange => {
+                    if self.definition.is_some() {
+                        self.control(&name, token.span);
+                    }
+                    let error = match parse_number(&name, forth.base().unwrap_or(10)) {
+                        Err(e) => e,
+                        _ if name == ";" => Error::SemicolonOutsideDefinition,
+                        _ => Error::UnknownWord(name.into_owned()),
+                    };
+                    self.report(error, token.span);
+                }
+                _ => {}
+            }
+        }
+        if let Some(span) = self.definition {
+            self.report(Error::UnterminatedDefinition, span);
+            self.end_definition();
+        }
+    }
+}
+
+impl Forth {
+    /// Checks `source` without running it: reports unknown words, colon
+    /// definitions that are left open, nested, unnamed or named like a
+    /// number, stray `;` and control structures that don't match up, in
+    /// source order. The interpreter itself is left unchanged, so words
+    /// `source` would define are only known to the check itself, and
+    /// anything decided at run time, such as `[IF]`, is not followed.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let f = Forth::new();
+    /// let source = ": 2X DUP + ; ;\n: 10 20 ;\n:";
+    /// let errors: Vec<_> = f.check(source).into_iter()
+    ///     .map(|d| (d.error, &source[d.span.start..d.span.end]))
+    ///     .collect();
+    /// assert_eq!(errors, [
+    ///     (Error::SemicolonOutsideDefinition, ";"),
+    ///     (Error::NumericName(String::from("10")), "10"),
+    ///     (Error::MissingName, ":"),
+    /// ]);
+    /// ```
+    pub fn check(&self, source: &str) -> Vec<Diagnostic> {
+        self.run_checker(source, false)
+    }
+
+    /// Checks only how `source` nests: colon definitions left open, `;`
+    /// outside one and control-flow words such as THEN, LOOP or REPEAT
+    /// without the word that opens them or closing the wrong one, in source
+    /// order. Unlike `check`, it doesn't look names up, so it is quick
+    /// enough to run as each key is typed.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let f = Forth::new();
+    /// let source = "; THEN\n: A BEGIN 1 IF UNTIL ;\n: B 0 DO NOPE";
+    /// let errors: Vec<_> = f.check_structure(source).into_iter()
+    ///     .map(|d| (d.error, &source[d.span.start..d.span.end]))
+    ///     .collect();
+    /// assert_eq!(errors, [
+    ///     (Error::SemicolonOutsideDefinition, ";"),
+    ///     (Error::UnbalancedControl(String::from("THEN")), "THEN"),
+    ///     (Error::UnbalancedControl(String::from("BEGIN")), "BEGIN"),
+    ///     (Error::UnbalancedControl(String::from("IF")), "IF"),
+    ///     (Error::UnbalancedControl(String::from("UNTIL")), "UNTIL"),
+    ///     (Error::UnterminatedDefinition, "B"),
+    ///     (Error::UnbalancedControl(String::from("DO")), "DO"),
+    /// ]);
+    /// ```
+    pub fn check_structure(&self, source: &str) -> Vec<Diagnostic> {
+        self.run_checker(source, true)
+    }
+
+    fn run_checker(&self, source: &str, structure_only: bool) -> Vec<Diagnostic> {
+        let mut checker = Checker {
+            structure_only,
+            definition: None,
+            control: Vec::new(),
+            diagnostics: Vec::new(),
+        };
+        checker.run(self, &self.tokenize(source));
+        checker.diagnostics.sort_by_key(|d| d.span.start);
+        checker.diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors(source: &str) -> Vec<(Error, &str)> {
+        Forth::new().check(source).into_iter().map(|d| (d.error, &source[d.span.start..d.span.end])).collect()
+    }
+
+    #[test]
+    fn unterminated_text_is_not_mistaken_for_names() {
+        assert_eq!(errors(r#"S" NOPE ; THEN"#), []);
+        assert_eq!(errors(r#": GREET S\" NOPE\" ;"#), [(Error::UnterminatedDefinition, "GREET")]);
+        assert_eq!(errors("( NOPE ( ; )"), []);
+        assert_eq!(errors("\\ NOPE\nNOPE"), [(Error::UnknownWord(String::from("NOPE")), "NOPE")]);
+    }
+
+    #[test]
+    fn spans_cover_multi_byte_names() {
+        let source = "\\ ½\n: ÉTÉ 1 ; été 日本 :";
+        assert_eq!(errors(source), [(Error::UnknownWord(String::from("日本")), "日本"), (Error::MissingName, ":")]);
+        let diagnostics = Forth::new().check(source);
+        assert_eq!(diagnostics[0].span, Span { start: 23, end: 29 });
+    }
+
+    #[test]
+    fn parsing_words_at_the_end_of_the_source_are_reported() {
+        assert_eq!(errors("DUP :"), [(Error::MissingName, ":")]);
+        assert_eq!(errors("1 CONSTANT"), [(Error::MissingName, "CONSTANT")]);
+        assert_eq!(errors("2 SYNONYM"), [(Error::MissingName, "SYNONYM")]);
+        assert_eq!(errors("SYNONYM ALIAS"), [(Error::InvalidWord, "SYNONYM")]);
+        assert_eq!(errors("5 CHAR"), [(Error::InvalidWord, "CHAR")]);
+        assert_eq!(errors("DROP '"), [(Error::InvalidWord, "'")]);
+        assert_eq!(errors(": C [CHAR]"), [(Error::UnterminatedDefinition, "C"), (Error::InvalidWord, "[CHAR]")]);
+    }
+}
@@ -0,0 +1,248 @@
+//! MS, TICKS, COUNTER, ELAPSED and TIME&DATE, which read the host's clock,
+//! and MS waiting on it or else pausing evaluation for the host to wait.
+
+use alloc::boxed::Box;
+
+use super::{Error, Forth, ForthResult, Value};
+
+/// Seconds from 1970 to midnight at the start of 2000, when the time
+/// TIME&DATE tells in a deterministic interpreter starts.
+const DETERMINISTIC_EPOCH: i64 = 946_684_800;
+
+/// The date and time of day, as TIME&DATE gives them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeAndDate {
+    pub second: u8,
+    pub minute: u8,
+    pub hour: u8,
+    /// Day of the month, from 1.
+    pub day: u8,
+    /// Month of the year, from 1.
+    pub month: u8,
+    pub year: i32,
+}
+
+/// Tells TICKS, COUNTER and TIME&DATE the time, and waits for MS, as the
+/// host decides.
+pub trait Clock {
+    /// Milliseconds since some fixed moment, such as when the clock was
+    /// made.
+    fn ticks(&mut self) -> u64;
+
+    /// Microseconds since the moment `ticks` counts from, for COUNTER to
+    /// time short runs with. Clocks that can't tell finer than `ticks`
+    /// needn't say.
+    fn micros(&mut self) -> u64 {
+        self.ticks().saturating_mul(1000)
+    }
+
+    /// The current date and time.
+    fn time_and_date(&mut self) -> TimeAndDate;
+
+    /// Waits `ms` milliseconds and returns `true`, or returns `false` to
+    /// have MS pause evaluation for the host to wait, as hosts that can't
+    /// block do.
+    fn sleep(&mut self, _ms: u64) -> bool {
+        false
+    }
+}
+
+/// The system clock, telling the time in UTC and sleeping the thread for
+/// MS.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct SystemClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl SystemClock {
+    /// A clock whose ticks count from now.
+    pub fn new() -> SystemClock {
+        SystemClock { start: std::time::Instant::now() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn ticks(&mut self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    fn micros(&mut self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+
+    fn time_and_date(&mut self) -> TimeAndDate {
+        let since_epoch = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        utc_time_and_date(since_epoch)
+    }
+
+    fn sleep(&mut self, ms: u64) -> bool {
+        std::thread::sleep(std::time::Duration::from_millis(ms));
+        true
+    }
+}
+
+/// The date and time in UTC `since_epoch` seconds after 1970 began.
+fn utc_time_and_date(since_epoch: i64) -> TimeAndDate {
+    let seconds = since_epoch.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(since_epoch.div_euclid(86_400));
+    TimeAndDate {
+        second: (seconds % 60) as u8,
+        minute: (seconds / 60 % 60) as u8,
+        hour: (seconds / 3600) as u8,
+        day,
+        month,
+        year,
+    }
+}
+
+/// The year, month and day `days` after 1970-01-01 in the proleptic
+/// Gregorian calendar.
+fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    // Counts from 0000-03-01, so that leap days end each 400-year era.
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year as i32, month as u8, day as u8)
+}
+
+pub(super) fn do_ms(forth: &mut Forth, _index: usize) -> ForthResult {
+    let ms = forth.pop()?.max(0) as u64;
+    if ms == 0 {
+        return Ok(());
+    }
+    if forth.deterministic {
+        forth.virtual_ticks += ms;
+        return Ok(());
+    }
+    if let Some(ref mut clock) = forth.clock {
+        if clock.sleep(ms) {
+            return Ok(());
+        }
+    }
+    // The word asking is run by the outermost `execute`.
+    forth.paused = forth.executing == 1;
+    Err(Error::WaitRequested(ms))
+}
+
+pub(super) fn do_ticks(forth: &mut Forth, _index: usize) -> ForthResult {
+    let ticks = forth.ticks().ok_or(Error::NoClock)?;
+    forth.push(ticks as Value);
+    Ok(())
+}
+
+/// COUNTER ( -- u ) reads the clock in microseconds, for ELAPSED. The
+/// count wraps as cells do.
+pub(super) fn do_counter(forth: &mut Forth, _index: usize) -> ForthResult {
+    let micros = forth.micros().ok_or(Error::NoClock)?;
+    forth.push(micros as Value);
+    Ok(())
+}
+
+/// ELAPSED ( u -- ms ) gives the milliseconds since COUNTER gave `u`.
+pub(super) fn do_elapsed(forth: &mut Forth, _index: usize) -> ForthResult {
+    let start = forth.pop()?;
+    let now = forth.micros().ok_or(Error::NoClock)? as Value;
+    forth.push(now.wrapping_sub(start) / 1000);
+    Ok(())
+}
+
+pub(super) fn do_time_and_date(forth: &mut Forth, _index: usize) -> ForthResult {
+    let now = match forth.clock {
+        _ if forth.deterministic => {
+            utc_time_and_date(DETERMINISTIC_EPOCH + (forth.virtual_ticks / 1000) as i64)
+        }
+        Some(ref mut clock) => clock.time_and_date(),
+        None => return Err(Error::NoClock),
+    };
+    for part in [now.second, now.minute, now.hour, now.day, now.month].iter() {
+        forth.push(Value::from(*part));
+    }
+    forth.push(now.year as Value);
+    Ok(())
+}
+
+impl Forth {
+    /// Has TICKS, COUNTER and TIME&DATE read `clock`, and MS wait on it;
+    /// without a clock they fail with `NoClock`. If the clock doesn't sleep, or
+    /// there is none, MS fails with `WaitRequested`, pausing the
+    /// evaluation if it can be resumed from there, for the host to wait
+    /// before calling `resume`. A deterministic interpreter ignores the
+    /// clock.
+    ///
+    /// ```
+    /// use forth_core::{Clock, Error, Forth, TimeAndDate};
+    ///
+    /// struct Stopped;
+    ///
+    /// impl Clock for Stopped {
+    ///     fn ticks(&mut self) -> u64 { 1000 }
+    ///     fn time_and_date(&mut self) -> TimeAndDate {
+    ///         TimeAndDate { second: 0, minute: 30, hour: 12, day: 1, month: 6, year: 2024 }
+    ///     }
+    /// }
+    ///
+    /// let mut f = Forth::new();
+    /// assert_eq!(f.eval("TICKS"), Err(Error::NoClock));
+    /// f.set_clock(Stopped);
+    /// f.eval("TICKS TIME&DATE").unwrap();
+    /// assert_eq!(f.stack(), vec![1000, 0, 30, 12, 1, 6, 2024]);
+    ///
+    /// assert_eq!(f.eval("1 250 MS 2"), Err(Error::WaitRequested(250)));
+    /// assert!(f.is_paused());
+    /// f.resume().unwrap();
+    /// assert_eq!(f.stack()[7..], [1, 2]);
+    ///
+    /// f.eval("COUNTER ELAPSED").unwrap();
+    /// assert_eq!(f.stack()[9..], [0]);
+    /// ```
+    pub fn set_clock<C>(&mut self, clock: C)
+        where C: Clock + 'static
+    {
+        if self.deterministic {
+            return;
+        }
+        self.clock = Some(Box::new(clock));
+    }
+
+    pub fn clear_clock(&mut self) {
+        self.clock = None;
+    }
+
+    /// The clock's ticks, or in a deterministic interpreter the
+    /// milliseconds MS has waited; `None` without a clock.
+    pub(super) fn ticks(&mut self) -> Option<u64> {
+        match self.clock {
+            _ if self.deterministic => Some(self.virtual_ticks),
+            Some(ref mut clock) => Some(clock.ticks()),
+            None => None,
+        }
+    }
+
+    /// The clock's `micros`, or in a deterministic interpreter the
+    /// microseconds MS has waited; `None` without a clock.
+    fn micros(&mut self) -> Option<u64> {
+        match self.clock {
+            _ if self.deterministic => Some(self.virtual_ticks.saturating_mul(1000)),
+            Some(ref mut clock) => Some(clock.micros()),
+            None => None,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Recording which words, and which instructions of colon definitions,
+//! ran while evaluating.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::Forth;
+
+/// Whether a dictionary entry ran while recording coverage, as listed by
+/// `Forth::coverage`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WordCoverage {
+    pub name: String,
+    pub user_defined: bool,
+    pub executed: bool,
+    /// For a compiled word, whether each instruction of its body ran, in
+    /// the order of the spans `Forth::definition_source` gives.
+    pub instructions: Vec<bool>,
+}
+
+/// What has run since coverage started, by dictionary index.
+#[derive(Clone, Default)]
+pub(super) struct Coverage {
+    words: Vec<bool>,
+    instructions: Vec<Vec<bool>>,
+}
+
+impl Forth {
+    /// Starts recording which words run, and which instructions of their
+    /// bodies, discarding what was recorded before. `Forth::coverage`
+    /// reports it.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQ DUP * ; : CUBE DUP SQ * ; : UNUSED 1 ;").unwrap();
+    /// f.start_coverage();
+    /// f.eval("3 SQ").unwrap();
+    /// let coverage = f.stop_coverage();
+    /// let word = |name| coverage.iter().find(|word| word.name == name).unwrap();
+    /// assert!(word("SQ").executed);
+    /// assert_eq!(word("SQ").instructions, vec![true, true]);
+    /// assert!(word("DUP").executed && !word("DUP").user_defined);
+    /// assert!(!word("CUBE").executed && !word("UNUSED").executed);
+    /// assert!(!coverage.iter().any(|word| word.name == "SWAP"));
+    /// ```
+    pub fn start_coverage(&mut self) {
+        self.coverage = Some(Coverage::default());
+    }
+
+    /// Stops recording coverage, returning what `coverage` would have.
+    pub fn stop_coverage(&mut self) -> Vec<WordCoverage> {
+        let coverage = self.coverage();
+        self.coverage = None;
+        coverage
+    }
+
+    /// Every word defined after the interpreter was built and every built-in
+    /// word that ran since coverage started, oldest first, and what of them
+    /// ran. Empty when not recording coverage.
+    pub fn coverage(&self) -> Vec<WordCoverage> {
+        let coverage = match self.coverage {
+            Some(ref coverage) => coverage,
+            None => return Vec::new(),
+        };
+        (0..self.words.len())
+            .filter_map(|index| {
+                let executed = coverage.words.get(index).cloned().unwrap_or(false);
+                let user_defined = index >= self.builtins;
+                if !executed && !user_defined {
+                    return None;
+                }
+                let word = &self.words[index];
+                let mut instructions = coverage.instructions.get(index).cloned().unwrap_or_default();
+                instructions.resize(if word.native { 0 } else { word.data.len() }, false);
+                Some(WordCoverage { name: String::from(self.word_name(index)), user_defined, executed, instructions })
+            })
+            .collect()
+    }
+
+    /// Records that the word at `index` started.
+    pub(super) fn cover_word(&mut self, index: usize) {
+        if let Some(ref mut coverage) = self.coverage {
+            if coverage.words.len() <= index {
+                coverage.words.resize(index + 1, false);
+            }
+            coverage.words[index] = true;
+        }
+    }
+
+    /// Records that instruction `pc` of the word at `index` is running.
+    pub(super) fn cover_instruction(&mut self, index: usize, pc: usize) {
+        if let Some(ref mut coverage) = self.coverage {
+            if coverage.instructions.len() <= index {
+                coverage.instructions.resize(index + 1, Vec::new());
+            }
+            let instructions = &mut coverage.instructions[index];
+            if instructions.len() <= pc {
+                instructions.resize(pc + 1, false);
+            }
+            instructions[pc] = true;
+        }
+    }
+}
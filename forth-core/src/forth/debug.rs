@@ -0,0 +1,364 @@
+//! Stepping through an evaluation and stopping it at breakpoints and
+//! watchpoints, built on the pausing that fuel uses.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::mem;
+
+use super::{scan_name, Error, Forth, ForthResult, Op, Span, Value};
+
+/// A debugging session started by `Forth::debug`.
+#[derive(Clone, Copy)]
+pub(super) struct Debugger {
+    /// Whether to stop before the next instruction rather than at the next
+    /// breakpoint.
+    stepping: bool,
+    /// Whether the instruction about to run is the one evaluation stopped
+    /// before, and should run rather than stop it again.
+    resuming: bool,
+    /// The instruction run last, which set off any watchpoint now hit.
+    last: Option<Op>,
+}
+
+/// A change that stops a `debug` session, noticed between the instructions
+/// it could stop at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Watchpoint {
+    /// The contents of the cell at this address change.
+    Cell(Value),
+    /// The stack grows past this many cells, or shrinks back to it.
+    StackDepth(usize),
+}
+
+/// A watchpoint that stopped a `debug` session, and the word whose
+/// instruction set it off, or `None` for a number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchHit {
+    pub watchpoint: Watchpoint,
+    pub word: Option<String>,
+}
+
+/// A watchpoint and what it last saw: the contents of the cell, or the
+/// depth of the stack.
+#[derive(Clone, Copy)]
+pub(super) struct Watch {
+    watchpoint: Watchpoint,
+    seen: Value,
+}
+
+/// A compiled word being run, as listed by `Forth::return_stack`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallFrame {
+    pub word: String,
+    /// Index in the word's body of the instruction to run next.
+    pub pc: usize,
+    /// Span of that instruction in the word's definition source, as
+    /// `Forth::definition_source` gives it.
+    pub span: Option<Span>,
+}
+
+/// A compiled word that was running when an evaluation failed, as listed
+/// by `Forth::error_trace`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorFrame {
+    pub word: String,
+    /// The name or number in the word's definition that failed, or called
+    /// the word of the frame before.
+    pub token: String,
+    /// Span of `token` in the word's definition source, as
+    /// `Forth::definition_source` gives it.
+    pub span: Option<Span>,
+    /// One-based line and column at which the definition began in the text
+    /// it was compiled from, if known.
+    pub defined_at: Option<(usize, usize)>,
+}
+
+impl Forth {
+    /// Stops `debug` sessions before any call of the word `name`, whether
+    /// from the input or a compiled word. Calls inlined into a definition
+    /// are not stopped at.
+    pub fn set_breakpoint(&mut self, name: &str) {
+        let name = self.canonical_name(name).into_owned();
+        if !self.breakpoints.contains(&name) {
+            self.breakpoints.push(name);
+        }
+    }
+
+    pub fn clear_breakpoint(&mut self, name: &str) {
+        let name = self.canonical_name(name);
+        self.breakpoints.retain(|breakpoint| *breakpoint != name);
+    }
+
+    /// The words `set_breakpoint` was given, in canonical form.
+    pub fn breakpoints(&self) -> &[String] {
+        &self.breakpoints
+    }
+
+    /// Stops `debug` sessions once `watchpoint` sees a change. Fails with
+    /// `InvalidAddress` for a cell outside memory.
+    ///
+    /// ```
+    /// use forth_core::{Forth, Watchpoint};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval("VARIABLE X : BUMP X @ 1 + X ! ; X").unwrap();
+    /// let x = f.pop().unwrap();
+    /// f.watch(Watchpoint::Cell(x)).unwrap();
+    /// assert_eq!(f.debug("2 BUMP 3"), Ok(true));
+    /// assert_eq!(f.run_until_breakpoint(), Ok(true));
+    /// let hit = f.watch_hit().unwrap();
+    /// assert_eq!(hit.word.as_deref(), Some("!"));
+    /// assert_eq!(f.token_queue(), vec!["3"]);
+    /// ```
+    pub fn watch(&mut self, watchpoint: Watchpoint) -> ForthResult {
+        let seen = self.watched(watchpoint)?;
+        if !self.watchpoints.iter().any(|watch| watch.watchpoint == watchpoint) {
+            self.watchpoints.push(Watch { watchpoint, seen });
+        }
+        Ok(())
+    }
+
+    pub fn unwatch(&mut self, watchpoint: Watchpoint) {
+        self.watchpoints.retain(|watch| watch.watchpoint != watchpoint);
+    }
+
+    pub fn watchpoints(&self) -> Vec<Watchpoint> {
+        self.watchpoints.iter().map(|watch| watch.watchpoint).collect()
+    }
+
+    /// The watchpoint that made the last `debug`, `step` or
+    /// `run_until_breakpoint` stop, if one did.
+    pub fn watch_hit(&self) -> Option<&WatchHit> {
+        self.watch_hit.as_ref()
+    }
+
+    fn watched(&self, watchpoint: Watchpoint) -> Result<Value, Error> {
+        match watchpoint {
+            Watchpoint::Cell(addr) => self.fetch(addr),
+            Watchpoint::StackDepth(_) => Ok(self.stack.len() as Value),
+        }
+    }
+
+    /// Takes note of what each watchpoint sees now, returning the first
+    /// that saw a change since last time.
+    fn check_watchpoints(&mut self) -> Option<Watchpoint> {
+        let mut hit = None;
+        for i in 0..self.watchpoints.len() {
+            let Watch { watchpoint, seen } = self.watchpoints[i];
+            // A cell past the end of memory, in an input buffer since
+            // shortened, keeps what it held.
+            let now = self.watched(watchpoint).unwrap_or(seen);
+            let changed = match watchpoint {
+                Watchpoint::Cell(_) => now != seen,
+                Watchpoint::StackDepth(depth) => (seen as usize <= depth) != (now as usize <= depth),
+            };
+            if changed && hit.is_none() {
+                hit = Some(watchpoint);
+            }
+            self.watchpoints[i].seen = now;
+        }
+        hit
+    }
+
+    /// Starts evaluating `input` as `eval` would, but stops before the
+    /// first name or number for `step` and `run_until_breakpoint` to carry
+    /// on. Returns whether evaluation stopped, rather than finishing or
+    /// failing. Evaluation can only stop outside EVALUATE and compiling,
+    /// which run to completion as a single step.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQ DUP * ;").unwrap();
+    /// f.set_breakpoint("sq");
+    /// assert_eq!(f.debug("2 3 SQ +"), Ok(true));
+    /// assert_eq!(f.token_queue(), vec!["2", "3", "SQ", "+"]);
+    ///
+    /// assert_eq!(f.run_until_breakpoint(), Ok(true));
+    /// assert_eq!(f.stack(), vec![2, 3]);
+    /// assert_eq!(f.token_queue(), vec!["SQ", "+"]);
+    ///
+    /// // Into SQ, then past DUP.
+    /// assert_eq!(f.step(), Ok(true));
+    /// assert_eq!(f.step(), Ok(true));
+    /// assert_eq!(f.stack(), vec![2, 3, 3]);
+    /// assert_eq!(f.return_stack()[0].word, "SQ");
+    /// assert_eq!(f.return_stack()[0].pc, 1);
+    ///
+    /// assert_eq!(f.run_until_breakpoint(), Ok(false));
+    /// assert_eq!(f.stack(), vec![11]);
+    /// ```
+    pub fn debug(&mut self, input: &str) -> Result<bool, Error> {
+        let result = self.with_input(input, |forth| {
+            forth.debugger = Some(Debugger { stepping: true, resuming: false, last: None });
+            forth.watch_hit = None;
+            forth.check_watchpoints();
+            forth.interpret()
+        });
+        self.debug_outcome(result)
+    }
+
+    /// Runs the next instruction of the evaluation `debug` started, whether
+    /// a name or number of the input or an instruction of a compiled word,
+    /// and stops again. Returns whether it stopped, rather than finishing
+    /// or failing; does nothing if no evaluation is being debugged.
+    pub fn step(&mut self) -> Result<bool, Error> {
+        self.continue_debugging(true)
+    }
+
+    /// Runs the evaluation `debug` started until it calls a word with a
+    /// breakpoint, a watchpoint sees a change, or it finishes or fails.
+    /// Returns whether it stopped.
+    pub fn run_until_breakpoint(&mut self) -> Result<bool, Error> {
+        self.continue_debugging(false)
+    }
+
+    fn continue_debugging(&mut self, stepping: bool) -> Result<bool, Error> {
+        match self.debugger {
+            Some(ref mut debugger) => {
+                debugger.stepping = stepping;
+                debugger.resuming = true;
+            }
+            None => return Ok(false),
+        }
+        self.watch_hit = None;
+        // Changes made while stopped weren't the evaluation's doing.
+        self.check_watchpoints();
+        let result = self.resume();
+        self.debug_outcome(result)
+    }
+
+    /// Turns the result of running a debugged evaluation into whether it
+    /// stopped, ending the session unless it can still be resumed.
+    fn debug_outcome(&mut self, result: ForthResult) -> Result<bool, Error> {
+        match result {
+            Err(Error::Stopped) => Ok(true),
+            result => {
+                if !self.paused {
+                    self.debugger = None;
+                }
+                result.map(|()| false)
+            }
+        }
+    }
+
+    /// The instruction a stopped evaluation will run next.
+    pub(super) fn stopped_before(&self) -> Option<Op> {
+        self.debugger.as_ref().filter(|_| self.paused).and_then(|debugger| debugger.last)
+    }
+
+    /// Whether debugging stops evaluation before `op`, run by the `execute`
+    /// `depth` calls in. Only the outermost can stop, as with fuel.
+    pub(super) fn debug_stop(&mut self, op: Op, depth: usize) -> ForthResult {
+        if self.executing != depth {
+            return Ok(());
+        }
+        let (stepping, last) = match self.debugger {
+            Some(ref mut debugger) => {
+                if mem::replace(&mut debugger.resuming, false) {
+                    debugger.last = Some(op);
+                    return Ok(());
+                }
+                (debugger.stepping, debugger.last.replace(op))
+            }
+            None => return Ok(()),
+        };
+        let breakpoint = match op {
+            Op::Call(index) => {
+                let name = self.word_name(index);
+                self.breakpoints.iter().any(|breakpoint| breakpoint == name)
+            }
+            Op::Literal(_) => false,
+        };
+        let watched = match self.watchpoints.is_empty() {
+            true => None,
+            false => self.check_watchpoints(),
+        };
+        if let Some(watchpoint) = watched {
+            let word = match last {
+                Some(Op::Call(index)) => Some(String::from(self.word_name(index))),
+                _ => None,
+            };
+            self.watch_hit = Some(WatchHit { watchpoint, word });
+        }
+        if stepping || breakpoint || watched.is_some() {
+            self.paused = true;
+            return Err(Error::Stopped);
+        }
+        Ok(())
+    }
+
+    /// The compiled words of a stopped or paused evaluation, outermost
+    /// first.
+    pub fn return_stack(&self) -> Vec<CallFrame> {
+        self.return_stack.iter().map(|frame| CallFrame {
+            word: String::from(self.word_name(frame.word)),
+            pc: frame.pc,
+            span: self.instruction_spans(frame.word).get(frame.pc).copied(),
+        }).collect()
+    }
+
+    /// The compiled words the last failed evaluation was running when it
+    /// failed, innermost first, each with the token of its definition it
+    /// had got to and where the definition began. A compiled word called
+    /// at the end of another's body takes its place, so the caller isn't
+    /// listed. Words restored from a snapshot or image don't know where they began.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": HALF 0 / ;\n: AVERAGE + HALF 1 + ;").unwrap();
+    /// f.eval("3 4 AVERAGE").unwrap_err();
+    /// let trace = f.error_trace();
+    /// assert_eq!((trace[0].word.as_str(), trace[0].token.as_str()), ("HALF", "/"));
+    /// assert_eq!(trace[0].defined_at, Some((1, 1)));
+    /// assert_eq!((trace[1].word.as_str(), trace[1].token.as_str()), ("AVERAGE", "HALF"));
+    /// assert_eq!(trace[1].defined_at, Some((2, 1)));
+    /// ```
+    pub fn error_trace(&self) -> &[ErrorFrame] {
+        &self.error_trace
+    }
+
+    /// Adds the frames from `base` up to those `error_trace` lists.
+    pub(super) fn trace_error(&mut self, base: usize) {
+        for frame in self.return_stack[base..].iter().rev() {
+            let word = &self.words[frame.word];
+            // The instruction run last, which failed or made the call.
+            let pc = frame.pc.saturating_sub(1);
+            let span = self.instruction_spans(frame.word).get(pc).copied();
+            let text = span.and_then(|span| word.source.get(span.start..span.end));
+            let token = match (text, self.body(frame.word).get(pc)) {
+                (Some(text), _) => String::from(text),
+                (None, Some(&Op::Call(index))) => String::from(self.word_name(index)),
+                (None, Some(&Op::Literal(v))) => v.to_string(),
+                (None, None) => String::new(),
+            };
+            let word = String::from(self.word_name(frame.word));
+            let defined_at = self.words[frame.word].defined_at;
+            self.error_trace.push(ErrorFrame { word, token, span, defined_at });
+        }
+    }
+
+    /// The names and numbers a stopped or paused evaluation has still to
+    /// interpret once its compiled words return, in order.
+    pub fn token_queue(&self) -> Vec<String> {
+        let mut queue: Vec<String> = self.pending.iter().map(|&op| match op {
+            Op::Call(index) => String::from(self.word_name(index)),
+            Op::Literal(v) => v.to_string(),
+        }).collect();
+        if let Some(ref lexed) = self.lexed {
+            queue.extend(lexed.remaining_names().map(String::from));
+            return queue;
+        }
+        let (start, len) = self.source;
+        let source = &self.memory[start..start + len];
+        let mut offset = self.to_in();
+        while let Some(span) = scan_name(source, offset) {
+            queue.push(String::from_utf8_lossy(&source[span.start..span.end]).into_owned());
+            offset = span.end;
+        }
+        queue
+    }
+}
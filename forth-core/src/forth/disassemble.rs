@@ -0,0 +1,58 @@
+//! Listings of the instructions words compile to.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use super::{Forth, Instruction};
+
+impl Forth {
+    /// A listing of the compiled body of the word `name`, one instruction
+    /// a line: its index, what it does, and the source it was compiled
+    /// from, after a `\`. Literals the optimizer worked out show all the
+    /// source they came from. `None` if there is no such word or it is
+    /// native.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::builder().optimize(true).build();
+    /// f.eval(": SQUARE DUP * ; : AREA SQUARE 2 3 * * ;").unwrap();
+    /// assert_eq!(f.disassemble("AREA").unwrap(), "\
+    /// : AREA
+    ///      0  call       SQUARE       \\ SQUARE
+    ///      1  literal    6            \\ 2 3 *
+    ///      2  primitive  *            \\ *
+    /// ;
+    /// ");
+    /// assert_eq!(f.disassemble("DUP"), None);
+    /// ```
+    pub fn disassemble(&self, name: &str) -> Option<String> {
+        let index = self.lookup_word(name)?;
+        let word = &self.words[index];
+        if word.native {
+            return None;
+        }
+        let mut listing = format!(": {}\n", self.word_name(index));
+        for (i, instruction) in self.body_instructions(index).into_iter().enumerate() {
+            let (kind, operand) = match instruction {
+                Instruction::Literal(v) => ("literal", v.to_string()),
+                Instruction::Primitive(name) => ("primitive", String::from(name)),
+                Instruction::Call(name) => ("call", String::from(name)),
+            };
+            let line = format!("{:>6}  {:<9}  {:<12}", i, kind, operand);
+            let source = self.instruction_spans(index).get(i).and_then(|span| word.source.get(span.start..span.end));
+            match source {
+                Some(source) => {
+                    let source: Vec<_> = source.split_whitespace().collect();
+                    let _ = writeln!(listing, "{} \\ {}", line, source.join(" "));
+                }
+                None => {
+                    let _ = writeln!(listing, "{}", line.trim_end());
+                }
+            }
+        }
+        listing.push_str(if word.immediate { "; IMMEDIATE\n" } else { ";\n" });
+        Some(listing)
+    }
+}
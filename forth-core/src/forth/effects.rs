@@ -0,0 +1,376 @@
+//! Stack effects of words, inferred from the effects of the built-in words
+//! they call, and `Forth::lint`, which checks source text with them.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::{fmt, mem};
+
+use super::{Diagnostic, Error, Forth, Op, SourceToken, Span, TokenKind};
+
+/// How many cells a word takes from the stack and how many it leaves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackEffect {
+    pub inputs: usize,
+    pub outputs: usize,
+}
+
+impl StackEffect {
+    const NONE: StackEffect = StackEffect { inputs: 0, outputs: 0 };
+
+    fn new(inputs: usize, outputs: usize) -> StackEffect {
+        StackEffect { inputs, outputs }
+    }
+
+    /// The effect of running `self` and then `next`.
+    fn then(self, next: StackEffect) -> StackEffect {
+        StackEffect {
+            inputs: self.inputs + next.inputs.saturating_sub(self.outputs),
+            outputs: next.outputs + self.outputs.saturating_sub(next.inputs),
+        }
+    }
+
+    /// How much deeper the stack is afterwards.
+    fn net(self) -> isize {
+        self.outputs as isize - self.inputs as isize
+    }
+}
+
+impl fmt::Display for StackEffect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "( {} -- {} )", self.inputs, self.outputs)
+    }
+}
+
+/// The effect of the built-in word `name`, at run time or, for immediate
+/// words, while compiling, if it is always the same.
+pub(super) fn builtin_effect(name: &str) -> Option<StackEffect> {
+    let (inputs, outputs) = match name {
+        "+" | "-" | "*" | "/" | "MOD" | "=" | "<>" => (2, 1),
+        "1+" | "1-" | "2*" | "2/" | "CELL+" | "CELLS" | "CHAR+" | "CHARS" | "@" | "C@" | "WORD" | "FREE" | "XC-SIZE"
+        | "INVERT" | "XCHAR+" | "BUTTON?" | "RANDOM" | "TASK" | "BLOCK" | "BUFFER" | "FIELD:"
+        | "CFIELD:" | "ELAPSED" | "S>F" | "F>S" | "BITS>F" | "RECV" | "$RECV?" => (1, 1),
+        "/MOD" | "SWAP" | "#" | "#S" | "#>" | "RESIZE" | "M*" => (2, 2),
+        "D+" => (4, 2),
+        "BEGIN-STRUCTURE" => (0, 2),
+        "END-STRUCTURE" => (2, 0),
+        "+FIELD" => (2, 1),
+        "FM/MOD" | "SM/REM" => (3, 2),
+        "WITHIN" => (3, 1),
+        "DUP" | "S>D" | "COUNT" | "PARSE" | "ALLOCATE" | "XC@+" | "EKEY>CHAR" | "2@" => (1, 2),
+        "OVER" => (2, 3),
+        "DROP" | "HOLD" | "XHOLD" | "SIGN" | "EMIT" | "XEMIT" | "." | "ALLOT" | "," | "CONSTANT" | "ALIAS"
+        | "SET-CURRENT" | "MS" | "SEED" | "LOG" | "EVERY-FRAME" | "+RECOGNIZER" | "CANCEL" | "STOP-TASK" | "LIST"
+        | "N>$" | "$SEND" | "$RECV" | "FG" | "BG" => (1, 0),
+        "!" | "C!" | "TYPE" | "DUMP" | ".R" | "U.R" | "ERASE" | "LOG-TYPE" | ">$" | "$SUB" | "2CONSTANT" | "SEND"
+        | "AT-XY" => (2, 0),
+        "MOVE" | "CMOVE" | "CMOVE>" | "FILL" | "2!" => (3, 0),
+        "X-SIZE" | "ACCEPT" | "AFTER" | "EVERY" | ">STRING" => (2, 1),
+        "XC!+?" => (3, 3),
+        ">NUMBER" => (4, 4),
+        "COMPARE" => (4, 1),
+        "BL" | ">IN" | "BASE" | "HERE" | "CHAR" | "[CHAR]" | "'" | "[DEFINED]" | "[UNDEFINED]" | "KEY" | "XKEY"
+        | "KEY?" | "EKEY" | "EKEY?" | "MOUSE-X" | "MOUSE-Y" | "TICKS" | "COUNTER" | "WORDLIST" | "FORTH-WORDLIST"
+        | "GET-CURRENT" | "$LEN" | "$DEPTH" | "$SEARCH" | "$=" | "\"" => (0, 1),
+        "SOURCE" | "PARSE-NAME" | "S\"" | "S\\\"" | "$@" => (0, 2),
+        "TIME&DATE" => (0, 6),
+        ":" | "(" | "\\" | "CREATE" | "VARIABLE" | "2VARIABLE" | "SYNONYM" | "DECIMAL" | "HEX" | "<#" | "CR"
+        | "DEFINITIONS" | "FORTH" | "ONLY" | "ALSO" | "PREVIOUS" | "STOP-FRAMES" | "SAVE-DICT" | "T{" | "[THEN]"
+        | "HELP" | "PAUSE" | "UPDATE" | "SAVE-BUFFERS" | "EMPTY-BUFFERS" | "FLUSH" | "$\"" | "$." | "$DROP" | "$DUP"
+        | "$SWAP" | "$+" | "MODULE" | "PRIVATE" | "PUBLIC" | "END-MODULE" | "PAGE" | "BOLD"
+        | "NORMAL" => (0, 0),
+        _ => return None,
+    };
+    Some(StackEffect::new(inputs, outputs))
+}
+
+/// An `[IF]` whose `[THEN]` hasn't been reached: the effect before it,
+/// and that of the first branch once `[ELSE]` ends it.
+struct Branch {
+    before: Option<StackEffect>,
+    first: Option<Option<StackEffect>>,
+}
+
+/// The colon definition being linted: its name, the effect of its body so
+/// far and whether EXIT has left it.
+struct Open {
+    name: String,
+    effect: Option<StackEffect>,
+    exited: bool,
+}
+
+struct Linter<'a> {
+    forth: &'a Forth,
+    /// Depth of the stack the source starts with, until an underflow
+    /// makes what follows uncertain, and the effect of the source so far.
+    depth: Option<usize>,
+    effect: Option<StackEffect>,
+    definition: Option<Open>,
+    /// Effects of the words the source defines, latest last.
+    defined: Vec<(String, Option<StackEffect>)>,
+    branches: Vec<Branch>,
+    /// For each quotation the definition is inside, innermost last, the
+    /// effect and exit of the code around it.
+    quotations: Vec<(Option<StackEffect>, bool)>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Linter<'a> {
+    fn report(&mut self, error: Error, span: Span) {
+        self.diagnostics.push(Diagnostic { error, span });
+    }
+
+    fn effect_of(&self, name: &str) -> Option<StackEffect> {
+        match self.defined.iter().rev().find(|(defined, _)| defined == name) {
+            Some(&(_, effect)) => effect,
+            None => self.forth.stack_effect(name),
+        }
+    }
+
+    /// The effect code is adding to: the definition's, or the source's.
+    fn current(&mut self) -> &mut Option<StackEffect> {
+        match self.definition {
+            Some(ref mut open) => &mut open.effect,
+            None => &mut self.effect,
+        }
+    }
+
+    /// Adds `effect` to the definition if in one, or else to the source,
+    /// reporting an underflow of the stack the source starts with.
+    fn apply(&mut self, effect: Option<StackEffect>, span: Span) {
+        match self.definition {
+            Some(ref mut open) if open.exited => return,
+            Some(ref mut open) => {
+                open.effect = open.effect.and_then(|so_far| effect.map(|effect| so_far.then(effect)));
+                return;
+            }
+            None => {}
+        }
+        self.effect = self.effect.and_then(|so_far| effect.map(|effect| so_far.then(effect)));
+        if let (Some(depth), Some(effect)) = (self.depth, self.effect) {
+            if effect.inputs > depth {
+                self.report(Error::StackUnderflow, span);
+                // Evaluation would stop here, so the rest can't be judged.
+                self.depth = None;
+            }
+        }
+    }
+
+    /// Adds `effect` to the source, even within a definition, as for the
+    /// flag `[IF]` takes while compiling.
+    fn apply_outside(&mut self, effect: StackEffect, span: Span) {
+        let open = self.definition.take();
+        self.apply(Some(effect), span);
+        self.definition = open;
+    }
+
+    fn bracket_if(&mut self, span: Span) {
+        self.apply_outside(StackEffect::new(1, 0), span);
+        let before = *self.current();
+        self.branches.push(Branch { before, first: None });
+    }
+
+    fn bracket_else(&mut self) {
+        let current = *self.current();
+        if let Some(branch) = self.branches.last_mut() {
+            branch.first = Some(current);
+            let before = branch.before;
+            *self.current() = before;
+        }
+    }
+
+    fn bracket_then(&mut self, span: Span) {
+        let branch = match self.branches.pop() {
+            Some(branch) => branch,
+            None => return,
+        };
+        // Without [ELSE], the other branch does nothing.
+        let first = branch.first.unwrap_or(branch.before);
+        let second = *self.current();
+        let joined = match (first, second) {
+            (Some(first), Some(second)) if first.net() != second.net() => {
+                self.report(Error::UnbalancedStack(String::from("[THEN]")), span);
+                None
+            }
+            (Some(first), Some(second)) if first.inputs >= second.inputs => Some(first),
+            (Some(_), second) => second,
+            _ => None,
+        };
+        *self.current() = joined;
+    }
+
+    fn run(&mut self, tokens: &[SourceToken]) {
+        for (i, token) in tokens.iter().enumerate() {
+            let span = token.span;
+            match token.kind {
+                TokenKind::Number => {
+                    self.apply(Some(StackEffect::new(0, 1)), span);
+                    continue;
+                }
+                TokenKind::Unknown | TokenKind::OutOfRange => {
+                    self.apply(None, span);
+                    continue;
+                }
+                TokenKind::Word | TokenKind::Defining => {}
+                _ => continue,
+            }
+            let name = self.forth.canonical_name(&token.text);
+            // The name a defining word gives, and for SYNONYM the one after.
+            let names = |n: usize| tokens.get(i + n).map(|token| self.forth.canonical_name(&token.text).into_owned());
+            let (next, after) = (names(1), names(2));
+            match &*name {
+                ":" if self.definition.is_none() => {
+                    if let Some(name) = next {
+                        self.definition = Some(Open { name, effect: Some(StackEffect::NONE), exited: false });
+                    }
+                }
+                ";" if self.definition.is_some() => {
+                    let open = self.definition.take().unwrap();
+                    self.defined.push((open.name, open.effect));
+                    self.quotations.clear();
+                }
+                "[IF]" => self.bracket_if(span),
+                "[ELSE]" => self.bracket_else(),
+                "[THEN]" => self.bracket_then(span),
+                // A quotation is judged apart, leaving only its token.
+                "[:" if self.definition.is_some() => {
+                    let open = self.definition.as_mut().unwrap();
+                    let around = open.effect.replace(StackEffect::NONE);
+                    self.quotations.push((around, mem::replace(&mut open.exited, false)));
+                }
+                ";]" if self.definition.is_some() && !self.quotations.is_empty() => {
+                    let (effect, exited) = self.quotations.pop().unwrap();
+                    let open = self.definition.as_mut().unwrap();
+                    open.effect = effect;
+                    open.exited = exited;
+                    self.apply(Some(StackEffect::new(0, 1)), span);
+                }
+                "EXIT" if self.definition.is_some() => {
+                    self.definition.as_mut().unwrap().exited = true;
+                }
+                "CONSTANT" | "VARIABLE" | "CREATE" | "BEGIN-STRUCTURE" if self.definition.is_none() => {
+                    self.apply(builtin_effect(&name), span);
+                    if let Some(defined) = next {
+                        self.defined.push((defined, Some(StackEffect::new(0, 1))));
+                    }
+                }
+                "2CONSTANT" | "2VARIABLE" if self.definition.is_none() => {
+                    self.apply(builtin_effect(&name), span);
+                    let outputs = if name == "2CONSTANT" { 2 } else { 1 };
+                    if let Some(defined) = next {
+                        self.defined.push((defined, Some(StackEffect::new(0, outputs))));
+                    }
+                }
+                "+FIELD" | "FIELD:" | "CFIELD:" if self.definition.is_none() => {
+                    self.apply(builtin_effect(&name), span);
+                    if let Some(defined) = next {
+                        self.defined.push((defined, Some(StackEffect::new(1, 1))));
+                    }
+                }
+                "SYNONYM" if self.definition.is_none() => {
+                    let target = after.and_then(|target| self.effect_of(&target));
+                    if let Some(defined) = next {
+                        self.defined.push((defined, target));
+                    }
+                }
+                "ALIAS" if self.definition.is_none() => {
+                    self.apply(builtin_effect(&name), span);
+                    if let Some(defined) = next {
+                        self.defined.push((defined, None));
+                    }
+                }
+                _ => {
+                    let effect = self.effect_of(&name);
+                    self.apply(effect, span);
+                }
+            }
+        }
+    }
+}
+
+impl Forth {
+    /// The stack effect of the word `name`: how many cells it takes and
+    /// leaves, worked out from those of the built-in words its definition
+    /// calls. `None` if the word isn't found, or its effect depends on
+    /// the stack or input, as with EXECUTE or a word defined by the host.
+    ///
+    /// ```
+    /// use forth_core::{Forth, StackEffect};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQUARE DUP * ; : CUBE DUP SQUARE * ; : RUN EXECUTE ;").unwrap();
+    /// assert_eq!(f.stack_effect("CUBE"), Some(StackEffect { inputs: 1, outputs: 1 }));
+    /// assert_eq!(f.stack_effect("OVER").unwrap().to_string(), "( 2 -- 3 )");
+    /// assert_eq!(f.stack_effect("RUN"), None);
+    /// ```
+    pub fn stack_effect(&self, name: &str) -> Option<StackEffect> {
+        let index = self.lookup_word(name)?;
+        self.word_effect(index, &mut Vec::new())
+    }
+
+    /// The effect of the word at `index`, unknown for words in `calling`,
+    /// whose definitions refer to themselves.
+    fn word_effect(&self, index: usize, calling: &mut Vec<usize>) -> Option<StackEffect> {
+        let word = &self.words[index];
+        if let Some(target) = word.alias_of {
+            return self.word_effect(target, calling);
+        }
+        if word.native {
+            // Words the host defined do as they please.
+            return match index < self.primitives {
+                true => builtin_effect(self.word_name(index)),
+                false => None,
+            };
+        }
+        if calling.contains(&index) {
+            return None;
+        }
+        calling.push(index);
+        let mut effect = Some(StackEffect::NONE);
+        for &op in self.body(index) {
+            let next = match op {
+                Op::Literal(_) => Some(StackEffect::new(0, 1)),
+                Op::Call(_) if self.primitive(op) == Some("EXIT") => break,
+                Op::Call(called) => self.word_effect(called, calling),
+            };
+            effect = effect.and_then(|so_far| next.map(|next| so_far.then(next)));
+        }
+        calling.pop();
+        effect
+    }
+
+    /// Checks `source` without running it for mistakes in how it uses the
+    /// stack, going by the effects `stack_effect` gives: code that will
+    /// underflow the stack as it is now, and `[IF]` branches that leave it
+    /// at different depths. Words `source` defines are judged by their
+    /// definitions. Once an effect can't be known, as for an unknown word
+    /// or EXECUTE, the stack isn't judged past it. `check` finds unknown
+    /// words and unbalanced control structures.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let f = Forth::new();
+    /// let source = ": AREA * ;\n3 AREA\n[DEFINED] AREA [IF] 1 2 [ELSE] 3 [THEN]";
+    /// let errors: Vec<_> = f.lint(source).into_iter()
+    ///     .map(|d| (d.error, &source[d.span.start..d.span.end]))
+    ///     .collect();
+    /// assert_eq!(errors, [
+    ///     (Error::StackUnderflow, "AREA"),
+    ///     (Error::UnbalancedStack(String::from("[THEN]")), "[THEN]"),
+    /// ]);
+    /// ```
+    pub fn lint(&self, source: &str) -> Vec<Diagnostic> {
+        let mut linter = Linter {
+            forth: self,
+            depth: Some(self.stack.len()),
+            effect: Some(StackEffect::NONE),
+            definition: None,
+            defined: Vec::new(),
+            branches: Vec::new(),
+            quotations: Vec::new(),
+            diagnostics: Vec::new(),
+        };
+        linter.run(&self.tokenize(source));
+        linter.diagnostics
+    }
+}
@@ -0,0 +1,171 @@
+//! Keyboard and pointer events the host feeds in, for EKEY and friends,
+//! and MOUSE-X, MOUSE-Y and BUTTON?.
+
+use alloc::vec::Vec;
+
+use super::arena::Arena;
+use super::intern::Names;
+use super::{Error, Forth, ForthResult, Op, Value, Word, FORTH_WORDLIST};
+
+/// A key pressed, as `Forth::push_key` queues it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    /// A key that types a character, which KEY reads too.
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    /// A function key, F1 to F12.
+    F(u8),
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+}
+
+/// The keys without characters, with the words naming them and the
+/// private-use characters that stand for them in the input queue, as on
+/// macOS.
+const KEYS: [(Key, &str, char); 22] = [
+    (Key::Up, "K-UP", '\u{F700}'),
+    (Key::Down, "K-DOWN", '\u{F701}'),
+    (Key::Left, "K-LEFT", '\u{F702}'),
+    (Key::Right, "K-RIGHT", '\u{F703}'),
+    (Key::F(1), "K-F1", '\u{F704}'),
+    (Key::F(2), "K-F2", '\u{F705}'),
+    (Key::F(3), "K-F3", '\u{F706}'),
+    (Key::F(4), "K-F4", '\u{F707}'),
+    (Key::F(5), "K-F5", '\u{F708}'),
+    (Key::F(6), "K-F6", '\u{F709}'),
+    (Key::F(7), "K-F7", '\u{F70A}'),
+    (Key::F(8), "K-F8", '\u{F70B}'),
+    (Key::F(9), "K-F9", '\u{F70C}'),
+    (Key::F(10), "K-F10", '\u{F70D}'),
+    (Key::F(11), "K-F11", '\u{F70E}'),
+    (Key::F(12), "K-F12", '\u{F70F}'),
+    (Key::Insert, "K-INSERT", '\u{F727}'),
+    (Key::Delete, "K-DELETE", '\u{F728}'),
+    (Key::Home, "K-HOME", '\u{F729}'),
+    (Key::End, "K-END", '\u{F72B}'),
+    (Key::PageUp, "K-PRIOR", '\u{F72C}'),
+    (Key::PageDown, "K-NEXT", '\u{F72D}'),
+];
+
+/// Whether `c` in the input queue stands for a key without a character,
+/// which only EKEY reads.
+pub(super) fn is_key_event(c: char) -> bool {
+    KEYS.iter().any(|&(_, _, code)| code == c)
+}
+
+/// Where the pointer is and which of its buttons are held, as
+/// `Forth::set_pointer` last said.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct Pointer {
+    x: Value,
+    y: Value,
+    buttons: u32,
+}
+
+pub(super) fn do_ekey(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.fill_input()?;
+    let c = forth.input.pop_front().ok_or(Error::NoInput)?;
+    forth.push(c as Value);
+    Ok(())
+}
+
+pub(super) fn do_ekey_question(forth: &mut Forth, _index: usize) -> ForthResult {
+    let ready = !forth.input.is_empty();
+    forth.push(if ready { -1 } else { 0 });
+    Ok(())
+}
+
+pub(super) fn do_ekey_to_char(forth: &mut Forth, _index: usize) -> ForthResult {
+    let x = forth.pop()?;
+    let is_char = match core::char::from_u32(x as u32) {
+        Some(c) => x >= 0 && !is_key_event(c),
+        None => false,
+    };
+    forth.push(x);
+    forth.push(if is_char { -1 } else { 0 });
+    Ok(())
+}
+
+pub(super) fn do_mouse_x(forth: &mut Forth, _index: usize) -> ForthResult {
+    let x = forth.pointer.x;
+    forth.push(x);
+    Ok(())
+}
+
+pub(super) fn do_mouse_y(forth: &mut Forth, _index: usize) -> ForthResult {
+    let y = forth.pointer.y;
+    forth.push(y);
+    Ok(())
+}
+
+pub(super) fn do_button_question(forth: &mut Forth, _index: usize) -> ForthResult {
+    let button = forth.pop()?;
+    let held = (0..32).contains(&button) && forth.pointer.buttons & (1 << button) != 0;
+    forth.push(if held { -1 } else { 0 });
+    Ok(())
+}
+
+/// Adds K-UP and the other constants for keys EKEY can return to the
+/// built-in `words`.
+pub(super) fn define_key_constants(words: &mut Vec<Word>, bodies: &mut Arena<Op>, names: &mut Names) {
+    for &(_, name, code) in KEYS.iter() {
+        let data = bodies.alloc(&[Op::Literal(code as Value)]);
+        words.push(Word::new_compiled(names.intern(name), data, FORTH_WORDLIST as usize));
+    }
+}
+
+impl Forth {
+    /// Queues a key press for EKEY, which returns the character typed, or
+    /// for keys without one the value of K-UP, K-F1 or the like. KEY and
+    /// ACCEPT read typed characters and skip other keys.
+    ///
+    /// ```
+    /// use forth_core::{Forth, Key};
+    ///
+    /// let mut f = Forth::new();
+    /// f.push_key(Key::Left);
+    /// f.push_key(Key::Char('a'));
+    /// f.eval("EKEY K-LEFT - EKEY EKEY>CHAR").unwrap();
+    /// assert_eq!(f.stack(), vec![0, 97, -1]);
+    ///
+    /// f.push_key(Key::Up);
+    /// f.push_key(Key::Char('b'));
+    /// f.eval("KEY").unwrap();
+    /// assert_eq!(f.stack()[3], 98);
+    /// ```
+    pub fn push_key(&mut self, key: Key) {
+        let c = match key {
+            Key::Char(c) => c,
+            key => match KEYS.iter().find(|&&(k, _, _)| k == key) {
+                Some(&(_, _, code)) => code,
+                // A function key past F12.
+                None => return,
+            },
+        };
+        self.input.push_back(c);
+    }
+
+    /// Moves the pointer MOUSE-X and MOUSE-Y report to `x` and `y`, and
+    /// holds the buttons whose bits are set in `buttons`, bit `n` for the
+    /// button BUTTON? calls `n`: 0 for the main button, 1 for the middle
+    /// and 2 for the secondary, as in DOM mouse events.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.set_pointer(120, 45, 0b100);
+    /// f.eval("MOUSE-X MOUSE-Y 0 BUTTON? 2 BUTTON?").unwrap();
+    /// assert_eq!(f.stack(), vec![120, 45, 0, -1]);
+    /// ```
+    pub fn set_pointer(&mut self, x: Value, y: Value, buttons: u32) {
+        self.pointer = Pointer { x, y, buttons };
+    }
+}
@@ -0,0 +1,103 @@
+//! Running an evaluation a name or number at a time, for Rust tools and
+//! tests to drive, built on the debugger's stepping.
+
+use alloc::string::String;
+
+use super::{Error, Forth, ForthResult, Op, Value};
+
+/// What `Execution::step` did.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Ran the named word of the input, along with any words it called,
+    /// or, for a defining word such as `:`, the definition it began.
+    Executed(String),
+    /// Pushed a number of the input.
+    Pushed(Value),
+    /// The evaluation failed, in the step before or in the input after it.
+    Failed(Error),
+    /// The evaluation is over.
+    Finished,
+}
+
+/// An evaluation `Forth::start` began, run a step at a time, or as an
+/// iterator of its steps up to `Finished`.
+pub struct Execution<'a> {
+    forth: &'a mut Forth,
+    /// How the evaluation ended, once it has, until `step` reports it.
+    outcome: Option<ForthResult>,
+}
+
+impl<'a> Execution<'a> {
+    /// Runs the next name or number of the input.
+    pub fn step(&mut self) -> Step {
+        if let Some(outcome) = self.outcome.take() {
+            self.outcome = Some(Ok(()));
+            return match outcome {
+                Ok(()) => Step::Finished,
+                Err(e) => Step::Failed(e),
+            };
+        }
+        let step = match self.forth.stopped_before() {
+            Some(Op::Call(index)) => Step::Executed(String::from(self.forth.word_name(index))),
+            Some(Op::Literal(v)) => Step::Pushed(v),
+            None => return Step::Finished,
+        };
+        loop {
+            match self.forth.step() {
+                // Stopped within a compiled word the step called.
+                Ok(true) if !self.forth.return_stack.is_empty() => continue,
+                Ok(true) => {}
+                Ok(false) => self.outcome = Some(Ok(())),
+                Err(e) => self.outcome = Some(Err(e)),
+            }
+            return step;
+        }
+    }
+
+    /// The interpreter, as the steps so far left it. Its `output` is what
+    /// the last step printed.
+    pub fn forth(&self) -> &Forth {
+        self.forth
+    }
+}
+
+impl<'a> Iterator for Execution<'a> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        match self.step() {
+            Step::Finished => None,
+            step => Some(step),
+        }
+    }
+}
+
+impl Forth {
+    /// Begins evaluating `source` as `eval` would, but leaves it to the
+    /// returned `Execution` to run, a name or number of the input at a
+    /// time. Definitions and EVALUATE run whole, as a single step.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth, Step};
+    ///
+    /// let mut f = Forth::new();
+    /// let mut run = f.start(": SQ DUP * ; 3 SQ NOPE");
+    /// assert_eq!(run.step(), Step::Executed(":".into()));
+    /// assert_eq!(run.step(), Step::Pushed(3));
+    /// assert_eq!(run.step(), Step::Executed("SQ".into()));
+    /// assert_eq!(run.forth().stack(), vec![9]);
+    /// assert_eq!(run.step(), Step::Failed(Error::UnknownWord("NOPE".into())));
+    /// assert_eq!(run.step(), Step::Finished);
+    ///
+    /// let steps: Vec<_> = f.start("1 2 +").collect();
+    /// assert_eq!(steps, [Step::Pushed(1), Step::Pushed(2), Step::Executed("+".into())]);
+    /// ```
+    pub fn start(&mut self, source: &str) -> Execution<'_> {
+        let outcome = match self.debug(source) {
+            Ok(true) => None,
+            Ok(false) => Some(Ok(())),
+            Err(e) => Some(Err(e)),
+        };
+        Execution { forth: self, outcome }
+    }
+}
@@ -0,0 +1,201 @@
+//! Pretty-printing of source text.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{Forth, SourceToken, TokenKind};
+
+/// How `Forth::format` writes the letters of names.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Case {
+    /// As in the source.
+    Preserve,
+    Upper,
+    Lower,
+}
+
+/// Settings for `Forth::format`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatOptions {
+    /// Spaces per level of indentation.
+    pub indent: usize,
+    pub case: Case,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions { indent: 2, case: Case::Preserve }
+    }
+}
+
+/// Control-flow words, by how they affect indentation.
+fn opens(name: &str) -> bool {
+    ["IF", "BEGIN", "DO", "?DO", "CASE", "OF"].contains(&name)
+}
+
+fn closes(name: &str) -> bool {
+    ["THEN", "REPEAT", "UNTIL", "AGAIN", "LOOP", "+LOOP", "ENDOF", "ENDCASE"].contains(&name)
+}
+
+fn continues(name: &str) -> bool {
+    ["ELSE", "WHILE"].contains(&name)
+}
+
+struct Formatter<'a> {
+    options: &'a FormatOptions,
+    out: String,
+    line: Vec<String>,
+    /// Indentation level of the line being built, and of the next line.
+    line_depth: usize,
+    depth: usize,
+}
+
+impl<'a> Formatter<'a> {
+    fn push(&mut self, piece: String) {
+        if self.line.is_empty() {
+            self.line_depth = self.depth;
+        }
+        self.line.push(piece);
+    }
+
+    fn newline(&mut self) {
+        if self.line.is_empty() {
+            return;
+        }
+        for _ in 0..self.line_depth * self.options.indent {
+            self.out.push(' ');
+        }
+        self.out.push_str(&self.line.join(" "));
+        self.out.push('\n');
+        self.line.clear();
+    }
+
+    fn blank_line(&mut self) {
+        self.newline();
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    fn name(&self, token: &SourceToken) -> String {
+        match self.options.case {
+            Case::Preserve => token.text.clone(),
+            Case::Upper => token.text.to_uppercase(),
+            Case::Lower => token.text.to_lowercase(),
+        }
+    }
+}
+
+impl Forth {
+    /// Re-indents `source`: each colon definition starts on its own line
+    /// with its body indented, control structures indent what they enclose,
+    /// and names are separated by single spaces. Comments and strings are
+    /// kept as written, as are line breaks, though runs of blank lines
+    /// shrink to one.
+    pub fn format(&self, source: &str, options: &FormatOptions) -> String {
+        let tokens = self.tokenize(source);
+        let mut f = Formatter { options, out: String::new(), line: Vec::new(), line_depth: 0, depth: 0 };
+        let mut in_definition = false;
+        // Set after `: name`, whose line may also hold a stack comment.
+        let mut in_header = false;
+        // Set after `;`, which may be followed by a comment on its line.
+        let mut after_definition = false;
+        let mut prev_end = 0;
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            let next = tokens.get(i + 1);
+            let name = self.canonical_name(&token.text);
+            let breaks = source[prev_end..token.span.start].matches('\n').count();
+            if breaks > 1 {
+                f.blank_line();
+            } else if breaks == 1 {
+                f.newline();
+            }
+            if in_header && name != "(" {
+                f.newline();
+                in_header = false;
+            }
+            if after_definition && !(breaks == 0 && name == "\\") {
+                f.newline();
+            }
+            after_definition = false;
+
+            // Parsing words and the text they take.
+            let text = next.filter(|next| token.kind == TokenKind::Word
+                && (next.kind == TokenKind::Comment || next.kind == TokenKind::String));
+            if let Some(text) = text {
+                let terminated = text.span.end < source.len();
+                let piece = match &*name {
+                    "(" => {
+                        let comment = text.text.trim();
+                        let close = if terminated { ")" } else { "" };
+                        if comment.is_empty() { format!("( {}", close) } else { format!("( {} {}", comment, close) }
+                    }
+                    "\\" => format!("\\ {}", text.text.trim_end()),
+                    _ => format!("{} {}{}", f.name(token), text.text, if terminated { "\"" } else { "" }),
+                };
+                f.push(String::from(piece.trim_end()));
+                if name == "\\" || in_header {
+                    f.newline();
+                }
+                in_header = false;
+                prev_end = text.span.end;
+                i += 2;
+                continue;
+            }
+            in_header = false;
+            prev_end = token.span.end;
+            i += 1;
+
+            if token.kind == TokenKind::Defining && name == ":" {
+                if let Some(defined) = next.filter(|next| next.kind == TokenKind::Definition) {
+                    f.newline();
+                    f.depth = 0;
+                    let piece = format!("{} {}", token.text, f.name(defined));
+                    f.push(piece);
+                    f.depth = 1;
+                    in_definition = true;
+                    in_header = true;
+                    prev_end = defined.span.end;
+                    i += 1;
+                    continue;
+                }
+            }
+
+            let piece = match token.kind {
+                TokenKind::Number | TokenKind::OutOfRange | TokenKind::Comment | TokenKind::String => token.text.clone(),
+                _ => f.name(token),
+            };
+            if !in_definition {
+                f.push(piece);
+            } else if name == ";" {
+                f.push(piece);
+                f.depth = 0;
+                after_definition = true;
+                in_definition = false;
+            } else if opens(&name) {
+                f.push(piece);
+                f.depth += 1;
+                f.newline();
+            } else if closes(&name) {
+                f.newline();
+                f.depth = f.depth.saturating_sub(1).max(1);
+                f.push(piece);
+            } else if continues(&name) {
+                f.newline();
+                f.depth = f.depth.saturating_sub(1).max(1);
+                f.push(piece);
+                f.depth += 1;
+                f.newline();
+            } else {
+                f.push(piece);
+            }
+        }
+        f.newline();
+        if !source.ends_with('\n') {
+            f.out.pop();
+        }
+        f.out
+    }
+}
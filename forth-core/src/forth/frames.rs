@@ -0,0 +1,62 @@
+//! EVERY-FRAME and STOP-FRAMES, which choose a word for the host to run
+//! on each frame of an animation with `Forth::run_frame`.
+
+use core::mem;
+
+use super::{Forth, ForthResult, Op};
+
+pub(super) fn do_every_frame(forth: &mut Forth, _index: usize) -> ForthResult {
+    let xt = forth.pop()?;
+    forth.frame_word = Some(forth.xt(xt)?);
+    Ok(())
+}
+
+pub(super) fn do_stop_frames(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.frame_word = None;
+    Ok(())
+}
+
+impl Forth {
+    /// Whether EVERY-FRAME has chosen a word for `run_frame` to run.
+    pub fn has_frame_word(&self) -> bool {
+        self.frame_word.is_some()
+    }
+
+    /// Runs the word EVERY-FRAME chose, for at most `fuel` instructions if
+    /// given, keeping the limit `set_fuel` set for later evaluations.
+    /// Output is collected as by `eval`. A frame that fails, or runs out of
+    /// fuel, can't be resumed; its word stops being run, as if by
+    /// STOP-FRAMES.
+    ///
+    /// Does nothing if no word is chosen, or while an evaluation is
+    /// running or paused, which a frame would otherwise cut short.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval("VARIABLE FRAMES : TICK FRAMES @ 1 + FRAMES ! ; ' TICK EVERY-FRAME").unwrap();
+    /// f.run_frame(None).unwrap();
+    /// f.run_frame(None).unwrap();
+    /// f.eval("FRAMES @").unwrap();
+    /// assert_eq!(f.stack(), vec![2]);
+    ///
+    /// f.eval(": SQ DUP * ; : BUSY 3 SQ SQ DROP ; ' BUSY EVERY-FRAME").unwrap();
+    /// assert_eq!(f.run_frame(Some(4)), Err(Error::OutOfFuel));
+    /// assert!(!f.has_frame_word());
+    /// ```
+    pub fn run_frame(&mut self, fuel: Option<u64>) -> ForthResult {
+        let index = match self.frame_word {
+            Some(index) if !self.paused && self.executing == 0 => index,
+            _ => return Ok(()),
+        };
+        let limit = mem::replace(&mut self.fuel, fuel);
+        let result = self.with_input("", |forth| forth.interpret_ops(&[Op::Call(index)]));
+        self.fuel = limit;
+        if result.is_err() {
+            self.discard_paused();
+            self.frame_word = None;
+        }
+        result
+    }
+}
@@ -0,0 +1,131 @@
+//! Reclaiming the bodies of definitions shadowed by later ones of the same
+//! name that nothing can reach any more.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem;
+
+use super::arena::{Arena, Slot};
+use super::{Forth, Op, Span, Value, CELL};
+
+/// What `Forth::gc` reclaimed.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GcStats {
+    /// Dictionary entries whose bodies were dropped.
+    pub words: usize,
+    /// Size of the instructions, spans and source text dropped.
+    pub bytes: usize,
+}
+
+impl Forth {
+    /// Drops the compiled body and source of each colon definition that a
+    /// later definition of the same name in the same wordlist hides, unless
+    /// something may still run it: a reachable word calling it, or its
+    /// execution token on the stack, in data space or compiled as a
+    /// literal. Any cell that holds its index counts, whether or not it is
+    /// meant as one. Entries keep their place, so execution tokens stay
+    /// valid. A `Program` compiled earlier may still call a reclaimed word,
+    /// which then does nothing.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": GREET 1 ; : GREET 2 ; : ANSWER 42 ; ' ANSWER : ANSWER 43 ;").unwrap();
+    /// let stats = f.gc();
+    /// assert_eq!(stats.words, 1);
+    /// f.eval("EXECUTE GREET").unwrap();
+    /// assert_eq!(f.stack(), vec![42, 2]);
+    /// // With its execution token gone, the first ANSWER goes too.
+    /// assert_eq!(f.gc().words, 1);
+    /// ```
+    pub fn gc(&mut self) -> GcStats {
+        let reachable = self.reachable_words();
+        let mut stats = GcStats::default();
+        for (index, word) in self.words.iter_mut().enumerate() {
+            if reachable[index] {
+                continue;
+            }
+            let bytes = word.data.len() * mem::size_of::<Op>()
+                + word.spans.len() * mem::size_of::<Span>()
+                + word.source.len();
+            if bytes == 0 {
+                continue;
+            }
+            word.data = Slot::default();
+            word.spans = Slot::default();
+            word.source = String::new();
+            stats.words += 1;
+            stats.bytes += bytes;
+        }
+        // The bodies left are copied into arenas of their own, so that the
+        // space of those dropped goes too.
+        let (mut bodies, mut body_spans) = (Arena::new(), Arena::new());
+        for word in &mut self.words {
+            word.data = bodies.alloc(self.bodies.get(word.data));
+            word.spans = body_spans.alloc(self.body_spans.get(word.spans));
+        }
+        self.bodies = bodies;
+        self.body_spans = body_spans;
+        stats
+    }
+
+    /// Which entries can't be reclaimed: those that can be found by name,
+    /// built in, native or aliases, the word EVERY-FRAME chose, the words
+    /// of timers, tasks and the recognizer chain, and anything their
+    /// bodies, the stacks, data space or definitions being compiled may
+    /// refer to.
+    fn reachable_words(&self) -> Vec<bool> {
+        let count = self.words.len();
+        let mut roots = Vec::new();
+        for (index, word) in self.words.iter().enumerate() {
+            let shadowed = || self.index[word.name.index()].iter()
+                .any(|&later| later > index && self.words[later].wordlist == word.wordlist);
+            if index < self.builtins || word.native || word.alias_of.is_some() || !shadowed() {
+                roots.push(index);
+            }
+        }
+        let xt = |v: Value| match v >= 0 && (v as u64) < count as u64 {
+            true => Some(v as usize),
+            false => None,
+        };
+        let op_refers = |op: &Op| match *op {
+            Op::Call(index) => Some(index),
+            Op::Literal(v) => xt(v),
+        };
+        roots.extend(self.stack.iter().filter_map(|&v| xt(v)));
+        // Cells may be stored at any address, so every offset is tried.
+        let data = &self.memory[..self.tib];
+        roots.extend(data.windows(CELL).filter_map(|bytes| {
+            let mut cell = [0; CELL];
+            cell.copy_from_slice(bytes);
+            xt(Value::from_le_bytes(cell))
+        }));
+        roots.extend(self.return_stack.iter().map(|frame| frame.word));
+        roots.extend(self.frame_word);
+        roots.extend(self.recognizer_words());
+        roots.extend(self.timers.list.iter().map(|timer| timer.word));
+        for task in &self.tasks.list {
+            roots.push(task.word);
+            roots.extend(task.stack.iter().filter_map(|&v| xt(v)));
+            roots.extend(task.return_stack.iter().map(|frame| frame.word));
+        }
+        roots.extend(self.pending.iter().filter_map(op_refers));
+        let compiling = self.definition.iter().chain(self.unfinished.iter().map(|unfinished| &unfinished.definition));
+        for definition in compiling {
+            roots.extend(definition.ops.iter().filter_map(op_refers));
+        }
+
+        let mut reachable = vec![false; count];
+        while let Some(index) = roots.pop() {
+            if mem::replace(&mut reachable[index], true) {
+                continue;
+            }
+            let word = &self.words[index];
+            roots.extend(self.bodies.get(word.data).iter().filter_map(op_refers));
+            roots.extend(word.alias_of);
+        }
+        reachable
+    }
+}
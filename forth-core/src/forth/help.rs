@@ -0,0 +1,66 @@
+//! HELP, which prints the stack comment a word was defined with, as
+//! `Forth::words` lists it.
+
+use alloc::format;
+
+use super::{scan_name, Forth, ForthResult};
+
+/// The `( before -- after )` comment right after the name in the colon
+/// definition `source`, parentheses included.
+fn stack_comment(source: &str) -> Option<&str> {
+    let input = source.as_bytes();
+    let colon = scan_name(input, 0)?;
+    let name = scan_name(input, colon.end)?;
+    let paren = scan_name(input, name.end)?;
+    if &source[paren.start..paren.end] != "(" {
+        return None;
+    }
+    let len = source[paren.end..].find(')')?;
+    Some(&source[paren.start..paren.end + len + 1])
+}
+
+/// HELP ( "name" -- ) prints the word's stack comment or, without one, the
+/// stack effect `stack_effect` infers for it.
+pub(super) fn do_help(forth: &mut Forth, _index: usize) -> ForthResult {
+    let index = forth.parse_word()?;
+    let name = forth.word_name(index);
+    let text = match forth.doc_at(index) {
+        Some(doc) => format!("{} {}\n", name, doc),
+        None => match forth.stack_effect(name) {
+            Some(effect) => format!("{} {}\n", name, effect),
+            None => format!("{}\n", name),
+        },
+    };
+    forth.emit(&text)
+}
+
+impl Forth {
+    /// The stack comment that follows the name in the colon definition of
+    /// `name`, such as `( n -- n*n )`, which HELP prints.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQUARE ( n -- n*n ) DUP * ; : CUBE DUP SQUARE * ;").unwrap();
+    /// assert_eq!(f.word_doc("square"), Some("( n -- n*n )"));
+    /// assert_eq!(f.word_doc("CUBE"), None);
+    /// assert_eq!(f.word_doc("NIP"), Some("( x1 x2 -- x2 )"));
+    ///
+    /// f.eval("HELP SQUARE HELP CUBE HELP DUP").unwrap();
+    /// assert_eq!(f.output(), "SQUARE ( n -- n*n )\nCUBE ( 1 -- 1 )\nDUP ( 1 -- 2 )\n");
+    /// ```
+    pub fn word_doc(&self, name: &str) -> Option<&str> {
+        self.doc_at(self.lookup_word(name)?)
+    }
+
+    /// The stack comment of the word at `index`, or of the word it is a
+    /// synonym for.
+    pub(super) fn doc_at(&self, index: usize) -> Option<&str> {
+        let word = &self.words[index];
+        match word.alias_of {
+            Some(target) => self.doc_at(target),
+            None => stack_comment(&word.source),
+        }
+    }
+}
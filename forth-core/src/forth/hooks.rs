@@ -0,0 +1,78 @@
+//! Host closures called as each word starts and finishes running.
+
+use alloc::boxed::Box;
+
+use super::Forth;
+
+/// Called with the name of a word and the depth of the data stack.
+pub type ExecutionHook = dyn FnMut(&str, usize);
+
+#[derive(Default)]
+pub(super) struct Hooks {
+    before: Option<Box<ExecutionHook>>,
+    after: Option<Box<ExecutionHook>>,
+}
+
+impl Forth {
+    /// Calls `hook` with the name of each word about to run, built in or
+    /// defined, and the depth of the data stack, so that the host can
+    /// watch execution as it happens.
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use forth_core::Forth;
+    ///
+    /// let log = Rc::new(RefCell::new(Vec::new()));
+    /// let (before, after) = (log.clone(), log.clone());
+    /// let mut f = Forth::new();
+    /// f.eval(": SQ DUP * ;").unwrap();
+    /// f.set_before_hook(move |name, depth| before.borrow_mut().push(format!("> {} {}", name, depth)));
+    /// f.set_after_hook(move |name, depth| after.borrow_mut().push(format!("< {} {}", name, depth)));
+    /// f.eval("3 SQ .").unwrap();
+    /// // SQ ends with a call of *, so it counts as finished when * starts.
+    /// assert_eq!(*log.borrow(), vec![
+    ///     "> SQ 1", "> DUP 1", "< DUP 2", "< SQ 2", "> * 2", "< * 1", "> . 1", "< . 0",
+    /// ]);
+    /// ```
+    pub fn set_before_hook<F>(&mut self, hook: F)
+        where F: FnMut(&str, usize) + 'static
+    {
+        self.hooks.before = Some(Box::new(hook));
+    }
+
+    /// Calls `hook` with the name of each word that has finished running
+    /// and the depth of the data stack it left. It isn't called for words
+    /// that fail. A call that ends a colon definition takes over its
+    /// caller's place on the return stack, so the caller counts as
+    /// finished when that call starts.
+    pub fn set_after_hook<F>(&mut self, hook: F)
+        where F: FnMut(&str, usize) + 'static
+    {
+        self.hooks.after = Some(Box::new(hook));
+    }
+
+    /// Stops calling the hooks `set_before_hook` and `set_after_hook` set.
+    pub fn clear_execution_hooks(&mut self) {
+        self.hooks = Hooks::default();
+    }
+
+    /// Whether either hook is set, which slows each call a little.
+    pub(super) fn hooked(&self) -> bool {
+        self.hooks.before.is_some() || self.hooks.after.is_some()
+    }
+
+    /// Tells the before hook that the word at `index` is starting.
+    pub(super) fn hook_before(&mut self, index: usize) {
+        if let Some(ref mut hook) = self.hooks.before {
+            hook(self.names.resolve(self.words[index].name), self.stack.len());
+        }
+    }
+
+    /// Tells the after hook that the word at `index` has finished.
+    pub(super) fn hook_after(&mut self, index: usize) {
+        if let Some(ref mut hook) = self.hooks.after {
+            hook(self.names.resolve(self.words[index].name), self.stack.len());
+        }
+    }
+}
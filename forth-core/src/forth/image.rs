@@ -0,0 +1,365 @@
+//! Binary images of the dictionary and data space.
+//!
+//! An image starts with `MAGIC`, a format version and the cell size in
+//! bytes, followed by little-endian fields in the order `Forth::save_image`
+//! writes them. Literals are cells; other integers are 32-bit. Strings and
+//! lists are prefixed with their length.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str;
+
+use heap::Heap;
+
+use super::{BlockBuffer, Error, Forth, ForthResult, Op, SavedWord, Span, Tasks, Timers, Value, CELL, HEAP_SIZE};
+
+const MAGIC: &[u8; 8] = b"FORTHIMG";
+const VERSION: u32 = 2;
+
+const FLAG_NATIVE: u8 = 1;
+const FLAG_IMMEDIATE: u8 = 2;
+
+const TAG_CALL: u8 = 0;
+const TAG_LITERAL: u8 = 1;
+
+/// Stands for `None` in optional word references.
+const NO_WORD: u32 = u32::MAX;
+
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, v: u8) {
+        self.bytes.push(v);
+    }
+
+    fn u32(&mut self, v: u32) {
+        self.bytes.extend_from_slice(&v.to_le_bytes());
+    }
+
+    fn usize(&mut self, v: usize) {
+        self.u32(v as u32);
+    }
+
+    fn bytes(&mut self, v: &[u8]) {
+        self.usize(v.len());
+        self.bytes.extend_from_slice(v);
+    }
+
+    fn op(&mut self, op: Op) {
+        match op {
+            Op::Call(index) => {
+                self.u8(TAG_CALL);
+                self.usize(index);
+            }
+            Op::Literal(v) => {
+                self.u8(TAG_LITERAL);
+                self.bytes.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+}
+
+/// Reads fields back, returning `None` when the image ends early or holds
+/// something malformed.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < n {
+            return None;
+        }
+        let (head, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Some(head)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let b = self.take(4)?;
+        Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn usize(&mut self) -> Option<usize> {
+        self.u32().map(|v| v as usize)
+    }
+
+    fn cell(&mut self) -> Option<Value> {
+        let mut bytes = [0; CELL];
+        bytes.copy_from_slice(self.take(CELL)?);
+        Some(Value::from_le_bytes(bytes))
+    }
+
+    /// A list length, rejected when the image can't possibly hold that many
+    /// items of at least `item_size` bytes.
+    fn len(&mut self, item_size: usize) -> Option<usize> {
+        let n = self.usize()?;
+        if n.checked_mul(item_size)? > self.bytes.len() {
+            return None;
+        }
+        Some(n)
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let n = self.len(1)?;
+        self.take(n)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        str::from_utf8(self.bytes()?).ok().map(String::from)
+    }
+
+    fn op(&mut self) -> Option<Op> {
+        match self.u8()? {
+            TAG_CALL => self.usize().map(Op::Call),
+            TAG_LITERAL => self.cell().map(Op::Literal),
+            _ => None,
+        }
+    }
+
+    fn word(&mut self) -> Option<SavedWord> {
+        let name = self.string()?;
+        let flags = self.u8()?;
+        let wordlist = self.usize()?;
+        let alias_of = match self.u32()? {
+            NO_WORD => None,
+            target => Some(target as usize),
+        };
+        let data = (0..self.len(5)?).map(|_| self.op()).collect::<Option<_>>()?;
+        let spans = (0..self.len(8)?)
+            .map(|_| Some(Span { start: self.usize()?, end: self.usize()? }))
+            .collect::<Option<_>>()?;
+        let source = self.string()?;
+        Some(SavedWord {
+            name,
+            native: flags & FLAG_NATIVE != 0,
+            data,
+            wordlist,
+            immediate: flags & FLAG_IMMEDIATE != 0,
+            alias_of,
+            spans,
+            source,
+        })
+    }
+}
+
+/// Everything an image holds, decoded but not yet checked against the
+/// interpreter loading it.
+struct Image {
+    data_space_size: usize,
+    here: usize,
+    wordlists: usize,
+    current: usize,
+    order: Vec<usize>,
+    words: Vec<SavedWord>,
+    heap_blocks: Vec<(usize, usize)>,
+    memory: Vec<u8>,
+}
+
+fn decode(bytes: &[u8]) -> Option<Image> {
+    let mut r = Reader { bytes };
+    if r.take(MAGIC.len())? != MAGIC || r.u32()? != VERSION || r.u8()? != CELL as u8 {
+        return None;
+    }
+    let data_space_size = r.usize()?;
+    let here = r.usize()?;
+    let wordlists = r.usize()?;
+    let current = r.usize()?;
+    let order = (0..r.len(4)?).map(|_| r.usize()).collect::<Option<_>>()?;
+    let words = (0..r.len(25)?).map(|_| r.word()).collect::<Option<_>>()?;
+    let heap_blocks = (0..r.len(8)?)
+        .map(|_| Some((r.usize()?, r.usize()?)))
+        .collect::<Option<_>>()?;
+    let memory = r.bytes()?.to_vec();
+    if !r.bytes.is_empty() {
+        return None;
+    }
+    Some(Image { data_space_size, here, wordlists, current, order, words, heap_blocks, memory })
+}
+
+impl Forth {
+    /// Saves the dictionary and data space, including the heap, as a
+    /// binary image for `load_image`. The stack and pending input are not
+    /// included.
+    pub fn save_image(&self) -> Vec<u8> {
+        let mut w = Writer { bytes: Vec::new() };
+        w.bytes.extend_from_slice(MAGIC);
+        w.u32(VERSION);
+        w.u8(CELL as u8);
+        w.usize(self.data_space_size);
+        w.usize(self.here);
+        w.usize(self.wordlists);
+        w.usize(self.current);
+        w.usize(self.order.len());
+        for &wordlist in &self.order {
+            w.usize(wordlist);
+        }
+        w.usize(self.words.len());
+        for word in &self.words {
+            w.bytes(self.names.resolve(word.name).as_bytes());
+            let mut flags = 0;
+            if word.native {
+                flags |= FLAG_NATIVE;
+            }
+            if word.immediate {
+                flags |= FLAG_IMMEDIATE;
+            }
+            w.u8(flags);
+            w.usize(word.wordlist);
+            w.u32(word.alias_of.map_or(NO_WORD, |target| target as u32));
+            w.usize(word.data.len());
+            for &op in self.bodies.get(word.data) {
+                w.op(op);
+            }
+            w.usize(word.spans.len());
+            for span in self.body_spans.get(word.spans) {
+                w.usize(span.start);
+                w.usize(span.end);
+            }
+            w.bytes(word.source.as_bytes());
+        }
+        let blocks: Vec<_> = self.heap.blocks().collect();
+        w.usize(blocks.len());
+        for (addr, len) in blocks {
+            w.usize(addr);
+            w.usize(len);
+        }
+        let memory = &self.memory[..self.tib];
+        let used = memory.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        w.bytes(&memory[..used]);
+        w.bytes
+    }
+
+    /// Replaces the dictionary and data space with those saved in `image`
+    /// by `save_image`, and empties the stack. Native words in the image
+    /// must already be defined here at the same positions, as they are in an
+    /// interpreter built the same way as the one that saved it. Fails with
+    /// `InvalidImage`, leaving the interpreter untouched, if the image is
+    /// malformed, from another format version or cell size, or doesn't fit,
+    /// as when its data space is larger than the builder's
+    /// `max_data_space_size`.
+    pub fn load_image(&mut self, image: &[u8]) -> ForthResult {
+        let image = decode(image).ok_or(Error::InvalidImage)?;
+        let tib = self.loaded_tib(image.data_space_size, image.here, image.memory.len())
+            .ok_or(Error::InvalidImage)?;
+        if !super::valid_wordlists(image.wordlists, image.current, &image.order, &image.words) {
+            return Err(Error::InvalidImage);
+        }
+        let heap = Heap::with_blocks(image.data_space_size, HEAP_SIZE, &image.heap_blocks)
+            .ok_or(Error::InvalidImage)?;
+        let (words, bodies, body_spans) = self.revive_words(image.words).ok_or(Error::InvalidImage)?;
+
+        let memory = super::new_memory(&image.memory, tib);
+        self.stack.clear();
+        self.words = words;
+        self.bodies = bodies;
+        self.body_spans = body_spans;
+        self.reindex();
+        self.wordlists = image.wordlists;
+        self.order = image.order;
+        self.current = image.current;
+        self.memory = memory;
+        self.here = image.here;
+        self.heap = heap;
+        self.data_space_size = image.data_space_size;
+        self.tib = tib;
+        self.source = (tib, 0);
+        self.return_stack.clear();
+        self.definition = None;
+        self.unfinished = None;
+        self.quotations.clear();
+        self.frame_word = None;
+        self.forget_recognizer_words();
+        self.timers = Timers::default();
+        self.tasks = Tasks::default();
+        self.strings.clear();
+        self.modules.clear();
+        self.block_buffer = BlockBuffer::default();
+        self.discard_paused();
+        self.observe_data_space();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_reload_the_words_defined_before_saving() {
+        let mut f = Forth::new();
+        f.eval(r#"VARIABLE COUNTER 5 COUNTER !  : GREET S" hi" ;  : BUMP COUNTER @ 1+ DUP COUNTER ! ;"#).unwrap();
+        let image = f.save_image();
+
+        let mut g = Forth::new();
+        g.load_image(&image).unwrap();
+        g.eval("BUMP BUMP GREET NIP").unwrap();
+        assert_eq!(g.stack(), vec![6, 7, 2]);
+    }
+
+    #[test]
+    fn loading_abandons_an_unfinished_definition() {
+        let image = Forth::new().save_image();
+        let mut f = Forth::new();
+        f.eval(": A3 3 ; : B [: A3 ;] A3").unwrap();
+        assert_eq!(f.unfinished_definition(), Some("B"));
+        f.load_image(&image).unwrap();
+        assert_eq!(f.unfinished_definition(), None);
+        assert_eq!(f.eval("; B"), Err(Error::SemicolonOutsideDefinition));
+        f.eval(": B 2 ; B").unwrap();
+        assert_eq!(f.stack(), vec![2]);
+    }
+
+    #[test]
+    fn truncated_and_corrupted_images_are_invalid() {
+        let mut f = Forth::new();
+        f.eval(": SQUARE DUP * ;").unwrap();
+        let image = f.save_image();
+
+        let mut g = Forth::new();
+        for len in 0..image.len() {
+            assert_eq!(g.load_image(&image[..len]), Err(Error::InvalidImage), "truncated to {}", len);
+        }
+
+        let mut corrupted = image.clone();
+        corrupted[0] ^= 0xFF;
+        assert_eq!(g.load_image(&corrupted), Err(Error::InvalidImage));
+
+        let mut corrupted = image.clone();
+        corrupted[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(g.load_image(&corrupted), Err(Error::InvalidImage));
+
+        // A data space smaller than the part already used.
+        let mut corrupted = image.clone();
+        corrupted[MAGIC.len() + 5..MAGIC.len() + 9].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(g.load_image(&corrupted), Err(Error::InvalidImage));
+
+        // One far larger than the builder allows, which isn't allocated.
+        let mut corrupted = image.clone();
+        corrupted[MAGIC.len() + 5..MAGIC.len() + 9].copy_from_slice(&(u32::MAX >> 1).to_le_bytes());
+        assert_eq!(g.load_image(&corrupted), Err(Error::InvalidImage));
+
+        // A data-space pointer inside the interpreter's own buffers.
+        let mut corrupted = image.clone();
+        corrupted[MAGIC.len() + 9..MAGIC.len() + 13].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(g.load_image(&corrupted), Err(Error::InvalidImage));
+
+        // Failed loads leave the interpreter as it was.
+        g.eval(": CUBE DUP DUP * * ; 3 CUBE").unwrap();
+        assert_eq!(g.stack(), vec![27]);
+
+        // Any other bit flipped either loads or is refused, but never
+        // panics.
+        for i in 0..image.len() {
+            let mut corrupted = image.clone();
+            corrupted[i] ^= 1;
+            let _ = g.load_image(&corrupted);
+        }
+    }
+}
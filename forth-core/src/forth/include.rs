@@ -0,0 +1,174 @@
+//! INCLUDE, INCLUDED, REQUIRE and REQUIRED, which read files from the
+//! host's source provider, or else pause evaluation for the host to fetch
+//! the named file and hand its text to `Forth::include`.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+
+use super::{lex, Error, Forth, ForthResult, Location, Span};
+
+/// Gives INCLUDED and REQUIRED the text of files by name, as the host
+/// decides. Closures taking a name can serve as one.
+pub trait SourceProvider {
+    /// The text of the file `name`, or `None` if there is no such file.
+    fn source(&mut self, name: &str) -> Option<String>;
+
+    /// `source`, for a provider that can fail other than by not having the
+    /// file. The error is that of the word including it.
+    fn try_source(&mut self, name: &str) -> Result<Option<String>, Error> {
+        Ok(self.source(name))
+    }
+}
+
+impl<F> SourceProvider for F
+    where F: FnMut(&str) -> Option<String>
+{
+    fn source(&mut self, name: &str) -> Option<String> {
+        self(name)
+    }
+}
+
+fn pop_name(forth: &mut Forth) -> Result<String, Error> {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    let range = forth.memory_range(addr, len)?;
+    Ok(String::from_utf8_lossy(&forth.memory[range]).into_owned())
+}
+
+pub(super) fn do_included(forth: &mut Forth, _index: usize) -> ForthResult {
+    let name = pop_name(forth)?;
+    forth.include_file(name)
+}
+
+pub(super) fn do_include(forth: &mut Forth, _index: usize) -> ForthResult {
+    let name = forth.parse_name().ok_or(Error::InvalidWord)?;
+    forth.include_file(name)
+}
+
+pub(super) fn do_required(forth: &mut Forth, _index: usize) -> ForthResult {
+    let name = pop_name(forth)?;
+    forth.require_file(name)
+}
+
+pub(super) fn do_require(forth: &mut Forth, _index: usize) -> ForthResult {
+    let name = forth.parse_name().ok_or(Error::InvalidWord)?;
+    forth.require_file(name)
+}
+
+impl Forth {
+    /// Has INCLUDED and REQUIRED read files from `provider` rather than
+    /// asking the host for each with `IncludeRequested`. Files read this
+    /// way run as EVALUATE would, and may include others.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.set_source_provider(|name: &str| match name {
+    ///     "square.fs" => Some(String::from(": SQUARE DUP * ;")),
+    ///     "cube.fs" => Some(String::from("REQUIRE square.fs : CUBE DUP SQUARE * ;")),
+    ///     _ => None,
+    /// });
+    /// f.eval("REQUIRE cube.fs REQUIRE square.fs 3 CUBE").unwrap();
+    /// assert_eq!(f.stack(), vec![27]);
+    /// assert_eq!(f.included_files(), ["cube.fs", "square.fs"]);
+    /// assert_eq!(f.eval("INCLUDE nope.fs"), Err(Error::FileNotFound("nope.fs".into())));
+    /// ```
+    pub fn set_source_provider<P>(&mut self, provider: P)
+        where P: SourceProvider + 'static
+    {
+        self.source_provider = Some(Box::new(provider));
+    }
+
+    pub fn clear_source_provider(&mut self) {
+        self.source_provider = None;
+    }
+
+    /// The names of the files INCLUDED and REQUIRED have read, or asked
+    /// the host for, in order, for REQUIRED not to read again.
+    pub fn included_files(&self) -> &[String] {
+        &self.included_files
+    }
+
+    fn require_file(&mut self, name: String) -> ForthResult {
+        if self.included_files.contains(&name) {
+            return Ok(());
+        }
+        self.include_file(name)
+    }
+
+    /// Interprets the file `name` from the source provider, or without one
+    /// fails with `IncludeRequested`, pausing the evaluation for the host
+    /// if it can be resumed from here, as running out of fuel would.
+    fn include_file(&mut self, name: String) -> ForthResult {
+        let text = match self.source_provider {
+            Some(ref mut provider) => provider.try_source(&name)?.ok_or_else(|| Error::FileNotFound(name.clone()))?,
+            None => {
+                // The word asking is run by the outermost `execute`.
+                self.paused = self.executing == 1;
+                if !self.included_files.contains(&name) {
+                    self.included_files.push(name.clone());
+                }
+                return Err(Error::IncludeRequested(name));
+            }
+        };
+        if !self.included_files.contains(&name) {
+            self.included_files.push(name);
+        }
+        self.evaluate_text(&text)
+    }
+
+    /// Interprets `text` as the contents of the file an evaluation paused
+    /// with `IncludeRequested` asked for, then leaves it paused for
+    /// `resume` to carry on after INCLUDE or INCLUDED. Output is collected
+    /// afresh. If `text` fails, the evaluation is abandoned and the error
+    /// located within `text`. Does nothing if no evaluation is paused.
+    ///
+    /// An included file runs as EVALUATE would, so it can only include
+    /// another through a source provider, and fails with `OutOfFuel` if it
+    /// needs more fuel than is left.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// let result = f.eval("INCLUDE square.fs 7 SQUARE");
+    /// assert_eq!(result, Err(Error::IncludeRequested("square.fs".into())));
+    /// f.include(": SQUARE DUP * ;").unwrap();
+    /// f.resume().unwrap();
+    /// assert_eq!(f.stack(), vec![49]);
+    /// ```
+    pub fn include(&mut self, text: &str) -> ForthResult {
+        if !self.paused {
+            return Ok(());
+        }
+        self.paused = false;
+        self.output.clear();
+        self.output_len = 0;
+        self.output_truncated = false;
+        self.error_location = None;
+        self.error_trace.clear();
+        self.executing += 1;
+        let result = self.evaluate_text(text);
+        self.executing -= 1;
+        match result {
+            Ok(()) => self.paused = true,
+            Err(_) => {
+                let Span { start: offset, end } = self.last_span;
+                if let Some(word) = text.get(offset..end) {
+                    self.error_location = Some(Location {
+                        word: String::from(word),
+                        offset,
+                        char_offset: text[..offset].chars().count(),
+                        token_index: lex(text).take_while(|span| span.start < offset).count(),
+                    });
+                }
+                self.pending.clear();
+                self.lexed = None;
+                self.return_stack.clear();
+            }
+        }
+        self.observe_data_space();
+        result
+    }
+}
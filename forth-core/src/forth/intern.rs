@@ -0,0 +1,50 @@
+//! Interning of word names.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::Map;
+
+/// A word name stored once in `Names`; equal names have equal symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct Symbol(u32);
+
+impl Symbol {
+    pub(super) fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Every word name the interpreter has seen, in the order first seen.
+/// Names are never removed, so symbols stay valid for the interpreter's
+/// lifetime.
+#[derive(Clone, Default)]
+pub(super) struct Names {
+    symbols: Map<String, Symbol>,
+    names: Vec<String>,
+}
+
+impl Names {
+    pub(super) fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(name) {
+            return symbol;
+        }
+        let symbol = Symbol(self.names.len() as u32);
+        self.names.push(String::from(name));
+        self.symbols.insert(String::from(name), symbol);
+        symbol
+    }
+
+    /// The symbol for `name`, if it has been interned.
+    pub(super) fn get(&self, name: &str) -> Option<Symbol> {
+        self.symbols.get(name).copied()
+    }
+
+    pub(super) fn resolve(&self, symbol: Symbol) -> &str {
+        &self.names[symbol.index()]
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.names.len()
+    }
+}
@@ -0,0 +1,126 @@
+//! Where program output goes and KEY and ACCEPT read from, behind one
+//! trait, so that the same words run on each host.
+
+use alloc::collections::VecDeque;
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::cell::RefCell;
+
+use super::{Error, Forth, ForthResult};
+
+/// Takes the output of EMIT, TYPE, `.` and the other printing words, and
+/// gives KEY and ACCEPT input, as the host decides.
+pub trait Io {
+    /// Takes output as it is produced.
+    fn write(&mut self, text: &str);
+
+    /// More text for KEY and ACCEPT once the queued input runs out, or
+    /// `None` at the end of the input.
+    fn read(&mut self) -> Option<String>;
+
+    /// `write`, for an `Io` that can fail to take output. The error is that
+    /// of the word that printed.
+    fn try_write(&mut self, text: &str) -> ForthResult {
+        self.write(text);
+        Ok(())
+    }
+
+    /// `read`, for an `Io` that can fail to give input. The error is that
+    /// of the KEY or ACCEPT that asked for more.
+    fn try_read(&mut self) -> Result<Option<String>, Error> {
+        Ok(self.read())
+    }
+}
+
+/// Output collected into, and input taken from, buffers that clones
+/// share, so the host can keep one clone while the interpreter has
+/// another.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryIo {
+    output: Rc<RefCell<String>>,
+    input: Rc<RefCell<VecDeque<String>>>,
+}
+
+impl MemoryIo {
+    pub fn new() -> MemoryIo {
+        MemoryIo::default()
+    }
+
+    /// Queues `text` for a later `read`.
+    pub fn push_input(&self, text: &str) {
+        self.input.borrow_mut().push_back(String::from(text));
+    }
+
+    /// Takes the output written so far, leaving none.
+    pub fn take_output(&self) -> String {
+        core::mem::take(&mut *self.output.borrow_mut())
+    }
+}
+
+impl Io for MemoryIo {
+    fn write(&mut self, text: &str) {
+        self.output.borrow_mut().push_str(text);
+    }
+
+    fn read(&mut self) -> Option<String> {
+        self.input.borrow_mut().pop_front()
+    }
+}
+
+/// Standard output, flushed after each write, and standard input, a line
+/// at a time.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdIo;
+
+#[cfg(feature = "std")]
+impl Io for StdIo {
+    fn write(&mut self, text: &str) {
+        use std::io::Write;
+
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(text.as_bytes()).and_then(|()| stdout.flush());
+    }
+
+    fn read(&mut self) -> Option<String> {
+        let mut line = String::new();
+        match std::io::stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line),
+        }
+    }
+}
+
+impl Forth {
+    /// Sends program output to `io` as it is produced, and has KEY and
+    /// ACCEPT read from it once the input queued with `push_input` runs
+    /// out, as `set_output_sink` and `set_input_source` would. A
+    /// deterministic interpreter still writes to it but doesn't read.
+    ///
+    /// ```
+    /// use forth_core::{Forth, MemoryIo};
+    ///
+    /// let io = MemoryIo::new();
+    /// io.push_input("hi");
+    /// let mut f = Forth::new();
+    /// f.set_io(io.clone());
+    /// f.eval("KEY EMIT KEY EMIT 42 .").unwrap();
+    /// assert_eq!(io.take_output(), "hi42 ");
+    /// assert_eq!(f.output(), "");
+    /// ```
+    pub fn set_io<I>(&mut self, io: I)
+        where I: Io + 'static
+    {
+        let io = Rc::new(RefCell::new(io));
+        let reader = io.clone();
+        self.set_fallible_output_sink(move |text| io.borrow_mut().try_write(text));
+        self.set_fallible_input_source(move || reader.borrow_mut().try_read());
+    }
+
+    /// Goes back to collecting output for `output` and reading only the
+    /// input queued with `push_input`.
+    pub fn clear_io(&mut self) {
+        self.clear_output_sink();
+        self.clear_input_source();
+    }
+}
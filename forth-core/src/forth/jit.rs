@@ -0,0 +1,143 @@
+//! Colon definitions handed to the host's `WordCompiler` as they are
+//! defined, to be run as native code, such as code compiled just in time.
+
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+
+use super::{do_exec, Forth, ForthResult, Op, Stack, Value};
+
+/// Native code a `WordCompiler` made for a colon definition. It returns
+/// `None`, leaving the stack as it found it, to have the interpreter run
+/// the definition instead, as for input it can't handle.
+pub type CompiledWord = dyn Fn(&mut Stack) -> Option<ForthResult>;
+
+/// An instruction of a colon definition, as a `WordCompiler` sees it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction<'a> {
+    /// Pushes the value.
+    Literal(Value),
+    /// Calls the built-in word of this name, such as `+` or `DUP`.
+    Primitive(&'a str),
+    /// Calls any other word, defined in Forth or by the host.
+    Call(&'a str),
+}
+
+/// Turns colon definitions into native code, as the host decides.
+/// Closures taking a name and body can serve as one.
+pub trait WordCompiler {
+    /// Native code for the definition `name`, or `None` to leave it to the
+    /// interpreter.
+    fn compile(&mut self, name: &str, body: &[Instruction]) -> Option<Box<CompiledWord>>;
+}
+
+impl<F> WordCompiler for F
+    where F: FnMut(&str, &[Instruction]) -> Option<Box<CompiledWord>>
+{
+    fn compile(&mut self, name: &str, body: &[Instruction]) -> Option<Box<CompiledWord>> {
+        self(name, body)
+    }
+}
+
+/// Runs the word at `index` through `code`, which stands in for its body,
+/// unless a `debug` session must see it step by step or there isn't fuel
+/// for the whole body.
+fn run_compiled(forth: &mut Forth, index: usize, code: &CompiledWord) -> ForthResult {
+    let steps = forth.words[index].data.len() as u64;
+    if forth.debugger.is_none() && forth.fuel.is_none_or(|fuel| fuel >= steps) {
+        if let Some(result) = code(&mut forth.stack) {
+            if let Some(ref mut fuel) = forth.fuel {
+                *fuel -= steps;
+            }
+            return result;
+        }
+    }
+    do_exec(forth, index)
+}
+
+impl Forth {
+    /// Offers each colon definition to `compiler` once it is defined, to
+    /// run natively from then on. A compiled word runs its body as one
+    /// step, taking the fuel for all of it, and is still stepped through
+    /// instruction by instruction by `debug`.
+    ///
+    /// ```
+    /// use forth_core::{CompiledWord, Error, Forth, Instruction, Stack};
+    ///
+    /// let mut f = Forth::new();
+    /// f.set_word_compiler(|_name: &str, body: &[Instruction]| -> Option<Box<CompiledWord>> {
+    ///     if body != [Instruction::Primitive("DUP"), Instruction::Primitive("*")] {
+    ///         return None;
+    ///     }
+    ///     Some(Box::new(|stack: &mut Stack| {
+    ///         let top = stack.last_mut()?;
+    ///         *top = top.checked_mul(*top)?;
+    ///         Some(Ok(()))
+    ///     }))
+    /// });
+    /// f.eval(": SQUARE DUP * ; 7 SQUARE").unwrap();
+    /// assert_eq!(f.stack(), vec![49]);
+    /// // Left to the interpreter, which reports the error.
+    /// assert_eq!(f.eval("DROP SQUARE"), Err(Error::StackUnderflow));
+    /// ```
+    pub fn set_word_compiler<C>(&mut self, compiler: C)
+        where C: WordCompiler + 'static
+    {
+        self.word_compiler = Some(Box::new(compiler));
+    }
+
+    /// Stops offering definitions to the compiler. Words it has compiled
+    /// stay compiled.
+    pub fn clear_word_compiler(&mut self) {
+        self.word_compiler = None;
+    }
+
+    /// The compiled body of the word `name`, or `None` if there is no such
+    /// word or it is native.
+    ///
+    /// ```
+    /// use forth_core::{Forth, Instruction};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQUARE DUP * ; : AREA SQUARE 3 * ;").unwrap();
+    /// assert_eq!(f.instructions("AREA").unwrap(), [
+    ///     Instruction::Call("SQUARE"),
+    ///     Instruction::Literal(3),
+    ///     Instruction::Primitive("*"),
+    /// ]);
+    /// assert_eq!(f.instructions("DUP"), None);
+    /// ```
+    pub fn instructions(&self, name: &str) -> Option<Vec<Instruction<'_>>> {
+        let index = self.lookup_word(name)?;
+        match self.words[index].native {
+            true => None,
+            false => Some(self.body_instructions(index)),
+        }
+    }
+
+    pub(super) fn body_instructions(&self, index: usize) -> Vec<Instruction<'_>> {
+        self.body(index).iter().map(|&op| match op {
+            Op::Literal(v) => Instruction::Literal(v),
+            Op::Call(i) => match self.primitive(op) {
+                Some(name) => Instruction::Primitive(name),
+                None => Instruction::Call(self.word_name(i)),
+            },
+        }).collect()
+    }
+
+    /// Has the word compiler, if any, compile the colon definition at
+    /// `index`.
+    pub(super) fn compile_natively(&mut self, index: usize) {
+        let mut compiler = match self.word_compiler.take() {
+            Some(compiler) => compiler,
+            None => return,
+        };
+        let body = self.body_instructions(index);
+        let name = self.word_name(index);
+        let code: Option<Rc<CompiledWord>> = compiler.compile(name, &body).map(Rc::from);
+        if let Some(code) = code {
+            self.words[index].exec = Some(Rc::new(move |forth: &mut Forth, index| run_compiled(forth, index, &*code)));
+        }
+        self.word_compiler = Some(compiler);
+    }
+}
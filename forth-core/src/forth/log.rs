@@ -0,0 +1,73 @@
+//! LOG and LOG-TYPE, which pass messages to the host for debugging,
+//! apart from program output.
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+
+use super::{Forth, ForthResult};
+
+pub(super) type LogSink = dyn FnMut(&str, Option<&str>) -> ForthResult;
+
+pub(super) fn do_log(forth: &mut Forth, _index: usize) -> ForthResult {
+    let v = forth.pop()?;
+    forth.log(&v.to_string())
+}
+
+pub(super) fn do_log_type(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    let range = forth.memory_range(addr, len)?;
+    let text = String::from_utf8_lossy(&forth.memory[range]).into_owned();
+    forth.log(&text)
+}
+
+impl Forth {
+    /// Has LOG ( n -- ) and LOG-TYPE ( c-addr u -- ) call `sink` with the
+    /// number, in decimal, or the string, and the name of the word they
+    /// were called from, if not from the input. Without a sink what they
+    /// log is dropped. Either way, program output is left alone.
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use forth_core::Forth;
+    ///
+    /// let log = Rc::new(RefCell::new(Vec::new()));
+    /// let sink = log.clone();
+    /// let mut f = Forth::new();
+    /// f.set_log_sink(move |message, word| {
+    ///     sink.borrow_mut().push(format!("{}: {}", word.unwrap_or("input"), message));
+    /// });
+    /// f.eval(": SQ DUP LOG DUP * ; S\" start\" LOG-TYPE 7 SQ .").unwrap();
+    /// assert_eq!(*log.borrow(), ["input: start", "SQ: 7"]);
+    /// assert_eq!(f.output(), "49 ");
+    /// ```
+    pub fn set_log_sink<F>(&mut self, mut sink: F)
+        where F: FnMut(&str, Option<&str>) + 'static
+    {
+        self.set_fallible_log_sink(move |message, word| {
+            sink(message, word);
+            Ok(())
+        });
+    }
+
+    /// Like `set_log_sink`, for a sink that can fail. Its error is that of
+    /// the LOG or LOG-TYPE that logged.
+    pub fn set_fallible_log_sink<F>(&mut self, sink: F)
+        where F: FnMut(&str, Option<&str>) -> ForthResult + 'static
+    {
+        self.log_sink = Some(Box::new(sink));
+    }
+
+    pub fn clear_log_sink(&mut self) {
+        self.log_sink = None;
+    }
+
+    pub(super) fn log(&mut self, message: &str) -> ForthResult {
+        let word = self.return_stack.last().map(|frame| self.word_name(frame.word).to_string());
+        match self.log_sink {
+            Some(ref mut sink) => sink(message, word.as_deref()),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! The text errors and warnings are shown with, in the language the host
+//! picks and with any of it replaced by the host's own, while `Error::kind`
+//! stays the same for programs to match on.
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+
+use super::{Error, Forth, Warning};
+
+/// A language messages come in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    /// The language of a tag such as `de` or `en-GB`, if messages come in
+    /// it.
+    ///
+    /// ```
+    /// use forth_core::Language;
+    ///
+    /// assert_eq!(Language::from_tag("de-AT"), Some(Language::German));
+    /// assert_eq!(Language::from_tag("EN"), Some(Language::English));
+    /// assert_eq!(Language::from_tag("xx"), None);
+    /// ```
+    pub fn from_tag(tag: &str) -> Option<Language> {
+        let primary = tag.split(['-', '_']).next().unwrap_or("");
+        match &*primary.to_ascii_lowercase() {
+            "en" => Some(Language::English),
+            "de" => Some(Language::German),
+            _ => None,
+        }
+    }
+
+    /// The tag `from_tag` takes for the language, such as `de`.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+}
+
+/// The messages for errors and warnings: those of a language, with any the
+/// host set in their place. A message is a template in which `{}` stands
+/// for the text the error carries, such as the unknown word.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Messages {
+    language: Language,
+    errors: BTreeMap<String, String>,
+    warnings: BTreeMap<String, String>,
+}
+
+impl Messages {
+    pub fn new(language: Language) -> Messages {
+        Messages { language, ..Messages::default() }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// Changes the language, keeping the messages the host set.
+    pub fn set_language(&mut self, language: Language) {
+        self.language = language;
+    }
+
+    /// Shows errors of `kind`, as `Error::kind` names it, with `template`
+    /// in place of the language's message.
+    ///
+    /// ```
+    /// use forth_core::{Error, Language, Messages};
+    ///
+    /// let mut messages = Messages::new(Language::German);
+    /// assert_eq!(messages.error(&Error::UnknownWord("FOO".into())), "unbekanntes Wort FOO");
+    /// messages.set_error("UnknownWord", "« {} » ?");
+    /// assert_eq!(messages.error(&Error::UnknownWord("FOO".into())), "« FOO » ?");
+    /// assert_eq!(messages.error(&Error::StackUnderflow), "Stapelunterlauf");
+    /// ```
+    pub fn set_error(&mut self, kind: &str, template: &str) {
+        self.errors.insert(String::from(kind), String::from(template));
+    }
+
+    /// Shows warnings of `kind`, as `Warning::kind` names it, with
+    /// `template` in place of the language's message.
+    pub fn set_warning(&mut self, kind: &str, template: &str) {
+        self.warnings.insert(String::from(kind), String::from(template));
+    }
+
+    /// The message for `error`.
+    pub fn error(&self, error: &Error) -> String {
+        let template = match self.errors.get(error.kind()) {
+            Some(template) => template,
+            None => error_template(self.language, error.kind()),
+        };
+        let argument = match *error {
+            Error::WaitRequested(ms) => Some(ms.to_string()),
+            _ => error.detail().map(String::from),
+        };
+        fill(template, argument.as_deref())
+    }
+
+    /// The message for `warning`.
+    pub fn warning(&self, warning: &Warning) -> String {
+        let template = match self.warnings.get(warning.kind()) {
+            Some(template) => template,
+            None => warning_template(self.language, warning.kind()),
+        };
+        fill(template, Some(warning.name()))
+    }
+}
+
+impl Forth {
+    /// The messages errors and warnings are shown with, in the language
+    /// the builder's `language` picked.
+    pub fn messages(&self) -> &Messages {
+        &self.messages
+    }
+
+    /// Shows errors and warnings with `messages` from now on.
+    ///
+    /// ```
+    /// use forth_core::{Forth, Messages};
+    ///
+    /// let mut f = Forth::builder().ok_prompt(true).build();
+    /// let mut messages = Messages::default();
+    /// messages.set_error("StackUnderflow", "the stack is empty");
+    /// f.set_messages(messages);
+    /// f.eval(".").unwrap_err();
+    /// assert_eq!(f.output(), "\nthe stack is empty\n");
+    /// ```
+    pub fn set_messages(&mut self, messages: Messages) {
+        self.messages = messages;
+    }
+}
+
+/// `template` with its `{}` replaced by `argument`.
+fn fill(template: &str, argument: Option<&str>) -> String {
+    match (template.find("{}"), argument) {
+        (Some(at), Some(argument)) => {
+            let mut message = String::from(&template[..at]);
+            message.push_str(argument);
+            message.push_str(&template[at + 2..]);
+            message
+        }
+        _ => String::from(template),
+    }
+}
+
+fn error_template(language: Language, kind: &str) -> &'static str {
+    match language {
+        Language::English => english_error(kind),
+        Language::German => german_error(kind),
+    }
+}
+
+fn warning_template(language: Language, kind: &str) -> &'static str {
+    match (language, kind) {
+        (Language::English, "Redefined") => "redefined {}",
+        (Language::English, _) => "{} is also a number",
+        (Language::German, "Redefined") => "{} neu definiert",
+        (Language::German, _) => "{} ist auch eine Zahl",
+    }
+}
+
+fn english_error(kind: &str) -> &'static str {
+    match kind {
+        "DivisionByZero" => "division by zero",
+        "Overflow" => "arithmetic overflow",
+        "StackUnderflow" => "stack underflow",
+        "StringStackUnderflow" => "string stack underflow",
+        "UnknownWord" => "unknown word {}",
+        "InvalidWord" => "invalid word",
+        "InvalidWordlist" => "invalid wordlist",
+        "InvalidAddress" => "invalid address",
+        "InvalidBase" => "invalid base",
+        "InvalidBlock" => "invalid block number",
+        "NoInput" => "no input available",
+        "NoStorage" => "no dictionary store available",
+        "NoClock" => "no clock available",
+        "NumberOutOfRange" => "number out of range: {}",
+        "StackOverflow" => "stack overflow",
+        "ReturnStackOverflow" => "return stack overflow",
+        "CallDepthExceeded" => "call depth exceeded",
+        "OutOfFuel" => "out of fuel",
+        "Stopped" => "stopped by the debugger",
+        "DictionaryFull" => "dictionary full",
+        "ProtectedWord" => "{} is protected",
+        "DictionaryFrozen" => "dictionary is frozen",
+        "DataSpaceFull" => "data space full",
+        "OutputLimitExceeded" => "output limit exceeded",
+        "InputTooLarge" => "input too large",
+        "NameTooLong" => "name too long",
+        "DefinitionTooLong" => "definition too long",
+        "InvalidSnapshot" => "snapshot does not match this interpreter",
+        "InvalidImage" => "invalid or incompatible image",
+        "UnterminatedDefinition" => "definition is missing ;",
+        "SemicolonOutsideDefinition" => "; outside a definition",
+        "NestedDefinition" => ": inside a definition",
+        "MissingName" => "missing name",
+        "NumericName" => "{} is a number, not a name",
+        "UnbalancedControl" => "unbalanced control structure at {}",
+        "UnbalancedStack" => "branches leave the stack at different depths at {}",
+        "IncludeRequested" => "{} needs to be included by the host",
+        "WaitRequested" => "waiting {} ms for the host",
+        "PauseRequested" => "paused for other tasks",
+        "FileNotFound" => "file not found: {}",
+        "TypeMismatch" => "type mismatch at {}",
+        "Thrown" => "{} failed",
+        _ => "{}",
+    }
+}
+
+fn german_error(kind: &str) -> &'static str {
+    match kind {
+        "DivisionByZero" => "Division durch null",
+        "Overflow" => "arithmetischer Überlauf",
+        "StackUnderflow" => "Stapelunterlauf",
+        "StringStackUnderflow" => "Stringstapelunterlauf",
+        "UnknownWord" => "unbekanntes Wort {}",
+        "InvalidWord" => "ungültiges Wort",
+        "InvalidWordlist" => "ungültige Wortliste",
+        "InvalidAddress" => "ungültige Adresse",
+        "InvalidBase" => "ungültige Zahlenbasis",
+        "InvalidBlock" => "ungültige Blocknummer",
+        "NoInput" => "keine Eingabe verfügbar",
+        "NoStorage" => "kein Wörterbuchspeicher verfügbar",
+        "NoClock" => "keine Uhr verfügbar",
+        "NumberOutOfRange" => "Zahl außerhalb des Bereichs: {}",
+        "StackOverflow" => "Stapelüberlauf",
+        "ReturnStackOverflow" => "Überlauf des Rücksprungstapels",
+        "CallDepthExceeded" => "Aufruftiefe überschritten",
+        "OutOfFuel" => "Rechenbudget aufgebraucht",
+        "Stopped" => "vom Debugger angehalten",
+        "DictionaryFull" => "Wörterbuch voll",
+        "ProtectedWord" => "{} ist geschützt",
+        "DictionaryFrozen" => "Wörterbuch ist eingefroren",
+        "DataSpaceFull" => "Datenspeicher voll",
+        "OutputLimitExceeded" => "Ausgabegrenze überschritten",
+        "InputTooLarge" => "Eingabe zu groß",
+        "NameTooLong" => "Name zu lang",
+        "DefinitionTooLong" => "Definition zu lang",
+        "InvalidSnapshot" => "Schnappschuss passt nicht zu diesem Interpreter",
+        "InvalidImage" => "ungültiges oder inkompatibles Abbild",
+        "UnterminatedDefinition" => "der Definition fehlt ;",
+        "SemicolonOutsideDefinition" => "; außerhalb einer Definition",
+        "NestedDefinition" => ": innerhalb einer Definition",
+        "MissingName" => "Name fehlt",
+        "NumericName" => "{} ist eine Zahl, kein Name",
+        "UnbalancedControl" => "Kontrollstruktur bei {} nicht ausgeglichen",
+        "UnbalancedStack" => "Zweige hinterlassen den Stapel bei {} unterschiedlich tief",
+        "IncludeRequested" => "{} muss vom Host eingebunden werden",
+        "WaitRequested" => "warte {} ms auf den Host",
+        "PauseRequested" => "für andere Tasks angehalten",
+        "FileNotFound" => "Datei nicht gefunden: {}",
+        "TypeMismatch" => "falscher Typ bei {}",
+        "Thrown" => "{} ist fehlgeschlagen",
+        _ => "{}",
+    }
+}
@@ -0,0 +1,92 @@
+//! MODULE, PRIVATE, PUBLIC and END-MODULE, which let a library keep its
+//! helpers to itself, and the qualified names, such as `GFX::LINE`, that
+//! reach a module's public words whatever else shares their names.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{Error, Forth, ForthResult, Op, Value};
+
+/// Separates a module's name from the name of one of its public words.
+const QUALIFIER: &str = "::";
+
+/// A module between MODULE and END-MODULE: the wordlists its public and
+/// private words go into, and where definitions went before it.
+#[derive(Debug, Clone)]
+pub(super) struct OpenModule {
+    public: usize,
+    private: usize,
+    current: usize,
+    order: Vec<usize>,
+}
+
+/// MODULE ( "name" -- ) defines `name` to push the wordlist of the module's
+/// public words, and has the words defined until END-MODULE go into it.
+pub(super) fn do_module(forth: &mut Forth, _index: usize) -> ForthResult {
+    let public = forth.wordlists;
+    forth.define_constant(public as Value)?;
+    forth.wordlists += 2;
+    let module = OpenModule { public, private: public + 1, current: forth.current, order: forth.order.clone() };
+    forth.order.push(module.public);
+    forth.order.push(module.private);
+    forth.current = module.public;
+    forth.modules.push(module);
+    Ok(())
+}
+
+/// PRIVATE ( -- ) has the words defined next be seen only within the
+/// module.
+pub(super) fn do_private(forth: &mut Forth, _index: usize) -> ForthResult {
+    let module = forth.modules.last().ok_or(Error::InvalidWordlist)?;
+    forth.current = module.private;
+    Ok(())
+}
+
+/// PUBLIC ( -- ) has the words defined next be seen outside the module,
+/// as they are until PRIVATE.
+pub(super) fn do_public(forth: &mut Forth, _index: usize) -> ForthResult {
+    let module = forth.modules.last().ok_or(Error::InvalidWordlist)?;
+    forth.current = module.public;
+    Ok(())
+}
+
+/// END-MODULE ( -- ) puts the search order and the wordlist definitions go
+/// into back as MODULE found them, and defines there the module's public
+/// words under their own names.
+pub(super) fn do_end_module(forth: &mut Forth, _index: usize) -> ForthResult {
+    let module = forth.modules.pop().ok_or(Error::InvalidWordlist)?;
+    let public: Vec<usize> = (0..forth.words.len()).filter(|&i| forth.words[i].wordlist == module.public).collect();
+    forth.order = module.order;
+    forth.current = module.current;
+    for (n, &index) in public.iter().enumerate() {
+        let name = forth.words[index].name;
+        // Only the last definition of a name is seen from outside.
+        if public[n + 1..].iter().any(|&later| forth.words[later].name == name) {
+            continue;
+        }
+        let name = String::from(forth.word_name(index));
+        forth.define_alias(&name, forth.words[index].alias_of.unwrap_or(index))?;
+    }
+    Ok(())
+}
+
+impl Forth {
+    /// Finds a name such as `GFX::LINE`: the latest public word `LINE` of
+    /// the module `GFX` defined, or of any wordlist a word pushes, looking
+    /// `GFX` up in turn.
+    pub(super) fn lookup_qualified(&self, name: &str) -> Option<usize> {
+        let split = name.rfind(QUALIFIER)?;
+        let (module, name) = (&name[..split], &name[split + QUALIFIER.len()..]);
+        if module.is_empty() || name.is_empty() {
+            return None;
+        }
+        let wordlist = match *self.body(self.lookup_word(module)?) {
+            [Op::Literal(wid)] => self.wordlist_id(wid).ok()?,
+            _ => return None,
+        };
+        let symbol = self.canonical_symbol(name)?;
+        self.index.get(symbol.index())?.iter().rev().cloned()
+            .find(|&i| self.words[i].wordlist == wordlist)
+            .map(|i| self.words[i].alias_of.unwrap_or(i))
+    }
+}
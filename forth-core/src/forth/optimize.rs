@@ -0,0 +1,127 @@
+//! Compile-time optimization of colon definitions: inlining and
+//! peephole rewriting.
+
+use super::arena::Slot;
+use super::{Body, Division, Forth, Op, Span, Value, CELL};
+
+/// `a b name` worked out at compile time, or `None` when it must be left
+/// to run, as when it would overflow or divide by zero. Quotients are
+/// rounded as `division` says.
+fn fold_binary(name: &str, a: Value, b: Value, division: Division) -> Option<Value> {
+    match name {
+        "+" => a.checked_add(b),
+        "-" => a.checked_sub(b),
+        "*" => a.checked_mul(b),
+        "/" => {
+            a.checked_div(b)?;
+            Some(division.divide(a.into(), b.into()).0 as Value)
+        }
+        _ => None,
+    }
+}
+
+fn fold_unary(name: &str, v: Value) -> Option<Value> {
+    match name {
+        "1+" | "CHAR+" => v.checked_add(1),
+        "1-" => v.checked_sub(1),
+        "2*" => v.checked_mul(2),
+        "2/" => Some(v >> 1),
+        "CELL+" => v.checked_add(CELL as Value),
+        "CELLS" => v.checked_mul(CELL as Value),
+        "CHARS" => Some(v),
+        _ => None,
+    }
+}
+
+/// Replaces the last `len` instructions with `op`, if any, spanning the
+/// names they came from.
+fn splice(ops: &mut Body<Op>, spans: &mut Body<Span>, len: usize, op: Option<Op>) {
+    let start = spans.len() - len;
+    let span = Span { start: spans[start].start, end: spans[spans.len() - 1].end };
+    ops.truncate(start);
+    spans.truncate(start);
+    if let Some(op) = op {
+        ops.push(op);
+        spans.push(span);
+    }
+}
+
+impl Forth {
+    /// Rewrites a compiled body to take fewer steps: arithmetic on literals
+    /// is done now, and values pushed only to be dropped aren't pushed.
+    /// Only built-in primitives are rewritten, never words defined over
+    /// them.
+    pub(super) fn peephole(&self, body: &[Op], body_spans: &[Span]) -> (Body<Op>, Body<Span>) {
+        let mut ops = Body::with_capacity(body.len());
+        let mut spans = Body::with_capacity(body.len());
+        for (&op, &span) in body.iter().zip(body_spans) {
+            ops.push(op);
+            spans.push(span);
+            while self.reduce(&mut ops, &mut spans) {}
+        }
+        (ops, spans)
+    }
+
+    /// The body to compile in place of a call to the word at `index`, if
+    /// it is a compiled word within the inlining limit. Bodies that EXIT
+    /// are always called, as inlined they would return from the caller.
+    pub(super) fn inline_body(&self, index: usize) -> Option<Slot> {
+        let word = &self.words[index];
+        let limit = self.inline_limit?;
+        if word.native || word.data.len() > limit {
+            return None;
+        }
+        if self.body(index).iter().any(|&op| self.primitive(op) == Some("EXIT")) {
+            return None;
+        }
+        Some(word.data)
+    }
+
+    /// Where the primitive `name` is in the dictionary.
+    pub(super) fn primitive_index(&self, name: &str) -> Option<usize> {
+        (0..self.primitives).find(|&index| self.word_name(index) == name)
+    }
+
+    /// The name of the primitive `op` calls, if it calls one.
+    pub(super) fn primitive(&self, op: Op) -> Option<&str> {
+        match op {
+            Op::Call(index) if index < self.primitives => Some(self.word_name(index)),
+            _ => None,
+        }
+    }
+
+    /// Rewrites the end of `ops` once, returning whether it changed.
+    fn reduce(&self, ops: &mut Body<Op>, spans: &mut Body<Span>) -> bool {
+        let name = match ops.last().and_then(|&op| self.primitive(op)) {
+            Some(name) => name,
+            None => return false,
+        };
+        if let [.., Op::Literal(a), Op::Literal(b), _] = ops[..] {
+            if let Some(v) = fold_binary(name, a, b, self.division) {
+                splice(ops, spans, 3, Some(Op::Literal(v)));
+                return true;
+            }
+        }
+        if let [.., Op::Literal(v), _] = ops[..] {
+            let folded = match name {
+                "DUP" => Some(Op::Literal(v)),
+                "DROP" => None,
+                _ => match fold_unary(name, v) {
+                    Some(v) => Some(Op::Literal(v)),
+                    None => return false,
+                },
+            };
+            // `v DUP` becomes `v v`, which may fold further.
+            let len = if name == "DUP" { 1 } else { 2 };
+            splice(ops, spans, len, folded);
+            return true;
+        }
+        if let [.., dup, _] = ops[..] {
+            if name == "DROP" && self.primitive(dup) == Some("DUP") {
+                splice(ops, spans, 2, None);
+                return true;
+            }
+        }
+        false
+    }
+}
@@ -0,0 +1,23 @@
+\ Words defined in Forth over the primitives, evaluated when the
+\ interpreter is built unless ForthBuilder::standard_prelude turns it off.
+
+-1 CONSTANT TRUE
+0 CONSTANT FALSE
+
+: NIP ( x1 x2 -- x2 ) SWAP DROP ;
+: TUCK ( x1 x2 -- x2 x1 x2 ) SWAP OVER ;
+: 2DUP ( x1 x2 -- x1 x2 x1 x2 ) OVER OVER ;
+: 2DROP ( x1 x2 -- ) DROP DROP ;
+
+: NEGATE ( n -- -n ) 0 SWAP - ;
+: 0= ( x -- flag ) 0 = ;
+
+: +! ( n addr -- ) SWAP OVER @ + SWAP ! ;
+: ? ( addr -- ) @ . ;
+: SPACE ( -- ) BL EMIT ;
+
+\ Combinators over DIP, which a standard interpreter keeps out of sight.
+[DEFINED] DIP [IF]
+: KEEP ( x xt -- x ) OVER SWAP DIP ;
+: BI ( x xt1 xt2 -- ) [: KEEP ;] DIP EXECUTE ;
+[THEN]
@@ -0,0 +1,118 @@
+//! Counting calls of each word, and the time spent in it, while
+//! evaluating.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::Forth;
+
+type Clock = dyn FnMut() -> f64;
+
+/// How often a word was called while profiling and how long it ran, as
+/// listed by `Forth::profile`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WordProfile {
+    pub name: String,
+    pub calls: u64,
+    /// Time spent running the word itself rather than the words it calls,
+    /// in the units of the profiling clock.
+    pub time: f64,
+}
+
+pub(super) struct Profiler {
+    clock: Box<Clock>,
+    /// Calls and time so far, by dictionary index.
+    stats: Vec<(u64, f64)>,
+    /// The word time is being charged to, and the time it started.
+    running: Option<usize>,
+    since: f64,
+}
+
+impl Profiler {
+    /// Charges the time since the last switch to the word that was
+    /// running, and starts timing `word`.
+    fn switch(&mut self, word: Option<usize>) {
+        let now = (self.clock)();
+        if let Some(running) = self.running {
+            self.stats_mut(running).1 += now - self.since;
+        }
+        self.running = word;
+        self.since = now;
+    }
+
+    fn stats_mut(&mut self, word: usize) -> &mut (u64, f64) {
+        if self.stats.len() <= word {
+            self.stats.resize(word + 1, (0, 0.0));
+        }
+        &mut self.stats[word]
+    }
+}
+
+impl Forth {
+    /// Starts counting calls of each word and the time spent running it,
+    /// as read from `clock`, discarding counts from earlier profiling.
+    /// `Forth::profile` reports them.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQ DUP * ; : QUAD SQ SQ ;").unwrap();
+    /// let mut ticks = 0.0;
+    /// f.start_profiling(move || { ticks += 1.0; ticks });
+    /// f.eval("3 QUAD QUAD DROP").unwrap();
+    /// let profile = f.stop_profiling();
+    /// let sq = profile.iter().find(|word| word.name == "SQ").unwrap();
+    /// assert_eq!(sq.calls, 4);
+    /// ```
+    pub fn start_profiling<F>(&mut self, clock: F)
+        where F: FnMut() -> f64 + 'static
+    {
+        self.profiler = Some(Profiler { clock: Box::new(clock), stats: Vec::new(), running: None, since: 0.0 });
+    }
+
+    /// Stops profiling, returning what `profile` would have.
+    pub fn stop_profiling(&mut self) -> Vec<WordProfile> {
+        let profile = self.profile();
+        self.profiler = None;
+        profile
+    }
+
+    /// The words called since profiling started, those that took the most
+    /// time first. Empty when not profiling.
+    pub fn profile(&self) -> Vec<WordProfile> {
+        let profiler = match self.profiler {
+            Some(ref profiler) => profiler,
+            None => return Vec::new(),
+        };
+        let mut profile: Vec<_> = profiler.stats.iter().enumerate()
+            .filter(|&(index, &(calls, _))| calls > 0 && index < self.words.len())
+            .map(|(index, &(calls, time))| WordProfile {
+                name: String::from(self.word_name(index)),
+                calls,
+                time,
+            })
+            .collect();
+        profile.sort_by(|a, b| b.time.partial_cmp(&a.time).unwrap_or(core::cmp::Ordering::Equal));
+        profile
+    }
+
+    /// Counts a call of the word at `index`, which runs from now on.
+    pub(super) fn profile_call(&mut self, index: usize) {
+        if let Some(ref mut profiler) = self.profiler {
+            profiler.stats_mut(index).0 += 1;
+            profiler.switch(Some(index));
+        }
+    }
+
+    /// Goes back to timing the word whose body is being run, if any, once
+    /// a call returns.
+    pub(super) fn profile_return(&mut self) {
+        let word = self.return_stack.last().map(|frame| frame.word);
+        if let Some(ref mut profiler) = self.profiler {
+            profiler.switch(word);
+        }
+    }
+}
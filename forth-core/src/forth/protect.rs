@@ -0,0 +1,77 @@
+//! Words the host protects from being redefined, and freezing the
+//! dictionary so that nothing more can be defined.
+
+use alloc::string::String;
+
+use super::{Error, Forth, ForthResult, Symbol};
+
+impl Forth {
+    /// Stops programs defining a word named `name`, in any wordlist, by `:`,
+    /// CREATE, SYNONYM or the like, which fail with `ProtectedWord`
+    /// instead, so that words the host relies on, such as those of a
+    /// grading harness, can't be replaced. The host may still define it
+    /// with `define_native`.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": ANSWER 42 ;").unwrap();
+    /// f.protect("answer");
+    /// assert_eq!(f.eval(": ANSWER 0 ;"), Err(Error::ProtectedWord(String::from("ANSWER"))));
+    /// assert_eq!(f.eval("0 CONSTANT ANSWER"), Err(Error::ProtectedWord(String::from("ANSWER"))));
+    /// assert_eq!(f.protected_words(), ["ANSWER"]);
+    /// ```
+    pub fn protect(&mut self, name: &str) {
+        let name = self.canonical_name(name).into_owned();
+        if !self.protected.contains(&name) {
+            self.protected.push(name);
+        }
+    }
+
+    pub fn unprotect(&mut self, name: &str) {
+        let name = self.canonical_name(name);
+        self.protected.retain(|protected| *protected != name);
+    }
+
+    /// The words `protect` was given, in canonical form.
+    pub fn protected_words(&self) -> &[String] {
+        &self.protected
+    }
+
+    /// Stops programs defining any more words, which fail with
+    /// `DictionaryFrozen`, as the builder's `frozen` does once the prelude
+    /// has run. Words already defined still run, and `define_native` may
+    /// still define more.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().prelude(": SQ DUP * ;").frozen(true).build();
+    /// assert!(f.is_frozen());
+    /// assert_eq!(f.eval("VARIABLE X"), Err(Error::DictionaryFrozen));
+    /// f.eval("3 SQ").unwrap();
+    /// f.thaw();
+    /// f.eval("VARIABLE X").unwrap();
+    /// ```
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    pub fn thaw(&mut self) {
+        self.frozen = false;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Fails with `ProtectedWord` if `name` may not be defined.
+    pub(super) fn check_protected(&self, name: Symbol) -> ForthResult {
+        let name = self.names.resolve(name);
+        match self.protected.iter().any(|protected| protected == name) {
+            true => Err(Error::ProtectedWord(String::from(name))),
+            false => Ok(()),
+        }
+    }
+}
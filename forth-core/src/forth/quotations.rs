@@ -0,0 +1,100 @@
+//! Quotations, `[: … ;]`, nameless definitions compiled inside another
+//! that leave their execution token when it runs, and TIMES and DIP, which
+//! run such tokens.
+
+use alloc::string::String;
+
+use super::{Body, Definition, Error, Forth, ForthResult, Op, Span, Value, Word};
+
+/// A definition set aside while `[:` compiles a quotation inside it, and
+/// where in the input the quotation began.
+#[derive(Clone)]
+pub(super) struct Enclosing {
+    definition: Definition,
+    start: usize,
+}
+
+/// `[:` ( -- quotation-sys ) starts compiling a quotation, which RECURSE
+/// inside it calls.
+pub(super) fn do_start_quotation(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.check_dictionary()?;
+    let definition = forth.definition.take().ok_or_else(|| Error::UnbalancedControl(String::from("[:")))?;
+    let word = definition.word;
+    forth.quotations.push(Enclosing { definition, start: forth.last_span.start });
+    forth.definition = Some(Definition { ops: Body::new(), spans: Body::new(), word, defined_at: None });
+    Ok(())
+}
+
+/// `;]` ( quotation-sys -- ) ends the quotation and compiles its execution
+/// token as a literal of the definition around it.
+pub(super) fn do_end_quotation(forth: &mut Forth, _index: usize) -> ForthResult {
+    let unbalanced = || Error::UnbalancedControl(String::from(";]"));
+    let enclosing = forth.quotations.pop().ok_or_else(unbalanced)?;
+    let quotation = forth.definition.replace(enclosing.definition).ok_or_else(unbalanced)?;
+    let index = forth.add_quotation(quotation, enclosing.start);
+    forth.literal(index as Value);
+    Ok(())
+}
+
+/// TIMES ( i*x xt n -- j*x ) runs `xt` `n` times.
+pub(super) fn do_times(forth: &mut Forth, _index: usize) -> ForthResult {
+    let count = forth.pop()?;
+    let xt = forth.pop()?;
+    let xt = forth.xt(xt)?;
+    for _ in 0..count.max(0) {
+        forth.execute(xt)?;
+    }
+    Ok(())
+}
+
+/// DIP ( i*x x xt -- j*x x ) runs `xt` with `x` set aside.
+pub(super) fn do_dip(forth: &mut Forth, _index: usize) -> ForthResult {
+    let xt = forth.pop()?;
+    let xt = forth.xt(xt)?;
+    let x = forth.pop()?;
+    forth.execute(xt)?;
+    forth.push(x);
+    Ok(())
+}
+
+impl Forth {
+    /// Adds `quotation`, begun at `start` in the input and ended by the
+    /// last name parsed, to the dictionary without a name, returning its
+    /// index.
+    fn add_quotation(&mut self, quotation: Definition, start: usize) -> usize {
+        let end = self.last_span.end;
+        let text = match self.lexed {
+            Some(ref lexed) => lexed.source(start, end),
+            None => {
+                let (source, len) = self.source;
+                String::from_utf8_lossy(&self.memory[source + start.min(len)..source + end.min(len)]).into_owned()
+            }
+        };
+        let spans: Body<Span> = quotation.spans.iter()
+            .map(|span| Span { start: span.start.saturating_sub(start), end: span.end.saturating_sub(start) })
+            .collect();
+        let (ops, spans) = match self.optimize {
+            true => self.peephole(&quotation.ops, &spans),
+            false => (quotation.ops, spans),
+        };
+        let name = self.names.intern("");
+        let mut word = Word::new_compiled(name, self.bodies.alloc(&ops), self.current);
+        word.spans = self.body_spans.alloc(&spans);
+        word.source = text;
+        let index = self.words.len();
+        self.add_word(word);
+        self.compile_natively(index);
+        // The definitions around the quotation, still to be added, now
+        // come a place later, RECURSE calls and all.
+        let enclosing = self.definition.iter_mut().chain(self.quotations.iter_mut().map(|e| &mut e.definition));
+        for definition in enclosing.filter(|definition| definition.word == Some(index)) {
+            definition.word = Some(index + 1);
+            for op in &mut definition.ops {
+                if *op == Op::Call(index) {
+                    *op = Op::Call(index + 1);
+                }
+            }
+        }
+        index
+    }
+}
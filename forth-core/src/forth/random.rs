@@ -0,0 +1,75 @@
+//! RANDOM and SEED, drawing from a small generator of the interpreter's own
+//! so that a program seeded the same way makes the same choices on every
+//! host.
+
+use alloc::string::ToString;
+
+use super::{Error, Forth, ForthResult, Value};
+
+/// A SplitMix64 generator.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Random {
+    state: u64,
+}
+
+impl Default for Random {
+    /// The generator as it is until SEED sets another seed.
+    fn default() -> Random {
+        Random { state: 0x2545_F491_4F6C_DD1D }
+    }
+}
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 to `n - 1`, scaled rather than taken modulo `n` to
+    /// keep the bias negligible.
+    fn below(&mut self, n: u64) -> u64 {
+        ((u128::from(self.next()) * u128::from(n)) >> 64) as u64
+    }
+}
+
+pub(super) fn do_random(forth: &mut Forth, _index: usize) -> ForthResult {
+    let n = forth.pop()?;
+    if n <= 0 {
+        return Err(Error::NumberOutOfRange(n.to_string()));
+    }
+    let r = forth.random.below(n as u64);
+    forth.push(r as Value);
+    Ok(())
+}
+
+pub(super) fn do_seed(forth: &mut Forth, _index: usize) -> ForthResult {
+    let seed = forth.pop()?;
+    forth.seed_random(seed as u64);
+    Ok(())
+}
+
+impl Forth {
+    /// Restarts the numbers RANDOM gives from `seed`, as SEED does. Every
+    /// interpreter starts from the same seed, so without one programs
+    /// still choose the same way each time they run.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.seed_random(42);
+    /// f.eval("6 RANDOM 6 RANDOM 6 RANDOM").unwrap();
+    /// let rolls = f.stack();
+    /// assert!(rolls.iter().all(|&roll| (0..6).contains(&roll)));
+    ///
+    /// let mut g = Forth::new();
+    /// g.eval("42 SEED 6 RANDOM 6 RANDOM 6 RANDOM").unwrap();
+    /// assert_eq!(g.stack(), rolls);
+    /// ```
+    pub fn seed_random(&mut self, seed: u64) {
+        self.random = Random { state: seed };
+    }
+}
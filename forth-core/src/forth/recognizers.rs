@@ -0,0 +1,147 @@
+//! The recognizer chain: parsers for literal forms of the host's or the
+//! program's own, such as fractions or colours, tried on a name that is
+//! neither a word nor a number before it is reported as unknown. Words
+//! add to it with +RECOGNIZER.
+
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+
+use super::{Error, Forth, ForthResult, Op, Value};
+
+/// Given a name as written, the cells of the literal it stands for, or
+/// `None` if it isn't one.
+pub type Recognizer = dyn Fn(&str) -> Option<Vec<Value>>;
+
+#[derive(Clone)]
+pub(super) enum RecognizerEntry {
+    Host(Rc<Recognizer>),
+    /// The word +RECOGNIZER was given, run as ( c-addr u -- i*x true | false ).
+    Word(usize),
+}
+
+pub(super) fn do_add_recognizer(forth: &mut Forth, _index: usize) -> ForthResult {
+    let xt = forth.pop()?;
+    let index = forth.xt(xt)?;
+    forth.recognizers.push(RecognizerEntry::Word(index));
+    Ok(())
+}
+
+impl Forth {
+    /// Adds `recognizer` to the chain tried on each name that is neither a
+    /// word nor a number, most recently added first. The cells it returns
+    /// are pushed, or compiled as literals inside a definition. Names that
+    /// are numbers, such as `#123` in decimal, never reach it.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// // Colours written as #RRGGBB.
+    /// f.add_recognizer(|name| match name.strip_prefix('#') {
+    ///     Some(hex) if hex.len() == 6 => i64::from_str_radix(hex, 16).ok().map(|v| vec![v as _]),
+    ///     _ => None,
+    /// });
+    /// f.eval(": ORANGE #FF8800 ; ORANGE #0000FF").unwrap();
+    /// assert_eq!(f.stack(), vec![0xFF8800, 255]);
+    /// assert_eq!(f.eval("#FF88"), Err(Error::UnknownWord("#FF88".into())));
+    /// ```
+    ///
+    /// Words can be recognizers too, given to +RECOGNIZER. They are run
+    /// with the name's address and length, and leave the literal's cells
+    /// and true, or false, above which anything left is dropped:
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// // Names starting with ~ stand for their length.
+    /// f.eval(": TILDE ( c-addr u -- u flag ) SWAP C@ [CHAR] ~ = ; ' TILDE +RECOGNIZER").unwrap();
+    /// f.eval("~abc ~").unwrap();
+    /// assert_eq!(f.stack(), vec![4, 1]);
+    /// ```
+    pub fn add_recognizer<F>(&mut self, recognizer: F)
+        where F: Fn(&str) -> Option<Vec<Value>> + 'static
+    {
+        self.recognizers.push(RecognizerEntry::Host(Rc::new(recognizer)));
+    }
+
+    /// Empties the recognizer chain, of host recognizers and words alike.
+    pub fn clear_recognizers(&mut self) {
+        self.recognizers.clear();
+    }
+
+    /// Whether a host recognizer takes `name`, as far as the tokenizer can
+    /// tell without running the words in the chain.
+    pub(super) fn host_recognizes(&self, name: &str) -> bool {
+        self.recognizers.iter().rev().any(|entry| match *entry {
+            RecognizerEntry::Host(ref recognizer) => recognizer(name).is_some(),
+            RecognizerEntry::Word(_) => false,
+        })
+    }
+
+    /// Drops the words from the chain, as when the dictionary is replaced
+    /// and their indices may mean something else.
+    pub(super) fn forget_recognizer_words(&mut self) {
+        self.recognizers.retain(|entry| matches!(*entry, RecognizerEntry::Host(_)));
+    }
+
+    /// The words in the chain, which `gc` must keep.
+    pub(super) fn recognizer_words(&self) -> impl Iterator<Item = usize> + '_ {
+        self.recognizers.iter().filter_map(|entry| match *entry {
+            RecognizerEntry::Word(index) => Some(index),
+            RecognizerEntry::Host(_) => None,
+        })
+    }
+
+    /// Tries the chain on `name`, then pushes or compiles the cells of the
+    /// literal it stands for, failing with `UnknownWord` if nothing takes
+    /// it.
+    pub(super) fn recognize(&mut self, name: &str) -> ForthResult {
+        let cells = match self.run_recognizers(name)? {
+            Some(cells) => cells,
+            None => return Err(self.unknown_word(name)),
+        };
+        for v in cells {
+            if self.definition.is_some() {
+                self.literal(v);
+            } else {
+                self.interpret_op(Op::Literal(v))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn run_recognizers(&mut self, name: &str) -> Result<Option<Vec<Value>>, Error> {
+        for i in (0..self.recognizers.len()).rev() {
+            let cells = match self.recognizers[i].clone() {
+                RecognizerEntry::Host(recognizer) => recognizer(name),
+                RecognizerEntry::Word(index) => self.run_recognizer_word(index, name)?,
+            };
+            if cells.is_some() {
+                return Ok(cells);
+            }
+        }
+        Ok(None)
+    }
+
+    /// Runs the word at `index` on a copy of `name` past the end of data
+    /// space, taking the cells it leaves if it recognizes it.
+    fn run_recognizer_word(&mut self, index: usize, name: &str) -> Result<Option<Vec<Value>>, Error> {
+        let depth = self.stack.len();
+        let start = self.memory.len();
+        self.memory.extend_from_slice(name.as_bytes());
+        self.push(start as Value);
+        self.push(name.len() as Value);
+        let result = self.execute(index);
+        self.memory.truncate(start);
+        result?;
+        if self.pop()? == 0 {
+            self.stack.truncate(depth);
+            return Ok(None);
+        }
+        if self.stack.len() < depth {
+            return Err(Error::StackUnderflow);
+        }
+        Ok(Some(self.stack.drain(depth..).collect()))
+    }
+}
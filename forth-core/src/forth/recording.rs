@@ -0,0 +1,207 @@
+//! Recording how each token of an evaluation changed the stack and data
+//! space, to step back and forth through afterwards.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{Forth, ForthResult, Span, Value};
+
+/// Bytes compared at once when looking for what a step wrote; only runs
+/// that differ are compared byte by byte.
+const CHUNK: usize = 64;
+
+/// A stretch of the data space a step wrote.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MemoryWrite {
+    pub addr: usize,
+    pub before: Vec<u8>,
+    pub after: Vec<u8>,
+}
+
+/// A token interpreted by `Forth::record`, and the changes it made. Only
+/// the cells above those it left alone are kept.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RecordedStep {
+    /// The name or number as it appears in the input.
+    pub token: String,
+    pub span: Span,
+    /// The cells it took off the stack, bottom first.
+    pub popped: Vec<Value>,
+    /// The cells it left in their place, bottom first.
+    pub pushed: Vec<Value>,
+    pub writes: Vec<MemoryWrite>,
+}
+
+/// The steps of an evaluation, as returned by `Forth::record`, and the
+/// stack and data space as they stood at one of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    pub steps: Vec<RecordedStep>,
+    /// Whether steps went unrecorded for lack of `max_steps`.
+    pub truncated: bool,
+    /// What `eval` would have returned.
+    pub result: ForthResult,
+    position: usize,
+    stack: Vec<Value>,
+    memory: Vec<u8>,
+}
+
+pub(super) struct Recorder {
+    max_steps: usize,
+    steps: Vec<RecordedStep>,
+    truncated: bool,
+    /// The stack and data space as the last step left them.
+    stack: Vec<Value>,
+    memory: Vec<u8>,
+}
+
+impl Recording {
+    /// How many steps have run at the current position: 0 before the
+    /// first, `steps.len()` after the last.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves to where `position` steps have run, at most `steps.len()`,
+    /// undoing or redoing the steps in between.
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.steps.len());
+        while self.position < position {
+            let step = &self.steps[self.position];
+            let writes = step.writes.iter().map(|w| (w.addr, &w.after[..]));
+            apply(&mut self.stack, &mut self.memory, &step.popped, &step.pushed, writes);
+            self.position += 1;
+        }
+        while self.position > position {
+            self.position -= 1;
+            let step = &self.steps[self.position];
+            let writes = step.writes.iter().rev().map(|w| (w.addr, &w.before[..]));
+            apply(&mut self.stack, &mut self.memory, &step.pushed, &step.popped, writes);
+        }
+    }
+
+    /// The stack at the current position, bottom first.
+    pub fn stack(&self) -> &[Value] {
+        &self.stack
+    }
+
+    /// The data space at the current position.
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+}
+
+/// Replaces `from`, the top of `stack`, with `to`, and writes each run of
+/// bytes at its address.
+fn apply<'a, I>(stack: &mut Vec<Value>, memory: &mut [u8], from: &[Value], to: &[Value], writes: I)
+    where I: Iterator<Item = (usize, &'a [u8])>
+{
+    stack.truncate(stack.len() - from.len());
+    stack.extend_from_slice(to);
+    for (addr, bytes) in writes {
+        memory[addr..addr + bytes.len()].copy_from_slice(bytes);
+    }
+}
+
+/// The runs of bytes in which `memory` differs from `seen`, which is
+/// brought up to date.
+fn writes(seen: &mut [u8], memory: &[u8]) -> Vec<MemoryWrite> {
+    let mut writes: Vec<MemoryWrite> = Vec::new();
+    for start in (0..memory.len()).step_by(CHUNK) {
+        let end = (start + CHUNK).min(memory.len());
+        if seen[start..end] == memory[start..end] {
+            continue;
+        }
+        for addr in start..end {
+            if seen[addr] == memory[addr] {
+                continue;
+            }
+            match writes.last_mut() {
+                Some(last) if last.addr + last.after.len() == addr => {
+                    last.before.push(seen[addr]);
+                    last.after.push(memory[addr]);
+                }
+                _ => writes.push(MemoryWrite { addr, before: vec![seen[addr]], after: vec![memory[addr]] }),
+            }
+            seen[addr] = memory[addr];
+        }
+    }
+    writes
+}
+
+impl Forth {
+    /// Evaluates `input` as `eval` does, recording how each name or number
+    /// of `input` that runs without error changes the stack and data
+    /// space, for up to `max_steps` of them. Steps are as `trace` has them.
+    /// The recording starts at its end, and can be moved to any step to
+    /// see the stack and data space there.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval("VARIABLE X X").unwrap();
+    /// let x = f.stack()[0] as usize;
+    /// f.eval("DROP 7").unwrap();
+    /// let mut recording = f.record("1 X ! 2 + X @", 1000);
+    /// assert_eq!(recording.steps.len(), 7);
+    /// assert_eq!(recording.stack(), [9, 1]);
+    ///
+    /// recording.seek(3);
+    /// assert_eq!(recording.steps[2].token, "!");
+    /// assert_eq!(recording.stack(), [7]);
+    /// assert_eq!(recording.memory()[x], 1);
+    ///
+    /// recording.seek(0);
+    /// assert_eq!(recording.stack(), [7]);
+    /// assert_eq!(recording.memory()[x], 0);
+    /// ```
+    pub fn record(&mut self, input: &str, max_steps: usize) -> Recording {
+        let stack = self.stack.clone();
+        let memory = self.data_space().to_vec();
+        self.recorder = Some(Recorder { max_steps, steps: Vec::new(), truncated: false, stack, memory });
+        let result = self.eval(input);
+        let recorder = self.recorder.take().unwrap();
+        Recording {
+            position: recorder.steps.len(),
+            steps: recorder.steps,
+            truncated: recorder.truncated,
+            result,
+            stack: recorder.stack,
+            memory: recorder.memory,
+        }
+    }
+
+    /// Records what the token at `span`, just interpreted, changed, if
+    /// recording and the token came from the outermost input rather than
+    /// EVALUATE.
+    pub(super) fn record_step(&mut self, span: Span) {
+        if self.executing > 0 || self.source.0 != self.tib {
+            return;
+        }
+        let memory = &self.memory[..self.data_space_size];
+        let recorder = match self.recorder {
+            Some(ref mut recorder) => recorder,
+            None => return,
+        };
+        if recorder.steps.len() >= recorder.max_steps {
+            recorder.truncated = true;
+            return;
+        }
+        let kept = recorder.stack.iter().zip(&self.stack).take_while(|(a, b)| a == b).count();
+        let popped = recorder.stack.split_off(kept);
+        let pushed = self.stack[kept..].to_vec();
+        recorder.stack.extend_from_slice(&pushed);
+        recorder.memory.resize(memory.len(), 0);
+        let bytes = &self.memory[self.tib + span.start..self.tib + span.end];
+        recorder.steps.push(RecordedStep {
+            token: String::from_utf8_lossy(bytes).into_owned(),
+            span,
+            popped,
+            pushed,
+            writes: writes(&mut recorder.memory, memory),
+        });
+    }
+}
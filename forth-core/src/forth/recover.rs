@@ -0,0 +1,96 @@
+//! Evaluation that carries on past unknown words and stack underflows, so
+//! every such mistake in a program can be shown at once.
+
+use alloc::vec::Vec;
+
+use super::{Diagnostic, Error, Forth, ForthResult, Span, TokenKind};
+
+/// The mistakes an evaluation carried on past, as returned by
+/// `Forth::eval_recovering`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recovery {
+    /// Unknown words and stack underflows, in the order they happened,
+    /// each at the name that failed.
+    pub diagnostics: Vec<Diagnostic>,
+    /// Any other error, which ended the evaluation as it would `eval`.
+    pub result: ForthResult,
+}
+
+/// Whether evaluation can carry on after `e`.
+fn recoverable(e: &Error) -> bool {
+    matches!(e, Error::UnknownWord(_) | Error::StackUnderflow)
+}
+
+impl Forth {
+    /// Evaluates `input` as `eval` does, but notes each unknown word and
+    /// stack underflow and carries on after the name that failed, or, in
+    /// a colon definition, after the `;` that ends it, leaving the word
+    /// undefined. Colon definitions are found as `check` finds them.
+    /// `error_location` and `error_trace` describe only an error that
+    /// ended the evaluation.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// let recovery = f.eval_recovering(": SQ DUP * ;\n: BAD 1 NOPE 2 ;\nDROP 3 SQ . OOPS 4");
+    /// let errors: Vec<_> = recovery.diagnostics.iter().map(|d| d.error.clone()).collect();
+    /// assert_eq!(errors, [
+    ///     Error::UnknownWord(String::from("NOPE")),
+    ///     Error::StackUnderflow,
+    ///     Error::UnknownWord(String::from("OOPS")),
+    /// ]);
+    /// assert_eq!(recovery.diagnostics[1].span.line_column(": SQ DUP * ;\n: BAD 1 NOPE 2 ;\nDROP"), (3, 1));
+    /// assert_eq!(recovery.result, Ok(()));
+    /// assert_eq!((f.output(), f.stack()), ("9 ", vec![4]));
+    /// assert!(f.eval("BAD").is_err());
+    /// ```
+    pub fn eval_recovering(&mut self, input: &str) -> Recovery {
+        let definitions = self.definitions(input);
+        let mut diagnostics = Vec::new();
+        let result = self.with_input(input, |forth| loop {
+            let start = forth.to_in();
+            let error = match forth.interpret() {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+            // Without a name read, there is nowhere further on to go.
+            if forth.paused || !recoverable(&error) || forth.to_in() <= start {
+                return Err(error);
+            }
+            let span = forth.input_span;
+            diagnostics.push(Diagnostic { error, span });
+            forth.error_trace.clear();
+            if let Some(definition) = definitions.iter().find(|d| d.start <= span.start && span.start < d.end) {
+                let to_in = forth.to_in().max(definition.end);
+                forth.set_to_in(to_in);
+            }
+        });
+        Recovery { diagnostics, result }
+    }
+
+    /// The spans of the colon definitions in `input`, from `:` to `;`, the
+    /// last running to the end if it isn't finished.
+    fn definitions(&self, input: &str) -> Vec<Span> {
+        let mut spans = Vec::new();
+        // A definition left unfinished by the last evaluation goes on here.
+        let mut open = self.unfinished.as_ref().map(|_| 0);
+        for token in self.tokenize(input) {
+            if token.kind != TokenKind::Word && token.kind != TokenKind::Defining {
+                continue;
+            }
+            match (&*self.canonical_name(&token.text), open) {
+                (":", None) => open = Some(token.span.start),
+                (";", Some(start)) => {
+                    spans.push(Span { start, end: token.span.end });
+                    open = None;
+                }
+                _ => {}
+            }
+        }
+        if let Some(start) = open {
+            spans.push(Span { start, end: input.len() });
+        }
+        spans
+    }
+}
@@ -0,0 +1,160 @@
+//! Saving and restoring interpreter state through serde.
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use heap::Heap;
+
+use super::{BlockBuffer, Error, Forth, ForthResult, SavedWord, Tasks, Timers, Value, Word, HEAP_SIZE};
+
+/// Serializable copy of a `Forth`'s state: stack, dictionary, data space,
+/// heap and pending input. Host callbacks are not part of it.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    stack: Vec<Value>,
+    words: Vec<SavedWord>,
+    wordlists: usize,
+    order: Vec<usize>,
+    current: usize,
+    /// Data space and heap, with trailing zero bytes dropped.
+    memory: Vec<u8>,
+    here: usize,
+    heap: Heap,
+    input: VecDeque<char>,
+    data_space_size: usize,
+    /// Bytes per cell in the interpreter that took the snapshot.
+    cell_size: usize,
+}
+
+impl Forth {
+    /// Captures the interpreter's state for later `restore`.
+    pub fn snapshot(&self) -> Snapshot {
+        let mut memory = self.memory[..self.tib].to_vec();
+        let used = memory.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        memory.truncate(used);
+        Snapshot {
+            stack: self.stack.clone(),
+            words: self.words.iter().map(|word| self.save_word(word)).collect(),
+            wordlists: self.wordlists,
+            order: self.order.clone(),
+            current: self.current,
+            memory,
+            here: self.here,
+            heap: self.heap.clone(),
+            input: self.input.clone(),
+            data_space_size: self.data_space_size,
+            cell_size: super::CELL,
+        }
+    }
+
+    /// Replaces the interpreter's state with `snapshot`. Native words in the
+    /// snapshot must already be defined here, under the same names and in
+    /// the same order, as they are when `self` was built the same way as the
+    /// interpreter that took the snapshot, with the same cell size. Fails
+    /// with `InvalidSnapshot`, and leaves `self` untouched, otherwise, or
+    /// if the snapshot doesn't hang together, as one edited by hand might
+    /// not, or its data space is larger than `max_data_space_size` allows.
+    pub fn restore(&mut self, snapshot: Snapshot) -> ForthResult {
+        if snapshot.cell_size != super::CELL
+            || !super::valid_wordlists(snapshot.wordlists, snapshot.current, &snapshot.order, &snapshot.words)
+        {
+            return Err(Error::InvalidSnapshot);
+        }
+        let tib = self.loaded_tib(snapshot.data_space_size, snapshot.here, snapshot.memory.len())
+            .ok_or(Error::InvalidSnapshot)?;
+        // The heap is rebuilt where this interpreter would put it, from the
+        // blocks alone.
+        let blocks: Vec<_> = snapshot.heap.blocks().collect();
+        let heap = Heap::with_blocks(snapshot.data_space_size, HEAP_SIZE, &blocks).ok_or(Error::InvalidSnapshot)?;
+        let (words, bodies, body_spans) = self.revive_words(snapshot.words).ok_or(Error::InvalidSnapshot)?;
+
+        let memory = super::new_memory(&snapshot.memory, tib);
+        self.stack = snapshot.stack;
+        self.words = words;
+        self.bodies = bodies;
+        self.body_spans = body_spans;
+        self.reindex();
+        self.wordlists = snapshot.wordlists;
+        self.order = snapshot.order;
+        self.current = snapshot.current;
+        self.memory = memory;
+        self.here = snapshot.here;
+        self.heap = heap;
+        self.input = snapshot.input;
+        self.data_space_size = snapshot.data_space_size;
+        self.tib = tib;
+        self.source = (tib, 0);
+        self.return_stack.clear();
+        self.definition = None;
+        self.unfinished = None;
+        self.quotations.clear();
+        self.frame_word = None;
+        self.forget_recognizer_words();
+        self.timers = Timers::default();
+        self.tasks = Tasks::default();
+        self.strings.clear();
+        self.modules.clear();
+        self.block_buffer = BlockBuffer::default();
+        self.discard_paused();
+        self.observe_data_space();
+        Ok(())
+    }
+
+    fn save_word(&self, word: &Word) -> SavedWord {
+        SavedWord {
+            name: String::from(self.names.resolve(word.name)),
+            native: word.native,
+            data: self.bodies.get(word.data).to_vec(),
+            wordlist: word.wordlist,
+            immediate: word.immediate,
+            alias_of: word.alias_of,
+            spans: self.body_spans.get(word.spans).to_vec(),
+            source: word.source.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restoring_abandons_an_unfinished_definition() {
+        let snapshot = Forth::new().snapshot();
+        let mut f = Forth::new();
+        f.eval(": A3 3 ; : B [: A3 ;] A3").unwrap();
+        assert_eq!(f.unfinished_definition(), Some("B"));
+        f.restore(snapshot).unwrap();
+        assert_eq!(f.unfinished_definition(), None);
+        assert_eq!(f.eval("; B"), Err(Error::SemicolonOutsideDefinition));
+        f.eval(": B 2 ; B").unwrap();
+        assert_eq!(f.stack(), vec![2]);
+    }
+
+    #[test]
+    fn inconsistent_snapshots_are_invalid() {
+        let mut f = Forth::new();
+        f.eval("1 2 : A3 3 ; 10 ALLOCATE DROP DROP").unwrap();
+        let edits: [fn(&mut Snapshot); 6] = [
+            |s| s.current = s.wordlists,
+            |s| s.order.push(s.wordlists + 1),
+            |s| s.words[0].wordlist = s.wordlists,
+            |s| s.here = 0,
+            |s| s.data_space_size = usize::MAX,
+            |s| s.heap = Heap::with_blocks(0, 64, &[(0, 8)]).unwrap(),
+        ];
+        for edit in edits.iter() {
+            let mut snapshot = f.snapshot();
+            edit(&mut snapshot);
+            let mut g = Forth::new();
+            g.eval("7").unwrap();
+            assert_eq!(g.restore(snapshot), Err(Error::InvalidSnapshot));
+            assert_eq!(g.stack(), vec![7]);
+        }
+        let mut g = Forth::new();
+        g.restore(f.snapshot()).unwrap();
+        g.eval("A3").unwrap();
+        assert_eq!(g.stack(), vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,112 @@
+//! What the last evaluation cost and defined, for hosts to show or hold
+//! programs to.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::Forth;
+
+/// What `Forth::eval_stats` reports of the last evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EvalStats {
+    /// Instructions run, counting both names and numbers interpreted and
+    /// the instructions of compiled words, as fuel does.
+    pub operations: u64,
+    /// The most items the data stack held.
+    pub max_stack_depth: usize,
+    /// The most frames the return stack held.
+    pub max_return_stack_depth: usize,
+    /// Dictionary entries added.
+    pub words_defined: usize,
+    /// Bytes of data space taken, by definitions, ALLOT and the like.
+    pub data_space_used: usize,
+}
+
+/// A dictionary entry the last evaluation added, as `Forth::defined_words`
+/// lists them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DefinedWord {
+    pub name: String,
+    /// Whether it hides an earlier word of the same name.
+    pub shadows: bool,
+}
+
+/// The counts behind `EvalStats`, and where the dictionary and data space
+/// ended when the evaluation started.
+#[derive(Clone, Copy, Default)]
+pub(super) struct Counters {
+    stats: EvalStats,
+    words: usize,
+    here: usize,
+}
+
+impl Forth {
+    /// What the last evaluation, including any `resume` of it, cost. Each
+    /// `eval` starts the counts afresh.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQ DUP * ; VARIABLE X 3 SQ X !").unwrap();
+    /// let stats = f.eval_stats();
+    /// assert_eq!(stats.operations, 9);
+    /// assert_eq!(stats.max_stack_depth, 2);
+    /// assert_eq!(stats.max_return_stack_depth, 1);
+    /// assert_eq!(stats.words_defined, 2);
+    /// assert!(stats.data_space_used > 0);
+    /// ```
+    pub fn eval_stats(&self) -> EvalStats {
+        EvalStats {
+            words_defined: self.words.len().saturating_sub(self.counters.words),
+            data_space_used: self.here.saturating_sub(self.counters.here),
+            ..self.counters.stats
+        }
+    }
+
+    /// The dictionary entries the last evaluation, including any `resume`
+    /// of it, added, oldest first.
+    ///
+    /// ```
+    /// use forth_core::{DefinedWord, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval(": SQUARE DUP * ;").unwrap();
+    /// f.eval("VARIABLE X : SQUARE SQUARE ; 3 SQUARE").unwrap();
+    /// assert_eq!(f.defined_words(), vec![
+    ///     DefinedWord { name: "X".into(), shadows: false },
+    ///     DefinedWord { name: "SQUARE".into(), shadows: true },
+    /// ]);
+    /// ```
+    pub fn defined_words(&self) -> Vec<DefinedWord> {
+        (self.counters.words.min(self.words.len())..self.words.len())
+            .map(|index| {
+                let symbol = self.words[index].name;
+                DefinedWord {
+                    name: String::from(self.word_name(index)),
+                    shadows: self.index[symbol.index()].iter().any(|&earlier| earlier < index),
+                }
+            })
+            .collect()
+    }
+
+    /// Starts counting afresh for a new evaluation.
+    pub(super) fn reset_counters(&mut self) {
+        self.counters = Counters { stats: EvalStats::default(), words: self.words.len(), here: self.here };
+    }
+
+    /// Counts an instruction about to run.
+    pub(super) fn count_operation(&mut self) {
+        let stats = &mut self.counters.stats;
+        stats.operations += 1;
+        stats.max_return_stack_depth = stats.max_return_stack_depth.max(self.return_stack.len());
+    }
+
+    /// Notes how deep the data stack is after an instruction.
+    pub(super) fn count_stack_depth(&mut self) {
+        let stats = &mut self.counters.stats;
+        stats.max_stack_depth = stats.max_stack_depth.max(self.stack.len());
+    }
+}
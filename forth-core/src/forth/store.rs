@@ -0,0 +1,122 @@
+//! Saving the user's colon definitions somewhere the host keeps them, and
+//! loading them back, for SAVE-DICT and LOAD-DICT.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use super::{Error, Forth, ForthResult, WordDefinition};
+
+type SaveWords = dyn FnMut(&[WordDefinition]) -> ForthResult;
+
+type LoadWords = dyn FnMut() -> Result<Vec<WordDefinition>, Error>;
+
+/// Where the host keeps saved definitions, as given to
+/// `Forth::set_dictionary_store`.
+pub(super) struct DictionaryStore {
+    save: Box<SaveWords>,
+    load: Box<LoadWords>,
+}
+
+pub(super) fn do_save_dict(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.save_dictionary()
+}
+
+pub(super) fn do_load_dict(forth: &mut Forth, _index: usize) -> ForthResult {
+    for word in forth.stored_words()? {
+        forth.evaluate_text(&word.source())?;
+    }
+    Ok(())
+}
+
+impl Forth {
+    /// Has SAVE-DICT pass the colon definitions made so far, as
+    /// `user_words` lists them, to `save`, and LOAD-DICT compile those
+    /// `load` returns. Either fails with what the host returns; without a
+    /// store they fail with `NoStorage`. A deterministic interpreter
+    /// ignores the store.
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use forth_core::Forth;
+    ///
+    /// let saved = Rc::new(RefCell::new(Vec::new()));
+    /// let store = |f: &mut Forth| {
+    ///     let (into, from) = (saved.clone(), saved.clone());
+    ///     f.set_dictionary_store(
+    ///         move |words| Ok(*into.borrow_mut() = words.to_vec()),
+    ///         move || Ok(from.borrow().clone()),
+    ///     );
+    /// };
+    ///
+    /// let mut f = Forth::new();
+    /// store(&mut f);
+    /// f.eval(": SQ DUP * ; SAVE-DICT").unwrap();
+    ///
+    /// let mut g = Forth::new();
+    /// store(&mut g);
+    /// g.eval("LOAD-DICT 7 SQ").unwrap();
+    /// assert_eq!(g.stack(), vec![49]);
+    /// ```
+    pub fn set_dictionary_store<S, L>(&mut self, save: S, load: L)
+        where S: FnMut(&[WordDefinition]) -> ForthResult + 'static,
+              L: FnMut() -> Result<Vec<WordDefinition>, Error> + 'static
+    {
+        if self.deterministic {
+            return;
+        }
+        self.dictionary_store = Some(DictionaryStore { save: Box::new(save), load: Box::new(load) });
+    }
+
+    pub fn clear_dictionary_store(&mut self) {
+        self.dictionary_store = None;
+    }
+
+    /// Saves the colon definitions made so far to the dictionary store, as
+    /// SAVE-DICT does.
+    pub fn save_dictionary(&mut self) -> ForthResult {
+        let words = self.user_words();
+        match self.dictionary_store {
+            Some(ref mut store) => (store.save)(&words),
+            None => Err(Error::NoStorage),
+        }
+    }
+
+    /// Compiles the definitions in the dictionary store, as LOAD-DICT
+    /// does, stopping at the first that fails.
+    pub fn load_dictionary(&mut self) -> ForthResult {
+        let words = self.stored_words()?;
+        self.define_words(&words)
+    }
+
+    fn stored_words(&mut self) -> Result<Vec<WordDefinition>, Error> {
+        match self.dictionary_store {
+            Some(ref mut store) => (store.load)(),
+            None => Err(Error::NoStorage),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_words_may_end_in_a_line_comment() {
+        let mut f = Forth::new();
+        f.set_dictionary_store(
+            |_| Ok(()),
+            || Ok(vec![WordDefinition { name: "SQ".into(), body: "DUP * \\ square it".into() }]),
+        );
+        f.eval("LOAD-DICT 3 SQ").unwrap();
+        assert_eq!(f.unfinished_definition(), None);
+        assert_eq!(f.stack(), vec![9]);
+
+        f.set_dictionary_store(
+            |_| Ok(()),
+            || Ok(vec![WordDefinition { name: "OPEN".into(), body: "1 ( never closed".into() }]),
+        );
+        assert_eq!(f.eval("LOAD-DICT"), Err(Error::UnterminatedDefinition));
+        assert_eq!(f.unfinished_definition(), None);
+    }
+}
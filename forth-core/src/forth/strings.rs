@@ -0,0 +1,225 @@
+//! The string words the builder's `strings` adds, which keep strings on a
+//! stack of their own, each in a block of the heap that is freed when the
+//! string is dropped, so that programs can work with text without
+//! managing memory themselves.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{parse_number, Error, Forth, ForthResult, Op, Unsigned, Value};
+
+/// A string on the string stack: where in the heap it is, and its length.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Handle {
+    addr: usize,
+    len: usize,
+}
+
+/// `$"` ( "ccc<quote>" -- ) ( $: -- s ) pushes the text up to the next `"`
+/// onto the string stack, when the definition runs if compiling.
+pub(super) fn do_string_quote(forth: &mut Forth, _index: usize) -> ForthResult {
+    let (start, len) = forth.parse(b'"');
+    let bytes = forth.memory[start..start + len].to_vec();
+    if forth.definition.is_none() {
+        return forth.push_string(&bytes);
+    }
+    let to_string = forth.primitive_index(">$").ok_or(Error::InvalidWord)?;
+    let (addr, len) = forth.store_bytes(&bytes)?;
+    forth.literal(addr);
+    forth.literal(len);
+    let span = forth.last_span;
+    if let Some(ref mut body) = forth.definition {
+        body.push(Op::Call(to_string), span);
+    }
+    Ok(())
+}
+
+/// `>$` ( c-addr u -- ) ( $: -- s ) pushes a copy of the string.
+pub(super) fn do_to_string(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    forth.check_range(addr, len)?;
+    let bytes = forth.memory[addr as usize..(addr + len) as usize].to_vec();
+    forth.push_string(&bytes)
+}
+
+/// `$@` ( -- c-addr u ) ( $: s -- s ) gives where the top string is, for
+/// as long as it stays on the string stack.
+pub(super) fn do_string_fetch(forth: &mut Forth, _index: usize) -> ForthResult {
+    let handle = forth.top_string()?;
+    forth.push(handle.addr as Value);
+    forth.push(handle.len as Value);
+    Ok(())
+}
+
+/// `$LEN` ( -- u ) ( $: s -- s )
+pub(super) fn do_string_len(forth: &mut Forth, _index: usize) -> ForthResult {
+    let handle = forth.top_string()?;
+    forth.push(handle.len as Value);
+    Ok(())
+}
+
+/// `$DEPTH` ( -- n )
+pub(super) fn do_string_depth(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.push(forth.strings.len() as Value);
+    Ok(())
+}
+
+/// `$.` ( $: s -- ) types the string.
+pub(super) fn do_string_dot(forth: &mut Forth, _index: usize) -> ForthResult {
+    let bytes = forth.pop_string()?;
+    forth.emit(&String::from_utf8_lossy(&bytes))
+}
+
+/// `$DROP` ( $: s -- )
+pub(super) fn do_string_drop(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.pop_string()?;
+    Ok(())
+}
+
+/// `$DUP` ( $: s -- s s )
+pub(super) fn do_string_dup(forth: &mut Forth, _index: usize) -> ForthResult {
+    let handle = forth.top_string()?;
+    let bytes = forth.memory[handle.addr..handle.addr + handle.len].to_vec();
+    forth.push_string(&bytes)
+}
+
+/// `$SWAP` ( $: s1 s2 -- s2 s1 )
+pub(super) fn do_string_swap(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.strings.len();
+    if len < 2 {
+        return Err(Error::StringStackUnderflow);
+    }
+    forth.strings.swap(len - 2, len - 1);
+    Ok(())
+}
+
+/// `$+` ( $: s1 s2 -- s1s2 )
+pub(super) fn do_string_plus(forth: &mut Forth, _index: usize) -> ForthResult {
+    let second = forth.pop_string()?;
+    let mut first = forth.pop_string()?;
+    first.extend_from_slice(&second);
+    forth.push_string(&first)
+}
+
+/// `$SUB` ( start len -- ) ( $: s -- s' ) keeps the `len` characters from
+/// `start` on, or as many of them as the string has.
+pub(super) fn do_string_sub(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?.max(0) as usize;
+    let start = forth.pop()?.max(0) as usize;
+    let bytes = forth.pop_string()?;
+    let start = start.min(bytes.len());
+    let end = start + len.min(bytes.len() - start);
+    forth.push_string(&bytes[start..end])
+}
+
+/// `$SEARCH` ( -- n ) ( $: s1 s2 -- s1 ) gives where `s2` first occurs in
+/// `s1`, or -1 if it doesn't.
+pub(super) fn do_string_search(forth: &mut Forth, _index: usize) -> ForthResult {
+    let needle = forth.pop_string()?;
+    let handle = forth.top_string()?;
+    let haystack = &forth.memory[handle.addr..handle.addr + handle.len];
+    let found = match needle.len() {
+        0 => Some(0),
+        len => haystack.windows(len).position(|window| window == &needle[..]),
+    };
+    forth.push(found.map_or(-1, |i| i as Value));
+    Ok(())
+}
+
+/// `$=` ( -- flag ) ( $: s1 s2 -- )
+pub(super) fn do_string_equals(forth: &mut Forth, _index: usize) -> ForthResult {
+    let second = forth.pop_string()?;
+    let first = forth.pop_string()?;
+    forth.push(if first == second { -1 } else { 0 });
+    Ok(())
+}
+
+/// `$>N` ( -- n true | false ) ( $: s -- ) reads the string as a number in
+/// the current base, as the interpreter would.
+pub(super) fn do_string_to_number(forth: &mut Forth, _index: usize) -> ForthResult {
+    let bytes = forth.pop_string()?;
+    let text = String::from_utf8_lossy(&bytes);
+    match parse_number(text.trim(), forth.base()?) {
+        Ok(Some(n)) => {
+            forth.push(n);
+            forth.push(-1);
+        }
+        _ => forth.push(0),
+    }
+    Ok(())
+}
+
+/// `N>$` ( n -- ) ( $: -- s ) writes `n` in the current base.
+pub(super) fn do_number_to_string(forth: &mut Forth, _index: usize) -> ForthResult {
+    let n = forth.pop()?;
+    let base = forth.base()?;
+    let mut magnitude = n.unsigned_abs();
+    let radix = Unsigned::from(base);
+    let mut digits = Vec::new();
+    loop {
+        let digit = (magnitude % radix) as u8;
+        digits.push(if digit < 10 { b'0' + digit } else { b'A' + digit - 10 });
+        magnitude /= radix;
+        if magnitude == 0 {
+            break;
+        }
+    }
+    if n < 0 {
+        digits.push(b'-');
+    }
+    digits.reverse();
+    forth.push_string(&digits)
+}
+
+impl Forth {
+    /// The strings on the string stack, bottom first, when the builder's
+    /// `strings` added the string words. `$"` pushes a string and `>$`
+    /// copies one from memory; `$@` gives where the top one is, `$.`
+    /// types it and `$DROP`, `$DUP` and `$SWAP` work as their data stack
+    /// counterparts. `$+` joins two strings, `$SUB` ( start len -- ) keeps
+    /// part of one, `$SEARCH` ( -- n ) finds the top string in the one
+    /// below, dropping it, and `$=` compares them. `$>N` and `N>$` convert
+    /// to and from numbers, and `$LEN` and `$DEPTH` tell sizes.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::builder().strings(true).build();
+    /// f.eval(": GREET $\" Hello, \" $SWAP $+ ; $\" world\" GREET $DUP $.").unwrap();
+    /// assert_eq!(f.output(), "Hello, world");
+    /// f.eval("$DUP $\" world\" $SEARCH 2 3 $SUB $LEN").unwrap();
+    /// assert_eq!(f.stack(), vec![7, 3]);
+    /// assert_eq!(f.strings(), vec!["Hello, world", "llo"]);
+    ///
+    /// f.eval("$DROP $DROP -42 N>$ $\" 8\" $+ $>N").unwrap();
+    /// assert_eq!(f.stack(), vec![7, 3, -428, -1]);
+    /// assert_eq!(f.eval("$DROP"), Err(Error::StringStackUnderflow));
+    /// ```
+    pub fn strings(&self) -> Vec<String> {
+        self.strings.iter()
+            .map(|handle| String::from_utf8_lossy(&self.memory[handle.addr..handle.addr + handle.len]).into_owned())
+            .collect()
+    }
+
+    /// Copies `bytes` into the heap and pushes them onto the string stack.
+    pub(super) fn push_string(&mut self, bytes: &[u8]) -> ForthResult {
+        // Empty blocks would share their address with the next one.
+        let addr = self.heap.allocate(bytes.len().max(1)).ok_or(Error::DataSpaceFull)?;
+        self.memory[addr..addr + bytes.len()].copy_from_slice(bytes);
+        self.strings.push(Handle { addr, len: bytes.len() });
+        Ok(())
+    }
+
+    /// Pops the top string, freeing its block, and returns what it held.
+    pub(super) fn pop_string(&mut self) -> Result<Vec<u8>, Error> {
+        let handle = self.strings.pop().ok_or(Error::StringStackUnderflow)?;
+        let bytes = self.memory[handle.addr..handle.addr + handle.len].to_vec();
+        self.heap.free(handle.addr);
+        Ok(bytes)
+    }
+
+    fn top_string(&self) -> Result<Handle, Error> {
+        self.strings.last().cloned().ok_or(Error::StringStackUnderflow)
+    }
+}
@@ -0,0 +1,58 @@
+//! BEGIN-STRUCTURE, +FIELD, FIELD:, CFIELD: and END-STRUCTURE, which
+//! name the offsets of the fields in a record and its size.
+
+use super::{Error, Forth, ForthResult, Op, Value, CELL};
+
+/// BEGIN-STRUCTURE ( "name" -- struct-sys 0 ) defines `name` to push the
+/// size of the structure, which END-STRUCTURE fills in.
+pub(super) fn do_begin_structure(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.define_constant(0)?;
+    forth.push((forth.words.len() - 1) as Value);
+    forth.push(0);
+    Ok(())
+}
+
+/// END-STRUCTURE ( struct-sys n -- ) makes the word BEGIN-STRUCTURE
+/// defined push `n`.
+pub(super) fn do_end_structure(forth: &mut Forth, _index: usize) -> ForthResult {
+    let size = forth.pop()?;
+    let index = forth.pop()?;
+    if index < 0 || index as usize >= forth.words.len() || !matches!(*forth.body(index as usize), [Op::Literal(_)]) {
+        return Err(Error::InvalidWord);
+    }
+    forth.words[index as usize].data = forth.bodies.alloc(&[Op::Literal(size)]);
+    Ok(())
+}
+
+/// +FIELD ( n1 n2 "name" -- n3 ) defines `name` ( addr -- addr+n1 ) for a
+/// field of `n2` bytes at offset `n1`, leaving the offset after it.
+pub(super) fn do_plus_field(forth: &mut Forth, _index: usize) -> ForthResult {
+    let size = forth.pop()?;
+    let offset = forth.pop()?;
+    forth.define_field(offset, size)
+}
+
+/// FIELD: ( n1 "name" -- n2 ) is +FIELD for a cell, aligned to one.
+pub(super) fn do_field(forth: &mut Forth, _index: usize) -> ForthResult {
+    let offset = forth.pop()?;
+    let cell = CELL as Value;
+    let aligned = offset.checked_add(cell - 1).ok_or(Error::Overflow)? / cell * cell;
+    forth.define_field(aligned, cell)
+}
+
+/// CFIELD: ( n1 "name" -- n2 ) is +FIELD for a character.
+pub(super) fn do_cfield(forth: &mut Forth, _index: usize) -> ForthResult {
+    let offset = forth.pop()?;
+    forth.define_field(offset, 1)
+}
+
+impl Forth {
+    /// Defines the next name in the input to add `offset` to an address,
+    /// and pushes the offset of the field after it, `size` bytes on.
+    fn define_field(&mut self, offset: Value, size: Value) -> ForthResult {
+        let plus = self.primitive_index("+").ok_or(Error::InvalidWord)?;
+        self.define_compiled(&[Op::Literal(offset), Op::Call(plus)])?;
+        self.push(offset.checked_add(size).ok_or(Error::Overflow)?);
+        Ok(())
+    }
+}
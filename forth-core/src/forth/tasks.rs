@@ -0,0 +1,132 @@
+//! TASK, PAUSE and STOP-TASK, which run words as tasks, each with its own
+//! stacks, taking turns when the host calls `Forth::run_tasks`.
+
+use alloc::vec::Vec;
+use core::mem;
+
+use super::{Error, Forth, ForthResult, Frame, Value};
+
+/// A word run as a task, and the stacks it had when it last yielded.
+#[derive(Clone)]
+pub(super) struct Task {
+    id: Value,
+    pub(super) word: usize,
+    pub(super) stack: Vec<Value>,
+    pub(super) return_stack: Vec<Frame>,
+    started: bool,
+}
+
+/// The tasks TASK started, in the order they take turns, and the
+/// identifier the next one gets.
+#[derive(Clone, Default)]
+pub(super) struct Tasks {
+    pub(super) list: Vec<Task>,
+    next_id: Value,
+}
+
+pub(super) fn do_task(forth: &mut Forth, _index: usize) -> ForthResult {
+    let xt = forth.pop()?;
+    let word = forth.xt(xt)?;
+    let tasks = &mut forth.tasks;
+    tasks.next_id = tasks.next_id.wrapping_add(1);
+    let id = tasks.next_id;
+    tasks.list.push(Task { id, word, stack: Vec::new(), return_stack: Vec::new(), started: false });
+    forth.push(id);
+    Ok(())
+}
+
+pub(super) fn do_pause(forth: &mut Forth, _index: usize) -> ForthResult {
+    // The word asking is run by the outermost `execute`.
+    forth.paused = forth.executing == 1;
+    Err(Error::PauseRequested)
+}
+
+pub(super) fn do_stop_task(forth: &mut Forth, _index: usize) -> ForthResult {
+    let id = forth.pop()?;
+    forth.tasks.list.retain(|task| task.id != id);
+    Ok(())
+}
+
+impl Forth {
+    /// How many tasks TASK started that haven't finished or been stopped.
+    pub fn task_count(&self) -> usize {
+        self.tasks.list.len()
+    }
+
+    /// Gives each task a turn, in the order TASK started them, sharing the
+    /// dictionary and data space but each with its own stacks. TASK
+    /// ( xt -- id ) starts a task that runs `xt` on empty stacks, and
+    /// STOP-TASK ( id -- ) stops one. A task's turn lasts until it runs
+    /// PAUSE, uses up `fuel` instructions if given, or finishes. Output is
+    /// collected as by `eval`. A task that fails is stopped, and the error
+    /// returned; the tasks after it take their turns on the next call.
+    ///
+    /// Outside a task, PAUSE fails with `PauseRequested`, pausing the
+    /// evaluation if it can be resumed from there, so that the host can
+    /// run the tasks before calling `resume`. Does nothing while an
+    /// evaluation is running.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth};
+    ///
+    /// let mut f = Forth::new();
+    /// f.eval("VARIABLE N : COUNTER N @ 1+ N ! PAUSE RECURSE ; ' COUNTER TASK DROP").unwrap();
+    /// f.eval(": SPIN RECURSE ; ' SPIN TASK CONSTANT SPINNER").unwrap();
+    /// f.run_tasks(Some(100)).unwrap();
+    ///
+    /// assert_eq!(f.eval(": MAIN 10 PAUSE N @ ; MAIN"), Err(Error::PauseRequested));
+    /// f.run_tasks(Some(100)).unwrap();
+    /// f.resume().unwrap();
+    /// assert_eq!(f.stack(), vec![10, 2]);
+    ///
+    /// f.eval("SPINNER STOP-TASK").unwrap();
+    /// assert_eq!(f.task_count(), 1);
+    /// ```
+    pub fn run_tasks(&mut self, fuel: Option<u64>) -> ForthResult {
+        if self.executing != 0 {
+            return Ok(());
+        }
+        self.output.clear();
+        self.output_len = 0;
+        self.output_truncated = false;
+        // Only tasks started before now take a turn.
+        let ids: Vec<Value> = self.tasks.list.iter().map(|task| task.id).collect();
+        for id in ids {
+            // An earlier task may have stopped it.
+            let task = match self.tasks.list.iter_mut().find(|task| task.id == id) {
+                Some(task) => task,
+                None => continue,
+            };
+            let word = task.word;
+            let started = mem::replace(&mut task.started, true);
+            let stack = mem::replace(&mut self.stack, mem::take(&mut task.stack));
+            let return_stack = mem::replace(&mut self.return_stack, mem::take(&mut task.return_stack));
+            let paused = mem::replace(&mut self.paused, false);
+            let limit = mem::replace(&mut self.fuel, fuel);
+            let result = match started {
+                true => self.run_frames(0, |_| Ok(())),
+                false => self.run_frames(0, |forth| forth.call(word)),
+            };
+            self.fuel = limit;
+            let yielded = mem::replace(&mut self.paused, paused);
+            let task_stack = mem::replace(&mut self.stack, stack);
+            let task_return_stack = mem::replace(&mut self.return_stack, return_stack);
+            let i = match self.tasks.list.iter().position(|task| task.id == id) {
+                Some(i) => i,
+                None => continue,
+            };
+            if yielded {
+                self.tasks.list[i].stack = task_stack;
+                self.tasks.list[i].return_stack = task_return_stack;
+                continue;
+            }
+            self.tasks.list.remove(i);
+            if result.is_err() {
+                self.observe_data_space();
+                return result;
+            }
+        }
+        self.observe_data_space();
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+//! AT-XY, PAGE and the attribute words FG, BG, BOLD and NORMAL, and the
+//! grid of characters they write to when the builder's `terminal` asks for
+//! one. Without a grid they send the ANSI escape sequences a real terminal
+//! understands instead.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{Forth, ForthResult, Value};
+
+/// Colours the grid starts in, of the 16 of the ANSI palette: light grey
+/// on black.
+const DEFAULT_FG: u8 = 7;
+const DEFAULT_BG: u8 = 0;
+
+/// A character on the grid, with the colours and weight it was written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GridCell {
+    pub ch: char,
+    /// Foreground and background, as indices into the 16 colours of the
+    /// ANSI palette.
+    pub fg: u8,
+    pub bg: u8,
+    pub bold: bool,
+}
+
+impl GridCell {
+    fn blank(fg: u8, bg: u8) -> GridCell {
+        GridCell { ch: ' ', fg, bg, bold: false }
+    }
+}
+
+/// A fixed-size screen of characters, written as output is, where AT-XY
+/// moves the cursor and PAGE clears it. Output past the right edge wraps
+/// to the next row, and past the bottom scrolls the screen up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerminalGrid {
+    columns: usize,
+    rows: usize,
+    cells: Vec<GridCell>,
+    cursor: (usize, usize),
+    /// Attributes characters are written in.
+    pen: GridCell,
+    version: u64,
+}
+
+impl TerminalGrid {
+    pub(super) fn new(columns: usize, rows: usize) -> TerminalGrid {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        TerminalGrid {
+            columns,
+            rows,
+            cells: vec![GridCell::blank(DEFAULT_FG, DEFAULT_BG); columns * rows],
+            cursor: (0, 0),
+            pen: GridCell::blank(DEFAULT_FG, DEFAULT_BG),
+            version: 0,
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Column and row the next character goes in, from the top left.
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// The cells of `row`, left to right. Panics if there is no such row.
+    pub fn row(&self, row: usize) -> &[GridCell] {
+        &self.cells[row * self.columns..(row + 1) * self.columns]
+    }
+
+    /// The characters on the grid, a line to a row without trailing spaces.
+    pub fn text(&self) -> String {
+        let lines: Vec<String> = (0..self.rows)
+            .map(|row| String::from(self.row(row).iter().map(|cell| cell.ch).collect::<String>().trim_end()))
+            .collect();
+        lines.join("\n")
+    }
+
+    /// A count of the changes made to the grid, so that a host drawing it
+    /// can tell whether it needs to again.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn write(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\n' => self.new_line(),
+                '\r' => self.cursor.0 = 0,
+                c if c.is_control() => {}
+                c => {
+                    if self.cursor.0 == self.columns {
+                        self.new_line();
+                    }
+                    let (column, row) = self.cursor;
+                    self.cells[row * self.columns + column] = GridCell { ch: c, ..self.pen };
+                    self.cursor.0 += 1;
+                }
+            }
+        }
+        self.version += 1;
+    }
+
+    fn new_line(&mut self) {
+        self.cursor.0 = 0;
+        if self.cursor.1 + 1 < self.rows {
+            self.cursor.1 += 1;
+            return;
+        }
+        self.cells.drain(..self.columns);
+        let blank = GridCell::blank(self.pen.fg, self.pen.bg);
+        self.cells.resize(self.columns * self.rows, blank);
+    }
+
+    fn page(&mut self) {
+        let blank = GridCell::blank(self.pen.fg, self.pen.bg);
+        for cell in &mut self.cells {
+            *cell = blank;
+        }
+        self.cursor = (0, 0);
+        self.version += 1;
+    }
+}
+
+/// A colour index from the stack, taken modulo 16.
+fn colour(v: Value) -> u8 {
+    v.rem_euclid(16) as u8
+}
+
+/// AT-XY ( column row -- ) moves the cursor, kept within the grid.
+pub(super) fn do_at_xy(forth: &mut Forth, _index: usize) -> ForthResult {
+    let row = forth.pop()?.max(0) as usize;
+    let column = forth.pop()?.max(0) as usize;
+    match forth.terminal {
+        Some(ref mut grid) => {
+            grid.cursor = (column.min(grid.columns - 1), row.min(grid.rows - 1));
+            grid.version += 1;
+            Ok(())
+        }
+        None => forth.emit(&format!("\x1b[{};{}H", row + 1, column + 1)),
+    }
+}
+
+/// PAGE ( -- ) clears the screen, leaving the cursor at the top left.
+pub(super) fn do_page(forth: &mut Forth, _index: usize) -> ForthResult {
+    match forth.terminal {
+        Some(ref mut grid) => {
+            grid.page();
+            Ok(())
+        }
+        None => forth.emit("\x1b[2J\x1b[H"),
+    }
+}
+
+/// FG ( colour -- ) and BG ( colour -- ) choose the colours later output
+/// is written in.
+pub(super) fn do_colour(forth: &mut Forth, index: usize) -> ForthResult {
+    let c = colour(forth.pop()?);
+    let foreground = forth.word_name(index) == "FG";
+    match forth.terminal {
+        Some(ref mut grid) if foreground => grid.pen.fg = c,
+        Some(ref mut grid) => grid.pen.bg = c,
+        None => {
+            let base = match (foreground, c < 8) {
+                (true, true) => 30,
+                (true, false) => 90 - 8,
+                (false, true) => 40,
+                (false, false) => 100 - 8,
+            };
+            return forth.emit(&format!("\x1b[{}m", base + c));
+        }
+    }
+    Ok(())
+}
+
+/// BOLD ( -- ) writes later output in bold.
+pub(super) fn do_bold(forth: &mut Forth, _index: usize) -> ForthResult {
+    match forth.terminal {
+        Some(ref mut grid) => {
+            grid.pen.bold = true;
+            Ok(())
+        }
+        None => forth.emit("\x1b[1m"),
+    }
+}
+
+/// NORMAL ( -- ) goes back to the colours and weight output starts in.
+pub(super) fn do_normal(forth: &mut Forth, _index: usize) -> ForthResult {
+    match forth.terminal {
+        Some(ref mut grid) => {
+            grid.pen = GridCell::blank(DEFAULT_FG, DEFAULT_BG);
+            Ok(())
+        }
+        None => forth.emit("\x1b[0m"),
+    }
+}
+
+impl Forth {
+    /// The grid output is written to, if the builder's `terminal` asked
+    /// for one.
+    ///
+    /// ```
+    /// use forth_core::Forth;
+    ///
+    /// let mut f = Forth::builder().terminal(Some((8, 3))).build();
+    /// f.eval("PAGE 3 0 AT-XY 12 FG CHAR @ EMIT NORMAL 0 2 AT-XY 1 . 22 . 333 .").unwrap();
+    /// // The space after 333 wrapped, scrolling the grid up a row.
+    /// let grid = f.terminal().unwrap();
+    /// assert_eq!(grid.text(), "\n1 22 333\n");
+    /// assert_eq!(grid.cursor(), (1, 2));
+    /// ```
+    pub fn terminal(&self) -> Option<&TerminalGrid> {
+        self.terminal.as_ref()
+    }
+
+    /// Copies `text`, just output, to the grid.
+    pub(super) fn write_terminal(&mut self, text: &str) {
+        if let Some(ref mut grid) = self.terminal {
+            grid.write(text);
+        }
+    }
+}
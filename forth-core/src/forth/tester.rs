@@ -0,0 +1,88 @@
+//! T{, -> and }T, which check that code leaves the results expected, as
+//! the tester the standard's test programs use does, logging mismatches.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::{Error, Forth, ForthResult, Value};
+
+/// The test under way, and how many have failed.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Tester {
+    /// Depth of the stack at T{, until }T.
+    depth: Option<usize>,
+    /// The results -> took off the stack.
+    actual: Option<Vec<Value>>,
+    failures: usize,
+}
+
+/// `values` in decimal, separated by spaces.
+fn cells(values: &[Value]) -> String {
+    match values.is_empty() {
+        true => String::from("nothing"),
+        false => values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(" "),
+    }
+}
+
+pub(super) fn do_t_open(forth: &mut Forth, _index: usize) -> ForthResult {
+    forth.tester.depth = Some(forth.stack.len());
+    forth.tester.actual = None;
+    Ok(())
+}
+
+pub(super) fn do_arrow(forth: &mut Forth, _index: usize) -> ForthResult {
+    let depth = forth.tester.depth.ok_or(Error::InvalidWord)?;
+    if forth.stack.len() < depth {
+        return Err(Error::StackUnderflow);
+    }
+    forth.tester.actual = Some(forth.stack.split_off(depth));
+    Ok(())
+}
+
+pub(super) fn do_t_close(forth: &mut Forth, _index: usize) -> ForthResult {
+    let depth = forth.tester.depth.take().ok_or(Error::InvalidWord)?;
+    let actual = forth.tester.actual.take().ok_or(Error::InvalidWord)?;
+    if forth.stack.len() < depth {
+        return Err(Error::StackUnderflow);
+    }
+    let expected = forth.stack.split_off(depth);
+    let problem = if expected.len() != actual.len() {
+        "wrong number of results"
+    } else if expected != actual {
+        "incorrect result"
+    } else {
+        return Ok(());
+    };
+    forth.tester.failures += 1;
+    forth.log(&format!("{}: expected {}, got {}", problem, cells(&expected), cells(&actual)))
+}
+
+impl Forth {
+    /// How many tests written `T{ code -> results }T` have failed, leaving
+    /// results other than those expected. T{ notes the depth of the stack,
+    /// -> takes what the code left above it, and }T compares that with the
+    /// results expected, taking them too. Each failure is passed to the
+    /// log sink, as LOG does.
+    ///
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use forth_core::Forth;
+    ///
+    /// let log = Rc::new(RefCell::new(Vec::new()));
+    /// let sink = log.clone();
+    /// let mut f = Forth::new();
+    /// f.set_log_sink(move |message, _word| sink.borrow_mut().push(String::from(message)));
+    /// f.eval("T{ 1 2 + -> 3 }T  T{ 7 2 /MOD -> 3 1 }T  T{ 1 2 -> 1 }T").unwrap();
+    /// assert_eq!(f.test_failures(), 2);
+    /// assert_eq!(*log.borrow(), [
+    ///     "incorrect result: expected 3 1, got 1 3",
+    ///     "wrong number of results: expected 1, got 1 2",
+    /// ]);
+    /// assert!(f.stack().is_empty());
+    /// ```
+    pub fn test_failures(&self) -> usize {
+        self.tester.failures
+    }
+}
@@ -0,0 +1,134 @@
+//! AFTER, EVERY and CANCEL, which schedule words for the host to run with
+//! `Forth::run_timers` once its clock says they are due.
+
+use alloc::vec::Vec;
+use core::mem;
+
+use super::{Error, Forth, ForthResult, Op, Value};
+
+/// A word to run once the clock reaches `due`, and every `period`
+/// milliseconds after if given.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Timer {
+    id: Value,
+    pub(super) word: usize,
+    due: u64,
+    period: Option<u64>,
+}
+
+/// The timers scheduled, and the identifier the next one gets.
+#[derive(Debug, Clone, Default)]
+pub(super) struct Timers {
+    pub(super) list: Vec<Timer>,
+    next_id: Value,
+}
+
+/// Schedules the word whose execution token is below `ms` on the stack,
+/// leaving the timer's identifier for CANCEL.
+fn schedule(forth: &mut Forth, periodic: bool) -> ForthResult {
+    let ms = forth.pop()?.max(0) as u64;
+    let xt = forth.pop()?;
+    let word = forth.xt(xt)?;
+    let now = forth.ticks().ok_or(Error::NoClock)?;
+    // A timer due every 0 ms would never let `run_timers` finish.
+    let period = if periodic { Some(ms.max(1)) } else { None };
+    let timers = &mut forth.timers;
+    timers.next_id = timers.next_id.wrapping_add(1);
+    let id = timers.next_id;
+    timers.list.push(Timer { id, word, due: now + ms, period });
+    forth.push(id);
+    Ok(())
+}
+
+pub(super) fn do_after(forth: &mut Forth, _index: usize) -> ForthResult {
+    schedule(forth, false)
+}
+
+pub(super) fn do_every(forth: &mut Forth, _index: usize) -> ForthResult {
+    schedule(forth, true)
+}
+
+pub(super) fn do_cancel(forth: &mut Forth, _index: usize) -> ForthResult {
+    let id = forth.pop()?;
+    forth.timers.list.retain(|timer| timer.id != id);
+    Ok(())
+}
+
+impl Forth {
+    /// When, in the clock's ticks, the next timer AFTER or EVERY scheduled
+    /// is due, so that the host can wait until then to call `run_timers`.
+    pub fn next_timer_due(&self) -> Option<u64> {
+        self.timers.list.iter().map(|timer| timer.due).min()
+    }
+
+    /// Runs the words of the timers due by the clock, earliest first, each
+    /// for at most `fuel` instructions if given, keeping the limit
+    /// `set_fuel` set for later evaluations. Output is collected as by
+    /// `eval`. A timer set by AFTER runs once; one set by EVERY runs again
+    /// a period later, once for any periods missed. A timer whose word
+    /// fails, or runs out of fuel, is cancelled, and the error returned;
+    /// the other timers due run on the next call.
+    ///
+    /// Does nothing without a clock, or while an evaluation is running or
+    /// paused, which a timer would otherwise cut short.
+    ///
+    /// ```
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    /// use forth_core::{Clock, Forth, TimeAndDate};
+    ///
+    /// struct Manual(Rc<Cell<u64>>);
+    ///
+    /// impl Clock for Manual {
+    ///     fn ticks(&mut self) -> u64 { self.0.get() }
+    ///     fn time_and_date(&mut self) -> TimeAndDate { TimeAndDate::default() }
+    /// }
+    ///
+    /// let now = Rc::new(Cell::new(0));
+    /// let mut f = Forth::new();
+    /// f.set_clock(Manual(now.clone()));
+    /// f.eval("VARIABLE N : TICK N @ 1 + N ! ; ' TICK 100 EVERY ' TICK 250 AFTER DROP").unwrap();
+    /// assert_eq!(f.next_timer_due(), Some(100));
+    ///
+    /// now.set(300);
+    /// f.run_timers(None).unwrap();
+    /// assert_eq!(f.next_timer_due(), Some(400));
+    /// f.eval("CANCEL N @").unwrap();
+    /// assert_eq!(f.stack(), vec![2]);
+    /// assert_eq!(f.next_timer_due(), None);
+    /// ```
+    pub fn run_timers(&mut self, fuel: Option<u64>) -> ForthResult {
+        if self.paused || self.executing != 0 || self.timers.list.is_empty() {
+            return Ok(());
+        }
+        let now = match self.ticks() {
+            Some(now) => now,
+            None => return Ok(()),
+        };
+        // Only timers due now run, not any their words schedule.
+        let mut due: Vec<Timer> = self.timers.list.iter().filter(|timer| timer.due <= now).cloned().collect();
+        due.sort_by_key(|timer| timer.due);
+        for timer in due {
+            // An earlier timer's word may have cancelled it.
+            let i = match self.timers.list.iter().position(|t| t.id == timer.id) {
+                Some(i) => i,
+                None => continue,
+            };
+            match timer.period {
+                Some(period) => self.timers.list[i].due += period * ((now - timer.due) / period + 1),
+                None => {
+                    self.timers.list.remove(i);
+                }
+            }
+            let limit = mem::replace(&mut self.fuel, fuel);
+            let result = self.with_input("", |forth| forth.interpret_ops(&[Op::Call(timer.word)]));
+            self.fuel = limit;
+            if result.is_err() {
+                self.discard_paused();
+                self.timers.list.retain(|t| t.id != timer.id);
+                return result;
+            }
+        }
+        Ok(())
+    }
+}
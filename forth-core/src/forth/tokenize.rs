@@ -0,0 +1,453 @@
+//! Splitting source text into classified tokens without running it.
+
+use alloc::borrow::Cow;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{parse_number, scan_name, Error, Forth, ForthResult, Location, Span};
+#[cfg(feature = "typed")]
+use super::parse_float;
+
+/// What a `SourceToken` is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TokenKind {
+    /// A name found in the dictionary or defined earlier in the source.
+    Word,
+    Number,
+    /// A name that is neither a word nor a valid number.
+    Unknown,
+    /// A number too large for a cell, which `eval` rejects with
+    /// `NumberOutOfRange`.
+    OutOfRange,
+    /// A word that defines the name following it, such as `:` or
+    /// `VARIABLE`.
+    Defining,
+    /// The name a defining word such as `:` or `VARIABLE` gives a new word.
+    Definition,
+    /// Any other name consumed by the preceding word, as by `[DEFINED]`.
+    Name,
+    /// The text of a `(` or `\` comment.
+    Comment,
+    /// The text of an `S"`, `S\"` or, with typed cells, `"` string
+    /// literal.
+    String,
+}
+
+/// A token of source text, as returned by `Forth::tokenize`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SourceToken {
+    pub kind: TokenKind,
+    pub text: String,
+    pub span: Span,
+}
+
+/// The tokens of some source text, read a name at a time, as returned by
+/// `Forth::tokens`.
+pub struct Tokens<'a> {
+    forth: &'a Forth,
+    source: &'a str,
+    offset: usize,
+    /// Names defined by the source so far, and the colon definition being
+    /// read, which only becomes visible at its `;`.
+    defined: Vec<String>,
+    definition: Option<String>,
+    /// Tokens of the last name read not yet returned: the name, and those
+    /// a parsing word took after it.
+    tokens: VecDeque<SourceToken>,
+}
+
+impl<'a> Tokens<'a> {
+    fn push(&mut self, kind: TokenKind, span: Span) {
+        let text = String::from(&self.source[span.start..span.end]);
+        self.tokens.push_back(SourceToken { kind, text, span });
+    }
+
+    fn next_name(&mut self) -> Option<Span> {
+        let span = scan_name(self.source.as_bytes(), self.offset)?;
+        self.offset = span.end;
+        Some(span)
+    }
+
+    fn canonical(&self, span: Span) -> Cow<'a, str> {
+        self.forth.canonical_name(&self.source[span.start..span.end])
+    }
+
+    fn classify(&self, name: &str) -> TokenKind {
+        if self.is_defined(name) || self.forth.lookup_word(name).is_some() {
+            return TokenKind::Word;
+        }
+        match parse_number(name, self.forth.base().unwrap_or(10)) {
+            Ok(Some(_)) => return TokenKind::Number,
+            Err(Error::NumberOutOfRange(_)) => return TokenKind::OutOfRange,
+            _ => {}
+        }
+        if self.is_float(name) || self.forth.host_recognizes(name) {
+            TokenKind::Number
+        } else {
+            TokenKind::Unknown
+        }
+    }
+
+    /// Whether `name` is a float literal, as with typed cells.
+    #[cfg(feature = "typed")]
+    fn is_float(&self, name: &str) -> bool {
+        self.forth.typed.is_some() && parse_float(name).is_some()
+    }
+
+    #[cfg(not(feature = "typed"))]
+    fn is_float(&self, _name: &str) -> bool {
+        false
+    }
+
+    /// Whether the source defines `name` before it, or a module and word
+    /// of the names `name` qualifies.
+    fn is_defined(&self, name: &str) -> bool {
+        let defined = |name: &str| self.defined.iter().any(|defined| defined == name);
+        defined(name) || name.rfind("::").is_some_and(|split| defined(&name[..split]) && defined(&name[split + 2..]))
+    }
+
+    /// Reads the name following a parsing word as a token of `kind`,
+    /// classifying it like any other name when `kind` is `None`.
+    fn parsed_name(&mut self, kind: Option<TokenKind>) -> Option<String> {
+        let span = self.next_name()?;
+        let name = self.canonical(span);
+        let kind = kind.unwrap_or_else(|| self.classify(&name));
+        self.push(kind, span);
+        Some(name.into_owned())
+    }
+
+    /// Reads text up to `end`, which returns the length of the text and of
+    /// its terminator within what remains of the source.
+    fn parsed_text<F>(&mut self, kind: TokenKind, end: F)
+        where F: Fn(&[u8]) -> (usize, usize)
+    {
+        // Parsing words skip the single delimiter after their name.
+        let start = (self.offset + 1).min(self.source.len());
+        let (len, terminator) = end(&self.source.as_bytes()[start..]);
+        self.offset = start + len + terminator;
+        self.push(kind, Span { start, end: start + len });
+    }
+
+    /// Reads the next name, and whatever text or names it parses, into
+    /// `tokens`. Returns false at the end of the source.
+    fn read(&mut self) -> bool {
+        let span = match self.next_name() {
+            Some(span) => span,
+            None => return false,
+        };
+        let name = self.canonical(span);
+        // `;` isn't in the dictionary; colon definitions look for it.
+        let kind = match &*name {
+            ";" if self.definition.is_some() => TokenKind::Word,
+            _ => self.classify(&name),
+        };
+        if kind != TokenKind::Word {
+            self.push(kind, span);
+            return true;
+        }
+        let defining = match &*name {
+            ":" => self.definition.is_none(),
+            "CREATE" | "VARIABLE" | "CONSTANT" | "2VARIABLE" | "2CONSTANT" | "ALIAS" | "SYNONYM"
+            | "BEGIN-STRUCTURE" | "+FIELD" | "FIELD:" | "CFIELD:" | "MODULE" => true,
+            _ => false,
+        };
+        self.push(if defining { TokenKind::Defining } else { kind }, span);
+        match &*name {
+            "(" => self.parsed_text(TokenKind::Comment, |rest| {
+                let mut depth = 1;
+                for (i, &c) in rest.iter().enumerate() {
+                    match c {
+                        b'(' => depth += 1,
+                        b')' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        return (i, 1);
+                    }
+                }
+                (rest.len(), 0)
+            }),
+            "\\" => self.parsed_text(TokenKind::Comment, |rest| {
+                rest.iter().position(|&c| c == b'\n').map_or((rest.len(), 0), |i| (i, 1))
+            }),
+            "S\"" | "$\"" | "\"" => self.parsed_text(TokenKind::String, |rest| {
+                rest.iter().position(|&c| c == b'"').map_or((rest.len(), 0), |i| (i, 1))
+            }),
+            "S\\\"" => self.parsed_text(TokenKind::String, |rest| {
+                let mut i = 0;
+                while i < rest.len() {
+                    match rest[i] {
+                        b'"' => return (i, 1),
+                        b'\\' => i += 2,
+                        _ => i += 1,
+                    }
+                }
+                (rest.len(), 0)
+            }),
+            ":" if self.definition.is_none() => {
+                self.definition = self.parsed_name(Some(TokenKind::Definition));
+            }
+            ";" => {
+                if let Some(name) = self.definition.take() {
+                    self.defined.push(name);
+                }
+            }
+            "CREATE" | "VARIABLE" | "CONSTANT" | "2VARIABLE" | "2CONSTANT" | "ALIAS" | "BEGIN-STRUCTURE" | "+FIELD"
+            | "FIELD:" | "CFIELD:" | "MODULE" => {
+                if let Some(name) = self.parsed_name(Some(TokenKind::Definition)) {
+                    self.defined.push(name);
+                }
+            }
+            "SYNONYM" => {
+                if let Some(name) = self.parsed_name(Some(TokenKind::Definition)) {
+                    self.parsed_name(None);
+                    self.defined.push(name);
+                }
+            }
+            "'" => {
+                self.parsed_name(None);
+            }
+            "INCLUDE" | "REQUIRE" => {
+                self.parsed_name(Some(TokenKind::String));
+            }
+            "[DEFINED]" | "[UNDEFINED]" | "CHAR" | "[CHAR]" | "HELP" => {
+                self.parsed_name(Some(TokenKind::Name));
+            }
+            _ => {}
+        }
+        true
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = SourceToken;
+
+    fn next(&mut self) -> Option<SourceToken> {
+        while self.tokens.is_empty() {
+            if !self.read() {
+                return None;
+            }
+        }
+        self.tokens.pop_front()
+    }
+}
+
+/// Tokens being evaluated by `Forth::eval_tokens`, which parsing words
+/// read in place of the input buffer.
+#[derive(Clone)]
+pub(super) struct Lexed {
+    tokens: Vec<SourceToken>,
+    next: usize,
+    /// Index of the last name read.
+    name: Option<usize>,
+}
+
+impl Lexed {
+    /// The next name, passing over any text the previous word didn't take.
+    pub(super) fn next_name(&mut self) -> Option<&SourceToken> {
+        while let Some(token) = self.tokens.get(self.next) {
+            self.next += 1;
+            if token.kind != TokenKind::Comment && token.kind != TokenKind::String {
+                self.name = Some(self.next - 1);
+                return Some(token);
+            }
+        }
+        None
+    }
+
+    /// The names not read yet, in order.
+    pub(super) fn remaining_names(&self) -> impl Iterator<Item = &str> {
+        self.tokens[self.next..].iter()
+            .filter(|token| token.kind != TokenKind::Comment && token.kind != TokenKind::String)
+            .map(|token| token.text.as_str())
+    }
+
+    /// The text of the next comment or string, or nothing if a name comes
+    /// first.
+    pub(super) fn next_text(&mut self) -> &str {
+        match self.tokens.get(self.next) {
+            Some(token) if token.kind == TokenKind::Comment || token.kind == TokenKind::String => {
+                self.next += 1;
+                &token.text
+            }
+            _ => "",
+        }
+    }
+
+    /// Rebuilds the source text from `start` to `end` out of the tokens in
+    /// it. Delimiters become spaces, except for the terminators comments
+    /// and strings need.
+    pub(super) fn source(&self, start: usize, end: usize) -> String {
+        let mut text = String::new();
+        let mut offset = start;
+        let mut terminator = None;
+        let mut previous = "";
+        for token in self.tokens.iter().filter(|token| token.span.start >= start && token.span.end <= end) {
+            if let Some(c) = terminator.take() {
+                if token.span.start > offset {
+                    text.push(c);
+                    offset += 1;
+                }
+            }
+            for _ in offset..token.span.start {
+                text.push(' ');
+            }
+            text.push_str(&token.text);
+            offset = token.span.end;
+            terminator = match token.kind {
+                TokenKind::Comment if previous == "\\" => Some('\n'),
+                TokenKind::Comment => Some(')'),
+                TokenKind::String => Some('"'),
+                _ => None,
+            };
+            previous = &token.text;
+        }
+        text
+    }
+
+    /// Where the last name read is, had the tokens been evaluated as text.
+    pub(super) fn location(&self, token_index: usize) -> Option<Location> {
+        let name = self.name?;
+        // Delimiters are all ASCII, so each byte between tokens is one
+        // character.
+        let mut char_offset = 0;
+        let mut end = 0;
+        for token in &self.tokens[..name] {
+            char_offset += token.span.start.saturating_sub(end) + token.text.chars().count();
+            end = token.span.end;
+        }
+        let token = &self.tokens[name];
+        Some(Location {
+            word: token.text.clone(),
+            offset: token.span.start,
+            char_offset: char_offset + token.span.start.saturating_sub(end),
+            token_index,
+        })
+    }
+}
+
+impl Forth {
+    /// Splits `source` into tokens the way the interpreter would read it,
+    /// without running it: names are classified against the dictionary and
+    /// the words `source` defines, and the text taken by parsing words such
+    /// as `(` and `S"` becomes a single token. Run-time decisions, such as
+    /// `[IF]`, are not followed.
+    ///
+    /// ```
+    /// use forth_core::{Error, Forth, Span, TokenKind};
+    ///
+    /// let mut f = Forth::new();
+    /// // Too large for a cell, whether of 32 or 64 bits.
+    /// let huge = "99999999999999999999999";
+    /// let kinds: Vec<_> = f.tokenize(&format!("1 {} FOO", huge)).iter().map(|t| t.kind).collect();
+    /// assert_eq!(kinds, [TokenKind::Number, TokenKind::OutOfRange, TokenKind::Unknown]);
+    /// let diagnostics = f.check(&format!("1 {}", huge));
+    /// assert_eq!(diagnostics[0].error, Error::NumberOutOfRange(huge.into()));
+    /// assert_eq!(diagnostics[0].span, Span { start: 2, end: 25 });
+    /// ```
+    pub fn tokenize(&self, source: &str) -> Vec<SourceToken> {
+        self.tokens(source).collect()
+    }
+
+    /// The tokens `tokenize` would give, read from `source` as they are
+    /// asked for rather than all at once, so that the first are ready
+    /// before a long source has been read, and none need be kept after.
+    ///
+    /// ```
+    /// use forth_core::{Forth, TokenKind};
+    ///
+    /// let f = Forth::new();
+    /// let source = ": SQ DUP * ; ".repeat(10_000);
+    /// let mut tokens = f.tokens(&source);
+    /// assert_eq!(tokens.next().unwrap().kind, TokenKind::Defining);
+    /// assert_eq!(tokens.next().unwrap().text, "SQ");
+    /// assert_eq!(tokens.filter(|t| t.kind == TokenKind::Unknown).count(), 0);
+    /// ```
+    pub fn tokens<'a>(&'a self, source: &'a str) -> Tokens<'a> {
+        Tokens {
+            forth: self,
+            source,
+            offset: 0,
+            defined: Vec::new(),
+            definition: None,
+            tokens: VecDeque::new(),
+        }
+    }
+
+    /// Evaluates tokens from `tokenize`, as `eval` would the source they
+    /// came from, but without scanning it again. Names are still looked up
+    /// as they are reached, so unlike a `Program` the tokens may define
+    /// words and use them. Parsing words take the comment or string token
+    /// after them, if any; `PARSE` and `WORD` find no other text.
+    pub fn eval_tokens(&mut self, tokens: &[SourceToken]) -> ForthResult {
+        let len = tokens.last().map_or(0, |token| token.span.end);
+        if self.max_input_len.is_some_and(|max| len > max) {
+            return self.with_input("", |_| Err(Error::InputTooLarge));
+        }
+        self.discard_paused();
+        self.lexed = Some(Lexed { tokens: tokens.to_vec(), next: 0, name: None });
+        let result = self.with_input("", Forth::interpret);
+        if !self.paused {
+            self.lexed = None;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(source: &str) -> Vec<(TokenKind, &str)> {
+        Forth::new().tokenize(source).into_iter().map(|t| (t.kind, &source[t.span.start..t.span.end])).collect()
+    }
+
+    #[test]
+    fn unterminated_text_runs_to_the_end_of_the_source() {
+        assert_eq!(tokens(r#"S" no end"#), [(TokenKind::Word, "S\""), (TokenKind::String, "no end")]);
+        assert_eq!(tokens(r#"S\" tab\t\"#), [(TokenKind::Word, "S\\\""), (TokenKind::String, "tab\\t\\")]);
+        assert_eq!(tokens("( outer ( inner ) 2"), [(TokenKind::Word, "("), (TokenKind::Comment, "outer ( inner ) 2")]);
+        assert_eq!(tokens("\\ to the end"), [(TokenKind::Word, "\\"), (TokenKind::Comment, "to the end")]);
+    }
+
+    #[test]
+    fn spans_count_bytes_of_multi_byte_characters() {
+        let source = "( é ) : 🚀 S\" naïve\" ; 🚀 ½";
+        let tokens = Forth::new().tokenize(source);
+        for token in &tokens {
+            assert_eq!(&source[token.span.start..token.span.end], token.text);
+        }
+        let kinds: Vec<_> = tokens.iter().map(|t| (t.kind, &*t.text)).collect();
+        assert_eq!(kinds, [
+            (TokenKind::Word, "("),
+            (TokenKind::Comment, "é "),
+            (TokenKind::Defining, ":"),
+            (TokenKind::Definition, "🚀"),
+            (TokenKind::Word, "S\""),
+            (TokenKind::String, "naïve"),
+            (TokenKind::Word, ";"),
+            (TokenKind::Word, "🚀"),
+            (TokenKind::Unknown, "½"),
+        ]);
+        assert_eq!(tokens[8].span, Span { start: source.len() - "½".len(), end: source.len() });
+    }
+
+    #[test]
+    fn parsing_words_at_the_end_of_the_source_take_nothing() {
+        for source in &["CHAR", "'", ":", "VARIABLE", "SYNONYM", "S\"", "(", "\\"] {
+            let tokens = Forth::new().tokenize(source);
+            let empty = Span { start: source.len(), end: source.len() };
+            match tokens.len() {
+                1 => {}
+                // Parsed text, empty, at the very end.
+                2 => assert_eq!(tokens[1].span, empty, "{}", source),
+                _ => panic!("{}: {:?}", source, tokens),
+            }
+        }
+        assert_eq!(tokens("SYNONYM NEW"), [(TokenKind::Defining, "SYNONYM"), (TokenKind::Definition, "NEW")]);
+        assert_eq!(tokens("S\" "), [(TokenKind::Word, "S\""), (TokenKind::String, "")]);
+    }
+}
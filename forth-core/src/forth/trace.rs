@@ -0,0 +1,107 @@
+//! Recording the stack after each token of an evaluation.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{Forth, ForthResult, Span, Value};
+
+/// Limits on what `Forth::trace` records.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TraceOptions {
+    /// Steps recorded before the rest of the evaluation runs untraced.
+    pub max_steps: usize,
+    /// Cells from the top of the stack kept at each step.
+    pub stack_depth: usize,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        TraceOptions { max_steps: 1000, stack_depth: 16 }
+    }
+}
+
+/// A token interpreted by `Forth::trace`, and the stack it left.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TraceStep {
+    /// The name or number as it appears in the input.
+    pub token: String,
+    pub span: Span,
+    /// The top of the stack, at most `stack_depth` cells of it, bottom
+    /// first.
+    pub stack: Vec<Value>,
+    /// How many cells were on the stack in all.
+    pub depth: usize,
+}
+
+/// The steps of an evaluation, as returned by `Forth::trace`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trace {
+    pub steps: Vec<TraceStep>,
+    /// Whether steps went unrecorded for lack of `max_steps`.
+    pub truncated: bool,
+    /// What `eval` would have returned.
+    pub result: ForthResult,
+}
+
+pub(super) struct Tracer {
+    options: TraceOptions,
+    steps: Vec<TraceStep>,
+    truncated: bool,
+}
+
+impl Forth {
+    /// Evaluates `input` as `eval` does, recording the stack after each
+    /// name or number of `input` that runs without error. Tokens read by
+    /// parsing words, such as the body of a colon definition, belong to
+    /// the step of the word that read them rather than having their own.
+    ///
+    /// ```
+    /// use forth_core::{Forth, TraceOptions};
+    ///
+    /// let mut f = Forth::new();
+    /// let trace = f.trace("2 3 + DUP *", &TraceOptions::default());
+    /// let steps: Vec<_> = trace.steps.iter()
+    ///     .map(|step| (step.token.as_str(), step.stack.clone()))
+    ///     .collect();
+    /// assert_eq!(steps, vec![
+    ///     ("2", vec![2]),
+    ///     ("3", vec![2, 3]),
+    ///     ("+", vec![5]),
+    ///     ("DUP", vec![5, 5]),
+    ///     ("*", vec![25]),
+    /// ]);
+    /// assert_eq!(trace.result, Ok(()));
+    /// ```
+    pub fn trace(&mut self, input: &str, options: &TraceOptions) -> Trace {
+        self.tracer = Some(Tracer { options: *options, steps: Vec::new(), truncated: false });
+        let result = self.eval(input);
+        let tracer = self.tracer.take().unwrap();
+        Trace { steps: tracer.steps, truncated: tracer.truncated, result }
+    }
+
+    /// Records the stack after the token at `span`, just interpreted, if
+    /// tracing and the token came from the outermost input rather than
+    /// EVALUATE.
+    pub(super) fn trace_step(&mut self, span: Span) {
+        if self.executing > 0 || self.source.0 != self.tib {
+            return;
+        }
+        let tracer = match self.tracer {
+            Some(ref mut tracer) => tracer,
+            None => return,
+        };
+        if tracer.steps.len() >= tracer.options.max_steps {
+            tracer.truncated = true;
+            return;
+        }
+        let bytes = &self.memory[self.tib + span.start..self.tib + span.end];
+        let depth = self.stack.len();
+        tracer.steps.push(TraceStep {
+            token: String::from_utf8_lossy(bytes).into_owned(),
+            span,
+            stack: self.stack[depth.saturating_sub(tracer.options.stack_depth)..].to_vec(),
+            depth,
+        });
+    }
+}
@@ -0,0 +1,387 @@
+//! Typed cells, which the builder's `typed` turns on with the `typed`
+//! feature: each item on the data stack is an integer, a float or a
+//! string, and arithmetic and printing go by which, so that beginners can
+//! work with numbers and text before they learn to keep track of cells
+//! themselves.
+//!
+//! Items keep their cell values, floats as their bits and strings as
+//! handles, while a stack of tags alongside says which each is. The tags
+//! are brought up to date after every word: the stack words carry them
+//! along, the words here set them, and any other word leaves integers
+//! where it changed the stack, and fails with `Error::TypeMismatch` if it
+//! takes anything but integers.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use super::effects::builtin_effect;
+use super::{do_arithmetic, do_dot, Error, Forth, ForthResult, Op, Unsigned, Value, WordExecutor};
+
+/// A float as typed cells hold it, as wide as a cell.
+#[cfg(not(feature = "cell64"))]
+pub type Float = f32;
+#[cfg(feature = "cell64")]
+pub type Float = f64;
+
+/// What an item on the data stack is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tag {
+    Int,
+    Float,
+    Str,
+}
+
+/// An item on the data stack, as `Forth::typed_stack` gives it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TypedValue {
+    Int(Value),
+    Float(Float),
+    Str(String),
+}
+
+impl fmt::Display for TypedValue {
+    /// As `.` prints it, without the trailing space. Floats always show a
+    /// decimal point or an exponent, so they can't be taken for integers.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TypedValue::Int(n) => write!(f, "{}", n),
+            TypedValue::Float(r) => write!(f, "{:?}", r),
+            TypedValue::Str(ref s) => f.write_str(s),
+        }
+    }
+}
+
+/// The tags of the data stack, bottom first, and the strings their
+/// handles index.
+#[derive(Clone, Default)]
+pub(super) struct Types {
+    tags: Vec<Tag>,
+    strings: Vec<String>,
+}
+
+impl Types {
+    /// A handle for `text`, shared with any equal string made before.
+    fn intern(&mut self, text: String) -> Value {
+        match self.strings.iter().position(|s| *s == text) {
+            Some(handle) => handle as Value,
+            None => {
+                self.strings.push(text);
+                (self.strings.len() - 1) as Value
+            }
+        }
+    }
+}
+
+/// For the words that only move items about, how many they take and which
+/// of those they leave, deepest first.
+fn shuffle(name: &str) -> Option<(usize, &'static [usize])> {
+    Some(match name {
+        "DUP" => (1, &[0, 0]),
+        "DROP" => (1, &[]),
+        "SWAP" => (2, &[1, 0]),
+        "OVER" => (2, &[0, 1, 0]),
+        _ => return None,
+    })
+}
+
+/// The word a typed interpreter runs in place of the primitive `name`.
+fn typed_word(name: &str) -> Option<&'static WordExecutor> {
+    Some(match name {
+        "+" | "-" | "*" | "/" => &do_typed_arithmetic,
+        "." => &do_typed_dot,
+        _ => return None,
+    })
+}
+
+/// Words that set the tags of what they leave themselves.
+fn sets_tags(name: &str) -> bool {
+    matches!(name, "\"" | ">STRING" | "S>F" | "F>S" | "BITS>F")
+}
+
+/// Reads `text` as a float literal, such as `1.5`, `-.5` or `2e10`: digits
+/// with a decimal point or an exponent.
+pub(super) fn parse_float(text: &str) -> Option<Float> {
+    let numeric = text.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c));
+    let float = text.contains(['.', 'e', 'E']);
+    if !numeric || !float || !text.contains(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+fn to_float(v: Value) -> Float {
+    Float::from_bits(v as Unsigned)
+}
+
+fn from_float(r: Float) -> Value {
+    r.to_bits() as Value
+}
+
+/// The typed versions of `+`, `-`, `*` and `/`: integers as usual, floats
+/// if either is one, and for `+` two strings joined.
+fn do_typed_arithmetic(forth: &mut Forth, index: usize) -> ForthResult {
+    let (x, y) = (forth.peek_typed(1)?, forth.peek_typed(0)?);
+    let op = forth.word_name(index).as_bytes()[0];
+    let result = match (x, y) {
+        ((Tag::Int, _), (Tag::Int, _)) => {
+            let result = do_arithmetic(forth, index);
+            forth.sync_tags();
+            return result;
+        }
+        ((Tag::Str, a), (Tag::Str, b)) if op == b'+' => {
+            let types = forth.typed.as_mut().unwrap();
+            let joined = types.strings[a as usize].clone() + &types.strings[b as usize];
+            (Tag::Str, types.intern(joined))
+        }
+        ((Tag::Str, _), _) | (_, (Tag::Str, _)) => return Err(forth.type_mismatch(index)),
+        ((xt, a), (yt, b)) => {
+            let (a, b) = (as_float(xt, a), as_float(yt, b));
+            let r = match op {
+                b'+' => a + b,
+                b'-' => a - b,
+                b'*' => a * b,
+                _ if b == 0.0 => return Err(Error::DivisionByZero),
+                _ => a / b,
+            };
+            (Tag::Float, from_float(r))
+        }
+    };
+    forth.pop_typed()?;
+    forth.pop_typed()?;
+    forth.push_typed(result.0, result.1);
+    Ok(())
+}
+
+fn as_float(tag: Tag, v: Value) -> Float {
+    match tag {
+        Tag::Float => to_float(v),
+        _ => v as Float,
+    }
+}
+
+/// The typed `.` prints floats and strings as well as integers.
+fn do_typed_dot(forth: &mut Forth, index: usize) -> ForthResult {
+    match forth.peek_typed(0)?.0 {
+        Tag::Int => {
+            let result = do_dot(forth, index);
+            forth.sync_tags();
+            result
+        }
+        _ => {
+            let value = forth.typed_value(forth.stack.len() - 1);
+            forth.pop_typed()?;
+            forth.emit(&format!("{} ", value))
+        }
+    }
+}
+
+/// `"` ( "ccc<quote>" -- s ) pushes the text up to the next `"` as a
+/// string, when the definition runs if compiling.
+pub(super) fn do_string_literal(forth: &mut Forth, _index: usize) -> ForthResult {
+    let (start, len) = forth.parse(b'"');
+    let bytes = forth.memory[start..start + len].to_vec();
+    if forth.definition.is_none() {
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+        let handle = forth.typed.as_mut().ok_or(Error::InvalidWord)?.intern(text);
+        forth.push_typed(Tag::Str, handle);
+        return Ok(());
+    }
+    let to_string = forth.primitive_index(">STRING").ok_or(Error::InvalidWord)?;
+    let (addr, len) = forth.store_bytes(&bytes)?;
+    forth.literal(addr);
+    forth.literal(len);
+    let span = forth.last_span;
+    if let Some(ref mut body) = forth.definition {
+        body.push(Op::Call(to_string), span);
+    }
+    Ok(())
+}
+
+/// `>STRING` ( c-addr u -- s ) makes a string of the text at `c-addr`.
+pub(super) fn do_to_string_value(forth: &mut Forth, index: usize) -> ForthResult {
+    let len = forth.pop_int(index)?;
+    let addr = forth.pop_int(index)?;
+    forth.check_range(addr, len)?;
+    let bytes = &forth.memory[addr as usize..(addr + len) as usize];
+    let text = String::from_utf8_lossy(bytes).into_owned();
+    let handle = forth.typed.as_mut().ok_or(Error::InvalidWord)?.intern(text);
+    forth.push_typed(Tag::Str, handle);
+    Ok(())
+}
+
+/// `S>F` ( n -- r ) makes a float of a number.
+pub(super) fn do_s_to_f(forth: &mut Forth, index: usize) -> ForthResult {
+    let (tag, v) = forth.pop_number(index)?;
+    forth.push_typed(Tag::Float, from_float(as_float(tag, v)));
+    Ok(())
+}
+
+/// `F>S` ( r -- n ) makes an integer of a number, dropping any fraction.
+pub(super) fn do_f_to_s(forth: &mut Forth, index: usize) -> ForthResult {
+    let n = match forth.pop_number(index)? {
+        (Tag::Float, v) => to_float(v) as Value,
+        (_, v) => v,
+    };
+    forth.push_typed(Tag::Int, n);
+    Ok(())
+}
+
+/// `BITS>F` ( x -- r ) takes the bits of `x` as a float, as compiled
+/// float literals do.
+pub(super) fn do_bits_to_f(forth: &mut Forth, index: usize) -> ForthResult {
+    let bits = forth.pop_int(index)?;
+    forth.push_typed(Tag::Float, bits);
+    Ok(())
+}
+
+impl Forth {
+    /// The data stack, bottom first, with each item as what it is. Without
+    /// typed cells, every item is an integer.
+    ///
+    /// ```
+    /// use forth_core::{Forth, TypedValue};
+    ///
+    /// let mut f = Forth::builder().typed(true).build();
+    /// f.eval("1 2.5 + \" Hello, \" \" world\" + 7 2 /").unwrap();
+    /// assert_eq!(f.typed_stack(), vec![
+    ///     TypedValue::Float(3.5),
+    ///     TypedValue::Str(String::from("Hello, world")),
+    ///     TypedValue::Int(3),
+    /// ]);
+    /// f.eval(": HALF 2.0 / ; 3 HALF . 1.0 S>F F>S . \" done\" .").unwrap();
+    /// assert_eq!(f.output(), "1.5 1 done ");
+    /// assert_eq!(f.eval("\" one\" 1 +").unwrap_err().to_string(), "type mismatch at +");
+    /// assert_eq!(f.eval("2.5 EMIT").unwrap_err().to_string(), "type mismatch at EMIT");
+    /// ```
+    pub fn typed_stack(&self) -> Vec<TypedValue> {
+        (0..self.stack.len()).map(|i| self.typed_value(i)).collect()
+    }
+
+    fn typed_value(&self, i: usize) -> TypedValue {
+        let v = self.stack[i];
+        let types = match self.typed {
+            Some(ref types) => types,
+            None => return TypedValue::Int(v),
+        };
+        match types.tags.get(i).cloned().unwrap_or(Tag::Int) {
+            Tag::Int => TypedValue::Int(v),
+            Tag::Float => TypedValue::Float(to_float(v)),
+            Tag::Str => TypedValue::Str(types.strings.get(v as usize).cloned().unwrap_or_default()),
+        }
+    }
+
+    /// Makes the tags as many as the items on the stack, any new items
+    /// being integers.
+    pub(super) fn sync_tags(&mut self) {
+        if let Some(ref mut types) = self.typed {
+            types.tags.resize(self.stack.len(), Tag::Int);
+        }
+    }
+
+    /// Runs the word at `index` as `call` would, but for the typed version
+    /// of the primitives that have one, and updates the tags after it.
+    pub(super) fn call_typed(&mut self, index: usize, exec: &WordExecutor) -> ForthResult {
+        self.sync_tags();
+        let name = self.primitive(Op::Call(index)).unwrap_or("");
+        let (typed, shuffle, effect) = (typed_word(name), shuffle(name), builtin_effect(name));
+        if let Some(typed) = typed {
+            return self.call_observed(index, typed);
+        }
+        if sets_tags(name) {
+            return self.call_observed(index, exec);
+        }
+        if let (None, Some(effect)) = (shuffle, effect) {
+            let tags = &self.typed.as_ref().unwrap().tags;
+            let taken = &tags[tags.len().saturating_sub(effect.inputs)..];
+            if taken.iter().any(|&tag| tag != Tag::Int) {
+                return Err(self.type_mismatch(index));
+            }
+        }
+        let before = self.stack.clone();
+        let result = self.call_observed(index, exec);
+        let after = &self.stack;
+        let types = self.typed.as_mut().unwrap();
+        let moved = shuffle.filter(|&(taken, left)| {
+            result.is_ok() && taken <= before.len() && after.len() == before.len() - taken + left.len()
+        });
+        types.tags = match moved {
+            Some((taken, left)) => {
+                let base = before.len() - taken;
+                let mut tags = types.tags[..base].to_vec();
+                tags.extend(left.iter().map(|&i| types.tags[base + i]));
+                tags
+            }
+            // What any other word leaves in place keeps its tag.
+            None => (0..after.len())
+                .map(|i| match before.get(i) {
+                    Some(&v) if v == after[i] => types.tags[i],
+                    _ => Tag::Int,
+                })
+                .collect(),
+        };
+        result
+    }
+
+    /// Pushes a float literal, or compiles it if compiling.
+    pub(super) fn float_literal(&mut self, r: Float) -> ForthResult {
+        let bits_to_f = self.primitive_index("BITS>F").ok_or(Error::InvalidWord)?;
+        match self.definition {
+            Some(ref mut body) => {
+                body.push(Op::Literal(from_float(r)), self.last_span);
+                body.push(Op::Call(bits_to_f), self.last_span);
+                Ok(())
+            }
+            None => {
+                self.interpret_op(Op::Literal(from_float(r)))?;
+                self.interpret_op(Op::Call(bits_to_f))
+            }
+        }
+    }
+
+    /// The tag and value of the item `depth` below the top.
+    fn peek_typed(&mut self, depth: usize) -> Result<(Tag, Value), Error> {
+        self.sync_tags();
+        let i = self.stack.len().checked_sub(depth + 1).ok_or(Error::StackUnderflow)?;
+        Ok((self.typed.as_ref().map_or(Tag::Int, |types| types.tags[i]), self.stack[i]))
+    }
+
+    fn pop_typed(&mut self) -> Result<(Tag, Value), Error> {
+        let top = self.peek_typed(0)?;
+        self.stack.pop();
+        if let Some(ref mut types) = self.typed {
+            types.tags.pop();
+        }
+        Ok(top)
+    }
+
+    fn push_typed(&mut self, tag: Tag, v: Value) {
+        self.sync_tags();
+        self.stack.push(v);
+        if let Some(ref mut types) = self.typed {
+            types.tags.push(tag);
+        }
+    }
+
+    /// Pops an integer for the word at `index`.
+    fn pop_int(&mut self, index: usize) -> Result<Value, Error> {
+        match self.pop_typed()? {
+            (Tag::Int, v) => Ok(v),
+            _ => Err(self.type_mismatch(index)),
+        }
+    }
+
+    /// Pops an integer or a float for the word at `index`.
+    fn pop_number(&mut self, index: usize) -> Result<(Tag, Value), Error> {
+        match self.pop_typed()? {
+            (Tag::Str, _) => Err(self.type_mismatch(index)),
+            number => Ok(number),
+        }
+    }
+
+    /// The error of the word at `index` being given the wrong type.
+    fn type_mismatch(&self, index: usize) -> Error {
+        Error::TypeMismatch(String::from(self.word_name(index)))
+    }
+}
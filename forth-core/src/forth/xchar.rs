@@ -0,0 +1,110 @@
+//! CHAR and [CHAR], and the extended character words. Characters on the
+//! stack are Unicode scalar values, while memory holds text as UTF-8, one
+//! byte to an address, so a character may take up to four.
+
+use core::str;
+
+use super::{Error, Forth, ForthResult, Value};
+
+/// Largest Unicode scalar value.
+const MAX_CHAR: Value = 0x10_FFFF;
+
+/// `c` as a character, or U+FFFD if it isn't a Unicode scalar value.
+pub(super) fn to_char(c: Value) -> char {
+    match (0..=MAX_CHAR).contains(&c) {
+        true => core::char::from_u32(c as u32).unwrap_or(char::REPLACEMENT_CHARACTER),
+        false => char::REPLACEMENT_CHARACTER,
+    }
+}
+
+/// The character `bytes` starts with and the number of bytes it takes, or
+/// U+FFFD and one byte if they don't start with a whole UTF-8 sequence.
+fn decode(bytes: &[u8]) -> (char, usize) {
+    let len = match bytes.first() {
+        Some(&b) if b < 0x80 => 1,
+        Some(&b) if b >= 0xf0 => 4,
+        Some(&b) if b >= 0xe0 => 3,
+        Some(&b) if b >= 0xc0 => 2,
+        _ => return (char::REPLACEMENT_CHARACTER, 1),
+    };
+    let c = bytes.get(..len)
+        .and_then(|bytes| str::from_utf8(bytes).ok())
+        .and_then(|text| text.chars().next());
+    match c {
+        Some(c) => (c, len),
+        None => (char::REPLACEMENT_CHARACTER, 1),
+    }
+}
+
+/// The first character of the next name in the input.
+fn parse_char(forth: &mut Forth) -> Result<Value, Error> {
+    let name = forth.parse_name().ok_or(Error::InvalidWord)?;
+    let c = name.chars().next().ok_or(Error::InvalidWord)?;
+    Ok(c as Value)
+}
+
+pub(super) fn do_char(forth: &mut Forth, _index: usize) -> ForthResult {
+    let c = parse_char(forth)?;
+    forth.stack.push(c);
+    Ok(())
+}
+
+pub(super) fn do_bracket_char(forth: &mut Forth, _index: usize) -> ForthResult {
+    let c = parse_char(forth)?;
+    forth.literal(c);
+    Ok(())
+}
+
+pub(super) fn do_xc_size(forth: &mut Forth, _index: usize) -> ForthResult {
+    let c = forth.pop()?;
+    forth.stack.push(to_char(c).len_utf8() as Value);
+    Ok(())
+}
+
+pub(super) fn do_x_size(forth: &mut Forth, _index: usize) -> ForthResult {
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    let range = forth.memory_range(addr, len)?;
+    let size = match range.is_empty() {
+        true => 0,
+        false => decode(&forth.memory[range]).1.min(len as usize),
+    };
+    forth.stack.push(size as Value);
+    Ok(())
+}
+
+pub(super) fn do_xc_fetch_plus(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    let start = forth.memory_range(addr, 1)?.start;
+    let (c, len) = decode(&forth.memory[start..]);
+    forth.stack.push(addr + len as Value);
+    forth.stack.push(c as Value);
+    Ok(())
+}
+
+pub(super) fn do_xc_store_plus_question(forth: &mut Forth, _index: usize) -> ForthResult {
+    let c = to_char(forth.pop()?);
+    let len = forth.pop()?;
+    let addr = forth.pop()?;
+    let range = forth.memory_range(addr, len)?;
+    let mut buf = [0; 4];
+    let bytes = c.encode_utf8(&mut buf).as_bytes();
+    let fits = bytes.len() <= range.len();
+    let size = match fits {
+        true => bytes.len(),
+        false => 0,
+    };
+    forth.memory[range.start..range.start + size].copy_from_slice(&bytes[..size]);
+    forth.stack.push(addr + size as Value);
+    forth.stack.push(len - size as Value);
+    forth.stack.push(if fits { -1 } else { 0 });
+    Ok(())
+}
+
+pub(super) fn do_xchar_plus(forth: &mut Forth, _index: usize) -> ForthResult {
+    let addr = forth.pop()?;
+    let start = forth.memory_range(addr, 1)?.start;
+    let len = decode(&forth.memory[start..]).1;
+    forth.stack.push(addr + len as Value);
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use alloc::collections::BTreeMap;
+
+use forth::CELL;
+
+/// Cell-aligned first-fit allocator handing out addresses from a fixed
+/// region of the interpreter's memory. Only the bookkeeping lives here; the
+/// bytes themselves stay in the interpreter's memory.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Heap {
+    start: usize,
+    end: usize,
+    /// Live blocks, keyed by address, with their sizes.
+    blocks: BTreeMap<usize, usize>,
+}
+
+fn align(size: usize) -> usize {
+    (size.max(1) + CELL - 1) & !(CELL - 1)
+}
+
+impl Heap {
+    pub fn new(start: usize, size: usize) -> Self {
+        Self {
+            start,
+            end: start + size,
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    /// Recreates a heap with the given live blocks, as listed by `blocks`.
+    /// Returns `None` if they overlap or fall outside the region.
+    pub fn with_blocks(start: usize, size: usize, blocks: &[(usize, usize)]) -> Option<Self> {
+        let mut heap = Self::new(start, size);
+        let mut free_from = start;
+        for &(addr, len) in blocks {
+            if addr < free_from || addr % CELL != 0 || len == 0 || len > heap.end - addr {
+                return None;
+            }
+            heap.blocks.insert(addr, len);
+            free_from = addr + align(len);
+        }
+        Some(heap)
+    }
+
+    /// Live blocks in address order, as `(address, size)` pairs.
+    pub fn blocks(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.blocks.iter().map(|(&addr, &len)| (addr, len))
+    }
+
+    /// Size of the live block at `addr`, if any.
+    pub fn size_of(&self, addr: usize) -> Option<usize> {
+        self.blocks.get(&addr).cloned()
+    }
+
+    pub fn allocate(&mut self, size: usize) -> Option<usize> {
+        let size = align(size);
+        let mut gap_start = self.start;
+        for (&addr, &len) in self.blocks.iter() {
+            if addr - gap_start >= size {
+                break;
+            }
+            gap_start = addr + align(len);
+        }
+        if self.end - gap_start < size {
+            return None;
+        }
+        self.blocks.insert(gap_start, size);
+        Some(gap_start)
+    }
+
+    pub fn free(&mut self, addr: usize) -> bool {
+        self.blocks.remove(&addr).is_some()
+    }
+
+    /// Grows or shrinks the block at `addr` in place when the following gap
+    /// allows it, otherwise moves it. Returns the new address; the caller is
+    /// responsible for copying the contents when it differs from `addr`.
+    pub fn resize(&mut self, addr: usize, size: usize) -> Option<usize> {
+        self.size_of(addr)?;
+        let size = align(size);
+        let limit = self.blocks.range(addr + 1..).next().map_or(self.end, |(&next, _)| next);
+        if limit - addr >= size {
+            self.blocks.insert(addr, size);
+            return Some(addr);
+        }
+        let old = self.blocks.remove(&addr).unwrap();
+        match self.allocate(size) {
+            Some(new_addr) => Some(new_addr),
+            None => {
+                self.blocks.insert(addr, old);
+                None
+            }
+        }
+    }
+}
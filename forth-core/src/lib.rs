@@ -0,0 +1,174 @@
+//! A small Forth interpreter for embedding in Rust programs.
+//!
+//! The interpreter is a single `Forth` value holding the data stack,
+//! dictionary and data space. Source text is fed to it with `Forth::eval`;
+//! the resulting stack and any printed text can then be inspected.
+//!
+//! ```
+//! use forth_core::Forth;
+//!
+//! let mut f = Forth::new();
+//! f.eval(": square dup * ; 3 square 4 square").unwrap();
+//! assert_eq!(f.stack_slice(), &[9, 16]);
+//! ```
+//!
+//! Hosts extend the language with `Forth::define_native`, receive output as
+//! it is produced through `Forth::set_output_sink`, or through an `Io` given
+//! to `Forth::set_io` that also supplies input, and queue keyboard input
+//! with `Forth::push_input`. Interpreter limits are configured with
+//! `Forth::builder`.
+//!
+//! Characters on the stack are Unicode scalar values: KEY and CHAR give
+//! them, and EMIT and HOLD take them. Memory holds text as UTF-8, a byte
+//! to an address, so `C@` reads bytes and the lengths `S"` and COUNT give
+//! are in bytes; XC@+, XCHAR+ and XC-SIZE step through text a character
+//! at a time. Names may contain any characters but spaces and controls.
+//!
+//! ```
+//! use forth_core::Forth;
+//!
+//! let mut f = Forth::new();
+//! f.eval(": 🚀 [CHAR] é EMIT CHAR EMIT ; 🚀 😀").unwrap();
+//! assert_eq!(f.output(), "é😀");
+//! f.eval(r#"S" café" SWAP DROP  S" 👍ok" DROP XC@+  CHAR ü XC-SIZE"#).unwrap();
+//! assert_eq!(f.stack()[0], 5);
+//! assert_eq!(f.stack()[2..], [0x1F44D, 2]);
+//! ```
+//!
+//! Records are laid out with the standard's structure words, which define
+//! a word for each field adding its offset to an address, and one for the
+//! size of the whole.
+//!
+//! ```
+//! use forth_core::Forth;
+//!
+//! let mut f = Forth::new();
+//! f.eval("BEGIN-STRUCTURE POINT FIELD: P.X FIELD: P.Y CFIELD: P.TAG 2 +FIELD P.PAD END-STRUCTURE").unwrap();
+//! f.eval("CREATE ORIGIN POINT ALLOT  5 ORIGIN P.Y !  ORIGIN P.Y @").unwrap();
+//! f.eval("ORIGIN P.TAG ORIGIN - 2 CELLS =  POINT 2 CELLS 3 + =").unwrap();
+//! assert_eq!(f.stack(), vec![5, -1, -1]);
+//! ```
+//!
+//! A double-cell number takes two cells on the stack, the most
+//! significant on top, as S>D, M* and D+ leave it. 2VARIABLE, 2CONSTANT,
+//! 2@ and 2! keep one in memory most significant cell first, reading and
+//! writing both cells at once.
+//!
+//! ```
+//! use forth_core::{Forth, Value};
+//!
+//! let mut f = Forth::new();
+//! f.eval("2VARIABLE TOTAL  100000 100000 M* TOTAL 2!  -1 S>D 2CONSTANT MINUS-ONE").unwrap();
+//! f.eval("TOTAL 2@ MINUS-ONE D+ 2DUP TOTAL 2!  TOTAL @").unwrap();
+//! let total: i128 = 100000 * 100000 - 1;
+//! let (low, high) = (total as Value, (total >> Value::BITS) as Value);
+//! assert_eq!(f.stack(), vec![low, high, high]);
+//! ```
+//!
+//! DUMP lists memory in hex and as text, which shows how `S"` and C!
+//! leave it.
+//!
+//! ```
+//! use forth_core::Forth;
+//!
+//! let mut f = Forth::new();
+//! f.eval(r#"S" Hi!" 2DUP DROP 1+ CHAR o SWAP C! DUMP"#).unwrap();
+//! assert!(f.output().ends_with(" 48 6F 21                                         Ho!\n"));
+//! ```
+//!
+//! .R and U.R print numbers right-aligned in a field, for tables that
+//! line up, taking more room when the number needs it.
+//!
+//! ```
+//! use forth_core::Forth;
+//!
+//! let mut f = Forth::new();
+//! f.eval(": ROW DUP 4 .R DUP 2* 4 .R 3 * 4 .R CR ; 1 ROW 3 ROW").unwrap();
+//! assert_eq!(f.output(), "   1   2   3\n   3   6   9\n");
+//! f.eval("-7 4 .R 255 HEX 4 U.R DECIMAL 12345 2 .R").unwrap();
+//! assert_eq!(f.output(), "  -7  FF12345");
+//! ```
+//!
+//! NUMBER? reads a number as the interpreter would, prefixes and all,
+//! while >NUMBER takes what digits it can, leaving the rest.
+//!
+//! ```
+//! use forth_core::Forth;
+//!
+//! let mut f = Forth::new();
+//! f.eval(r#"S" $FF" NUMBER? S" 12x" NUMBER? 0 0 S" 12x" >NUMBER NIP"#).unwrap();
+//! assert_eq!(f.stack(), vec![255, -1, 0, 12, 0, 1]);
+//! ```
+//!
+//! MODULE begins a module, whose words are public until PRIVATE and again
+//! after PUBLIC. END-MODULE ends it, leaving only the public words in
+//! sight, while `GFX::LINE` names the public word `LINE` of the module
+//! `GFX` whatever else has taken the name since.
+//!
+//! ```
+//! use forth_core::{Error, Forth};
+//!
+//! let mut f = Forth::new();
+//! f.eval("MODULE GFX PRIVATE : HELPER 2* ; PUBLIC : LINE HELPER 1+ ; END-MODULE").unwrap();
+//! f.eval(": LINE DROP 0 ; 5 LINE 5 GFX::LINE").unwrap();
+//! assert_eq!(f.stack(), vec![0, 11]);
+//! assert_eq!(f.eval("1 HELPER"), Err(Error::UnknownWord("HELPER".into())));
+//! assert_eq!(f.eval("1 GFX::HELPER"), Err(Error::UnknownWord("GFX::HELPER".into())));
+//! ```
+//!
+//! Inside a definition, `[: … ;]` compiles a quotation, a nameless word
+//! whose execution token is left when the definition runs. Quotations
+//! nest, and RECURSE inside one calls the quotation. TIMES runs a token a
+//! number of times, DIP runs it with the top of the stack set aside, and
+//! KEEP and BI, built on DIP, run tokens on a value while keeping it.
+//!
+//! ```
+//! use forth_core::Forth;
+//!
+//! let mut f = Forth::new();
+//! f.eval(": POWERS ( n -- 1 2 ... 2^n ) 1 SWAP [: DUP 2* ;] SWAP TIMES ; 4 POWERS").unwrap();
+//! assert_eq!(f.stack(), vec![1, 2, 4, 8, 16]);
+//!
+//! let mut f = Forth::new();
+//! f.eval(": ADD ( n1 n2 -- n3 ) [: [: + ;] EXECUTE ;] EXECUTE ; 3 4 ADD").unwrap();
+//! f.eval(": BUMP-UNDER ( n x -- n+1 x ) [: 1+ ;] DIP ; 10 BUMP-UNDER").unwrap();
+//! f.eval(": BOTH ( n -- 2n n-1 ) [: 2* ;] [: 1- ;] BI ; 6 BOTH").unwrap();
+//! assert_eq!(f.stack(), vec![8, 10, 12, 5]);
+//! ```
+//!
+//! Without the default `std` feature the crate is `#![no_std]` and needs
+//! only `alloc`. The `serde` feature adds `Forth::snapshot` and
+//! `Forth::restore` for saving interpreter state. With `cell64`, cells
+//! and `Value` are 64-bit rather than 32-bit. The `typed` feature adds
+//! the builder's `typed` option, for stack items that are integers,
+//! floats or strings.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
+#[cfg(feature = "std")]
+extern crate core;
+extern crate smallvec;
+
+mod heap;
+mod forth;
+
+pub use forth::{
+    lex, BlockStorage, CallFrame, Case, Channels, Clock, CompiledWord, DefinedWord, Diagnostic, Division, Error,
+    ErrorFrame, EvalStats, Execution, ExecutionHook, FormatOptions, Forth, ForthBuilder, ForthResult, GcStats, GridCell,
+    Instruction, Io, Key, Language, Lexer, Location, MemoryBlocks, MemoryChannels, MemoryIo, MemoryWrite, Messages,
+    Overflow, PreludeError, Program, Recognizer, RecordedStep, Recording, Recovery, SourceProvider, SourceToken, Span,
+    Stack, StackEffect, Step, TerminalGrid, TimeAndDate, TokenKind, Tokens, Trace, TraceOptions, TraceStep, Value,
+    Warning, WatchHit, Watchpoint, WordCompiler, WordCoverage, WordDefinition, WordInfo, WordProfile, BLOCK_SIZE,
+    FORTH_WORDLIST,
+};
+#[cfg(feature = "serde")]
+pub use forth::Snapshot;
+#[cfg(feature = "std")]
+pub use forth::{FileBlocks, StdIo, SystemClock};
+#[cfg(feature = "typed")]
+pub use forth::{Float, TypedValue};
@@ -0,0 +1,85 @@
+/*
+ * A Forth interpreter for embedding in C and C++ programs. Link with the
+ * library `cargo build -p forth-ffi --release` builds.
+ *
+ *     forth_t *forth = forth_new();
+ *     const char *code = "2 3 + .";
+ *     if (forth_eval(forth, code, strlen(code)) != 0)
+ *         fprintf(stderr, "%s\n", forth_last_error(forth));
+ *     size_t len;
+ *     const char *output = forth_output(forth, &len);
+ *     fwrite(output, 1, len, stdout);
+ *     forth_free(forth);
+ */
+
+#ifndef FORTH_H
+#define FORTH_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An interpreter, which keeps its stack and definitions between calls. */
+typedef struct Interpreter forth_t;
+
+/* The data stack, as a word defined with `forth_define` sees it. */
+typedef struct ForthStack forth_stack_t;
+
+/* A word the host defines. Returns 0 on success, or a THROW code to fail
+ * with, which `forth_eval` then returns. */
+typedef int32_t (*forth_word_fn)(forth_stack_t *stack, void *user_data);
+
+/* A fresh interpreter, to be freed with `forth_free`. */
+forth_t *forth_new(void);
+
+/* Frees an interpreter. Does nothing with NULL. */
+void forth_free(forth_t *forth);
+
+/* Interprets `len` bytes of UTF-8 source. Returns 0, or the THROW code of
+ * the error evaluation stopped with, whose message `forth_last_error`
+ * then gives. */
+int32_t forth_eval(forth_t *forth, const char *code, size_t len);
+
+/* Carries on with an evaluation that paused, returning as `forth_eval`
+ * does. */
+int32_t forth_resume(forth_t *forth);
+
+/* The message of the error the last evaluation stopped with, or NULL if
+ * it finished. Valid until the next evaluation. */
+const char *forth_last_error(const forth_t *forth);
+
+/* The output of the last evaluation, as UTF-8 that isn't NUL-terminated,
+ * its length stored in `len`. Valid until the interpreter is next used. */
+const char *forth_output(const forth_t *forth, size_t *len);
+
+/* Copies up to `capacity` cells of the data stack, bottom first, to
+ * `cells`. Returns the depth of the stack, which may be more. */
+size_t forth_stack(const forth_t *forth, int64_t *cells, size_t capacity);
+
+/* Pushes `value` onto the data stack. */
+void forth_push(forth_t *forth, int64_t value);
+
+/* Defines the word `name` to call `function` with the data stack and
+ * `user_data`. Returns 0, or -1 if `name` isn't UTF-8 or `function` is
+ * NULL. */
+int32_t forth_define(forth_t *forth, const char *name, forth_word_fn function, void *user_data);
+
+/* How many cells are on `stack`. */
+size_t forth_stack_depth(const forth_stack_t *stack);
+
+/* Pushes `value` onto `stack`. */
+void forth_stack_push(forth_stack_t *stack, int64_t value);
+
+/* Pops the top of `stack` into `value`. Returns false, leaving `value`
+ * alone, if `stack` is empty. */
+bool forth_stack_pop(forth_stack_t *stack, int64_t *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,298 @@
+//! The interpreter behind a C interface, for hosts written in C, C++ or any
+//! language that can call C. `include/forth.h` declares it.
+//!
+//! Build it with `cargo build -p forth-ffi --release`, which leaves a shared
+//! library (`libforth_ffi.so`, `.dylib` or `forth_ffi.dll`) and a static one
+//! in `target/release`. Cells cross the interface as `int64_t`, and are
+//! truncated to 32 bits unless forth-core has the `cell64` feature.
+//!
+//! With the `python` feature the library is also a Python module,
+//! `forth_ffi`, with the interpreter as the class `Forth`.
+//!
+//! ```
+//! extern crate forth_ffi;
+//!
+//! use std::ptr;
+//!
+//! use forth_ffi::*;
+//!
+//! extern "C" fn triple(stack: *mut ForthStack, _user_data: *mut std::os::raw::c_void) -> i32 {
+//!     let mut v = 0;
+//!     unsafe {
+//!         if !forth_stack_pop(stack, &mut v) {
+//!             return -4;
+//!         }
+//!         forth_stack_push(stack, v * 3);
+//!     }
+//!     0
+//! }
+//!
+//! unsafe {
+//!     let forth = forth_new();
+//!     assert_eq!(forth_define(forth, b"TRIPLE\0".as_ptr().cast(), Some(triple), ptr::null_mut()), 0);
+//!     let code = "5 TRIPLE DUP .";
+//!     assert_eq!(forth_eval(forth, code.as_ptr().cast(), code.len()), 0);
+//!     let mut cells = [0; 4];
+//!     assert_eq!(forth_stack(forth, cells.as_mut_ptr(), cells.len()), 1);
+//!     assert_eq!(cells[0], 15);
+//!
+//!     assert_eq!(forth_eval(forth, "NOPE".as_ptr().cast(), 4), -13);
+//!     let message = std::ffi::CStr::from_ptr(forth_last_error(forth));
+//!     assert_eq!(message.to_str().unwrap(), "unknown word NOPE");
+//!     forth_free(forth);
+//! }
+//! ```
+
+extern crate forth_core;
+// pyo3's macros refer to `::core`, which edition 2015 needs declared.
+#[cfg(feature = "python")]
+extern crate core;
+#[cfg(feature = "python")]
+extern crate pyo3;
+
+#[cfg(feature = "python")]
+mod python;
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+use std::slice;
+
+use forth_core::{Error, Forth, ForthResult, Stack, Value};
+
+/// An interpreter, with the message of the last error it stopped with.
+pub struct Interpreter {
+    forth: Forth,
+    error: Option<CString>,
+}
+
+/// The data stack, as a callback word sees it.
+#[repr(transparent)]
+pub struct ForthStack(Stack);
+
+/// A word the host defines with `forth_define`. It returns 0 on success,
+/// or a THROW code to fail with, which `forth_eval` then returns.
+pub type ForthWordFn = extern "C" fn(stack: *mut ForthStack, user_data: *mut c_void) -> i32;
+
+impl Interpreter {
+    /// Keeps the message of `result`'s error, returning its THROW code, or
+    /// 0 if there is none.
+    fn outcome(&mut self, result: ForthResult) -> i32 {
+        match result {
+            Ok(()) => {
+                self.error = None;
+                0
+            }
+            Err(e) => {
+                let message = e.to_string().replace('\0', "");
+                self.error = Some(CString::new(message).unwrap());
+                e.throw_code()
+            }
+        }
+    }
+}
+
+/// A fresh interpreter, to be freed with `forth_free`.
+#[no_mangle]
+pub extern "C" fn forth_new() -> *mut Interpreter {
+    Box::into_raw(Box::new(Interpreter { forth: Forth::new(), error: None }))
+}
+
+/// Frees an interpreter `forth_new` made. Does nothing with a null pointer.
+///
+/// # Safety
+///
+/// `forth` must be null or from `forth_new`, and not freed already.
+#[no_mangle]
+pub unsafe extern "C" fn forth_free(forth: *mut Interpreter) {
+    if !forth.is_null() {
+        drop(Box::from_raw(forth));
+    }
+}
+
+/// Interprets the `len` bytes of UTF-8 at `code`, returning 0 or the THROW
+/// code of the error it stopped with, whose message `forth_last_error`
+/// then gives. Invalid UTF-8 is replaced.
+///
+/// # Safety
+///
+/// `forth` must be from `forth_new`, and `code` point to `len` readable
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn forth_eval(forth: *mut Interpreter, code: *const c_char, len: usize) -> i32 {
+    let forth = &mut *forth;
+    let code = match len {
+        0 => Default::default(),
+        _ => String::from_utf8_lossy(slice::from_raw_parts(code.cast::<u8>(), len)),
+    };
+    let result = forth.forth.eval(&code);
+    forth.outcome(result)
+}
+
+/// Carries on with an evaluation that paused, as `forth_eval` would report
+/// it, returning 0 or the THROW code of the error it stopped with.
+///
+/// # Safety
+///
+/// `forth` must be from `forth_new`.
+#[no_mangle]
+pub unsafe extern "C" fn forth_resume(forth: *mut Interpreter) -> i32 {
+    let forth = &mut *forth;
+    let result = forth.forth.resume();
+    forth.outcome(result)
+}
+
+/// The message of the error the last evaluation stopped with, or null if
+/// it finished. The text stays valid until the next evaluation.
+///
+/// # Safety
+///
+/// `forth` must be from `forth_new`.
+#[no_mangle]
+pub unsafe extern "C" fn forth_last_error(forth: *const Interpreter) -> *const c_char {
+    (*forth).error.as_ref().map_or(ptr::null(), |message| message.as_ptr())
+}
+
+/// The output of the last evaluation, as UTF-8 without a terminating NUL,
+/// its length stored in `len`. The text stays valid until the interpreter
+/// is next used.
+///
+/// # Safety
+///
+/// `forth` must be from `forth_new`, and `len` writable.
+#[no_mangle]
+pub unsafe extern "C" fn forth_output(forth: *const Interpreter, len: *mut usize) -> *const c_char {
+    let output = (*forth).forth.output();
+    *len = output.len();
+    output.as_ptr().cast()
+}
+
+/// Copies up to `capacity` cells of the data stack, bottom first, to
+/// `cells`, returning its depth, which may be more.
+///
+/// # Safety
+///
+/// `forth` must be from `forth_new`, and `cells` point to room for
+/// `capacity` cells.
+#[no_mangle]
+pub unsafe extern "C" fn forth_stack(forth: *const Interpreter, cells: *mut i64, capacity: usize) -> usize {
+    let stack = (*forth).forth.stack();
+    for (i, &v) in stack.iter().take(capacity).enumerate() {
+        *cells.add(i) = v.into();
+    }
+    stack.len()
+}
+
+/// Pushes `value` onto the data stack.
+///
+/// # Safety
+///
+/// `forth` must be from `forth_new`.
+#[no_mangle]
+pub unsafe extern "C" fn forth_push(forth: *mut Interpreter, value: i64) {
+    (*forth).forth.push(value as Value);
+}
+
+/// Defines the word `name` to call `function` with the data stack and
+/// `user_data`. Returns 0, or -1 if `name` isn't UTF-8 or `function` is
+/// null. A nonzero return from `function` fails the word with that code as
+/// its THROW code, which CATCH catches and `forth_eval` returns.
+///
+/// # Safety
+///
+/// `forth` must be from `forth_new`, `name` a NUL-terminated string, and
+/// `user_data` usable by `function` for as long as the interpreter lives.
+#[no_mangle]
+pub unsafe extern "C" fn forth_define(
+    forth: *mut Interpreter,
+    name: *const c_char,
+    function: Option<ForthWordFn>,
+    user_data: *mut c_void,
+) -> i32 {
+    let (name, function) = match (CStr::from_ptr(name).to_str(), function) {
+        (Ok(name), Some(function)) => (name, function),
+        _ => return -1,
+    };
+    let word = String::from(name);
+    (*forth).forth.define_native(name, move |stack| {
+        let stack = stack as *mut Stack as *mut ForthStack;
+        match function(stack, user_data) {
+            0 => Ok(()),
+            code => Err(Error::Thrown(word.clone(), code)),
+        }
+    });
+    0
+}
+
+/// How many cells are on `stack`.
+///
+/// # Safety
+///
+/// `stack` must be one passed to a word's function, while it runs.
+#[no_mangle]
+pub unsafe extern "C" fn forth_stack_depth(stack: *const ForthStack) -> usize {
+    (*stack).0.len()
+}
+
+/// Pushes `value` onto `stack`.
+///
+/// # Safety
+///
+/// `stack` must be one passed to a word's function, while it runs.
+#[no_mangle]
+pub unsafe extern "C" fn forth_stack_push(stack: *mut ForthStack, value: i64) {
+    (*stack).0.push(value as Value);
+}
+
+/// Pops the top of `stack` into `value`, returning false, and leaving
+/// `value` alone, if `stack` is empty.
+///
+/// # Safety
+///
+/// `stack` must be one passed to a word's function, while it runs, and
+/// `value` writable.
+#[no_mangle]
+pub unsafe extern "C" fn forth_stack_pop(stack: *mut ForthStack, value: *mut i64) -> bool {
+    match (*stack).0.pop() {
+        Some(v) => {
+            *value = v.into();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern "C" fn fail(stack: *mut ForthStack, _user_data: *mut c_void) -> i32 {
+        let mut code = 0;
+        unsafe {
+            if !forth_stack_pop(stack, &mut code) {
+                return -4;
+            }
+        }
+        code as i32
+    }
+
+    #[test]
+    fn words_fail_with_the_code_their_function_returns() {
+        unsafe {
+            let forth = forth_new();
+            assert_eq!(forth_define(forth, b"FAIL\0".as_ptr().cast(), Some(fail), ptr::null_mut()), 0);
+            let code = "-300 FAIL";
+            assert_eq!(forth_eval(forth, code.as_ptr().cast(), code.len()), -300);
+            let message = CStr::from_ptr(forth_last_error(forth));
+            assert_eq!(message.to_str().unwrap(), "FAIL failed");
+            assert_eq!(forth_eval(forth, "FAIL".as_ptr().cast(), 4), -4);
+
+            let code = "7 ' FAIL CATCH 0 ' FAIL CATCH";
+            assert_eq!(forth_eval(forth, code.as_ptr().cast(), code.len()), 0);
+            let mut cells = [0; 4];
+            assert_eq!(forth_stack(forth, cells.as_mut_ptr(), cells.len()), 3);
+            assert_eq!(cells[1..3], [7, 0]);
+            forth_free(forth);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! The interpreter as a Python class, for scripting it from notebooks, as
+//! when grading programs in bulk. Build the module with the `python`
+//! feature, for instance with `maturin develop -m forth-ffi/Cargo.toml
+//! --features python`, then:
+//!
+//! ```python
+//! from forth_ffi import Forth, ForthError
+//!
+//! f = Forth()
+//! f.define_word("TWICE", lambda stack: stack.append(stack.pop() * 2))
+//! f.eval(": SQ DUP * ;")
+//! start = f.snapshot()
+//! f.eval("3 SQ TWICE .")
+//! assert f.output == "18 " and f.stack == []
+//! f.restore(start)
+//! try:
+//!     f.eval("NOPE")
+//! except ForthError as e:
+//!     assert e.kind == "UnknownWord"
+//! ```
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyList};
+
+use forth_core::{Error, Forth, ForthResult, Value};
+
+create_exception!(
+    forth_ffi,
+    ForthError,
+    PyException,
+    "Why an evaluation stopped: its message, with the error's `kind`, such as \"UnknownWord\", and THROW `code`."
+);
+
+/// An interpreter, which keeps its stack and definitions between calls.
+#[pyclass(name = "Forth", unsendable)]
+struct Interpreter {
+    forth: Forth,
+}
+
+/// `e` as a `ForthError`.
+fn forth_error(py: Python, e: Error) -> PyErr {
+    let err = ForthError::new_err(e.to_string());
+    let value = err.value(py);
+    // Setting attributes of a fresh exception can't fail.
+    let _ = value.setattr("kind", e.kind());
+    let _ = value.setattr("code", e.throw_code());
+    err
+}
+
+fn check(py: Python, result: ForthResult) -> PyResult<()> {
+    result.map_err(|e| forth_error(py, e))
+}
+
+#[pymethods]
+impl Interpreter {
+    #[new]
+    fn new() -> Self {
+        Interpreter { forth: Forth::new() }
+    }
+
+    /// Interprets `code`, raising `ForthError` if it stops with an error.
+    fn eval(&mut self, py: Python, code: &str) -> PyResult<()> {
+        let result = self.forth.eval(code);
+        check(py, result)
+    }
+
+    /// Carries on with an evaluation that paused.
+    fn resume(&mut self, py: Python) -> PyResult<()> {
+        let result = self.forth.resume();
+        check(py, result)
+    }
+
+    /// The data stack, bottom first.
+    #[getter]
+    fn stack(&self) -> Vec<Value> {
+        self.forth.stack()
+    }
+
+    /// The output of the last evaluation.
+    #[getter]
+    fn output(&self) -> &str {
+        self.forth.output()
+    }
+
+    fn push(&mut self, value: Value) {
+        self.forth.push(value);
+    }
+
+    /// Takes the top of the data stack, raising `ForthError` if it is
+    /// empty.
+    fn pop(&mut self, py: Python) -> PyResult<Value> {
+        self.forth.pop().map_err(|e| forth_error(py, e))
+    }
+
+    /// Limits how many words later evaluations may run, or lifts the limit
+    /// with `None`, so that a program that never ends raises `ForthError`
+    /// rather than hanging.
+    #[pyo3(signature = (fuel=None))]
+    fn set_fuel(&mut self, fuel: Option<u64>) {
+        self.forth.set_fuel(fuel);
+    }
+
+    /// Defines the word `name` to call `function` with the data stack as a
+    /// list, bottom first, which `function` may change in place. An
+    /// exception it raises fails the word with the exception's message.
+    fn define_word(&mut self, name: &str, function: PyObject) {
+        self.forth.define_native(name, move |stack| {
+            Python::with_gil(|py| {
+                let list = PyList::new(py, stack.iter())?;
+                function.call1(py, (&list,))?;
+                *stack = list.extract()?;
+                Ok(())
+            }).map_err(|e: PyErr| Error::Native(e.to_string()))
+        });
+    }
+
+    /// A copy of the interpreter as it stands, for `restore` to go back to.
+    fn snapshot(&self) -> Interpreter {
+        Interpreter { forth: self.forth.fork() }
+    }
+
+    /// Puts the interpreter back as it was when `snapshot` was taken. The
+    /// snapshot can be restored again.
+    fn restore(&mut self, snapshot: PyRef<Interpreter>) {
+        self.forth = snapshot.forth.fork();
+    }
+
+    /// The dictionary and data space as bytes, for `load_image` in this
+    /// interpreter or another with the same words defined by
+    /// `define_word`, in the same order.
+    fn save_image<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.forth.save_image())
+    }
+
+    /// Replaces the dictionary and data space with those of `image`,
+    /// emptying the stack.
+    fn load_image(&mut self, py: Python, image: &[u8]) -> PyResult<()> {
+        let result = self.forth.load_image(image);
+        check(py, result)
+    }
+}
+
+#[pymodule]
+fn forth_ffi(m: &Bound<PyModule>) -> PyResult<()> {
+    m.add_class::<Interpreter>()?;
+    m.add("ForthError", m.py().get_type::<ForthError>())?;
+    Ok(())
+}
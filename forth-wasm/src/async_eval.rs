@@ -0,0 +1,186 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use forth_core::{Error, Forth};
+
+use super::{
+    from_js, result_stack, to_js, warnings, EvalError, EvalResult, EvalStats, Interpreter, JsEvalAsyncOptions,
+    JsEvalResultPromise,
+};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(callback: &JsValue, delay: i32);
+}
+
+/// How `Forth.evalAsync` splits up the work, given from JavaScript as an
+/// `EvalAsyncOptions` object.
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct EvalAsyncOptions {
+    /// Steps run between yields to the event loop.
+    chunk_size: u32,
+    /// An `AbortSignal`, or anything else with an `aborted` flag, checked
+    /// at each yield.
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    signal: JsValue,
+}
+
+impl Default for EvalAsyncOptions {
+    fn default() -> Self {
+        EvalAsyncOptions { chunk_size: 10_000, signal: JsValue::UNDEFINED }
+    }
+}
+
+/// An `evalAsync` in progress, carried from one chunk to the next.
+struct AsyncEval {
+    forth: Rc<RefCell<Forth>>,
+    paused_code: Rc<RefCell<String>>,
+    code: String,
+    chunk_size: u64,
+    /// What is left of the limit set by `setFuel`, if any.
+    fuel: Option<u64>,
+    /// Output of the chunks run so far, each of which starts afresh, and
+    /// whether any of it was cut short.
+    output: String,
+    output_truncated: bool,
+    max_stack_items: Option<usize>,
+    signal: JsValue,
+    resolve: Function,
+    reject: Function,
+}
+
+impl AsyncEval {
+    /// Why the evaluation was cancelled, if `signal` says it was.
+    fn abort_reason(&self) -> Option<JsValue> {
+        if self.signal.is_undefined() || self.signal.is_null() {
+            return None;
+        }
+        let aborted = Reflect::get(&self.signal, &JsValue::from_str("aborted")).ok()?;
+        if !aborted.is_truthy() {
+            return None;
+        }
+        match Reflect::get(&self.signal, &JsValue::from_str("reason")) {
+            Ok(reason) if !reason.is_undefined() => Some(reason),
+            _ => Some(js_sys::Error::new("evaluation aborted").into()),
+        }
+    }
+
+    /// Runs the next chunk, starting the evaluation if `first`, then
+    /// either settles the promise or schedules another chunk.
+    fn step(mut self, first: bool) {
+        let mut forth = self.forth.borrow_mut();
+        if !first {
+            if !forth.is_paused() {
+                // Another evaluation replaced this one between chunks.
+                let error = js_sys::Error::new("evaluation was interrupted");
+                let _ = self.reject.call1(&JsValue::NULL, &error);
+                return;
+            }
+            if let Some(reason) = self.abort_reason() {
+                forth.set_fuel(self.fuel);
+                *self.paused_code.borrow_mut() = self.code.clone();
+                let _ = self.reject.call1(&JsValue::NULL, &reason);
+                return;
+            }
+        }
+        let chunk = self.fuel.map_or(self.chunk_size, |fuel| fuel.min(self.chunk_size));
+        forth.set_fuel(Some(chunk));
+        let result = if first { forth.eval(&self.code) } else { forth.resume() };
+        let used = chunk - forth.fuel().unwrap_or(0);
+        self.fuel = self.fuel.map(|fuel| fuel - used);
+        self.output.push_str(forth.output());
+        self.output_truncated |= forth.output_truncated();
+        // PAUSE lets the tasks take a turn, a chunk each at most.
+        let result = match result {
+            Err(Error::PauseRequested) if forth.is_paused() => {
+                let tasks = forth.run_tasks(Some(self.chunk_size));
+                self.output.push_str(forth.output());
+                self.output_truncated |= forth.output_truncated();
+                tasks.and(result)
+            }
+            result => result,
+        };
+        // Out of fuel for this chunk, waiting for MS, or PAUSEd.
+        let delay = match result {
+            Err(Error::OutOfFuel) if self.fuel != Some(0) => Some(0),
+            Err(Error::WaitRequested(ms)) => Some(ms.min(i32::MAX as u64) as i32),
+            Err(Error::PauseRequested) => Some(0),
+            _ => None,
+        };
+        if let (Some(delay), true) = (delay, forth.is_paused()) {
+            drop(forth);
+            let callback = Closure::once_into_js(move || self.step(false));
+            set_timeout(&callback, delay);
+            return;
+        }
+        forth.set_fuel(self.fuel);
+        if forth.is_paused() {
+            *self.paused_code.borrow_mut() = self.code.clone();
+        }
+        let error = result.err().map(|e| EvalError::new(&forth, &self.code, &e));
+        let (stack, stack_truncated) = result_stack(&forth, self.max_stack_items);
+        let outcome: JsValue = to_js(&EvalResult {
+            stack,
+            stack_truncated,
+            output: self.output,
+            output_truncated: self.output_truncated,
+            warnings: warnings(&forth),
+            stats: EvalStats::from(forth.eval_stats()),
+            defined_words: forth.defined_words(),
+            error,
+        });
+        drop(forth);
+        let _ = self.resolve.call1(&JsValue::NULL, &outcome);
+    }
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Interprets `code` like `evalResult`, but `options.chunkSize` steps
+    /// at a time, yielding to the event loop in between so the page stays
+    /// responsive. MS waits without blocking the page, carrying on once
+    /// the time is up, and PAUSE gives the tasks TASK started a turn.
+    /// Resolves to the outcome. A limit set by `setFuel` still applies to
+    /// the evaluation as a whole.
+    ///
+    /// Once `options.signal` is aborted, evaluation stops at the next
+    /// yield and the promise rejects with the signal's reason; the
+    /// evaluation is left for `resume`. Evaluating other code before the
+    /// promise settles cuts it short, rejecting the promise. Work inside
+    /// EVALUATE can't be split up, and fails with `OutOfFuel` if it takes
+    /// more than a chunk. Throws if `options` is malformed.
+    #[wasm_bindgen(js_name = evalAsync)]
+    pub fn eval_async(&mut self, code: &str, options: Option<JsEvalAsyncOptions>)
+        -> Result<JsEvalResultPromise, JsValue>
+    {
+        let options: EvalAsyncOptions = from_js(&options.map_or(JsValue::UNDEFINED, JsValue::from))?;
+        let forth = self.forth.clone();
+        let paused_code = self.paused_code.clone();
+        let code = String::from(code);
+        let fuel = forth.borrow().fuel();
+        let max_stack_items = self.forth_options.max_stack_items;
+        let promise = Promise::new(&mut |resolve, reject| {
+            let eval = AsyncEval {
+                forth: forth.clone(),
+                paused_code: paused_code.clone(),
+                code: code.clone(),
+                chunk_size: u64::from(options.chunk_size.max(1)),
+                fuel,
+                output: String::new(),
+                output_truncated: false,
+                max_stack_items,
+                signal: options.signal.clone(),
+                resolve,
+                reject,
+            };
+            eval.step(true);
+        });
+        Ok(promise.unchecked_into())
+    }
+}
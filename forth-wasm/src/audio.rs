@@ -0,0 +1,191 @@
+//! Words that play sound through the Web Audio API, with the `audio`
+//! feature.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{AudioContext, GainNode, OscillatorNode, OscillatorType};
+
+use forth_core::{Error, Forth, ForthResult, Stack, Value};
+
+use super::js_error;
+
+/// The instrument the sound words play, and the notes it is playing.
+struct Synth {
+    context: AudioContext,
+    /// Envelope times in seconds, and the sustain level as a fraction of
+    /// the volume.
+    attack: f64,
+    decay: f64,
+    sustain: f64,
+    release: f64,
+    /// Loudness from 0 to 1.
+    volume: f64,
+    wave: OscillatorType,
+    /// When, in the context's time, the next TONE starts, so that tones
+    /// play one after another.
+    cursor: f64,
+    /// Notes started by NOTE-ON, by frequency.
+    held: Vec<(Value, OscillatorNode, GainNode)>,
+}
+
+impl Synth {
+    /// Starts a note of `freq` Hz at `start`, rising to the sustain level
+    /// as the envelope says.
+    fn note(&self, freq: f64, start: f64) -> Result<(OscillatorNode, GainNode), JsValue> {
+        let oscillator = self.context.create_oscillator()?;
+        oscillator.set_type(self.wave);
+        oscillator.frequency().set_value_at_time(freq as f32, start)?;
+        let gain = self.context.create_gain()?;
+        let level = gain.gain();
+        level.set_value_at_time(0.0, start)?;
+        level.linear_ramp_to_value_at_time(self.volume as f32, start + self.attack)?;
+        level.linear_ramp_to_value_at_time((self.volume * self.sustain) as f32, start + self.attack + self.decay)?;
+        oscillator.connect_with_audio_node(&gain)?;
+        gain.connect_with_audio_node(&self.context.destination())?;
+        oscillator.start_with_when(start)?;
+        Ok((oscillator, gain))
+    }
+
+    /// Lets a note fade away from `end` over the release time.
+    fn release(&self, oscillator: &OscillatorNode, gain: &GainNode, end: f64) -> Result<(), JsValue> {
+        let level = gain.gain();
+        level.cancel_scheduled_values(end)?;
+        // Falls to within 2% of silence by the end of the release.
+        level.set_target_at_time(0.0, end, (self.release / 4.0).max(0.001))?;
+        oscillator.stop_with_when(end + self.release)
+    }
+
+    fn now(&self) -> f64 {
+        self.context.current_time()
+    }
+}
+
+/// Takes the top cell off `stack` as a number.
+fn pop(stack: &mut Stack) -> Result<f64, Error> {
+    stack.pop().map(|v| v as f64).ok_or(Error::StackUnderflow)
+}
+
+fn seconds(ms: f64) -> f64 {
+    ms.max(0.0) / 1000.0
+}
+
+/// Defines `name` as a word running `f` on the synth, waking the audio
+/// context first in case the browser started it suspended.
+fn define<F>(forth: &mut Forth, synth: &Rc<RefCell<Synth>>, name: &str, f: F)
+    where F: Fn(&mut Synth, &mut Stack) -> ForthResult + 'static
+{
+    let synth = synth.clone();
+    forth.define_native(name, move |stack| {
+        let mut synth = synth.borrow_mut();
+        let _ = synth.context.resume();
+        f(&mut synth, stack)
+    });
+}
+
+/// Defines the sound words, which play through a new audio context:
+///
+/// - `TONE` ( freq ms -- ) plays a note of `freq` Hz for `ms`
+///   milliseconds, once the tones before it have finished, so that a run
+///   of TONEs plays a tune.
+/// - `REST` ( ms -- ) leaves a gap of `ms` milliseconds before the next
+///   TONE.
+/// - `NOTE-ON` ( freq -- ) starts a note of `freq` Hz that plays until
+///   `NOTE-OFF` ( freq -- ) releases it.
+/// - `ENVELOPE` ( attack decay sustain release -- ) shapes later notes:
+///   they rise to full volume over `attack` milliseconds, fall to
+///   `sustain` percent of it over `decay`, and fade away over `release`
+///   once they end.
+/// - `WAVEFORM` ( n -- ) picks the sound of later notes: 0 sine, 1 square,
+///   2 sawtooth or 3 triangle.
+/// - `VOLUME` ( percent -- ) sets how loud later notes are.
+/// - `SILENCE` ( -- ) stops every note, including TONEs still to come.
+///
+/// Browsers keep audio quiet until the user interacts with the page, so
+/// attach from an event handler, such as a click. Fails if the browser
+/// has no Web Audio.
+pub fn attach(forth: &mut Forth) -> Result<(), JsValue> {
+    let context = AudioContext::new()?;
+    let synth = Rc::new(RefCell::new(Synth {
+        cursor: context.current_time(),
+        context,
+        attack: 0.01,
+        decay: 0.05,
+        sustain: 0.8,
+        release: 0.05,
+        volume: 0.5,
+        wave: OscillatorType::Square,
+        held: Vec::new(),
+    }));
+
+    define(forth, &synth, "TONE", |synth, stack| {
+        let ms = pop(stack)?;
+        let freq = pop(stack)?;
+        let start = synth.cursor.max(synth.now());
+        let end = start + seconds(ms);
+        let (oscillator, gain) = synth.note(freq, start).map_err(js_error)?;
+        synth.release(&oscillator, &gain, end).map_err(js_error)?;
+        synth.cursor = end;
+        Ok(())
+    });
+    define(forth, &synth, "REST", |synth, stack| {
+        let ms = pop(stack)?;
+        synth.cursor = synth.cursor.max(synth.now()) + seconds(ms);
+        Ok(())
+    });
+    define(forth, &synth, "NOTE-ON", |synth, stack| {
+        let freq = pop(stack)?;
+        let now = synth.now();
+        if let Some(i) = synth.held.iter().position(|&(f, _, _)| f as f64 == freq) {
+            let (_, oscillator, gain) = synth.held.remove(i);
+            synth.release(&oscillator, &gain, now).map_err(js_error)?;
+        }
+        let (oscillator, gain) = synth.note(freq, now).map_err(js_error)?;
+        synth.held.push((freq as Value, oscillator, gain));
+        Ok(())
+    });
+    define(forth, &synth, "NOTE-OFF", |synth, stack| {
+        let freq = pop(stack)?;
+        if let Some(i) = synth.held.iter().position(|&(f, _, _)| f as f64 == freq) {
+            let (_, oscillator, gain) = synth.held.remove(i);
+            synth.release(&oscillator, &gain, synth.now()).map_err(js_error)?;
+        }
+        Ok(())
+    });
+    define(forth, &synth, "ENVELOPE", |synth, stack| {
+        let release = pop(stack)?;
+        let sustain = pop(stack)?;
+        let decay = pop(stack)?;
+        let attack = pop(stack)?;
+        synth.attack = seconds(attack);
+        synth.decay = seconds(decay);
+        synth.sustain = sustain.clamp(0.0, 100.0) / 100.0;
+        synth.release = seconds(release);
+        Ok(())
+    });
+    define(forth, &synth, "WAVEFORM", |synth, stack| {
+        synth.wave = match pop(stack)? as i64 {
+            0 => OscillatorType::Sine,
+            1 => OscillatorType::Square,
+            2 => OscillatorType::Sawtooth,
+            3 => OscillatorType::Triangle,
+            n => return Err(Error::Native(format!("no waveform {}", n))),
+        };
+        Ok(())
+    });
+    define(forth, &synth, "VOLUME", |synth, stack| {
+        synth.volume = pop(stack)?.clamp(0.0, 100.0) / 100.0;
+        Ok(())
+    });
+    define(forth, &synth, "SILENCE", |synth, _| {
+        // A new context drops everything scheduled on the old one.
+        let context = AudioContext::new().map_err(js_error)?;
+        let _ = synth.context.close();
+        synth.cursor = context.current_time();
+        synth.context = context;
+        synth.held.clear();
+        Ok(())
+    });
+    Ok(())
+}
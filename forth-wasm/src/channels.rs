@@ -0,0 +1,65 @@
+//! Channels that interpreters on the same page share for SEND and RECV,
+//! and that the page can send and receive on too, to pass messages to
+//! interpreters in other workers.
+
+use wasm_bindgen::prelude::*;
+
+use forth_core::{Channels, MemoryChannels, Value};
+
+use super::Interpreter;
+
+/// Numbered channels carrying cells and strings between the interpreters
+/// given them with `setChannels`, exported to JavaScript as `Channels`.
+#[wasm_bindgen(js_name = Channels)]
+pub struct SharedChannels {
+    channels: MemoryChannels,
+}
+
+#[wasm_bindgen(js_class = Channels)]
+impl SharedChannels {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SharedChannels {
+        SharedChannels { channels: MemoryChannels::new() }
+    }
+
+    /// Queues `value` on `channel` for RECV.
+    pub fn send(&mut self, channel: Value, value: Value) {
+        self.channels.send(channel, value);
+    }
+
+    /// Takes the oldest cell SEND left on `channel`, if any.
+    pub fn receive(&mut self, channel: Value) -> Option<Value> {
+        self.channels.receive(channel)
+    }
+
+    /// Queues `text` on `channel` for `$RECV`.
+    #[wasm_bindgen(js_name = sendString)]
+    pub fn send_string(&mut self, channel: Value, text: String) {
+        self.channels.send_string(channel, text);
+    }
+
+    /// Takes the oldest string `$SEND` left on `channel`, if any.
+    #[wasm_bindgen(js_name = receiveString)]
+    pub fn receive_string(&mut self, channel: Value) -> Option<String> {
+        self.channels.receive_string(channel)
+    }
+}
+
+impl Default for SharedChannels {
+    fn default() -> Self {
+        SharedChannels::new()
+    }
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Has SEND and RECV, and with the `strings` option `$SEND` and
+    /// `$RECV`, use `channels`, which other interpreters may share. Until
+    /// then, and after `reset`, the interpreter has channels of its own.
+    /// RECV waiting for a message pauses as PAUSE does, so `evalAsync`
+    /// carries on once another interpreter or the page has sent one.
+    #[wasm_bindgen(js_name = setChannels)]
+    pub fn set_channels(&mut self, channels: &SharedChannels) {
+        self.forth.borrow_mut().set_channels(channels.channels.clone());
+    }
+}
@@ -0,0 +1,209 @@
+//! Translating colon definitions into WebAssembly modules, for the `jit`
+//! and `export` features.
+//!
+//! Definitions built only of literals and the arithmetic and stack
+//! primitives below become a module of one function, taking the cells the
+//! body reads as parameters and returning those it leaves. Arithmetic is
+//! done in 64 bits and traps on results that don't fit a cell.
+
+use std::mem;
+
+use wasm_encoder::{
+    BlockType, CodeSection, ExportKind, ExportSection, FunctionSection, Instruction as Wasm, Module, TypeSection,
+    ValType,
+};
+
+use forth_core::{Instruction, Value};
+
+/// A module made by `translate`, and how many cells its function takes
+/// and leaves.
+pub(super) struct WasmWord {
+    pub module: Vec<u8>,
+    // Only the JIT reads these; exported modules give them in their type.
+    #[cfg_attr(not(feature = "jit"), allow(dead_code))]
+    pub inputs: usize,
+    #[cfg_attr(not(feature = "jit"), allow(dead_code))]
+    pub outputs: usize,
+}
+
+/// What a primitive does to the cells it takes.
+#[derive(Clone)]
+enum Effect {
+    /// Takes two and leaves the result of the instruction, checked to fit.
+    Binary(Wasm<'static>),
+    /// Takes one and leaves the result of the instruction with the operand.
+    Unary(Wasm<'static>, i64),
+    /// Takes one and leaves half of it, rounded down, which always fits.
+    Halve,
+    Identity,
+    Dup,
+    Drop,
+    Swap,
+    Over,
+}
+
+impl Effect {
+    /// The effect of the primitive `name`, if it can be compiled.
+    fn of(name: &str) -> Option<Effect> {
+        let cell = mem::size_of::<Value>() as i64;
+        Some(match name {
+            "+" => Effect::Binary(Wasm::I64Add),
+            "-" => Effect::Binary(Wasm::I64Sub),
+            "*" => Effect::Binary(Wasm::I64Mul),
+            "1+" | "CHAR+" => Effect::Unary(Wasm::I64Add, 1),
+            "1-" => Effect::Unary(Wasm::I64Sub, 1),
+            "2*" => Effect::Unary(Wasm::I64Mul, 2),
+            "CELL+" => Effect::Unary(Wasm::I64Add, cell),
+            "CELLS" => Effect::Unary(Wasm::I64Mul, cell),
+            "2/" => Effect::Halve,
+            "CHARS" => Effect::Identity,
+            "DUP" => Effect::Dup,
+            "DROP" => Effect::Drop,
+            "SWAP" => Effect::Swap,
+            "OVER" => Effect::Over,
+            _ => return None,
+        })
+    }
+
+    /// How many cells it takes, and how many it leaves.
+    fn arity(&self) -> (usize, usize) {
+        match self {
+            Effect::Binary(_) => (2, 1),
+            Effect::Unary(..) | Effect::Halve | Effect::Identity => (1, 1),
+            Effect::Dup => (1, 2),
+            Effect::Drop => (1, 0),
+            Effect::Swap => (2, 2),
+            Effect::Over => (2, 3),
+        }
+    }
+}
+
+/// A function being generated, which keeps each cell in a local of its
+/// own so that stack primitives only move local indices about.
+struct Codegen {
+    code: Vec<Wasm<'static>>,
+    /// Locals holding the cells, bottom first.
+    stack: Vec<u32>,
+    /// The parameters, then the 64-bit locals.
+    params: u32,
+    locals: u32,
+}
+
+impl Codegen {
+    /// Sets a new local to the value the code leaves, and pushes it.
+    fn push(&mut self) -> u32 {
+        let local = self.params + self.locals;
+        self.locals += 1;
+        self.code.push(Wasm::LocalSet(local));
+        self.stack.push(local);
+        local
+    }
+
+    /// Pushes the result of `op` on the top two cells, or on the top cell
+    /// and `operand`.
+    fn arithmetic(&mut self, op: Wasm<'static>, operand: Option<i64>) {
+        let b = match operand {
+            Some(v) => Wasm::I64Const(v),
+            None => Wasm::LocalGet(self.stack.pop().unwrap()),
+        };
+        let a = self.stack.pop().unwrap();
+        self.code.extend_from_slice(&[Wasm::LocalGet(a), b, op]);
+        let result = self.push();
+        // Traps unless the result is the same taken as a 32-bit cell.
+        self.code.extend_from_slice(&[
+            Wasm::LocalGet(result),
+            Wasm::LocalGet(result),
+            Wasm::I32WrapI64,
+            Wasm::I64ExtendI32S,
+            Wasm::I64Ne,
+            Wasm::If(BlockType::Empty),
+            Wasm::Unreachable,
+            Wasm::End,
+        ]);
+    }
+
+    fn apply(&mut self, effect: Effect) {
+        let n = self.stack.len();
+        match effect {
+            Effect::Binary(op) => self.arithmetic(op, None),
+            Effect::Unary(op, operand) => self.arithmetic(op, Some(operand)),
+            Effect::Halve => {
+                let a = self.stack.pop().unwrap();
+                self.code.extend_from_slice(&[Wasm::LocalGet(a), Wasm::I64Const(1), Wasm::I64ShrS]);
+                self.push();
+            }
+            Effect::Identity => {}
+            Effect::Dup => self.stack.push(self.stack[n - 1]),
+            Effect::Drop => {
+                self.stack.pop();
+            }
+            Effect::Swap => self.stack.swap(n - 2, n - 1),
+            Effect::Over => self.stack.push(self.stack[n - 2]),
+        }
+    }
+}
+
+/// A module exporting as `name` a function that does what `body` does to
+/// the stack, or why it can't be made: 64-bit cells, or an instruction
+/// other than a literal or one of `PRIMITIVES`.
+pub(super) fn translate(body: &[Instruction], name: &str) -> Result<WasmWord, String> {
+    // Only 32-bit cells are checked for overflow.
+    if mem::size_of::<Value>() != 4 {
+        return Err(String::from("only 32-bit cells can be compiled"));
+    }
+    // The cells the body reads from below where it started.
+    let mut depth = 0isize;
+    let mut lowest = 0isize;
+    for instruction in body {
+        let (taken, left) = match *instruction {
+            Instruction::Literal(_) => (0, 1),
+            Instruction::Primitive(word) => match Effect::of(word) {
+                Some(effect) => effect.arity(),
+                None => return Err(format!("{} can't be compiled", word)),
+            },
+            Instruction::Call(word) => return Err(format!("{} can't be compiled", word)),
+        };
+        depth -= taken as isize;
+        lowest = lowest.min(depth);
+        depth += left as isize;
+    }
+    let inputs = (-lowest) as u32;
+
+    let mut gen = Codegen { code: Vec::new(), stack: Vec::new(), params: inputs, locals: 0 };
+    for param in 0..inputs {
+        gen.code.extend_from_slice(&[Wasm::LocalGet(param), Wasm::I64ExtendI32S]);
+        gen.push();
+    }
+    for instruction in body {
+        match *instruction {
+            Instruction::Literal(v) => {
+                #[allow(clippy::useless_conversion)]
+                gen.code.push(Wasm::I64Const(i64::from(v)));
+                gen.push();
+            }
+            Instruction::Primitive(word) => gen.apply(Effect::of(word).unwrap()),
+            Instruction::Call(_) => unreachable!(),
+        }
+    }
+    let outputs = gen.stack.len();
+    for &local in &gen.stack {
+        gen.code.extend_from_slice(&[Wasm::LocalGet(local), Wasm::I32WrapI64]);
+    }
+    gen.code.push(Wasm::End);
+
+    let mut types = TypeSection::new();
+    types.ty().function(vec![ValType::I32; inputs as usize], vec![ValType::I32; outputs]);
+    let mut functions = FunctionSection::new();
+    functions.function(0);
+    let mut exports = ExportSection::new();
+    exports.export(name, ExportKind::Func, 0);
+    let mut function = wasm_encoder::Function::new(vec![(gen.locals, ValType::I64)]);
+    for op in &gen.code {
+        function.instruction(op);
+    }
+    let mut code = CodeSection::new();
+    code.function(&function);
+    let mut module = Module::new();
+    module.section(&types).section(&functions).section(&exports).section(&code);
+    Ok(WasmWord { module: module.finish(), inputs: inputs as usize, outputs })
+}
@@ -0,0 +1,89 @@
+//! Words that read and change elements of the page by id, with the `dom`
+//! feature.
+
+use std::convert::TryFrom;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{Document, Element};
+
+use forth_core::{Error, Forth, ForthResult, Stack, Value};
+
+use super::js_error;
+
+fn pop(stack: &mut Stack) -> Result<Value, Error> {
+    stack.pop().ok_or(Error::StackUnderflow)
+}
+
+/// Takes an address and length off `stack`, length on top, and returns the
+/// part of `memory` they describe.
+fn pop_buffer<'a>(stack: &mut Stack, memory: &'a mut [u8]) -> Result<&'a mut [u8], Error> {
+    let len = usize::try_from(pop(stack)?).map_err(|_| Error::InvalidAddress)?;
+    let addr = usize::try_from(pop(stack)?).map_err(|_| Error::InvalidAddress)?;
+    let end = addr.checked_add(len).ok_or(Error::InvalidAddress)?;
+    memory.get_mut(addr..end).ok_or(Error::InvalidAddress)
+}
+
+fn pop_string(stack: &mut Stack, memory: &mut [u8]) -> Result<String, Error> {
+    pop_buffer(stack, memory).map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Defines `name` as a word running `f` on the element whose id is the
+/// string on top of the stack, below which are the word's other operands.
+fn define<F>(forth: &mut Forth, document: &Document, name: &str, f: F)
+    where F: Fn(&Element, &mut Stack, &mut [u8]) -> ForthResult + 'static
+{
+    let document = document.clone();
+    forth.define_native_with_memory(name, move |stack, memory| {
+        let id = pop_string(stack, memory)?;
+        let element = document.get_element_by_id(&id)
+            .ok_or_else(|| Error::Native(format!("no element with id {}", id)))?;
+        f(&element, stack, memory)
+    });
+}
+
+/// Defines the DOM words, each finding an element of the page by the id
+/// string on top of the stack:
+///
+/// - `SET-TEXT` ( c-addr u id-addr id-len -- ) replaces the element's
+///   contents with text.
+/// - `GET-TEXT` ( c-addr u id-addr id-len -- n ) copies as much of its text
+///   as fits in the buffer, returning the number of bytes copied.
+/// - `SET-ATTR` ( value-addr value-len name-addr name-len id-addr id-len
+///   -- ) sets an attribute.
+/// - `ADD-CLASS` and `REMOVE-CLASS` ( c-addr u id-addr id-len -- ) add and
+///   remove a class.
+///
+/// A missing element, or an operation the browser refuses, fails with
+/// `Error::Native`. Fails if there is no document to work on.
+pub fn attach(forth: &mut Forth) -> Result<(), JsValue> {
+    let document = web_sys::window().and_then(|window| window.document())
+        .ok_or_else(|| JsValue::from_str("no document to attach to"))?;
+
+    define(forth, &document, "SET-TEXT", |element, stack, memory| {
+        let text = pop_string(stack, memory)?;
+        element.set_text_content(Some(&text));
+        Ok(())
+    });
+    define(forth, &document, "GET-TEXT", |element, stack, memory| {
+        let text = element.text_content().unwrap_or_default();
+        let buffer = pop_buffer(stack, memory)?;
+        let len = text.len().min(buffer.len());
+        buffer[..len].copy_from_slice(&text.as_bytes()[..len]);
+        stack.push(len as Value);
+        Ok(())
+    });
+    define(forth, &document, "SET-ATTR", |element, stack, memory| {
+        let name = pop_string(stack, memory)?;
+        let value = pop_string(stack, memory)?;
+        element.set_attribute(&name, &value).map_err(js_error)
+    });
+    define(forth, &document, "ADD-CLASS", |element, stack, memory| {
+        let class = pop_string(stack, memory)?;
+        element.class_list().add_1(&class).map_err(js_error)
+    });
+    define(forth, &document, "REMOVE-CLASS", |element, stack, memory| {
+        let class = pop_string(stack, memory)?;
+        element.class_list().remove_1(&class).map_err(js_error)
+    });
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! Feeding the page's keyboard and pointer events to the interpreter, for
+//! KEY, EKEY, MOUSE-X, MOUSE-Y and BUTTON?.
+
+use wasm_bindgen::prelude::*;
+
+use forth_core::{Key, Value};
+
+use super::{field, Interpreter};
+
+/// The key a DOM keyboard event's `key` names, if the interpreter has a
+/// use for it.
+pub(super) fn key_named(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Key::Char(c));
+    }
+    Some(match name {
+        "Enter" => Key::Char('\n'),
+        "Tab" => Key::Char('\t'),
+        "Backspace" => Key::Char('\u{8}'),
+        "Escape" => Key::Char('\u{1b}'),
+        "ArrowUp" => Key::Up,
+        "ArrowDown" => Key::Down,
+        "ArrowLeft" => Key::Left,
+        "ArrowRight" => Key::Right,
+        "Insert" => Key::Insert,
+        "Delete" => Key::Delete,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+        _ => match name.strip_prefix('F').and_then(|n| n.parse().ok()) {
+            Some(n @ 1..=12) => Key::F(n),
+            _ => return None,
+        },
+    })
+}
+
+/// The key a DOM keyboard event presses, unless it is a shortcut held with
+/// Ctrl, Alt or Meta, which the page keeps.
+pub(super) fn event_key(event: &JsValue) -> Option<Key> {
+    let shortcut = ["ctrlKey", "altKey", "metaKey"].iter().any(|&modifier| field(event, modifier).is_truthy());
+    if shortcut {
+        return None;
+    }
+    key_named(&field(event, "key").as_string()?)
+}
+
+/// The buttons held in a DOM `buttons` mask, main, secondary and middle
+/// in its low bits, as the bits BUTTON? numbers as in a DOM `button`:
+/// main, middle and secondary.
+pub(super) fn buttons(mask: u32) -> u32 {
+    (mask & !0b110) | (mask & 0b10) << 1 | (mask & 0b100) >> 1
+}
+
+/// The position and buttons of a DOM mouse or pointer event, relative to
+/// its target.
+pub(super) fn event_pointer(event: &JsValue) -> (f64, f64, u32) {
+    let number = |name| field(event, name).as_f64().unwrap_or(0.0);
+    (number("offsetX"), number("offsetY"), number("buttons") as u32)
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Queues the key pressed by a `keydown` event for KEY and EKEY.
+    /// Returns whether it did, for the page to call `preventDefault`;
+    /// shortcuts held with Ctrl, Alt or Meta and keys the interpreter has
+    /// no use for are left alone.
+    #[wasm_bindgen(js_name = keyEvent)]
+    pub fn key_event(&mut self, event: &JsValue) -> bool {
+        match event_key(event) {
+            Some(key) => {
+                self.forth.borrow_mut().push_key(key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Has MOUSE-X, MOUSE-Y and BUTTON? report the pointer of a mouse or
+    /// pointer event, relative to the element it happened on.
+    #[wasm_bindgen(js_name = pointerEvent)]
+    pub fn pointer_event(&mut self, event: &JsValue) {
+        let (x, y, mask) = event_pointer(event);
+        self.set_pointer(x, y, mask);
+    }
+
+    /// Has MOUSE-X and MOUSE-Y report `x` and `y`, and BUTTON? the buttons
+    /// held in `buttons`, a mask as DOM mouse events give it.
+    #[wasm_bindgen(js_name = setPointer)]
+    pub fn set_pointer(&mut self, x: f64, y: f64, buttons: u32) {
+        self.forth.borrow_mut().set_pointer(x as Value, y as Value, self::buttons(buttons));
+    }
+}
@@ -0,0 +1,28 @@
+//! Exporting words as standalone WebAssembly modules, with the `export`
+//! feature.
+
+use wasm_bindgen::prelude::*;
+
+use super::codegen::translate;
+use super::{flatten, Interpreter};
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// A WebAssembly module exporting the word `name` as a function of
+    /// that name, to use outside the interpreter. The function takes the
+    /// cells the word reads as `i32` parameters, bottom of the stack first,
+    /// and returns those it leaves; arithmetic that overflows traps. Words
+    /// it calls are compiled in, so the module needs no imports. Throws if
+    /// `name`, or a word it calls, isn't a colon definition, if it uses anything but literals,
+    /// `+ - * 1+ 1- 2* 2/ CELL+ CELLS CHAR+ CHARS`, `DUP DROP SWAP OVER`
+    /// and words made of those, or with 64-bit cells. Only with the
+    /// `export` feature.
+    #[wasm_bindgen(js_name = exportWasm)]
+    pub fn export_wasm(&self, name: &str) -> Result<Vec<u8>, JsValue> {
+        let forth = self.forth.borrow();
+        let mut body = Vec::new();
+        flatten(&forth, name, 0, &mut body).map_err(|e| JsValue::from_str(&e))?;
+        let word = translate(&body, name).map_err(|e| JsValue::from_str(&e))?;
+        Ok(word.module)
+    }
+}
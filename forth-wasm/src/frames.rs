@@ -0,0 +1,130 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+
+use forth_core::Forth;
+
+use super::terminal::grid_to_js;
+use super::{error_to_js, from_js, Interpreter, JsFrameOptions};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = requestAnimationFrame)]
+    fn request_animation_frame(callback: &JsValue) -> i32;
+}
+
+/// How `Forth.startFrames` runs frames, given from JavaScript as a
+/// `FrameOptions` object.
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct FrameOptions {
+    /// Most instructions a frame may run.
+    fuel: u64,
+    /// Called with the `EvalError` a frame fails with.
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    on_error: JsValue,
+    /// Called with the `TerminalGrid` after each frame that changed it.
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    on_terminal: JsValue,
+}
+
+impl Default for FrameOptions {
+    fn default() -> Self {
+        FrameOptions { fuel: 1_000_000, on_error: JsValue::UNDEFINED, on_terminal: JsValue::UNDEFINED }
+    }
+}
+
+/// Frames being run for an interpreter, until this is dropped.
+pub(super) struct Animation {
+    running: Rc<Cell<bool>>,
+}
+
+impl Drop for Animation {
+    fn drop(&mut self) {
+        self.running.set(false);
+    }
+}
+
+/// A loop of frames, carried from one to the next.
+struct FrameLoop {
+    forth: Rc<RefCell<Forth>>,
+    fuel: u64,
+    on_error: Option<Function>,
+    on_terminal: Option<Function>,
+    /// Version of the grid `on_terminal` was last given.
+    drawn: Option<u64>,
+    running: Rc<Cell<bool>>,
+}
+
+impl FrameLoop {
+    fn schedule(self) {
+        // Each callback frees itself once called, so only the last of a
+        // stopped loop leaks.
+        let callback = Closure::once_into_js(move || self.tick());
+        request_animation_frame(&callback);
+    }
+
+    /// Runs the frame word, unless the interpreter is busy, then asks for
+    /// the next frame.
+    fn tick(mut self) {
+        if !self.running.get() {
+            return;
+        }
+        if let Ok(mut forth) = self.forth.try_borrow_mut() {
+            let result = forth.run_frame(Some(self.fuel));
+            let version = forth.terminal().map(|grid| grid.version());
+            let grid = match self.on_terminal {
+                Some(_) if version != self.drawn => grid_to_js(&forth),
+                _ => None,
+            };
+            drop(forth);
+            if let Err(e) = result {
+                if let Some(ref on_error) = self.on_error {
+                    let _ = on_error.call1(&JsValue::NULL, &error_to_js(e));
+                }
+            }
+            if let (Some(ref on_terminal), Some(grid)) = (&self.on_terminal, grid) {
+                self.drawn = version;
+                let _ = on_terminal.call1(&JsValue::NULL, &grid);
+            }
+        }
+        self.schedule();
+    }
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Runs the word EVERY-FRAME chooses on each animation frame, with at
+    /// most `options.fuel` instructions per frame, until `stopFrames`.
+    /// Frames are skipped while an evaluation is running or paused, such
+    /// as between the chunks of `evalAsync`. A frame that fails stops its
+    /// word being run, as STOP-FRAMES does, and is passed to
+    /// `options.onError`. Output goes to the output callback, if set, and
+    /// the grid of the `terminal` option to `options.onTerminal` after each
+    /// frame that changes it.
+    /// Throws if `options` is malformed.
+    #[wasm_bindgen(js_name = startFrames)]
+    pub fn start_frames(&mut self, options: Option<JsFrameOptions>) -> Result<(), JsValue> {
+        let options: FrameOptions = from_js(&options.map_or(JsValue::UNDEFINED, JsValue::from))?;
+        let running = Rc::new(Cell::new(true));
+        FrameLoop {
+            forth: self.forth.clone(),
+            fuel: options.fuel,
+            on_error: options.on_error.dyn_into().ok(),
+            on_terminal: options.on_terminal.dyn_into().ok(),
+            drawn: None,
+            running: running.clone(),
+        }.schedule();
+        self.animation = Some(Animation { running });
+        Ok(())
+    }
+
+    /// Stops running frames. The word EVERY-FRAME chose stays chosen for
+    /// the next `startFrames`.
+    #[wasm_bindgen(js_name = stopFrames)]
+    pub fn stop_frames(&mut self) {
+        self.animation = None;
+    }
+}
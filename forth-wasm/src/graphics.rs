@@ -0,0 +1,141 @@
+//! Turtle graphics words that draw on a canvas supplied by the page, with
+//! the `graphics` feature.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use forth_core::{Error, Forth, ForthResult, Stack};
+
+/// Where the turtle is on the canvas and whether it draws as it moves.
+struct Turtle {
+    context: CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    x: f64,
+    y: f64,
+    /// Degrees clockwise from straight up.
+    heading: f64,
+    pen_down: bool,
+}
+
+impl Turtle {
+    /// Puts the turtle in the middle of the canvas, facing up.
+    fn home(&mut self) {
+        self.x = self.width / 2.0;
+        self.y = self.height / 2.0;
+        self.heading = 0.0;
+    }
+
+    fn line(&self, x1: f64, y1: f64, x2: f64, y2: f64) {
+        self.context.begin_path();
+        self.context.move_to(x1, y1);
+        self.context.line_to(x2, y2);
+        self.context.stroke();
+    }
+}
+
+/// Takes the top `n` cells off `stack`, bottom first, failing with
+/// `StackUnderflow` and leaving the stack alone if there are fewer.
+fn pop_args(stack: &mut Stack, n: usize) -> Result<Vec<f64>, Error> {
+    if stack.len() < n {
+        return Err(Error::StackUnderflow);
+    }
+    let start = stack.len() - n;
+    Ok(stack.drain(start..).map(|v| v as f64).collect())
+}
+
+/// Defines `name` as a word running `f` on the turtle.
+fn define<F>(forth: &mut Forth, turtle: &Rc<RefCell<Turtle>>, name: &str, f: F)
+    where F: Fn(&mut Turtle, &mut Stack) -> ForthResult + 'static
+{
+    let turtle = turtle.clone();
+    forth.define_native(name, move |stack| f(&mut turtle.borrow_mut(), stack));
+}
+
+/// Defines the graphics words, drawing on `canvas` with a turtle that
+/// starts in its middle, facing up, with the pen down:
+///
+/// - `PENDOWN` ( -- ) and `PENUP` ( -- ) start and stop the turtle drawing
+///   as it moves.
+/// - `FORWARD` ( n -- ) moves the turtle `n` pixels the way it faces.
+/// - `TURN` ( degrees -- ) turns it clockwise.
+/// - `HOME` ( -- ) moves it back to the middle, facing up, without drawing.
+/// - `LINE` ( x1 y1 x2 y2 -- ) and `RECT` ( x y width height -- ) draw
+///   at the coordinates given, leaving the turtle where it is.
+/// - `COLOR` ( r g b -- ) sets the colour drawn in, each part 0 to 255.
+/// - `CLEARSCREEN` ( -- ) erases the canvas and sends the turtle home.
+///
+/// Fails if the canvas has no 2D context.
+pub fn attach(forth: &mut Forth, canvas: &HtmlCanvasElement) -> Result<(), JsValue> {
+    let context = canvas.get_context("2d")?
+        .ok_or_else(|| JsValue::from_str("canvas has no 2d context"))?
+        .dyn_into::<CanvasRenderingContext2d>()?;
+    let mut turtle = Turtle {
+        context,
+        width: canvas.width() as f64,
+        height: canvas.height() as f64,
+        x: 0.0,
+        y: 0.0,
+        heading: 0.0,
+        pen_down: true,
+    };
+    turtle.home();
+    let turtle = Rc::new(RefCell::new(turtle));
+
+    define(forth, &turtle, "PENDOWN", |turtle, _| {
+        turtle.pen_down = true;
+        Ok(())
+    });
+    define(forth, &turtle, "PENUP", |turtle, _| {
+        turtle.pen_down = false;
+        Ok(())
+    });
+    define(forth, &turtle, "FORWARD", |turtle, stack| {
+        let distance = pop_args(stack, 1)?[0];
+        let heading = turtle.heading.to_radians();
+        let (x, y) = (turtle.x + distance * heading.sin(), turtle.y - distance * heading.cos());
+        if turtle.pen_down {
+            turtle.line(turtle.x, turtle.y, x, y);
+        }
+        turtle.x = x;
+        turtle.y = y;
+        Ok(())
+    });
+    define(forth, &turtle, "TURN", |turtle, stack| {
+        let degrees = pop_args(stack, 1)?[0];
+        turtle.heading = (turtle.heading + degrees) % 360.0;
+        Ok(())
+    });
+    define(forth, &turtle, "HOME", |turtle, _| {
+        turtle.home();
+        Ok(())
+    });
+    define(forth, &turtle, "LINE", |turtle, stack| {
+        let args = pop_args(stack, 4)?;
+        turtle.line(args[0], args[1], args[2], args[3]);
+        Ok(())
+    });
+    define(forth, &turtle, "RECT", |turtle, stack| {
+        let args = pop_args(stack, 4)?;
+        turtle.context.stroke_rect(args[0], args[1], args[2], args[3]);
+        Ok(())
+    });
+    define(forth, &turtle, "COLOR", |turtle, stack| {
+        let args = pop_args(stack, 3)?;
+        let part = |v: f64| v.clamp(0.0, 255.0);
+        let color = format!("rgb({}, {}, {})", part(args[0]), part(args[1]), part(args[2]));
+        turtle.context.set_stroke_style_str(&color);
+        turtle.context.set_fill_style_str(&color);
+        Ok(())
+    });
+    define(forth, &turtle, "CLEARSCREEN", |turtle, _| {
+        turtle.context.clear_rect(0.0, 0.0, turtle.width, turtle.height);
+        turtle.home();
+        Ok(())
+    });
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use forth_core::{Error, Forth, ForthResult};
+
+use super::{
+    js_error, result_stack, to_js, warnings, EvalError, EvalResult, EvalStats, Interpreter, JsEvalResultPromise,
+};
+
+#[wasm_bindgen]
+extern "C" {
+    fn fetch(url: &str) -> Promise;
+
+    type Response;
+
+    #[wasm_bindgen(method, getter)]
+    fn ok(this: &Response) -> bool;
+    #[wasm_bindgen(method, getter)]
+    fn status(this: &Response) -> u16;
+    #[wasm_bindgen(method)]
+    fn text(this: &Response) -> Promise;
+}
+
+/// An `evalIncluding` in progress, carried from one fetch to the next.
+struct AsyncInclude {
+    forth: Rc<RefCell<Forth>>,
+    paused_code: Rc<RefCell<String>>,
+    code: String,
+    /// Output of the evaluation and the files included so far, each of
+    /// which starts afresh, and whether any of it was cut short.
+    output: String,
+    output_truncated: bool,
+    max_stack_items: Option<usize>,
+    resolve: Function,
+}
+
+/// Calls `fulfilled` or `rejected` with `include` once `promise` settles.
+fn settle<F, R>(promise: &Promise, include: AsyncInclude, fulfilled: F, rejected: R)
+    where F: FnOnce(AsyncInclude, JsValue) + 'static,
+          R: FnOnce(AsyncInclude, JsValue) + 'static
+{
+    let include = Rc::new(RefCell::new(Some(include)));
+    let taken = include.clone();
+    let fulfilled = Closure::once_into_js(move |value: JsValue| {
+        if let Some(include) = taken.borrow_mut().take() {
+            fulfilled(include, value);
+        }
+    });
+    let rejected = Closure::once_into_js(move |e: JsValue| {
+        if let Some(include) = include.borrow_mut().take() {
+            rejected(include, e);
+        }
+    });
+    // `Promise::then2` wants closures kept alive by hand; these free
+    // themselves once called, leaving only the other to leak.
+    let then: Function = Reflect::get(promise, &JsValue::from_str("then")).unwrap().unchecked_into();
+    let _ = then.call2(promise, &fulfilled, &rejected);
+}
+
+impl AsyncInclude {
+    /// Fetches the file the evaluation asked for if `result` says it did,
+    /// or settles the promise with the outcome.
+    fn proceed(mut self, result: ForthResult) {
+        let forth = self.forth.borrow();
+        self.output.push_str(forth.output());
+        self.output_truncated |= forth.output_truncated();
+        if forth.is_paused() {
+            *self.paused_code.borrow_mut() = self.code.clone();
+        }
+        let url = match result {
+            Err(Error::IncludeRequested(ref url)) if forth.is_paused() => url.clone(),
+            result => {
+                let error = result.err().map(|e| EvalError::new(&forth, &self.code, &e));
+                drop(forth);
+                self.finish(error);
+                return;
+            }
+        };
+        drop(forth);
+        let failed = url.clone();
+        settle(&fetch(&url), self, move |include, response| {
+            let response: Response = response.unchecked_into();
+            if !response.ok() {
+                let e = Error::Native(format!("could not fetch: status {}", response.status()));
+                include.fail(&url, None, &e);
+                return;
+            }
+            let failed = url.clone();
+            settle(&response.text(), include, move |include, text| {
+                include.included(&url, &text.as_string().unwrap_or_default());
+            }, move |include, e| include.fail(&failed, None, &js_error(e)));
+        }, move |include, e| include.fail(&failed, None, &js_error(e)));
+    }
+
+    /// Evaluates the text of the file at `url`, then carries on with the
+    /// evaluation that asked for it.
+    fn included(mut self, url: &str, text: &str) {
+        let result = self.forth.borrow_mut().include(text);
+        if let Err(e) = result {
+            self.fail(url, Some(text), &e);
+            return;
+        }
+        self.output.push_str(self.forth.borrow().output());
+        self.output_truncated |= self.forth.borrow().output_truncated();
+        let result = self.forth.borrow_mut().resume();
+        self.proceed(result);
+    }
+
+    /// Settles the promise with `e`, raised by the file at `url`, located
+    /// within its `text` if that was evaluated.
+    fn fail(mut self, url: &str, text: Option<&str>, e: &Error) {
+        let forth = self.forth.borrow();
+        let mut error = match text {
+            Some(text) => {
+                self.output.push_str(forth.output());
+                self.output_truncated |= forth.output_truncated();
+                let location = forth.error_location();
+                let mut error = EvalError::at(location, text, e);
+                error.line = location.map(|l| text[..l.offset].matches('\n').count() + 1);
+                error
+            }
+            None => EvalError::at(None, "", e),
+        };
+        error.message = match error.line {
+            Some(line) => format!("{}:{}: {}", url, line, e),
+            None => format!("{}: {}", url, e),
+        };
+        error.url = Some(String::from(url));
+        drop(forth);
+        self.finish(Some(error));
+    }
+
+    fn finish(self, error: Option<EvalError>) {
+        let forth = self.forth.borrow();
+        let (stack, stack_truncated) = result_stack(&forth, self.max_stack_items);
+        let outcome: JsValue = to_js(&EvalResult {
+            stack,
+            stack_truncated,
+            output: self.output,
+            output_truncated: self.output_truncated,
+            warnings: warnings(&forth),
+            stats: EvalStats::from(forth.eval_stats()),
+            defined_words: forth.defined_words(),
+            error,
+        });
+        drop(forth);
+        let _ = self.resolve.call1(&JsValue::NULL, &outcome);
+    }
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Interprets `code` like `evalResult`, but resolves INCLUDE and
+    /// INCLUDED by fetching the named URL, relative to the page, and
+    /// interpreting the text as Forth before carrying on. Resolves to the
+    /// outcome; an error in an included file, or a failure to fetch it,
+    /// gives its `url`, and the `line` within it where known. Files fetched
+    /// this way can't include others; with a source provider set, files
+    /// are read from it instead.
+    #[wasm_bindgen(js_name = evalIncluding)]
+    pub fn eval_including(&mut self, code: &str) -> JsEvalResultPromise {
+        let forth = self.forth.clone();
+        let paused_code = self.paused_code.clone();
+        let code = String::from(code);
+        let max_stack_items = self.forth_options.max_stack_items;
+        let promise = Promise::new(&mut |resolve, _| {
+            let include = AsyncInclude {
+                forth: forth.clone(),
+                paused_code: paused_code.clone(),
+                code: code.clone(),
+                output: String::new(),
+                output_truncated: false,
+                max_stack_items,
+                resolve,
+            };
+            let result = forth.borrow_mut().eval(&code);
+            include.proceed(result);
+        });
+        promise.unchecked_into()
+    }
+}
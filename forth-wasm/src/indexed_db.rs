@@ -0,0 +1,222 @@
+//! Keeping images and blocks in IndexedDB, which holds far more than
+//! `localStorage`, in a database named by the `storageKey` option.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use js_sys::{Array, Function, Promise, Reflect, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use forth_core::{BlockStorage, ForthResult, MemoryBlocks};
+
+use super::worker::{JsBooleanPromise, JsVoidPromise};
+use super::{error_to_js, Interpreter};
+
+/// The object stores of the database: images by name, and blocks by
+/// number, each as `[number, bytes]`.
+const IMAGES: &str = "images";
+const BLOCKS: &str = "blocks";
+/// The name images are saved under when not given one.
+const DEFAULT_IMAGE: &str = "image";
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_namespace = indexedDB, js_name = open)]
+    fn open_request(name: &str, version: u32) -> Result<Request, JsValue>;
+
+    #[derive(Clone)]
+    type Request;
+
+    #[wasm_bindgen(method, getter)]
+    fn result(this: &Request) -> JsValue;
+    #[wasm_bindgen(method, getter)]
+    fn error(this: &Request) -> JsValue;
+    #[wasm_bindgen(method, setter)]
+    fn set_onsuccess(this: &Request, handler: &JsValue);
+    #[wasm_bindgen(method, setter)]
+    fn set_onerror(this: &Request, handler: &JsValue);
+    #[wasm_bindgen(method, setter)]
+    fn set_onupgradeneeded(this: &Request, handler: &JsValue);
+
+    #[derive(Clone)]
+    type Database;
+
+    #[wasm_bindgen(method, catch)]
+    fn transaction(this: &Database, store: &str, mode: &str) -> Result<Transaction, JsValue>;
+    #[wasm_bindgen(method, js_name = createObjectStore)]
+    fn create_object_store(this: &Database, name: &str);
+
+    #[derive(Clone)]
+    type Transaction;
+
+    #[wasm_bindgen(method, js_name = objectStore)]
+    fn object_store(this: &Transaction, name: &str) -> ObjectStore;
+    #[wasm_bindgen(method, getter)]
+    fn error(this: &Transaction) -> JsValue;
+    #[wasm_bindgen(method, setter)]
+    fn set_oncomplete(this: &Transaction, handler: &JsValue);
+    #[wasm_bindgen(method, setter)]
+    fn set_onerror(this: &Transaction, handler: &JsValue);
+    #[wasm_bindgen(method, setter)]
+    fn set_onabort(this: &Transaction, handler: &JsValue);
+
+    type ObjectStore;
+
+    #[wasm_bindgen(method, catch)]
+    fn put(this: &ObjectStore, value: &JsValue, key: &JsValue) -> Result<Request, JsValue>;
+    #[wasm_bindgen(method, catch)]
+    fn get(this: &ObjectStore, key: &JsValue) -> Result<Request, JsValue>;
+    #[wasm_bindgen(method, catch, js_name = getAll)]
+    fn get_all(this: &ObjectStore) -> Result<Request, JsValue>;
+}
+
+/// The promise `promise.then(fulfilled)` returns. `fulfilled` frees
+/// itself once called, and is left to leak if `promise` rejects.
+fn then<F>(promise: &Promise, fulfilled: F) -> Promise
+    where F: FnOnce(JsValue) -> Result<JsValue, JsValue> + 'static
+{
+    let fulfilled = Closure::once_into_js(fulfilled);
+    let then: Function = Reflect::get(promise, &JsValue::from_str("then")).unwrap().unchecked_into();
+    then.call1(promise, &fulfilled).unwrap().unchecked_into()
+}
+
+/// Settles with the result of `request`, or its error.
+fn requested(request: &Request) -> Promise {
+    Promise::new(&mut |resolve, reject| {
+        let done = request.clone();
+        let failed = request.clone();
+        request.set_onsuccess(&Closure::once_into_js(move |_: JsValue| {
+            let _ = resolve.call1(&JsValue::NULL, &done.result());
+        }));
+        request.set_onerror(&Closure::once_into_js(move |_: JsValue| {
+            let _ = reject.call1(&JsValue::NULL, &failed.error());
+        }));
+    })
+}
+
+/// Settles once `transaction` has committed, or with its error.
+fn committed(transaction: &Transaction) -> Promise {
+    Promise::new(&mut |resolve, reject| {
+        let failed = transaction.clone();
+        let fail = Closure::once_into_js(move |_: JsValue| {
+            let _ = reject.call1(&JsValue::NULL, &failed.error());
+        });
+        transaction.set_oncomplete(&Closure::once_into_js(move |_: JsValue| {
+            let _ = resolve.call0(&JsValue::NULL);
+        }));
+        transaction.set_onerror(&fail);
+        transaction.set_onabort(&fail);
+    })
+}
+
+/// Opens the database named `name`, making its object stores the first
+/// time.
+fn open(name: &str) -> Promise {
+    let request = match open_request(name, 1) {
+        Ok(request) => request,
+        Err(e) => return Promise::reject(&e),
+    };
+    let upgrading = request.clone();
+    request.set_onupgradeneeded(&Closure::once_into_js(move |_: JsValue| {
+        let database: Database = upgrading.result().unchecked_into();
+        database.create_object_store(IMAGES);
+        database.create_object_store(BLOCKS);
+    }));
+    requested(&request)
+}
+
+/// Blocks read from IndexedDB when `useIndexedDbBlocks` was called, kept
+/// in memory for BLOCK, and written through to the database as FLUSH
+/// writes them.
+struct IndexedDbBlocks {
+    blocks: MemoryBlocks,
+    database: Database,
+}
+
+impl BlockStorage for IndexedDbBlocks {
+    fn read(&mut self, n: u32, block: &mut [u8]) -> ForthResult {
+        self.blocks.read(n, block)
+    }
+
+    fn write(&mut self, n: u32, block: &[u8]) -> ForthResult {
+        self.blocks.write(n, block)?;
+        // A failed write shows in the transaction; the copy in memory
+        // stays good for this session either way.
+        if let Ok(transaction) = self.database.transaction(BLOCKS, "readwrite") {
+            let value = Array::of2(&JsValue::from(n), &Uint8Array::from(block));
+            let _ = transaction.object_store(BLOCKS).put(&value, &JsValue::from(n));
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Saves the dictionary and data space, as `saveImage` gives them, in
+    /// IndexedDB under `name`, by default `"image"`. Resolves once they
+    /// are stored.
+    #[wasm_bindgen(js_name = saveImageToIndexedDb)]
+    pub fn save_image_to_indexed_db(&self, name: Option<String>) -> JsVoidPromise {
+        let image = Uint8Array::from(&self.forth.borrow().save_image()[..]);
+        let name = JsValue::from(name.as_deref().unwrap_or(DEFAULT_IMAGE));
+        let saved = then(&open(&self.forth_options.storage_key()), move |database| {
+            let transaction = database.unchecked_into::<Database>().transaction(IMAGES, "readwrite")?;
+            transaction.object_store(IMAGES).put(&image, &name)?;
+            Ok(committed(&transaction).into())
+        });
+        saved.unchecked_into()
+    }
+
+    /// Replaces the dictionary and data space with the image saved in
+    /// IndexedDB under `name`, by default `"image"`, as `loadImage` does.
+    /// Resolves to whether there was one, or rejects with an `EvalError`
+    /// if it is invalid.
+    #[wasm_bindgen(js_name = loadImageFromIndexedDb)]
+    pub fn load_image_from_indexed_db(&self, name: Option<String>) -> JsBooleanPromise {
+        let forth = self.forth.clone();
+        let name = JsValue::from(name.as_deref().unwrap_or(DEFAULT_IMAGE));
+        let image = then(&open(&self.forth_options.storage_key()), move |database| {
+            let transaction = database.unchecked_into::<Database>().transaction(IMAGES, "readonly")?;
+            Ok(requested(&transaction.object_store(IMAGES).get(&name)?).into())
+        });
+        let loaded = then(&image, move |image| {
+            if image.is_undefined() {
+                return Ok(JsValue::FALSE);
+            }
+            let image = Uint8Array::new(&image).to_vec();
+            forth.borrow_mut().load_image(&image).map_err(error_to_js)?;
+            Ok(JsValue::TRUE)
+        });
+        loaded.unchecked_into()
+    }
+
+    /// Has the block words keep blocks in IndexedDB rather than
+    /// `localStorage`, until the interpreter is reset. Resolves once the
+    /// blocks saved before are read.
+    #[wasm_bindgen(js_name = useIndexedDbBlocks)]
+    pub fn use_indexed_db_blocks(&self) -> JsVoidPromise {
+        let forth = self.forth.clone();
+        let database = Rc::new(RefCell::new(None));
+        let opened = database.clone();
+        let saved = then(&open(&self.forth_options.storage_key()), move |database| {
+            let database: Database = database.unchecked_into();
+            let transaction = database.transaction(BLOCKS, "readonly")?;
+            let saved = requested(&transaction.object_store(BLOCKS).get_all()?);
+            *opened.borrow_mut() = Some(database);
+            Ok(saved.into())
+        });
+        let used = then(&saved, move |saved| {
+            let mut blocks = MemoryBlocks::default();
+            for entry in Array::from(&saved).iter() {
+                let entry = Array::from(&entry);
+                let n = entry.get(0).as_f64().unwrap_or(0.0) as u32;
+                blocks.write(n, &Uint8Array::new(&entry.get(1)).to_vec()).map_err(error_to_js)?;
+            }
+            let database = database.borrow_mut().take().unwrap();
+            forth.borrow_mut().set_block_storage(IndexedDbBlocks { blocks, database });
+            Ok(JsValue::UNDEFINED)
+        });
+        used.unchecked_into()
+    }
+}
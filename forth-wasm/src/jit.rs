@@ -0,0 +1,107 @@
+//! Compiling hot colon definitions to WebAssembly, with the `jit` feature.
+//! A call whose arithmetic overflows traps, leaving the word to the
+//! interpreter to report or wrap the result as configured.
+
+use std::cell::RefCell;
+
+use js_sys::{Array, Function, Object, Reflect, Uint8Array, WebAssembly};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use forth_core::{CompiledWord, Forth, Instruction, Stack, Value};
+
+use super::codegen::{translate, WasmWord};
+use super::Interpreter;
+
+/// Calls of a word before it is compiled, unless `enableJit` says.
+const THRESHOLD: u32 = 100;
+
+/// How far a word has got towards running as WebAssembly.
+enum State {
+    /// Interpreted, having been called so many times.
+    Cold(u32),
+    Compiled(Function),
+    /// The browser refused the module.
+    Failed,
+}
+
+/// A word to run as WebAssembly once it has been called often enough.
+struct JitWord {
+    code: WasmWord,
+    threshold: u32,
+    state: RefCell<State>,
+}
+
+impl JitWord {
+    /// The compiled function, once the word is hot.
+    fn function(&self) -> Option<Function> {
+        let mut state = self.state.borrow_mut();
+        if let State::Cold(calls) = *state {
+            if calls + 1 < self.threshold {
+                *state = State::Cold(calls + 1);
+                return None;
+            }
+            *state = match instantiate(&self.code.module) {
+                Ok(function) => State::Compiled(function),
+                Err(_) => State::Failed,
+            };
+        }
+        match *state {
+            State::Compiled(ref function) => Some(function.clone()),
+            _ => None,
+        }
+    }
+
+    fn run(&self, stack: &mut Stack) -> Option<()> {
+        let base = stack.len().checked_sub(self.code.inputs)?;
+        let function = self.function()?;
+        let args: Array = stack[base..].iter().map(|&v| JsValue::from(v)).collect();
+        // A trap, from overflow, leaves the word to the interpreter.
+        let results = function.apply(&JsValue::NULL, &args).ok()?;
+        stack.truncate(base);
+        match self.code.outputs {
+            0 => {}
+            1 => stack.push(results.as_f64()? as Value),
+            _ => stack.extend(Array::from(&results).iter().map(|v| v.as_f64().unwrap_or(0.0) as Value)),
+        }
+        Some(())
+    }
+}
+
+fn instantiate(module: &[u8]) -> Result<Function, JsValue> {
+    let module = WebAssembly::Module::new(&Uint8Array::from(module))?;
+    let instance = WebAssembly::Instance::new(&module, &Object::new())?;
+    Ok(Reflect::get(&instance.exports(), &JsValue::from_str("run"))?.unchecked_into())
+}
+
+/// Has colon definitions defined from now on compiled to WebAssembly once
+/// they have been called `threshold` times.
+fn enable(forth: &mut Forth, threshold: u32) {
+    forth.set_word_compiler(move |_name: &str, body: &[Instruction]| -> Option<Box<CompiledWord>> {
+        let code = translate(body, "run").ok()?;
+        let word = JitWord { code, threshold, state: RefCell::new(State::Cold(0)) };
+        Some(Box::new(move |stack: &mut Stack| word.run(stack).map(Ok)))
+    });
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Compiles colon definitions made from now on to WebAssembly once
+    /// they have been called `threshold` times, 100 unless given. Only
+    /// definitions of literals, `+ - * 1+ 1- 2* 2/ CELL+ CELLS CHAR+ CHARS`
+    /// and `DUP DROP SWAP OVER` are compiled, and only with 32-bit cells;
+    /// the interpreter runs the rest, and any call that overflows or
+    /// underflows the stack. Compiled words still take fuel for each
+    /// instruction, and are stepped through by `debug`. Like output
+    /// callbacks, this is undone by `reset`. Only with the `jit` feature.
+    #[wasm_bindgen(js_name = enableJit)]
+    pub fn enable_jit(&mut self, threshold: Option<u32>) {
+        enable(&mut self.forth.borrow_mut(), threshold.unwrap_or(THRESHOLD));
+    }
+
+    /// Stops compiling new definitions. Words already compiled stay so.
+    #[wasm_bindgen(js_name = disableJit)]
+    pub fn disable_jit(&mut self) {
+        self.forth.borrow_mut().clear_word_compiler();
+    }
+}
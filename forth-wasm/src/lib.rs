@@ -0,0 +1,1998 @@
+extern crate forth_core;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+extern crate serde_wasm_bindgen;
+extern crate cfg_if;
+extern crate wasm_bindgen;
+extern crate js_sys;
+#[cfg(any(feature = "graphics", feature = "dom", feature = "audio", feature = "websocket"))]
+extern crate web_sys;
+#[cfg(any(feature = "jit", feature = "export"))]
+extern crate wasm_encoder;
+
+mod utils;
+mod async_eval;
+mod channels;
+#[cfg(any(feature = "jit", feature = "export"))]
+mod codegen;
+mod events;
+#[cfg(feature = "export")]
+mod export;
+mod frames;
+mod include;
+mod indexed_db;
+#[cfg(feature = "jit")]
+mod jit;
+mod worker;
+mod recording;
+mod render;
+mod session;
+mod terminal;
+mod timers;
+mod transpile;
+#[cfg(feature = "graphics")]
+mod graphics;
+#[cfg(feature = "dom")]
+mod dom;
+#[cfg(feature = "audio")]
+mod audio;
+#[cfg(feature = "websocket")]
+mod websocket;
+#[cfg(feature = "node")]
+mod node;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use cfg_if::cfg_if;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+cfg_if! {
+    // When the `wee_alloc` feature is enabled, use `wee_alloc` as the global
+    // allocator.
+    if #[cfg(feature = "wee_alloc")] {
+        extern crate wee_alloc;
+        #[global_allocator]
+        static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
+    }
+}
+
+use forth_core::{
+    BlockStorage, Case, Clock, DefinedWord, Diagnostic, FormatOptions, Forth, ForthResult, Error, Instruction, Io, Language,
+    Location, Program, SourceProvider, SourceToken, TimeAndDate, TokenKind, Value, Watchpoint, WordDefinition,
+};
+use render::{escape_html, RenderOptions};
+pub use session::SessionManager;
+pub use worker::InterpreterWorker;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &'static str = r#"
+export type ErrorKind = "DivisionByZero" | "Overflow" | "StackUnderflow" | "UnknownWord"
+    | "StringStackUnderflow" | "InvalidWord" | "InvalidWordlist" | "InvalidAddress" | "InvalidBase" | "NoInput"
+    | "NoStorage" | "NoClock"
+    | "NumberOutOfRange" | "StackOverflow" | "ReturnStackOverflow" | "CallDepthExceeded"
+    | "OutOfFuel" | "Stopped" | "DictionaryFull" | "ProtectedWord"
+    | "DictionaryFrozen" | "DataSpaceFull" | "OutputLimitExceeded" | "InputTooLarge" | "NameTooLong"
+    | "DefinitionTooLong"
+    | "InvalidSnapshot" | "InvalidImage" | "UnterminatedDefinition"
+    | "SemicolonOutsideDefinition" | "NestedDefinition" | "MissingName" | "NumericName" | "UnbalancedControl"
+    | "UnbalancedStack" | "IncludeRequested" | "WaitRequested" | "PauseRequested" | "FileNotFound" | "InvalidBlock"
+    | "TypeMismatch" | "Native" | "Callback" | "Thrown";
+
+export interface EvalError {
+    kind: ErrorKind;
+    message: string;
+    detail?: string;
+    throwCode: number;
+    word?: string;
+    start?: number;
+    end?: number;
+    tokenIndex?: number;
+    url?: string;
+    line?: number;
+    trace: ErrorFrame[];
+}
+
+export interface ErrorFrame {
+    word: string;
+    token: string;
+    line?: number;
+    column?: number;
+}
+
+export interface EvalWarning {
+    kind: "Redefined" | "NumericName";
+    name: string;
+    message: string;
+}
+
+export interface EvalResult {
+    stack: Cell[];
+    stackTruncated: boolean;
+    output: string;
+    outputTruncated: boolean;
+    warnings: EvalWarning[];
+    stats: EvalStats;
+    definedWords: DefinedWord[];
+    error?: EvalError;
+}
+
+export interface DefinedWord {
+    name: string;
+    shadows: boolean;
+}
+
+export interface EvalStats {
+    operations: number;
+    maxStackDepth: number;
+    maxReturnStackDepth: number;
+    wordsDefined: number;
+    dataSpaceUsed: number;
+}
+
+export interface TraceOptions {
+    maxSteps?: number;
+    stackDepth?: number;
+}
+
+export interface TraceStep {
+    token: string;
+    start: number;
+    end: number;
+    stack: Cell[];
+    depth: number;
+}
+
+export interface TraceResult extends EvalResult {
+    steps: TraceStep[];
+    truncated: boolean;
+}
+
+export interface RecordOptions {
+    maxSteps?: number;
+}
+
+export interface MemoryWrite {
+    addr: number;
+    before: number[];
+    after: number[];
+}
+
+export interface RecordedStep {
+    token: string;
+    start: number;
+    end: number;
+    popped: Cell[];
+    pushed: Cell[];
+    writes: MemoryWrite[];
+}
+
+export interface RecoveryResult extends EvalResult {
+    diagnostics: EvalError[];
+}
+
+export interface WatchHit {
+    cell?: Cell;
+    stackDepth?: number;
+    word?: string;
+}
+
+export interface DebugResult extends EvalResult {
+    stopped: boolean;
+    watch?: WatchHit;
+}
+
+export interface CallFrame {
+    word: string;
+    pc: number;
+    start?: number;
+    end?: number;
+}
+
+export interface InterpretOptions {
+    input?: string;
+}
+
+export interface ForthOptions {
+    prelude?: string;
+    skipStandardPrelude?: boolean;
+    maxWords?: number;
+    maxOutput?: number;
+    maxInputLen?: number;
+    maxTokens?: number;
+    maxNameLen?: number;
+    maxDefinitionLen?: number;
+    dataSpaceSize?: number;
+    truncateOutput?: boolean;
+    maxStackItems?: number;
+    standard?: boolean;
+    deterministic?: boolean;
+    frozen?: boolean;
+    undo?: boolean;
+    strings?: boolean;
+    typed?: boolean;
+    echoInput?: boolean;
+    okPrompt?: boolean;
+    terminal?: TerminalSize;
+    language?: string;
+    errorMessages?: Record<string, string>;
+    warningMessages?: Record<string, string>;
+    storageKey?: string;
+}
+
+export interface RenderOptions {
+    mode?: "html" | "text";
+    separator?: string;
+    topFirst?: boolean;
+    base?: number;
+    maxItems?: number;
+}
+
+export type TokenKind = "Word" | "Number" | "Unknown" | "OutOfRange" | "Defining" | "Definition"
+    | "Name" | "Comment" | "String";
+
+export interface Token {
+    kind: TokenKind;
+    text: string;
+    start: number;
+    end: number;
+}
+
+export interface WordInfo {
+    name: string;
+    user_defined: boolean;
+    immediate: boolean;
+    length: number;
+    wordlist: number;
+    doc?: string;
+}
+
+export interface GcStats {
+    words: number;
+    bytes: number;
+}
+
+export interface WordProfile {
+    name: string;
+    calls: number;
+    time: number;
+}
+
+export interface WordCoverage {
+    name: string;
+    user_defined: boolean;
+    executed: boolean;
+    instructions: boolean[];
+}
+
+export interface WordDefinition {
+    name: string;
+    body: string;
+}
+
+export interface EvalAsyncOptions {
+    chunkSize?: number;
+    signal?: { readonly aborted: boolean; readonly reason?: any };
+}
+
+export interface FrameOptions {
+    fuel?: number;
+    onError?: (error: EvalError) => void;
+    onTerminal?: (grid: TerminalGrid) => void;
+}
+
+export interface TerminalSize {
+    columns: number;
+    rows: number;
+}
+
+export interface TerminalCell {
+    ch: string;
+    fg: number;
+    bg: number;
+    bold: boolean;
+}
+
+export interface TerminalGrid {
+    columns: number;
+    rows: number;
+    cursorX: number;
+    cursorY: number;
+    version: number;
+    cells: TerminalCell[][];
+}
+
+export interface TimerOptions {
+    fuel?: number;
+    interval?: number;
+    onError?: (error: EvalError) => void;
+}
+"#;
+
+/// Cells are numbers, or BigInts with the `bigint` feature, whose 64-bit
+/// cells don't all fit in a number.
+#[cfg(not(feature = "bigint"))]
+#[wasm_bindgen(typescript_custom_section)]
+const CELL_TYPE: &'static str = "export type Cell = number;";
+#[cfg(feature = "bigint")]
+#[wasm_bindgen(typescript_custom_section)]
+const CELL_TYPE: &'static str = "export type Cell = bigint;";
+
+#[cfg(feature = "node")]
+#[wasm_bindgen(typescript_custom_section)]
+const NODE_TYPES: &'static str = r#"
+export interface NodeOptions {
+    baseDir?: string;
+}
+"#;
+
+#[cfg(not(feature = "bigint"))]
+type CellArray = js_sys::Int32Array;
+#[cfg(feature = "bigint")]
+type CellArray = js_sys::BigInt64Array;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "EvalResult")]
+    pub type JsEvalResult;
+    #[wasm_bindgen(typescript_type = "Promise<EvalResult>")]
+    pub type JsEvalResultPromise;
+    #[wasm_bindgen(typescript_type = "EvalError[]")]
+    pub type JsEvalErrors;
+    #[wasm_bindgen(typescript_type = "TraceResult")]
+    pub type JsTraceResult;
+    #[wasm_bindgen(typescript_type = "TraceOptions")]
+    pub type JsTraceOptions;
+    #[wasm_bindgen(typescript_type = "RecordOptions")]
+    pub type JsRecordOptions;
+    #[wasm_bindgen(typescript_type = "RecordedStep[]")]
+    pub type JsRecordedSteps;
+    #[wasm_bindgen(typescript_type = "RecoveryResult")]
+    pub type JsRecoveryResult;
+    #[wasm_bindgen(typescript_type = "DebugResult")]
+    pub type JsDebugResult;
+    #[wasm_bindgen(typescript_type = "CallFrame[]")]
+    pub type JsCallFrames;
+    #[wasm_bindgen(typescript_type = "InterpretOptions")]
+    pub type JsInterpretOptions;
+    #[wasm_bindgen(typescript_type = "EvalAsyncOptions")]
+    pub type JsEvalAsyncOptions;
+    #[wasm_bindgen(typescript_type = "FrameOptions")]
+    pub type JsFrameOptions;
+    #[wasm_bindgen(typescript_type = "TerminalGrid")]
+    pub type JsTerminalGrid;
+    #[wasm_bindgen(typescript_type = "TimerOptions")]
+    pub type JsTimerOptions;
+    #[wasm_bindgen(typescript_type = "ForthOptions")]
+    pub type JsForthOptions;
+    #[cfg(feature = "node")]
+    #[wasm_bindgen(typescript_type = "NodeOptions")]
+    pub type JsNodeOptions;
+    #[wasm_bindgen(typescript_type = "RenderOptions")]
+    pub type JsRenderOptions;
+    #[wasm_bindgen(typescript_type = "Token[]")]
+    pub type JsTokens;
+    #[wasm_bindgen(typescript_type = "WordInfo[]")]
+    pub type JsWordInfos;
+    #[wasm_bindgen(typescript_type = "WordDefinition[]")]
+    pub type JsWordDefinitions;
+    #[wasm_bindgen(typescript_type = "GcStats")]
+    pub type JsGcStats;
+    #[wasm_bindgen(typescript_type = "WordProfile[]")]
+    pub type JsWordProfiles;
+    #[wasm_bindgen(typescript_type = "WordCoverage[]")]
+    pub type JsWordCoverage;
+
+    #[wasm_bindgen(js_namespace = console, js_name = log)]
+    fn console_log(text: &str);
+
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+
+    #[wasm_bindgen(catch, js_namespace = localStorage, js_name = getItem)]
+    fn local_storage_get(key: &str) -> Result<Option<String>, JsValue>;
+    #[wasm_bindgen(catch, js_namespace = localStorage, js_name = setItem)]
+    fn local_storage_set(key: &str, value: &str) -> Result<(), JsValue>;
+}
+
+/// Converts `value` to the plain JavaScript value the TypeScript type
+/// behind `T` describes.
+fn to_js<S: Serialize, T: JsCast>(value: &S) -> T {
+    serde_wasm_bindgen::to_value(value).unwrap().unchecked_into()
+}
+
+/// Serializes `cells` as the TypeScript `Cell[]` type describes.
+#[cfg(not(feature = "bigint"))]
+fn serialize_cells<S: serde::Serializer>(cells: &[Value], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(cells)
+}
+
+/// Serializes `cells` as the TypeScript `Cell[]` type describes, widened
+/// so that `serde_wasm_bindgen` makes BigInts of them.
+#[cfg(feature = "bigint")]
+fn serialize_cells<S: serde::Serializer>(cells: &[Value], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(cells.iter().map(|&v| i128::from(v)))
+}
+
+/// Serializes a cell, if any, as the TypeScript `Cell` type describes.
+#[cfg(not(feature = "bigint"))]
+fn serialize_cell<S: serde::Serializer>(cell: &Option<Value>, serializer: S) -> Result<S::Ok, S::Error> {
+    cell.serialize(serializer)
+}
+
+/// Serializes a cell, if any, as the TypeScript `Cell` type describes.
+#[cfg(feature = "bigint")]
+fn serialize_cell<S: serde::Serializer>(cell: &Option<Value>, serializer: S) -> Result<S::Ok, S::Error> {
+    cell.map(i128::from).serialize(serializer)
+}
+
+/// Reads a plain JavaScript value, throwing if it doesn't have the expected
+/// shape. `undefined` gives the defaults.
+fn from_js<T: Default + for<'de> serde::Deserialize<'de>>(value: &JsValue) -> Result<T, JsValue> {
+    if value.is_undefined() || value.is_null() {
+        return Ok(T::default());
+    }
+    serde_wasm_bindgen::from_value(value.clone()).map_err(JsValue::from)
+}
+
+/// CSS class `highlight` gives tokens of `kind`.
+fn token_class(kind: TokenKind) -> &'static str {
+    match kind {
+        TokenKind::Word => "forth-word",
+        TokenKind::Number => "forth-number",
+        TokenKind::Unknown => "forth-unknown",
+        TokenKind::OutOfRange => "forth-out-of-range",
+        TokenKind::Defining => "forth-defining",
+        TokenKind::Definition => "forth-definition",
+        TokenKind::Name => "forth-name",
+        TokenKind::Comment => "forth-comment",
+        TokenKind::String => "forth-string",
+    }
+}
+
+fn render(f: &Forth, result: ForthResult, options: &RenderOptions) -> String {
+    match result {
+        Ok(()) => {
+            let stack = render::render_stack(f.stack_slice(), options);
+            if f.output().is_empty() {
+                return stack;
+            }
+            let output = options.text(f.output());
+            if stack.is_empty() {
+                output
+            } else {
+                format!("{}{}{}", output, options.line_break(), stack)
+            }
+        }
+        Err(e) => options.text(&format!("Error: {}", f.messages().error(&e))),
+    }
+}
+
+/// Outcome of an evaluation: the resulting stack, bottom first, the text
+/// the program printed, what its definitions were warned of, what it cost
+/// and, if evaluation stopped early, the error. `stackTruncated` says
+/// whether the deepest items were left out for the `maxStackItems` option,
+/// and `outputTruncated` whether output was cut short at `maxOutput` for
+/// the `truncateOutput` option.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EvalResult {
+    #[serde(serialize_with = "serialize_cells")]
+    stack: Vec<Value>,
+    stack_truncated: bool,
+    output: String,
+    output_truncated: bool,
+    warnings: Vec<EvalWarning>,
+    stats: EvalStats,
+    defined_words: Vec<DefinedWord>,
+    error: Option<EvalError>,
+}
+
+/// The stack for a result, bottom first, with only the `max` items nearest
+/// the top if given, and whether any were left out.
+fn result_stack(f: &Forth, max: Option<usize>) -> (Vec<Value>, bool) {
+    let stack = f.stack_slice();
+    let shown = max.unwrap_or(stack.len()).min(stack.len());
+    (stack[stack.len() - shown..].to_vec(), shown < stack.len())
+}
+
+fn eval_result(f: &Forth, code: &str, result: ForthResult, max_stack_items: Option<usize>) -> JsEvalResult {
+    let (stack, stack_truncated) = result_stack(f, max_stack_items);
+    to_js(&EvalResult {
+        stack,
+        stack_truncated,
+        output: String::from(f.output()),
+        output_truncated: f.output_truncated(),
+        warnings: warnings(f),
+        stats: EvalStats::from(f.eval_stats()),
+        defined_words: f.defined_words(),
+        error: result.err().map(|e| EvalError::new(f, code, &e)),
+    })
+}
+
+/// What an evaluation cost and defined, as `Forth::eval_stats` reports.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EvalStats {
+    operations: u64,
+    max_stack_depth: usize,
+    max_return_stack_depth: usize,
+    words_defined: usize,
+    data_space_used: usize,
+}
+
+impl From<forth_core::EvalStats> for EvalStats {
+    fn from(stats: forth_core::EvalStats) -> EvalStats {
+        EvalStats {
+            operations: stats.operations,
+            max_stack_depth: stats.max_stack_depth,
+            max_return_stack_depth: stats.max_return_stack_depth,
+            words_defined: stats.words_defined,
+            data_space_used: stats.data_space_used,
+        }
+    }
+}
+
+/// A warning about a definition that didn't stop evaluation. `kind` is
+/// `Redefined` or `NumericName`, and `name` the word defined.
+#[derive(Serialize)]
+struct EvalWarning {
+    kind: &'static str,
+    name: String,
+    message: String,
+}
+
+/// What the last evaluation's definitions were warned of.
+fn warnings(f: &Forth) -> Vec<EvalWarning> {
+    f.warnings().iter().map(|w| EvalWarning {
+        kind: w.kind(),
+        name: String::from(w.name()),
+        message: f.messages().warning(w),
+    }).collect()
+}
+
+/// An error, with where in the input it happened if known. `kind` is a
+/// stable identifier such as `StackUnderflow`, `detail` the text the error
+/// carries, such as the unknown word, and `throwCode` the code THROW would
+/// give it in a standard system, such as -4. Offsets are in UTF-16 code
+/// units, and `tokenIndex` counts the names in the input before `word`.
+/// `trace` lists the compiled words the error happened in, innermost
+/// first. Methods such as `pop` that fail outside any evaluation throw one
+/// without a location.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EvalError {
+    kind: &'static str,
+    message: String,
+    detail: Option<String>,
+    throw_code: i32,
+    word: Option<String>,
+    start: Option<usize>,
+    end: Option<usize>,
+    token_index: Option<usize>,
+    /// The included file the error happened in, and the line within it.
+    url: Option<String>,
+    line: Option<usize>,
+    trace: Vec<EvalErrorFrame>,
+}
+
+/// A compiled word an error happened in, the token of its definition that
+/// failed or made the call, and the line and column the definition began
+/// at, if known.
+#[derive(Serialize)]
+struct EvalErrorFrame {
+    word: String,
+    token: String,
+    line: Option<usize>,
+    column: Option<usize>,
+}
+
+/// Outcome of `Forth.trace`: an `EvalResult` along with the stack after
+/// each token.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceResult {
+    #[serde(serialize_with = "serialize_cells")]
+    stack: Vec<Value>,
+    stack_truncated: bool,
+    output: String,
+    output_truncated: bool,
+    warnings: Vec<EvalWarning>,
+    error: Option<EvalError>,
+    steps: Vec<TraceStep>,
+    truncated: bool,
+}
+
+/// Outcome of `Forth.evalRecovering`: an `EvalResult` along with the
+/// mistakes evaluation carried on past.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecoveryResult {
+    #[serde(serialize_with = "serialize_cells")]
+    stack: Vec<Value>,
+    stack_truncated: bool,
+    output: String,
+    output_truncated: bool,
+    warnings: Vec<EvalWarning>,
+    error: Option<EvalError>,
+    diagnostics: Vec<EvalError>,
+}
+
+/// A token as traced by `Forth.trace`, with offsets in UTF-16 code units.
+#[derive(Serialize)]
+struct TraceStep {
+    token: String,
+    start: usize,
+    end: usize,
+    #[serde(serialize_with = "serialize_cells")]
+    stack: Vec<Value>,
+    depth: usize,
+}
+
+/// Settings for `Forth.trace`.
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TraceOptions {
+    max_steps: usize,
+    stack_depth: usize,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        let defaults = forth_core::TraceOptions::default();
+        TraceOptions { max_steps: defaults.max_steps, stack_depth: defaults.stack_depth }
+    }
+}
+
+/// Outcome of `Forth.debug`, `step` and `runUntilBreakpoint`: an
+/// `EvalResult`, and whether evaluation stopped to be carried on.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DebugResult {
+    #[serde(serialize_with = "serialize_cells")]
+    stack: Vec<Value>,
+    stack_truncated: bool,
+    output: String,
+    output_truncated: bool,
+    warnings: Vec<EvalWarning>,
+    error: Option<EvalError>,
+    stopped: bool,
+    watch: Option<WatchHit>,
+}
+
+/// The watchpoint that stopped a `debug` session, by the cell or stack
+/// depth it watches, and the word that set it off.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WatchHit {
+    #[serde(serialize_with = "serialize_cell", skip_serializing_if = "Option::is_none")]
+    cell: Option<Value>,
+    stack_depth: Option<usize>,
+    word: Option<String>,
+}
+
+fn debug_result(f: &Forth, code: &str, result: Result<bool, Error>, max_stack_items: Option<usize>) -> JsDebugResult {
+    let (stopped, error) = match result {
+        Ok(stopped) => (stopped, None),
+        Err(e) => (false, Some(EvalError::new(f, code, &e))),
+    };
+    let watch = f.watch_hit().map(|hit| {
+        let (cell, stack_depth) = match hit.watchpoint {
+            Watchpoint::Cell(addr) => (Some(addr), None),
+            Watchpoint::StackDepth(depth) => (None, Some(depth)),
+        };
+        WatchHit { cell, stack_depth, word: hit.word.clone() }
+    });
+    let (stack, stack_truncated) = result_stack(f, max_stack_items);
+    to_js(&DebugResult {
+        stack,
+        stack_truncated,
+        output: String::from(f.output()),
+        output_truncated: f.output_truncated(),
+        warnings: warnings(f),
+        error,
+        stopped,
+        watch,
+    })
+}
+
+/// A compiled word being run, as listed by `Forth.returnStack`. Offsets
+/// are in UTF-16 code units of the word's definition source.
+#[derive(Serialize)]
+struct CallFrame {
+    word: String,
+    pc: usize,
+    start: Option<usize>,
+    end: Option<usize>,
+}
+
+/// Settings for `interpret`.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct InterpretOptions {
+    /// Text queued for KEY and ACCEPT to read.
+    input: String,
+}
+
+/// Settings for the `Forth` constructor, kept for `reset`.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct ForthOptions {
+    /// Source evaluated when the interpreter is created or reset.
+    prelude: Option<String>,
+    /// Whether to leave out the words written in Forth over the primitives,
+    /// as turning off the builder's `standard_prelude` does.
+    skip_standard_prelude: bool,
+    /// Limits on untrusted code, as set by the builder options of the same
+    /// names.
+    max_words: Option<usize>,
+    max_output: Option<usize>,
+    max_input_len: Option<usize>,
+    max_tokens: Option<usize>,
+    max_name_len: Option<usize>,
+    max_definition_len: Option<usize>,
+    data_space_size: Option<usize>,
+    /// Whether output past `max_output` is dropped rather than failing,
+    /// as the builder's `truncate_output` does.
+    truncate_output: bool,
+    /// Most stack items, nearest the top, that results give.
+    max_stack_items: Option<usize>,
+    /// Whether to behave as a standard system, as the builder's `standard`
+    /// does.
+    standard: bool,
+    /// Whether the same program and input always give the same results, as
+    /// the builder's `deterministic` sets; the page's clock and
+    /// `localStorage` are then left alone.
+    deterministic: bool,
+    /// Whether programs may define no words once the prelude has run, as
+    /// the builder's `frozen` sets.
+    frozen: bool,
+    /// Whether evaluations can be reverted with `undo`, as the builder's
+    /// `undo` sets.
+    undo: bool,
+    /// Whether to add the string stack words, as the builder's `strings`
+    /// sets.
+    strings: bool,
+    /// Whether stack items are integers, floats or strings, as the
+    /// builder's `typed` sets, when built with the `typed` feature.
+    typed: bool,
+    /// Whether output starts with the input and ends with ` ok` or the
+    /// error, as the builder's `echo_input` and `ok_prompt` set.
+    echo_input: bool,
+    ok_prompt: bool,
+    /// Size of the grid output is also written to, as the builder's
+    /// `terminal` sets.
+    terminal: Option<terminal::TerminalSize>,
+    /// The tag of the language messages come in, such as `de`, and
+    /// templates to show errors and warnings of the kinds they are keyed
+    /// by with instead, as `Messages` takes them.
+    language: Option<String>,
+    error_messages: BTreeMap<String, String>,
+    warning_messages: BTreeMap<String, String>,
+    /// Name under which SAVE-DICT and LOAD-DICT keep definitions in
+    /// `localStorage`, and the start of the names blocks are kept under.
+    storage_key: Option<String>,
+}
+
+impl ForthOptions {
+    fn storage_key(&self) -> String {
+        self.storage_key.clone().unwrap_or_else(|| String::from("forth-dictionary"))
+    }
+}
+
+/// A token as returned by `Forth.tokenize`.
+#[derive(Serialize)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+// JavaScript strings index by UTF-16 code unit.
+fn utf16_offset(code: &str, offset: usize) -> usize {
+    code[..offset].encode_utf16().count()
+}
+
+impl EvalError {
+    fn new(f: &Forth, code: &str, e: &Error) -> Self {
+        let trace = f.error_trace().iter().map(|frame| EvalErrorFrame {
+            word: frame.word.clone(),
+            token: frame.token.clone(),
+            line: frame.defined_at.map(|(line, _)| line),
+            column: frame.defined_at.map(|(_, column)| column),
+        }).collect();
+        EvalError { trace, message: f.messages().error(e), ..EvalError::at(f.error_location(), code, e) }
+    }
+
+    fn at(location: Option<&Location>, code: &str, e: &Error) -> Self {
+        EvalError {
+            kind: e.kind(),
+            message: e.to_string(),
+            detail: e.detail().map(String::from),
+            throw_code: e.throw_code(),
+            word: location.map(|l| l.word.clone()),
+            start: location.map(|l| utf16_offset(code, l.offset)),
+            end: location.map(|l| utf16_offset(code, l.offset + l.word.len())),
+            token_index: location.map(|l| l.token_index),
+            url: None,
+            line: None,
+            trace: Vec::new(),
+        }
+    }
+
+    fn from_diagnostic(f: &Forth, code: &str, d: &Diagnostic) -> Self {
+        EvalError {
+            kind: d.error.kind(),
+            message: f.messages().error(&d.error),
+            detail: d.error.detail().map(String::from),
+            throw_code: d.error.throw_code(),
+            word: Some(String::from(&code[d.span.start..d.span.end])),
+            start: Some(utf16_offset(code, d.span.start)),
+            end: Some(utf16_offset(code, d.span.end)),
+            token_index: None,
+            url: None,
+            line: None,
+            trace: Vec::new(),
+        }
+    }
+}
+
+/// An `EvalError` for `e`, which happened outside any input, to throw or
+/// pass to a callback.
+fn error_to_js(e: Error) -> JsValue {
+    to_js(&EvalError::at(None, "", &e))
+}
+
+/// Most calls deep a word may go, as recursive words can't be exported
+/// or transpiled.
+const CALL_DEPTH: usize = 64;
+
+/// Appends the body of the word `name` to `body`, with the bodies of the
+/// words it calls in place of the calls.
+fn flatten<'a>(forth: &'a Forth, name: &str, depth: usize, body: &mut Vec<Instruction<'a>>) -> Result<(), String> {
+    if depth == CALL_DEPTH {
+        return Err(format!("{} calls words too deeply to be compiled", name));
+    }
+    let instructions = forth.instructions(name).ok_or_else(|| format!("{} isn't a colon definition", name))?;
+    for instruction in instructions {
+        match instruction {
+            Instruction::Call(word) => flatten(forth, word, depth + 1, body)?,
+            instruction => body.push(instruction),
+        }
+    }
+    Ok(())
+}
+
+/// Interprets `code` in a fresh interpreter and returns the outcome.
+/// Throws if `options` isn't an `InterpretOptions` object.
+#[wasm_bindgen]
+pub fn interpret(code: &str, options: Option<JsInterpretOptions>) -> Result<JsEvalResult, JsValue> {
+    utils::set_panic_hook();
+    let options: InterpretOptions = from_js(&options.map_or(JsValue::UNDEFINED, JsValue::from))?;
+    let mut f = Forth::new();
+    f.push_input(&options.input);
+    let result = f.eval(code);
+    Ok(eval_result(&f, code, result, None))
+}
+
+/// Input compiled once by `Forth.compileProgram` to be run many times.
+#[wasm_bindgen(js_name = Program)]
+pub struct CompiledProgram {
+    program: Program,
+}
+
+/// Source tokenized once by `Forth.prepare` to be evaluated many times.
+#[wasm_bindgen(js_name = Tokens)]
+pub struct PreparedSource {
+    code: String,
+    tokens: Vec<SourceToken>,
+}
+
+/// An interpreter that keeps its stack and definitions between calls to
+/// `eval`, exported to JavaScript as `Forth`.
+#[wasm_bindgen(js_name = Forth)]
+pub struct Interpreter {
+    /// Shared with any `evalAsync` in progress.
+    forth: Rc<RefCell<Forth>>,
+    options: RenderOptions,
+    forth_options: ForthOptions,
+    /// Source of the evaluation waiting for `resume`, which errors are
+    /// located in.
+    paused_code: Rc<RefCell<String>>,
+    /// Frames started by `startFrames`.
+    animation: Option<frames::Animation>,
+    /// Timers started by `startTimers`.
+    timers: Option<timers::TimerRunner>,
+}
+
+/// A fresh interpreter configured by `options` that has run its prelude, or
+/// the `EvalError` it failed with, located within the prelude.
+fn build(options: &ForthOptions) -> Result<Forth, JsValue> {
+    let mut builder = Forth::builder()
+        .max_words(options.max_words)
+        .max_output(options.max_output)
+        .max_input_len(options.max_input_len)
+        .max_tokens(options.max_tokens)
+        .max_name_len(options.max_name_len)
+        .max_definition_len(options.max_definition_len)
+        .truncate_output(options.truncate_output)
+        .standard(options.standard)
+        .deterministic(options.deterministic)
+        .frozen(options.frozen)
+        .undo(options.undo)
+        .strings(options.strings)
+        .echo_input(options.echo_input)
+        .ok_prompt(options.ok_prompt)
+        .terminal(options.terminal.map(terminal::TerminalSize::size))
+        .language(options.language.as_deref().and_then(Language::from_tag).unwrap_or_default())
+        .standard_prelude(!options.skip_standard_prelude);
+    if let Some(size) = options.data_space_size {
+        builder = builder.data_space_size(size);
+    }
+    #[cfg(feature = "typed")]
+    {
+        builder = builder.typed(options.typed);
+    }
+    let prelude = options.prelude.as_deref();
+    if let Some(prelude) = prelude {
+        builder = builder.prelude(prelude);
+    }
+    let mut forth = builder.try_build()
+        .map_err(|e| to_js::<_, JsValue>(&EvalError::at(e.location.as_ref(), prelude.unwrap_or(""), &e.error)))?;
+    let mut messages = forth.messages().clone();
+    for (kind, template) in &options.error_messages {
+        messages.set_error(kind, template);
+    }
+    for (kind, template) in &options.warning_messages {
+        messages.set_warning(kind, template);
+    }
+    forth.set_messages(messages);
+    use_local_storage(&mut forth, options.storage_key());
+    forth.set_clock(BrowserClock);
+    forth.set_log_sink(log_to_console);
+    Ok(forth)
+}
+
+/// The `len` bytes of `memory` from `addr`, or an `InvalidAddress` error
+/// to throw if they don't fit.
+fn memory_range(memory: &[u8], addr: usize, len: usize) -> Result<std::ops::Range<usize>, JsValue> {
+    match addr.checked_add(len) {
+        Some(end) if end <= memory.len() => Ok(addr..end),
+        _ => Err(error_to_js(Error::InvalidAddress)),
+    }
+}
+
+/// The property `name` of `object`, or `undefined`.
+fn field(object: &JsValue, name: &str) -> JsValue {
+    js_sys::Reflect::get(object, &JsValue::from_str(name)).unwrap_or(JsValue::UNDEFINED)
+}
+
+/// The message of an exception thrown in JavaScript.
+fn js_message(e: JsValue) -> String {
+    match e.dyn_into::<js_sys::Error>() {
+        Ok(e) => String::from(e.message()),
+        Err(e) => e.as_string().unwrap_or_else(|| String::from("browser error")),
+    }
+}
+
+/// Turns an exception thrown by the browser into an error of the word that
+/// caused it.
+fn js_error(e: JsValue) -> Error {
+    Error::Native(js_message(e))
+}
+
+/// Turns an exception thrown by a callback the page handed over into an
+/// error of the word that called it, which CATCH can tell apart by its
+/// throw code.
+fn callback_error(e: JsValue) -> Error {
+    Error::Callback(js_message(e))
+}
+
+/// Writes what LOG and LOG-TYPE log to the browser console, after the
+/// word that logged it.
+fn log_to_console(message: &str, word: Option<&str>) {
+    match word {
+        Some(word) => console_log(&format!("[{}] {}", word, message)),
+        None => console_log(message),
+    }
+}
+
+/// The page's clock, read through `performance.now()` and `Date`, telling
+/// the local time. It can't sleep, so MS pauses evaluation, which
+/// `evalAsync` resumes once the time is up.
+struct BrowserClock;
+
+impl Clock for BrowserClock {
+    fn ticks(&mut self) -> u64 {
+        performance_now() as u64
+    }
+
+    fn micros(&mut self) -> u64 {
+        (performance_now() * 1000.0) as u64
+    }
+
+    fn time_and_date(&mut self) -> TimeAndDate {
+        let now = js_sys::Date::new_0();
+        TimeAndDate {
+            second: now.get_seconds() as u8,
+            minute: now.get_minutes() as u8,
+            hour: now.get_hours() as u8,
+            day: now.get_date() as u8,
+            month: now.get_month() as u8 + 1,
+            year: now.get_full_year() as i32,
+        }
+    }
+}
+
+/// Output and input through JavaScript functions, as `setIo` takes them.
+struct CallbackIo {
+    write: js_sys::Function,
+    read: Option<js_sys::Function>,
+}
+
+impl Io for CallbackIo {
+    fn write(&mut self, text: &str) {
+        let _ = self.try_write(text);
+    }
+
+    fn read(&mut self) -> Option<String> {
+        self.try_read().ok().flatten()
+    }
+
+    fn try_write(&mut self, text: &str) -> ForthResult {
+        self.write.call1(&JsValue::NULL, &JsValue::from_str(text)).map(drop).map_err(callback_error)
+    }
+
+    fn try_read(&mut self) -> Result<Option<String>, Error> {
+        match self.read {
+            Some(ref read) => callback_text(read.call0(&JsValue::NULL).map_err(callback_error)?, "read"),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Files read through the JavaScript function `setSourceProvider` takes.
+struct CallbackSourceProvider {
+    callback: js_sys::Function,
+}
+
+impl SourceProvider for CallbackSourceProvider {
+    fn source(&mut self, name: &str) -> Option<String> {
+        self.try_source(name).ok().flatten()
+    }
+
+    fn try_source(&mut self, name: &str) -> Result<Option<String>, Error> {
+        let text = self.callback.call1(&JsValue::NULL, &JsValue::from_str(name)).map_err(callback_error)?;
+        callback_text(text, "the source provider")
+    }
+}
+
+/// The string a callback returned, or `None` for `null` or `undefined`,
+/// failing with a `Callback` error naming the callback for anything else.
+fn callback_text(value: JsValue, callback: &str) -> Result<Option<String>, Error> {
+    if value.is_null() || value.is_undefined() {
+        return Ok(None);
+    }
+    value.as_string().map(Some).ok_or_else(|| {
+        let kind = value.js_typeof().as_string().unwrap_or_default();
+        Error::Callback(format!("{} returned a {} rather than a string", callback, kind))
+    })
+}
+
+/// Blocks kept in `localStorage`, each under the storage key followed by
+/// `-block-` and its number, one character per byte.
+struct LocalStorageBlocks {
+    key: String,
+}
+
+impl BlockStorage for LocalStorageBlocks {
+    fn read(&mut self, n: u32, block: &mut [u8]) -> ForthResult {
+        let text = local_storage_get(&format!("{}-block-{}", self.key, n)).map_err(js_error)?;
+        for (byte, c) in block.iter_mut().zip(text.unwrap_or_default().chars()) {
+            *byte = c as u32 as u8;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, n: u32, block: &[u8]) -> ForthResult {
+        let text: String = block.iter().map(|&byte| char::from(byte)).collect();
+        local_storage_set(&format!("{}-block-{}", self.key, n), &text).map_err(js_error)
+    }
+}
+
+/// Has SAVE-DICT and LOAD-DICT keep definitions in `localStorage` under
+/// `key`, as JSON, and the block words keep blocks there too.
+fn use_local_storage(forth: &mut Forth, key: String) {
+    forth.set_block_storage(LocalStorageBlocks { key: key.clone() });
+    let load_key = key.clone();
+    forth.set_dictionary_store(
+        move |words| {
+            let json = serde_json::to_string(words).unwrap();
+            local_storage_set(&key, &json).map_err(js_error)
+        },
+        move || match local_storage_get(&load_key).map_err(js_error)? {
+            Some(json) => serde_json::from_str(&json)
+                .map_err(|_| Error::Native(String::from("saved dictionary is corrupt"))),
+            None => Ok(Vec::new()),
+        },
+    );
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Creates an interpreter, first evaluating `options.prelude` if
+    /// given. Throws if `options` is malformed, or throws an `EvalError`
+    /// located within the prelude if the prelude fails.
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: Option<JsForthOptions>) -> Result<Interpreter, JsValue> {
+        utils::set_panic_hook();
+        let options: ForthOptions = from_js(&options.map_or(JsValue::UNDEFINED, JsValue::from))?;
+        Ok(Interpreter {
+            forth: Rc::new(RefCell::new(build(&options)?)),
+            options: RenderOptions::new(),
+            forth_options: options,
+            paused_code: Rc::default(),
+            animation: None,
+            timers: None,
+        })
+    }
+
+    /// Interprets `code` and renders the program output and resulting stack
+    /// for a transcript, as HTML or plain text by the interpreter's render
+    /// options, or the error as `Error: ...`. `evalResult` gives the same outcome as an
+    /// `EvalResult`.
+    pub fn eval(&mut self, code: &str) -> String {
+        let result = self.forth.borrow_mut().eval(code);
+        self.note_paused(code);
+        render(&self.forth.borrow(), result, &self.options)
+    }
+
+    /// Compiles `code` for `runProgram`, throwing an `EvalError` if it
+    /// doesn't compile.
+    #[wasm_bindgen(js_name = compileProgram)]
+    pub fn compile_program(&mut self, code: &str) -> Result<CompiledProgram, JsValue> {
+        let program = self.forth.borrow_mut().compile_program(code);
+        match program {
+            Ok(program) => Ok(CompiledProgram { program }),
+            Err(e) => Err(to_js(&EvalError::new(&self.forth.borrow(), code, &e))),
+        }
+    }
+
+    /// Runs a program from `compileProgram` and returns the structured
+    /// outcome.
+    #[wasm_bindgen(js_name = runProgram)]
+    pub fn run_program(&mut self, program: &CompiledProgram) -> JsEvalResult {
+        let result = self.forth.borrow_mut().run(&program.program);
+        self.note_paused("");
+        eval_result(&self.forth.borrow(), "", result, self.forth_options.max_stack_items)
+    }
+
+    /// Tokenizes `code` for `evalTokens`.
+    pub fn prepare(&self, code: &str) -> PreparedSource {
+        PreparedSource { code: String::from(code), tokens: self.forth.borrow().tokenize(code) }
+    }
+
+    /// Evaluates source prepared by `prepare` without tokenizing it again
+    /// and returns the structured outcome.
+    #[wasm_bindgen(js_name = evalTokens)]
+    pub fn eval_tokens(&mut self, source: &PreparedSource) -> JsEvalResult {
+        let result = self.forth.borrow_mut().eval_tokens(&source.tokens);
+        self.note_paused(&source.code);
+        eval_result(&self.forth.borrow(), &source.code, result, self.forth_options.max_stack_items)
+    }
+
+    /// Sets how `eval` renders its outcome. Fields left out take their
+    /// defaults: HTML, with program output and errors escaped, a line
+    /// break between items, top first, decimal and no limit.
+    /// Throws if `options` isn't a `RenderOptions` object.
+    #[wasm_bindgen(js_name = setRenderOptions)]
+    pub fn set_render_options(&mut self, options: JsRenderOptions) -> Result<(), JsValue> {
+        self.options = from_js(&options)?;
+        Ok(())
+    }
+
+    /// Interprets `code` and returns the structured outcome.
+    #[wasm_bindgen(js_name = evalResult)]
+    pub fn eval_result(&mut self, code: &str) -> JsEvalResult {
+        let result = self.forth.borrow_mut().eval(code);
+        self.note_paused(code);
+        eval_result(&self.forth.borrow(), code, result, self.forth_options.max_stack_items)
+    }
+
+    /// The name of the colon definition the last evaluation ended inside,
+    /// before its `;`. The next evaluation carries on compiling it.
+    #[wasm_bindgen(js_name = unfinishedDefinition)]
+    pub fn unfinished_definition(&self) -> Option<String> {
+        self.forth.borrow().unfinished_definition().map(String::from)
+    }
+
+    /// Reverts the most recent evaluation, if the interpreter was created
+    /// with `undo` set, returning whether there was one to revert.
+    pub fn undo(&mut self) -> bool {
+        self.forth.borrow_mut().undo()
+    }
+
+    /// Gives each task TASK started a turn, of at most `fuel` instructions
+    /// if given, as between the chunks of `evalAsync` when PAUSE asks.
+    /// Throws the error of a task that fails, which is stopped.
+    #[wasm_bindgen(js_name = runTasks)]
+    pub fn run_tasks(&mut self, fuel: Option<u32>) -> Result<(), JsValue> {
+        self.forth.borrow_mut().run_tasks(fuel.map(u64::from))
+            .map_err(error_to_js)
+    }
+
+    /// How many tasks TASK started are still running.
+    #[wasm_bindgen(js_name = taskCount)]
+    pub fn task_count(&self) -> usize {
+        self.forth.borrow().task_count()
+    }
+
+    /// The strings on the string stack, bottom first.
+    pub fn strings(&self) -> Vec<String> {
+        self.forth.borrow().strings()
+    }
+
+    /// Abandons the definition the last evaluation left unfinished, if any.
+    #[wasm_bindgen(js_name = cancelDefinition)]
+    pub fn cancel_definition(&mut self) {
+        self.forth.borrow_mut().cancel_definition();
+    }
+
+    /// Interprets `code` like `evalResult`, but if it fails puts the
+    /// stacks, dictionary and data space back as they were before.
+    #[wasm_bindgen(js_name = evalTransactional)]
+    pub fn eval_transactional(&mut self, code: &str) -> JsEvalResult {
+        let result = self.forth.borrow_mut().eval_transactional(code);
+        self.note_paused(code);
+        eval_result(&self.forth.borrow(), code, result, self.forth_options.max_stack_items)
+    }
+
+    /// Interprets `code` like `evalResult`, also recording the stack after
+    /// each name or number in `code`, for up to `maxSteps` tokens (1000 by
+    /// default) and `stackDepth` cells from the top (16 by default). Throws
+    /// if `options` isn't a `TraceOptions` object.
+    pub fn trace(&mut self, code: &str, options: Option<JsTraceOptions>) -> Result<JsTraceResult, JsValue> {
+        let options: TraceOptions = from_js(&options.map_or(JsValue::UNDEFINED, JsValue::from))?;
+        let options = forth_core::TraceOptions { max_steps: options.max_steps, stack_depth: options.stack_depth };
+        let trace = self.forth.borrow_mut().trace(code, &options);
+        self.note_paused(code);
+        let f = self.forth.borrow();
+        let (stack, stack_truncated) = result_stack(&f, self.forth_options.max_stack_items);
+        Ok(to_js(&TraceResult {
+            stack,
+            stack_truncated,
+            output: String::from(f.output()),
+            output_truncated: f.output_truncated(),
+            warnings: warnings(&f),
+            error: trace.result.err().map(|e| EvalError::new(&f, code, &e)),
+            steps: trace.steps.into_iter().map(|step| TraceStep {
+                start: utf16_offset(code, step.span.start),
+                end: utf16_offset(code, step.span.end),
+                token: step.token,
+                stack: step.stack,
+                depth: step.depth,
+            }).collect(),
+            truncated: trace.truncated,
+        }))
+    }
+
+    /// Interprets `code` like `evalResult`, but carries on past unknown
+    /// words and stack underflows, after the name that failed or the end
+    /// of the colon definition it was in, listing each in `diagnostics`.
+    /// `error` is any other error, which ended the evaluation.
+    #[wasm_bindgen(js_name = evalRecovering)]
+    pub fn eval_recovering(&mut self, code: &str) -> JsRecoveryResult {
+        let recovery = self.forth.borrow_mut().eval_recovering(code);
+        self.note_paused(code);
+        let f = self.forth.borrow();
+        let (stack, stack_truncated) = result_stack(&f, self.forth_options.max_stack_items);
+        to_js(&RecoveryResult {
+            stack,
+            stack_truncated,
+            output: String::from(f.output()),
+            output_truncated: f.output_truncated(),
+            warnings: warnings(&f),
+            error: recovery.result.err().map(|e| EvalError::new(&f, code, &e)),
+            diagnostics: recovery.diagnostics.iter().map(|d| EvalError::from_diagnostic(&f, code, d)).collect(),
+        })
+    }
+
+    /// Starts interpreting `code` as `evalResult` would, but stops before
+    /// the first name or number for `step` and `runUntilBreakpoint` to
+    /// carry on.
+    pub fn debug(&mut self, code: &str) -> JsDebugResult {
+        let result = self.forth.borrow_mut().debug(code);
+        *self.paused_code.borrow_mut() = String::from(code);
+        debug_result(&self.forth.borrow(), code, result, self.forth_options.max_stack_items)
+    }
+
+    /// Runs the next instruction of the evaluation `debug` started and
+    /// stops again.
+    pub fn step(&mut self) -> JsDebugResult {
+        let result = self.forth.borrow_mut().step();
+        debug_result(&self.forth.borrow(), &self.paused_code.borrow(), result, self.forth_options.max_stack_items)
+    }
+
+    /// Runs the evaluation `debug` started until it calls a word with a
+    /// breakpoint, a watchpoint sees a change, or it finishes or fails.
+    #[wasm_bindgen(js_name = runUntilBreakpoint)]
+    pub fn run_until_breakpoint(&mut self) -> JsDebugResult {
+        let result = self.forth.borrow_mut().run_until_breakpoint();
+        debug_result(&self.forth.borrow(), &self.paused_code.borrow(), result, self.forth_options.max_stack_items)
+    }
+
+    /// Stops `debug` before any call of the word `name`.
+    #[wasm_bindgen(js_name = setBreakpoint)]
+    pub fn set_breakpoint(&mut self, name: &str) {
+        self.forth.borrow_mut().set_breakpoint(name);
+    }
+
+    #[wasm_bindgen(js_name = clearBreakpoint)]
+    pub fn clear_breakpoint(&mut self, name: &str) {
+        self.forth.borrow_mut().clear_breakpoint(name);
+    }
+
+    /// The words with breakpoints, in upper case unless the interpreter is
+    /// case-sensitive.
+    pub fn breakpoints(&self) -> Vec<String> {
+        self.forth.borrow().breakpoints().to_vec()
+    }
+
+    /// Stops `debug` once the contents of the cell at `addr` change.
+    /// Throws if `addr` is outside memory.
+    #[wasm_bindgen(js_name = watchCell)]
+    pub fn watch_cell(&mut self, addr: Value) -> Result<(), JsValue> {
+        self.forth.borrow_mut().watch(Watchpoint::Cell(addr))
+            .map_err(error_to_js)
+    }
+
+    #[wasm_bindgen(js_name = unwatchCell)]
+    pub fn unwatch_cell(&mut self, addr: Value) {
+        self.forth.borrow_mut().unwatch(Watchpoint::Cell(addr));
+    }
+
+    /// Stops `debug` once the stack grows past `depth` cells, or shrinks
+    /// back to it.
+    #[wasm_bindgen(js_name = watchStackDepth)]
+    pub fn watch_stack_depth(&mut self, depth: usize) {
+        // Only cells can be out of range.
+        self.forth.borrow_mut().watch(Watchpoint::StackDepth(depth)).unwrap();
+    }
+
+    #[wasm_bindgen(js_name = unwatchStackDepth)]
+    pub fn unwatch_stack_depth(&mut self, depth: usize) {
+        self.forth.borrow_mut().unwatch(Watchpoint::StackDepth(depth));
+    }
+
+    /// The compiled words of a stopped evaluation, outermost first.
+    #[wasm_bindgen(js_name = returnStack)]
+    pub fn return_stack(&self) -> JsCallFrames {
+        let f = self.forth.borrow();
+        let frames: Vec<_> = f.return_stack().into_iter().map(|frame| {
+            // The name may have been redefined since, leaving spans that
+            // don't fit the source found.
+            let source = f.definition_source(&frame.word).map_or("", |(source, _)| source);
+            let offset = |offset: usize| source.get(..offset).map(|text| text.encode_utf16().count());
+            CallFrame {
+                start: frame.span.and_then(|span| offset(span.start)),
+                end: frame.span.and_then(|span| offset(span.end)),
+                word: frame.word,
+                pc: frame.pc,
+            }
+        }).collect();
+        to_js(&frames)
+    }
+
+    /// The names and numbers a stopped evaluation has still to interpret
+    /// once its compiled words return.
+    #[wasm_bindgen(js_name = tokenQueue)]
+    pub fn token_queue(&self) -> Vec<String> {
+        self.forth.borrow().token_queue()
+    }
+
+    /// Limits evaluation to `fuel` more steps, each a name or number
+    /// interpreted or an instruction of a compiled word. Evaluation that
+    /// runs out fails with `OutOfFuel` and can be carried on by `resume`.
+    /// Without `fuel`, evaluation is unlimited.
+    #[wasm_bindgen(js_name = setFuel)]
+    pub fn set_fuel(&mut self, fuel: Option<u32>) {
+        self.forth.borrow_mut().set_fuel(fuel.map(u64::from));
+    }
+
+    /// The fuel left, or `undefined` if evaluation is unlimited.
+    pub fn fuel(&self) -> Option<f64> {
+        self.forth.borrow().fuel().map(|fuel| fuel as f64)
+    }
+
+    /// Whether the last evaluation ran out of fuel and can be resumed.
+    #[wasm_bindgen(js_name = isPaused)]
+    pub fn is_paused(&self) -> bool {
+        self.forth.borrow().is_paused()
+    }
+
+    /// Carries on with the evaluation that ran out of fuel, after
+    /// `setFuel` has given it more, and returns the structured outcome.
+    /// Errors are located in the code first evaluated.
+    pub fn resume(&mut self) -> JsEvalResult {
+        let result = self.forth.borrow_mut().resume();
+        eval_result(&self.forth.borrow(), &self.paused_code.borrow(), result, self.forth_options.max_stack_items)
+    }
+
+    /// Contents of the data stack, bottom first.
+    pub fn stack(&self) -> Vec<Value> {
+        self.forth.borrow().stack()
+    }
+
+    /// Contents of the data stack, bottom first, each item as `.` would
+    /// print it, floats and strings included.
+    #[cfg(feature = "typed")]
+    #[wasm_bindgen(js_name = typedStack)]
+    pub fn typed_stack(&self) -> Vec<String> {
+        self.forth.borrow().typed_stack().iter().map(|v| v.to_string()).collect()
+    }
+
+    /// Pushes `v` onto the data stack.
+    pub fn push(&mut self, v: Value) {
+        self.forth.borrow_mut().push(v);
+    }
+
+    /// Removes and returns the top of the data stack. Throws if it is empty.
+    pub fn pop(&mut self) -> Result<Value, JsValue> {
+        self.forth.borrow_mut().pop().map_err(error_to_js)
+    }
+
+    /// The item `n` places below the top of the data stack, or `undefined`.
+    pub fn peek(&self, n: usize) -> Option<Value> {
+        self.forth.borrow().peek(n)
+    }
+
+    /// Number of items on the data stack.
+    #[wasm_bindgen(js_name = stackLength)]
+    pub fn stack_length(&self) -> usize {
+        self.forth.borrow().len()
+    }
+
+    /// Copies as much of the data stack as fits into `buf`, bottom first,
+    /// and returns the number of items copied. Lets a caller poll the stack
+    /// into a reused buffer without allocating.
+    #[wasm_bindgen(js_name = copyStack)]
+    pub fn copy_stack(&self, buf: &mut [Value]) -> usize {
+        let forth = self.forth.borrow();
+        let stack = forth.stack_slice();
+        let n = stack.len().min(buf.len());
+        buf[..n].copy_from_slice(&stack[..n]);
+        n
+    }
+
+    /// An `Int32Array`, or `BigInt64Array` with the `bigint` feature,
+    /// viewing the data stack in wasm memory, bottom first, without copying
+    /// it.
+    ///
+    /// The view is only valid until the interpreter is used again: any call
+    /// that changes the stack or grows wasm memory may leave it dangling.
+    #[wasm_bindgen(js_name = stackView)]
+    pub fn stack_view(&self) -> CellArray {
+        unsafe { CellArray::view(self.forth.borrow().stack_slice()) }
+    }
+
+    /// An independent copy of this interpreter, for trying out code that
+    /// may be discarded. Output callbacks are not carried over.
+    pub fn fork(&self) -> Interpreter {
+        let mut forth = self.forth.borrow().fork();
+        use_local_storage(&mut forth, self.forth_options.storage_key());
+        forth.set_clock(BrowserClock);
+        forth.set_log_sink(log_to_console);
+        Interpreter {
+            forth: Rc::new(RefCell::new(forth)),
+            options: self.options.clone(),
+            forth_options: self.forth_options.clone(),
+            paused_code: Rc::new(RefCell::new(self.paused_code.borrow().clone())),
+            animation: None,
+            timers: None,
+        }
+    }
+
+    /// Discards the stack, definitions and data space, then runs the
+    /// prelude again, keeping the limits the interpreter was created with.
+    pub fn reset(&mut self) {
+        *self.forth.borrow_mut() = build(&self.forth_options).unwrap();
+    }
+
+    /// Calls `callback` with each piece of program output as it is
+    /// produced, instead of collecting it into the result. Should it throw,
+    /// the word that printed fails with a `Callback` error carrying the
+    /// exception's message, which CATCH catches as throw code -272.
+    #[wasm_bindgen(js_name = setOutputCallback)]
+    pub fn set_output_callback(&mut self, callback: js_sys::Function) {
+        self.forth.borrow_mut().set_fallible_output_sink(move |text| {
+            callback.call1(&JsValue::NULL, &JsValue::from_str(text)).map(drop).map_err(callback_error)
+        });
+    }
+
+    /// Goes back to collecting program output into the result.
+    #[wasm_bindgen(js_name = clearOutputCallback)]
+    pub fn clear_output_callback(&mut self) {
+        self.forth.borrow_mut().clear_output_sink();
+    }
+
+    /// Calls `write` with each piece of program output as it is produced,
+    /// and `read`, if given, for more text for KEY and ACCEPT once the
+    /// queued input runs out; it returns a string, or `null` at the end of
+    /// the input. Should either throw, or `read` return anything else, the
+    /// word that called it fails with a `Callback` error saying why.
+    #[wasm_bindgen(js_name = setIo)]
+    pub fn set_io(&mut self, write: js_sys::Function, read: Option<js_sys::Function>) {
+        self.forth.borrow_mut().set_io(CallbackIo { write, read });
+    }
+
+    /// Goes back to collecting program output into the result and reading
+    /// only queued input.
+    #[wasm_bindgen(js_name = clearIo)]
+    pub fn clear_io(&mut self) {
+        self.forth.borrow_mut().clear_io();
+    }
+
+    /// Calls `callback` with what LOG and LOG-TYPE log, and the name of the
+    /// word they were called from, if any, instead of writing it to the
+    /// browser console. Should it throw, LOG or LOG-TYPE fails with a
+    /// `Callback` error carrying the exception's message.
+    #[wasm_bindgen(js_name = setLogCallback)]
+    pub fn set_log_callback(&mut self, callback: js_sys::Function) {
+        self.forth.borrow_mut().set_fallible_log_sink(move |message, word| {
+            let word = word.map_or(JsValue::UNDEFINED, JsValue::from_str);
+            callback.call2(&JsValue::NULL, &JsValue::from_str(message), &word).map(drop).map_err(callback_error)
+        });
+    }
+
+    /// Goes back to writing what LOG and LOG-TYPE log to the browser
+    /// console.
+    #[wasm_bindgen(js_name = clearLogCallback)]
+    pub fn clear_log_callback(&mut self) {
+        self.forth.borrow_mut().set_log_sink(log_to_console);
+    }
+
+    /// Calls `before` with the name of each word about to run and the
+    /// depth of the data stack, and `after` with the name of each word
+    /// that has finished and the depth it left, either of which may be
+    /// left out. Replaces hooks set earlier; they are gone after `reset`.
+    #[wasm_bindgen(js_name = setExecutionHooks)]
+    pub fn set_execution_hooks(&mut self, before: Option<js_sys::Function>, after: Option<js_sys::Function>) {
+        let mut forth = self.forth.borrow_mut();
+        forth.clear_execution_hooks();
+        if let Some(before) = before {
+            forth.set_before_hook(move |name, depth| {
+                let _ = before.call2(&JsValue::NULL, &JsValue::from_str(name), &JsValue::from(depth as u32));
+            });
+        }
+        if let Some(after) = after {
+            forth.set_after_hook(move |name, depth| {
+                let _ = after.call2(&JsValue::NULL, &JsValue::from_str(name), &JsValue::from(depth as u32));
+            });
+        }
+    }
+
+    /// Stops calling the hooks `setExecutionHooks` set.
+    #[wasm_bindgen(js_name = clearExecutionHooks)]
+    pub fn clear_execution_hooks(&mut self) {
+        self.forth.borrow_mut().clear_execution_hooks();
+    }
+
+    /// Defines turtle graphics words drawing on `canvas`: PENDOWN, PENUP,
+    /// FORWARD, TURN, HOME, LINE, RECT, COLOR and CLEARSCREEN. Like output
+    /// callbacks, they are gone after `reset`. Throws if the canvas has no
+    /// 2D context. Only with the `graphics` feature.
+    #[cfg(feature = "graphics")]
+    #[wasm_bindgen(js_name = attachCanvas)]
+    pub fn attach_canvas(&mut self, canvas: &web_sys::HtmlCanvasElement) -> Result<(), JsValue> {
+        graphics::attach(&mut self.forth.borrow_mut(), canvas)
+    }
+
+    /// Defines words that change elements of the page, found by id:
+    /// SET-TEXT, GET-TEXT, SET-ATTR, ADD-CLASS and REMOVE-CLASS. Like output
+    /// callbacks, they are gone after `reset`. Throws if there is no
+    /// document. Only with the `dom` feature.
+    #[cfg(feature = "dom")]
+    #[wasm_bindgen(js_name = attachDom)]
+    pub fn attach_dom(&mut self) -> Result<(), JsValue> {
+        dom::attach(&mut self.forth.borrow_mut())
+    }
+
+    /// Defines words that play sound: TONE, REST, NOTE-ON, NOTE-OFF,
+    /// ENVELOPE, WAVEFORM, VOLUME and SILENCE. Like output callbacks, they
+    /// are gone after `reset`. Call it from an event handler, such as a
+    /// click, for the browser to allow sound. Throws if there is no Web
+    /// Audio. Only with the `audio` feature.
+    #[cfg(feature = "audio")]
+    #[wasm_bindgen(js_name = attachAudio)]
+    pub fn attach_audio(&mut self) -> Result<(), JsValue> {
+        audio::attach(&mut self.forth.borrow_mut())
+    }
+
+    /// Defines words that talk to servers over WebSockets: WS-CONNECT,
+    /// WS-SEND, WS-RECV? and WS-CLOSE. Messages received wait in a queue
+    /// until WS-RECV? takes them. Like output callbacks, the words are gone
+    /// after `reset`, which closes their connections. Only with the
+    /// `websocket` feature.
+    #[cfg(feature = "websocket")]
+    #[wasm_bindgen(js_name = attachWebSocket)]
+    pub fn attach_websocket(&mut self) {
+        websocket::attach(&mut self.forth.borrow_mut());
+    }
+
+    /// Saves the colon definitions made so far to `localStorage`, as
+    /// SAVE-DICT does, under `options.storageKey`. Throws if storage is
+    /// unavailable or full.
+    #[wasm_bindgen(js_name = saveDict)]
+    pub fn save_dict(&mut self) -> Result<(), JsValue> {
+        self.forth.borrow_mut().save_dictionary()
+            .map_err(error_to_js)
+    }
+
+    /// Compiles the definitions `saveDict` or SAVE-DICT last saved, as
+    /// LOAD-DICT does. Throws if storage is unavailable or a definition
+    /// fails to compile.
+    #[wasm_bindgen(js_name = loadDict)]
+    pub fn load_dict(&mut self) -> Result<(), JsValue> {
+        self.forth.borrow_mut().load_dictionary()
+            .map_err(error_to_js)
+    }
+
+    /// Has INCLUDE, INCLUDED, REQUIRE and REQUIRED read files by calling
+    /// `callback` with the name, which returns the text, or `undefined` or
+    /// `null` if there is no such file. Files are then read as evaluation
+    /// reaches them, without `evalIncluding`, and may include others.
+    /// Should `callback` throw or return anything else, the word including
+    /// the file fails with a `Callback` error saying why.
+    #[wasm_bindgen(js_name = setSourceProvider)]
+    pub fn set_source_provider(&mut self, callback: js_sys::Function) {
+        self.forth.borrow_mut().set_source_provider(CallbackSourceProvider { callback });
+    }
+
+    /// Goes back to asking for files with `IncludeRequested`, for
+    /// `evalIncluding` to fetch.
+    #[wasm_bindgen(js_name = clearSourceProvider)]
+    pub fn clear_source_provider(&mut self) {
+        self.forth.borrow_mut().clear_source_provider();
+    }
+
+    /// The names of the files included so far, which REQUIRE and REQUIRED
+    /// skip.
+    #[wasm_bindgen(js_name = includedFiles)]
+    pub fn included_files(&self) -> Vec<String> {
+        self.forth.borrow().included_files().to_vec()
+    }
+
+    /// The size in bytes of the data space, which a buffer given to
+    /// `shareDataSpace` needs at least.
+    #[wasm_bindgen(js_name = dataSpaceSize)]
+    pub fn data_space_size(&self) -> usize {
+        self.forth.borrow().data_space().len()
+    }
+
+    /// A copy of the `len` bytes of the data space from `addr`. Throws if
+    /// they aren't all within it.
+    #[wasm_bindgen(js_name = getMemory)]
+    pub fn get_memory(&self, addr: usize, len: usize) -> Result<Vec<u8>, JsValue> {
+        let forth = self.forth.borrow();
+        let range = memory_range(forth.data_space(), addr, len)?;
+        Ok(forth.data_space()[range].to_vec())
+    }
+
+    /// Writes `bytes` into the data space from `addr`. Throws, writing
+    /// nothing, if they don't all fit within it.
+    #[wasm_bindgen(js_name = setMemory)]
+    pub fn set_memory(&mut self, addr: usize, bytes: &[u8]) -> Result<(), JsValue> {
+        let mut forth = self.forth.borrow_mut();
+        let range = memory_range(forth.data_space(), addr, bytes.len())?;
+        forth.data_space_mut()[range].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// A `Uint8Array` viewing the data space in wasm memory, indexed by
+    /// address, without copying it, so that a page can draw from it every
+    /// frame. Writing to it changes the data space.
+    ///
+    /// The view is only valid until wasm memory grows, which any call may
+    /// do; get a fresh one rather than keeping it.
+    #[wasm_bindgen(js_name = memoryView)]
+    pub fn memory_view(&self) -> js_sys::Uint8Array {
+        unsafe { js_sys::Uint8Array::view(self.forth.borrow().data_space()) }
+    }
+
+    /// Copies the data space to the start of `buffer` now and whenever an
+    /// evaluation, `resume` or debugging step stops, so that a page can
+    /// watch the memory of an interpreter running in a worker without
+    /// asking for it. Throws if `buffer` is smaller than `dataSpaceSize`;
+    /// should a restored snapshot's data space be larger, only what fits
+    /// is copied.
+    #[wasm_bindgen(js_name = shareDataSpace)]
+    pub fn share_data_space(&mut self, buffer: js_sys::SharedArrayBuffer) -> Result<(), JsValue> {
+        let mut forth = self.forth.borrow_mut();
+        let len = forth.data_space().len() as u32;
+        if buffer.byte_length() < len {
+            return Err(JsValue::from_str(&format!("the data space needs a buffer of {} bytes", len)));
+        }
+        let view = js_sys::Uint8Array::new(&buffer);
+        // Restoring a snapshot may change the size of the data space.
+        let copy = move |data: &[u8]| {
+            let len = data.len().min(view.length() as usize);
+            view.subarray(0, len as u32).copy_from(&data[..len]);
+        };
+        copy(forth.data_space());
+        forth.set_data_space_observer(copy);
+        Ok(())
+    }
+
+    /// Stops copying the data space to the buffer given to
+    /// `shareDataSpace`.
+    #[wasm_bindgen(js_name = unshareDataSpace)]
+    pub fn unshare_data_space(&mut self) {
+        self.forth.borrow_mut().clear_data_space_observer();
+    }
+
+    /// Queues `text` to be read by KEY and ACCEPT.
+    #[wasm_bindgen(js_name = pushInput)]
+    pub fn push_input(&mut self, text: &str) {
+        self.forth.borrow_mut().push_input(text);
+    }
+
+    /// The interpreter's stack, definitions and data space as JSON, for
+    /// `restore` to pick up later.
+    pub fn snapshot(&self) -> String {
+        serde_json::to_string(&self.forth.borrow().snapshot()).unwrap()
+    }
+
+    /// Replaces the interpreter's state with one saved by `snapshot`.
+    /// Throws if `json` is not such a snapshot.
+    pub fn restore(&mut self, json: &str) -> Result<(), JsValue> {
+        let snapshot = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        self.forth.borrow_mut().restore(snapshot)
+            .map_err(error_to_js)
+    }
+
+    /// The dictionary and data space as a binary image for `loadImage`.
+    #[wasm_bindgen(js_name = saveImage)]
+    pub fn save_image(&self) -> Vec<u8> {
+        self.forth.borrow().save_image()
+    }
+
+    /// Replaces the dictionary and data space with those in `image`, saved
+    /// by `saveImage`. Throws if the image is invalid.
+    #[wasm_bindgen(js_name = loadImage)]
+    pub fn load_image(&mut self, image: &[u8]) -> Result<(), JsValue> {
+        self.forth.borrow_mut().load_image(image)
+            .map_err(error_to_js)
+    }
+
+    /// Every dictionary entry, oldest first.
+    pub fn words(&self) -> JsWordInfos {
+        to_js(&self.forth.borrow().words())
+    }
+
+    /// The stack comment after the name in the definition of `name`, which
+    /// HELP prints, or `undefined` if there is none.
+    #[wasm_bindgen(js_name = wordDoc)]
+    pub fn word_doc(&self, name: &str) -> Option<String> {
+        self.forth.borrow().word_doc(name).map(String::from)
+    }
+
+    /// A listing of the instructions the word `name` compiled to, each
+    /// with its index and the source it came from, or `undefined` for
+    /// unknown and native words.
+    pub fn disassemble(&self, name: &str) -> Option<String> {
+        self.forth.borrow().disassemble(name)
+    }
+
+    /// How many tests written `T{ code -> results }T` have failed. Each
+    /// failure is also logged, as LOG does.
+    #[wasm_bindgen(js_name = testFailures)]
+    pub fn test_failures(&self) -> usize {
+        self.forth.borrow().test_failures()
+    }
+
+    /// Drops the bodies of definitions hidden by later ones of the same
+    /// name that nothing refers to any more, returning how many and their
+    /// size in bytes.
+    pub fn gc(&mut self) -> JsGcStats {
+        to_js(&self.forth.borrow_mut().gc())
+    }
+
+    /// Stops programs defining a word named `name`, which throws a
+    /// `ProtectedWord` error instead. Not kept by `reset`.
+    pub fn protect(&mut self, name: &str) {
+        self.forth.borrow_mut().protect(name);
+    }
+
+    pub fn unprotect(&mut self, name: &str) {
+        self.forth.borrow_mut().unprotect(name);
+    }
+
+    /// The protected words, in upper case unless the interpreter is
+    /// case-sensitive.
+    #[wasm_bindgen(js_name = protectedWords)]
+    pub fn protected_words(&self) -> Vec<String> {
+        self.forth.borrow().protected_words().to_vec()
+    }
+
+    /// Stops programs defining any more words, which throws a
+    /// `DictionaryFrozen` error instead.
+    pub fn freeze(&mut self) {
+        self.forth.borrow_mut().freeze();
+    }
+
+    pub fn thaw(&mut self) {
+        self.forth.borrow_mut().thaw();
+    }
+
+    #[wasm_bindgen(js_name = isFrozen)]
+    pub fn is_frozen(&self) -> bool {
+        self.forth.borrow().is_frozen()
+    }
+
+    /// Starts counting calls of each word and the milliseconds spent
+    /// running it, discarding counts from earlier profiling.
+    #[wasm_bindgen(js_name = startProfiling)]
+    pub fn start_profiling(&mut self) {
+        self.forth.borrow_mut().start_profiling(performance_now);
+    }
+
+    /// Stops profiling, returning what `profile` would have.
+    #[wasm_bindgen(js_name = stopProfiling)]
+    pub fn stop_profiling(&mut self) -> JsWordProfiles {
+        to_js(&self.forth.borrow_mut().stop_profiling())
+    }
+
+    /// The words called since `startProfiling`, those that took the most
+    /// time first. Empty when not profiling.
+    pub fn profile(&self) -> JsWordProfiles {
+        to_js(&self.forth.borrow().profile())
+    }
+
+    /// Starts recording which words run, and which instructions of their
+    /// bodies, discarding what was recorded before.
+    #[wasm_bindgen(js_name = startCoverage)]
+    pub fn start_coverage(&mut self) {
+        self.forth.borrow_mut().start_coverage();
+    }
+
+    /// Stops recording coverage, returning what `coverage` would have.
+    #[wasm_bindgen(js_name = stopCoverage)]
+    pub fn stop_coverage(&mut self) -> JsWordCoverage {
+        to_js(&self.forth.borrow_mut().stop_coverage())
+    }
+
+    /// Every word defined since the interpreter was made and every built-in
+    /// word that ran since `startCoverage`, with whether each instruction
+    /// of their bodies ran. Empty when not recording coverage.
+    pub fn coverage(&self) -> JsWordCoverage {
+        to_js(&self.forth.borrow().coverage())
+    }
+
+    /// Problems in `code` found without running it, in source order.
+    pub fn check(&self, code: &str) -> JsEvalErrors {
+        let f = self.forth.borrow();
+        let errors: Vec<_> = f.check(code).iter().map(|d| EvalError::from_diagnostic(&f, code, d)).collect();
+        to_js(&errors)
+    }
+
+    /// Definitions and control structures in `code` that don't nest,
+    /// quickly enough to check as each key is typed, in source order.
+    #[wasm_bindgen(js_name = checkStructure)]
+    pub fn check_structure(&self, code: &str) -> JsEvalErrors {
+        let f = self.forth.borrow();
+        let errors: Vec<_> = f.check_structure(code).iter().map(|d| EvalError::from_diagnostic(&f, code, d)).collect();
+        to_js(&errors)
+    }
+
+    /// Mistakes in how `code` uses the stack found without running it:
+    /// underflows of the stack as it is now, and `[IF]` branches leaving
+    /// it at different depths, in source order.
+    pub fn lint(&self, code: &str) -> JsEvalErrors {
+        let f = self.forth.borrow();
+        let errors: Vec<_> = f.lint(code).iter().map(|d| EvalError::from_diagnostic(&f, code, d)).collect();
+        to_js(&errors)
+    }
+
+    /// The tokens of `code` as read by the interpreter, without running it.
+    /// Offsets are in UTF-16 code units.
+    pub fn tokenize(&self, code: &str) -> JsTokens {
+        let source_tokens = self.forth.borrow().tokenize(code);
+        let tokens: Vec<_> = source_tokens.iter().map(|token| Token {
+            kind: token.kind,
+            text: &token.text,
+            start: utf16_offset(code, token.span.start),
+            end: utf16_offset(code, token.span.end),
+        }).collect();
+        to_js(&tokens)
+    }
+
+    /// `code` as HTML, with each token wrapped in a `<span>` whose class
+    /// says how the interpreter will treat it: `forth-word`, `forth-number`,
+    /// `forth-unknown`, `forth-out-of-range`, `forth-defining`,
+    /// `forth-definition`, `forth-name`, `forth-comment` or `forth-string`.
+    /// Text between tokens is kept.
+    pub fn highlight(&self, code: &str) -> String {
+        let mut html = String::new();
+        let mut end = 0;
+        for token in self.forth.borrow().tokens(code) {
+            html.push_str(&escape_html(&code[end..token.span.start]));
+            html.push_str(&format!("<span class=\"{}\">{}</span>",
+                token_class(token.kind), escape_html(&token.text)));
+            end = token.span.end;
+        }
+        html.push_str(&escape_html(&code[end..]));
+        html
+    }
+
+    /// `code` re-indented, with `indent` spaces per level. `letter_case` may
+    /// be `"upper"` or `"lower"` to change the case of names.
+    pub fn format(&self, code: &str, indent: usize, letter_case: Option<String>) -> String {
+        let case = match letter_case.as_deref() {
+            Some("upper") => Case::Upper,
+            Some("lower") => Case::Lower,
+            _ => Case::Preserve,
+        };
+        self.forth.borrow().format(code, &FormatOptions { indent, case })
+    }
+
+    /// Names of visible words starting with `prefix`, most recently defined
+    /// first.
+    pub fn complete(&self, prefix: &str) -> Vec<String> {
+        self.forth.borrow().complete(prefix)
+    }
+
+    /// The colon definitions made so far, oldest first, or if `names` are
+    /// given only theirs and those of the words they use, so that another
+    /// interpreter's `importWords` can take just those. Throws if a name
+    /// has no colon definition.
+    #[wasm_bindgen(js_name = exportWords)]
+    pub fn export_words(&self, names: Option<Vec<String>>) -> Result<JsWordDefinitions, JsValue> {
+        let forth = self.forth.borrow();
+        match names {
+            Some(names) => {
+                let names: Vec<&str> = names.iter().map(String::as_str).collect();
+                forth.export_words(&names).map(|words| to_js(&words)).map_err(error_to_js)
+            }
+            None => Ok(to_js(&forth.user_words())),
+        }
+    }
+
+    /// Defines the words produced by `exportWords`. Throws if `words` is
+    /// malformed or a definition fails to compile.
+    #[wasm_bindgen(js_name = importWords)]
+    pub fn import_words(&mut self, words: JsWordDefinitions) -> Result<(), JsValue> {
+        let words: Vec<WordDefinition> = from_js(&words)?;
+        self.forth.borrow_mut().define_words(&words)
+            .map_err(error_to_js)
+    }
+}
+
+impl Interpreter {
+    /// Keeps `code` for `resume` if its evaluation paused.
+    fn note_paused(&mut self, code: &str) {
+        if self.forth.borrow().is_paused() {
+            *self.paused_code.borrow_mut() = String::from(code);
+        }
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        let forth_options = ForthOptions::default();
+        Interpreter {
+            forth: Rc::new(RefCell::new(build(&forth_options).unwrap())),
+            options: RenderOptions::new(),
+            forth_options,
+            paused_code: Rc::default(),
+            animation: None,
+            timers: None,
+        }
+    }
+}
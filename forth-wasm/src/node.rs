@@ -0,0 +1,90 @@
+//! Running under Node.js, with the `node` feature: files come from the
+//! local filesystem and output goes to the process's standard output.
+//! Build the package for Node with
+//! `wasm-pack build --target nodejs -- --features node`.
+
+use wasm_bindgen::prelude::*;
+
+use forth_core::Forth;
+
+use super::{build, eval_result, from_js, ForthOptions, Interpreter, JsEvalResult, JsForthOptions, JsNodeOptions};
+
+#[wasm_bindgen(module = "fs")]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = readFileSync)]
+    fn read_file_sync(path: &str, encoding: &str) -> Result<String, JsValue>;
+}
+
+#[wasm_bindgen(module = "path")]
+extern "C" {
+    #[wasm_bindgen(js_name = resolve)]
+    fn resolve_path(base: &str, path: &str) -> String;
+    fn dirname(path: &str) -> String;
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["process", "stdout"], js_name = write)]
+    fn stdout_write(text: &str);
+
+    #[wasm_bindgen(js_namespace = process)]
+    fn cwd() -> String;
+
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(text: &str);
+}
+
+/// Where `Forth.attachNode` reads files from, given from JavaScript as a
+/// `NodeOptions` object.
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct NodeOptions {
+    /// Directory names are resolved against; the working directory if not
+    /// given.
+    base_dir: Option<String>,
+}
+
+/// Has INCLUDE and its kin read files under `base_dir`, output written to
+/// standard output as it is produced, and what LOG and LOG-TYPE log
+/// written to standard error, so that it doesn't mix with the output.
+fn attach(forth: &mut Forth, base_dir: String) {
+    forth.set_source_provider(move |name: &str| read_file_sync(&resolve_path(&base_dir, name), "utf8").ok());
+    forth.set_output_sink(stdout_write);
+    forth.set_log_sink(|message: &str, word: Option<&str>| match word {
+        Some(word) => console_error(&format!("[{}] {}", word, message)),
+        None => console_error(message),
+    });
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Sets the interpreter up for scripting under Node.js: INCLUDE,
+    /// INCLUDED, REQUIRE and REQUIRED read files relative to
+    /// `options.baseDir`, or the working directory, output is written to
+    /// `process.stdout` as it is produced rather than collected into
+    /// results, and LOG and LOG-TYPE write to standard error. Like output
+    /// callbacks, this is undone by `reset`. Throws if `options` is
+    /// malformed. Only with the `node` feature.
+    #[wasm_bindgen(js_name = attachNode)]
+    pub fn attach_node(&mut self, options: Option<JsNodeOptions>) -> Result<(), JsValue> {
+        let options: NodeOptions = from_js(&options.map_or(JsValue::UNDEFINED, JsValue::from))?;
+        attach(&mut self.forth.borrow_mut(), options.base_dir.unwrap_or_else(cwd));
+        Ok(())
+    }
+}
+
+/// Interprets the file at `path` in a fresh interpreter set up as
+/// `Forth.attachNode` does, with files it includes found relative to it,
+/// and returns the outcome, whose output has already gone to standard
+/// output. Throws if `options` is malformed, the prelude fails or the file
+/// can't be read. Only with the `node` feature.
+#[wasm_bindgen(js_name = runFile)]
+pub fn run_file(path: &str, options: Option<JsForthOptions>) -> Result<JsEvalResult, JsValue> {
+    super::utils::set_panic_hook();
+    let options: ForthOptions = from_js(&options.map_or(JsValue::UNDEFINED, JsValue::from))?;
+    let code = read_file_sync(path, "utf8")?;
+    let mut forth = build(&options)?;
+    attach(&mut forth, dirname(&resolve_path(&cwd(), path)));
+    let result = forth.eval(&code);
+    Ok(eval_result(&forth, &code, result, options.max_stack_items))
+}
@@ -0,0 +1,131 @@
+//! Recording an evaluation to step back and forth through, for a
+//! time-travel slider.
+
+use wasm_bindgen::prelude::*;
+
+use forth_core::{Recording, Value};
+
+use super::{
+    eval_result, from_js, serialize_cells, to_js, utf16_offset, Interpreter, JsEvalResult, JsRecordOptions,
+    JsRecordedSteps,
+};
+
+/// Settings for `Forth.record`.
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct RecordOptions {
+    max_steps: usize,
+}
+
+impl Default for RecordOptions {
+    fn default() -> Self {
+        RecordOptions { max_steps: 1000 }
+    }
+}
+
+/// A step as recorded by `Forth.record`, with offsets in UTF-16 code
+/// units.
+#[derive(Serialize)]
+struct RecordedStep<'a> {
+    token: &'a str,
+    start: usize,
+    end: usize,
+    #[serde(serialize_with = "serialize_cells")]
+    popped: &'a [Value],
+    #[serde(serialize_with = "serialize_cells")]
+    pushed: &'a [Value],
+    writes: Vec<MemoryWrite<'a>>,
+}
+
+#[derive(Serialize)]
+struct MemoryWrite<'a> {
+    addr: usize,
+    before: &'a [u8],
+    after: &'a [u8],
+}
+
+/// An evaluation recorded by `Forth.record`, positioned at one of its
+/// steps.
+#[wasm_bindgen(js_name = Recording)]
+pub struct RecordedEval {
+    recording: Recording,
+    code: String,
+    result: JsValue,
+}
+
+#[wasm_bindgen(js_class = Recording)]
+impl RecordedEval {
+    /// The outcome of the evaluation, as `evalResult` gives it.
+    pub fn result(&self) -> JsEvalResult {
+        self.result.clone().unchecked_into()
+    }
+
+    /// How each name or number changed the stack and data space, in
+    /// order.
+    pub fn steps(&self) -> JsRecordedSteps {
+        let code = &self.code;
+        let steps: Vec<_> = self.recording.steps.iter().map(|step| RecordedStep {
+            token: &step.token,
+            start: utf16_offset(code, step.span.start),
+            end: utf16_offset(code, step.span.end),
+            popped: &step.popped,
+            pushed: &step.pushed,
+            writes: step.writes.iter().map(|write| MemoryWrite {
+                addr: write.addr,
+                before: &write.before,
+                after: &write.after,
+            }).collect(),
+        }).collect();
+        to_js(&steps)
+    }
+
+    /// Whether steps went unrecorded for lack of `maxSteps`.
+    #[wasm_bindgen(getter)]
+    pub fn truncated(&self) -> bool {
+        self.recording.truncated
+    }
+
+    /// How many steps were recorded.
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.recording.steps.len()
+    }
+
+    /// How many steps have run at the current position, from 0 before the
+    /// first to `length` after the last.
+    #[wasm_bindgen(getter)]
+    pub fn position(&self) -> usize {
+        self.recording.position()
+    }
+
+    /// Moves to where `position` steps have run, at most `length`.
+    pub fn seek(&mut self, position: usize) {
+        self.recording.seek(position);
+    }
+
+    /// Contents of the data stack at the current position, bottom first.
+    pub fn stack(&self) -> Vec<Value> {
+        self.recording.stack().to_vec()
+    }
+
+    /// A copy of the data space at the current position.
+    pub fn memory(&self) -> Vec<u8> {
+        self.recording.memory().to_vec()
+    }
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Interprets `code` like `evalResult`, also recording how each name
+    /// or number in `code` changes the stack and data space, for up to
+    /// `maxSteps` tokens (1000 by default). The recording starts after its
+    /// last step, and can be moved to any other to show the stack and
+    /// memory there. Throws if `options` isn't a `RecordOptions` object.
+    pub fn record(&mut self, code: &str, options: Option<JsRecordOptions>) -> Result<RecordedEval, JsValue> {
+        let options: RecordOptions = from_js(&options.map_or(JsValue::UNDEFINED, JsValue::from))?;
+        let recording = self.forth.borrow_mut().record(code, options.max_steps);
+        self.note_paused(code);
+        let result = eval_result(&self.forth.borrow(), code, recording.result.clone(), self.forth_options.max_stack_items);
+        Ok(RecordedEval { recording, code: String::from(code), result: result.into() })
+    }
+}
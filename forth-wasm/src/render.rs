@@ -0,0 +1,105 @@
+use forth_core::Value;
+
+/// What `Forth.eval` renders: HTML, in which the program's output and
+/// error messages are escaped and lines end in `<br/>`, or plain text.
+#[derive(Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputMode {
+    Html,
+    Text,
+}
+
+/// How `Forth.eval` turns the stack into text, given from JavaScript as a
+/// `RenderOptions` object whose fields all default as in `new`.
+#[derive(Clone, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RenderOptions {
+    mode: OutputMode,
+    /// Put between stack items; a line break in the output mode if not
+    /// given.
+    separator: Option<String>,
+    /// Whether the top of the stack is rendered first rather than last.
+    top_first: bool,
+    /// Numeric base between 2 and 36; other values fall back to decimal.
+    base: u32,
+    /// Renders at most this many items nearest the top of the stack,
+    /// followed by `...` when some are left out.
+    max_items: Option<usize>,
+}
+
+impl RenderOptions {
+    /// The default rendering: HTML, decimal, top of stack first, one item
+    /// per `<br/>`-separated line, without a limit on the number of items.
+    pub fn new() -> RenderOptions {
+        RenderOptions {
+            mode: OutputMode::Html,
+            separator: None,
+            top_first: true,
+            base: 10,
+            max_items: None,
+        }
+    }
+
+    /// What ends a line in the output mode.
+    pub fn line_break(&self) -> &'static str {
+        match self.mode {
+            OutputMode::Html => "<br/>",
+            OutputMode::Text => "\n",
+        }
+    }
+
+    /// `text`, such as program output, made safe to show in the output
+    /// mode.
+    pub fn text(&self, text: &str) -> String {
+        match self.mode {
+            OutputMode::Html => escape_html(text).replace('\n', "<br/>"),
+            OutputMode::Text => String::from(text),
+        }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn format_number(v: Value, base: u32) -> String {
+    let mut n = u128::from(v.unsigned_abs());
+    let mut digits = Vec::new();
+    loop {
+        let digit = std::char::from_digit((n % u128::from(base)) as u32, base).unwrap();
+        digits.push(digit.to_ascii_uppercase());
+        n /= u128::from(base);
+        if n == 0 {
+            break;
+        }
+    }
+    if v < 0 {
+        digits.push('-');
+    }
+    digits.iter().rev().collect()
+}
+
+/// Renders `stack` (bottom first) according to `options`.
+pub fn render_stack(stack: &[Value], options: &RenderOptions) -> String {
+    let base = if (2..=36).contains(&options.base) { options.base } else { 10 };
+    let shown = options.max_items.unwrap_or(stack.len()).min(stack.len());
+    let mut items = stack[stack.len() - shown..].iter()
+        .map(|&v| format_number(v, base))
+        .collect::<Vec<_>>();
+    let truncated = shown < stack.len();
+    if options.top_first {
+        items.reverse();
+        if truncated {
+            items.push(String::from("..."));
+        }
+    } else if truncated {
+        items.insert(0, String::from("..."));
+    }
+    items.join(options.separator.as_deref().unwrap_or(options.line_break()))
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeMap;
+
+use wasm_bindgen::prelude::*;
+
+use forth_core::Forth;
+
+use super::{eval_result, JsEvalResult};
+
+/// Independent interpreters kept under names chosen by the page, so it can
+/// host several REPLs without sharing stacks or definitions.
+#[wasm_bindgen]
+pub struct SessionManager {
+    sessions: BTreeMap<String, Forth>,
+}
+
+fn no_session(id: &str) -> JsValue {
+    JsValue::from_str(&format!("no session named {}", id))
+}
+
+#[wasm_bindgen]
+impl SessionManager {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SessionManager {
+        super::utils::set_panic_hook();
+        SessionManager { sessions: BTreeMap::new() }
+    }
+
+    /// Starts a fresh interpreter named `id`. Throws if there already is
+    /// one.
+    pub fn create(&mut self, id: &str) -> Result<(), JsValue> {
+        if self.sessions.contains_key(id) {
+            return Err(JsValue::from_str(&format!("session {} already exists", id)));
+        }
+        self.sessions.insert(String::from(id), Forth::new());
+        Ok(())
+    }
+
+    /// Names of the live sessions, in sorted order.
+    pub fn list(&self) -> Vec<String> {
+        self.sessions.keys().cloned().collect()
+    }
+
+    pub fn has(&self, id: &str) -> bool {
+        self.sessions.contains_key(id)
+    }
+
+    /// Interprets `code` in session `id` and returns the outcome. Throws if
+    /// there is no such session.
+    pub fn eval(&mut self, id: &str, code: &str) -> Result<JsEvalResult, JsValue> {
+        let forth = self.sessions.get_mut(id).ok_or_else(|| no_session(id))?;
+        let result = forth.eval(code);
+        Ok(eval_result(forth, code, result, None))
+    }
+
+    /// Discards session `id`'s stack and definitions, keeping the session.
+    /// Throws if there is no such session.
+    pub fn reset(&mut self, id: &str) -> Result<(), JsValue> {
+        let forth = self.sessions.get_mut(id).ok_or_else(|| no_session(id))?;
+        *forth = Forth::new();
+        Ok(())
+    }
+
+    /// Drops session `id`, returning whether it existed.
+    pub fn destroy(&mut self, id: &str) -> bool {
+        self.sessions.remove(id).is_some()
+    }
+}
+
+impl Default for SessionManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
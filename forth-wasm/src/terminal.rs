@@ -0,0 +1,78 @@
+//! The grid of characters output is written to when the `terminal` option
+//! asks for one, for the page to draw screen-oriented programs from.
+
+use wasm_bindgen::prelude::*;
+
+use forth_core::{Forth, GridCell, TerminalGrid};
+
+use super::{to_js, Interpreter, JsTerminalGrid};
+
+/// Columns and rows of the grid, as the `terminal` option gives them.
+#[derive(Clone, Copy, Deserialize)]
+pub(super) struct TerminalSize {
+    columns: usize,
+    rows: usize,
+}
+
+impl TerminalSize {
+    pub(super) fn size(self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+}
+
+/// A cell of the grid, its character as a string.
+#[derive(Serialize)]
+struct Cell {
+    ch: String,
+    fg: u8,
+    bg: u8,
+    bold: bool,
+}
+
+impl From<&GridCell> for Cell {
+    fn from(cell: &GridCell) -> Cell {
+        Cell { ch: cell.ch.to_string(), fg: cell.fg, bg: cell.bg, bold: cell.bold }
+    }
+}
+
+/// The grid as `Forth.terminal` gives it, a row of cells to each line.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Grid {
+    columns: usize,
+    rows: usize,
+    cursor_x: usize,
+    cursor_y: usize,
+    version: u64,
+    cells: Vec<Vec<Cell>>,
+}
+
+impl From<&TerminalGrid> for Grid {
+    fn from(grid: &TerminalGrid) -> Grid {
+        let (cursor_x, cursor_y) = grid.cursor();
+        Grid {
+            columns: grid.columns(),
+            rows: grid.rows(),
+            cursor_x,
+            cursor_y,
+            version: grid.version(),
+            cells: (0..grid.rows()).map(|row| grid.row(row).iter().map(Cell::from).collect()).collect(),
+        }
+    }
+}
+
+/// `forth`'s grid as JavaScript, if it has one.
+pub(super) fn grid_to_js(forth: &Forth) -> Option<JsTerminalGrid> {
+    forth.terminal().map(|grid| to_js(&Grid::from(grid)))
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// The grid of characters output is written to, with the colours and
+    /// weight of each as AT-XY, PAGE, FG, BG, BOLD and NORMAL left them, or
+    /// undefined if the interpreter was created without the `terminal`
+    /// option.
+    pub fn terminal(&self) -> Option<JsTerminalGrid> {
+        grid_to_js(&self.forth.borrow())
+    }
+}
@@ -0,0 +1,124 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use js_sys::Function;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use forth_core::Forth;
+
+use super::{error_to_js, from_js, performance_now, Interpreter, JsTimerOptions};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(callback: &JsValue, delay: i32);
+}
+
+/// How `Forth.startTimers` runs timers, given from JavaScript as a
+/// `TimerOptions` object.
+#[derive(Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct TimerOptions {
+    /// Most instructions a timer's word may run.
+    fuel: u64,
+    /// Longest wait, in milliseconds, between looks for timers due, which
+    /// bounds how late timers set after a wait began run.
+    interval: u32,
+    /// Called with the `EvalError` a timer's word fails with.
+    #[serde(with = "serde_wasm_bindgen::preserve")]
+    on_error: JsValue,
+}
+
+impl Default for TimerOptions {
+    fn default() -> Self {
+        TimerOptions { fuel: 1_000_000, interval: 50, on_error: JsValue::UNDEFINED }
+    }
+}
+
+/// Timers being run for an interpreter, until this is dropped.
+pub(super) struct TimerRunner {
+    running: Rc<Cell<bool>>,
+}
+
+impl Drop for TimerRunner {
+    fn drop(&mut self) {
+        self.running.set(false);
+    }
+}
+
+/// A chain of timeouts, each waiting for the next timer due.
+struct TimerLoop {
+    forth: Rc<RefCell<Forth>>,
+    fuel: u64,
+    interval: u32,
+    on_error: Option<Function>,
+    running: Rc<Cell<bool>>,
+}
+
+impl TimerLoop {
+    /// Waits until the next timer is due, or `interval` passes.
+    fn schedule(self, next_due: Option<u64>) {
+        let now = performance_now() as u64;
+        let delay = next_due.map_or(self.interval, |due| (due.saturating_sub(now) as u32).min(self.interval));
+        // Each callback frees itself once called, so only the last of a
+        // stopped loop leaks.
+        let callback = Closure::once_into_js(move || self.tick());
+        set_timeout(&callback, delay as i32);
+    }
+
+    /// Runs the timers due, unless the interpreter is busy, then waits
+    /// for the next.
+    fn tick(self) {
+        if !self.running.get() {
+            return;
+        }
+        let mut next_due = None;
+        if let Ok(mut forth) = self.forth.try_borrow_mut() {
+            let result = forth.run_timers(Some(self.fuel));
+            next_due = forth.next_timer_due();
+            if let Err(e) = result {
+                let error = error_to_js(e);
+                drop(forth);
+                if let Some(ref on_error) = self.on_error {
+                    let _ = on_error.call1(&JsValue::NULL, &error);
+                }
+            }
+        }
+        self.schedule(next_due);
+    }
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// Runs the words AFTER and EVERY schedule once they are due, with at
+    /// most `options.fuel` instructions each, until `stopTimers`. Timers
+    /// set while none were due run up to `options.interval` milliseconds
+    /// late, 50 by default. Timers wait while an evaluation is running or
+    /// paused, such as between the chunks of `evalAsync`. A timer whose
+    /// word fails is cancelled, and the error passed to `options.onError`.
+    /// Output goes to the output callback, if set. Throws if `options` is
+    /// malformed.
+    #[wasm_bindgen(js_name = startTimers)]
+    pub fn start_timers(&mut self, options: Option<JsTimerOptions>) -> Result<(), JsValue> {
+        let options: TimerOptions = from_js(&options.map_or(JsValue::UNDEFINED, JsValue::from))?;
+        let running = Rc::new(Cell::new(true));
+        let next_due = self.forth.borrow().next_timer_due();
+        TimerLoop {
+            forth: self.forth.clone(),
+            fuel: options.fuel,
+            interval: options.interval.max(1),
+            on_error: options.on_error.dyn_into().ok(),
+            running: running.clone(),
+        }.schedule(next_due);
+        self.timers = Some(TimerRunner { running });
+        Ok(())
+    }
+
+    /// Stops running timers. Those scheduled stay so, for the next
+    /// `startTimers`, until CANCEL cancels them.
+    #[wasm_bindgen(js_name = stopTimers)]
+    pub fn stop_timers(&mut self) {
+        self.timers = None;
+    }
+}
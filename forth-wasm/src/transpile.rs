@@ -0,0 +1,216 @@
+//! Translating colon definitions into JavaScript functions, to lift
+//! arithmetic out of the playground and into other code.
+//!
+//! As with the WebAssembly export, only definitions built of literals and
+//! the arithmetic and stack primitives below can be translated. Each cell
+//! becomes a constant of its own, so stack primitives only move names
+//! about. Arithmetic wraps and division rounds toward zero, as the
+//! interpreter does by default.
+
+use std::mem;
+
+use wasm_bindgen::prelude::*;
+
+use forth_core::{Instruction, Value};
+
+use super::{flatten, Interpreter};
+
+/// What a primitive does to the cells it takes. In the expressions, `$0`
+/// stands for the first cell taken, the deepest, `$1` for the next, and
+/// so on.
+enum Effect {
+    /// Takes this many and leaves the values of the expressions.
+    Compute(usize, &'static [&'static str]),
+    /// Takes two and, unless the second is zero, leaves the values of the
+    /// expressions.
+    Divide(&'static [&'static str]),
+    /// Takes this many and leaves those of them at the indices given.
+    Shuffle(usize, &'static [usize]),
+}
+
+impl Effect {
+    /// The effect of the primitive `name`, if it can be translated.
+    fn of(name: &str) -> Option<Effect> {
+        Some(match name {
+            "+" => Effect::Compute(2, &["$0 + $1 | 0"]),
+            "-" => Effect::Compute(2, &["$0 - $1 | 0"]),
+            "*" => Effect::Compute(2, &["Math.imul($0, $1)"]),
+            "/" => Effect::Divide(&["$0 / $1 | 0"]),
+            "MOD" => Effect::Divide(&["$0 % $1 | 0"]),
+            "/MOD" => Effect::Divide(&["$0 % $1 | 0", "$0 / $1 | 0"]),
+            "1+" | "CHAR+" => Effect::Compute(1, &["$0 + 1 | 0"]),
+            "1-" => Effect::Compute(1, &["$0 - 1 | 0"]),
+            "2*" => Effect::Compute(1, &["$0 << 1"]),
+            "2/" => Effect::Compute(1, &["$0 >> 1"]),
+            "CELL+" => Effect::Compute(1, &["$0 + 4 | 0"]),
+            "CELLS" => Effect::Compute(1, &["$0 << 2"]),
+            "WITHIN" => Effect::Compute(3, &["($0 - $1 >>> 0) < ($2 - $1 >>> 0) ? -1 : 0"]),
+            "=" => Effect::Compute(2, &["$0 === $1 ? -1 : 0"]),
+            "<>" => Effect::Compute(2, &["$0 !== $1 ? -1 : 0"]),
+            "INVERT" => Effect::Compute(1, &["~$0"]),
+            "CHARS" => Effect::Shuffle(1, &[0]),
+            "DUP" => Effect::Shuffle(1, &[0, 0]),
+            "DROP" => Effect::Shuffle(1, &[]),
+            "SWAP" => Effect::Shuffle(2, &[1, 0]),
+            "OVER" => Effect::Shuffle(2, &[0, 1, 0]),
+            _ => return None,
+        })
+    }
+
+    /// How many cells it takes.
+    fn taken(&self) -> usize {
+        match *self {
+            Effect::Compute(taken, _) | Effect::Shuffle(taken, _) => taken,
+            Effect::Divide(_) => 2,
+        }
+    }
+
+    /// How many cells it leaves.
+    fn left(&self) -> usize {
+        match *self {
+            Effect::Compute(_, left) | Effect::Divide(left) => left.len(),
+            Effect::Shuffle(_, left) => left.len(),
+        }
+    }
+}
+
+/// A function being generated: its statements, and the names or literals
+/// standing for the cells on the stack, bottom first.
+struct Codegen {
+    lines: Vec<String>,
+    stack: Vec<String>,
+    constants: usize,
+}
+
+impl Codegen {
+    /// Declares a constant set to `expression`, and pushes it.
+    fn push(&mut self, expression: &str, operands: &[String]) {
+        let mut expression = String::from(expression);
+        for (i, operand) in operands.iter().enumerate().rev() {
+            expression = expression.replace(&format!("${}", i), operand);
+        }
+        let name = format!("v{}", self.constants);
+        self.constants += 1;
+        self.lines.push(format!("    const {} = {};", name, expression));
+        self.stack.push(name);
+    }
+
+    fn apply(&mut self, effect: Effect) {
+        let operands = self.stack.split_off(self.stack.len() - effect.taken());
+        match effect {
+            Effect::Compute(_, expressions) => {
+                for expression in expressions {
+                    self.push(expression, &operands);
+                }
+            }
+            Effect::Divide(expressions) => {
+                self.lines.push(format!("    if ({} === 0) throw new RangeError(\"division by zero\");", operands[1]));
+                for expression in expressions {
+                    self.push(expression, &operands);
+                }
+            }
+            Effect::Shuffle(_, indices) => {
+                self.stack.extend(indices.iter().map(|&i| operands[i].clone()));
+            }
+        }
+    }
+}
+
+/// `name` as a JavaScript identifier: lower case, with `_` for characters
+/// identifiers can't have, and after names JavaScript reserves.
+fn identifier(name: &str) -> String {
+    const RESERVED: &[&str] = &[
+        "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do",
+        "else", "enum", "export", "extends", "false", "finally", "for", "function", "if", "import", "in",
+        "instanceof", "let", "new", "null", "return", "static", "super", "switch", "this", "throw", "true", "try",
+        "typeof", "var", "void", "while", "with", "yield",
+    ];
+    let mut identifier: String = name
+        .chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '$' => c.to_ascii_lowercase(),
+            _ => '_',
+        })
+        .collect();
+    if identifier.starts_with(|c: char| c.is_ascii_digit()) || RESERVED.contains(&&*identifier) {
+        identifier.insert(0, '_');
+    }
+    identifier
+}
+
+/// The source of a JavaScript function named after `name` that does what
+/// `body` does to the stack, or why it can't be made: 64-bit cells, or an
+/// instruction other than a literal or one of the primitives `Effect`
+/// knows.
+fn transpile(body: &[Instruction], name: &str) -> Result<String, String> {
+    // Cells are kept in JavaScript numbers, wrapped to 32 bits.
+    if mem::size_of::<Value>() != 4 {
+        return Err(String::from("only 32-bit cells can be transpiled"));
+    }
+    // The cells the body reads from below where it started.
+    let mut depth = 0isize;
+    let mut lowest = 0isize;
+    for instruction in body {
+        let effect = match *instruction {
+            Instruction::Literal(_) => {
+                depth += 1;
+                continue;
+            }
+            Instruction::Primitive(word) => match Effect::of(word) {
+                Some(effect) => effect,
+                None => return Err(format!("{} can't be transpiled", word)),
+            },
+            Instruction::Call(word) => return Err(format!("{} can't be transpiled", word)),
+        };
+        depth -= effect.taken() as isize;
+        lowest = lowest.min(depth);
+        depth += effect.left() as isize;
+    }
+    let inputs = (-lowest) as usize;
+
+    let params: Vec<String> = (0..inputs).map(|i| format!("a{}", i)).collect();
+    let mut gen = Codegen { lines: Vec::new(), stack: params.clone(), constants: 0 };
+    for instruction in body {
+        match *instruction {
+            Instruction::Literal(v) if v < 0 => gen.stack.push(format!("({})", v)),
+            Instruction::Literal(v) => gen.stack.push(v.to_string()),
+            Instruction::Primitive(word) => gen.apply(Effect::of(word).unwrap()),
+            Instruction::Call(_) => unreachable!(),
+        }
+    }
+    match gen.stack.len() {
+        0 => {}
+        1 => gen.lines.push(format!("    return {};", gen.stack[0])),
+        _ => gen.lines.push(format!("    return [{}];", gen.stack.join(", "))),
+    }
+    let mut source = format!("function {}({}) {{\n", identifier(name), params.join(", "));
+    for line in &gen.lines {
+        source.push_str(line);
+        source.push('\n');
+    }
+    source.push_str("}\n");
+    Ok(source)
+}
+
+#[wasm_bindgen(js_class = Forth)]
+impl Interpreter {
+    /// The source of a JavaScript function doing what the word `name`
+    /// does, to paste into other code. It is named after the word, in
+    /// lower case, takes the cells the word reads as numbers, bottom of the
+    /// stack first, and returns the one it leaves, or an array of them if
+    /// it leaves several. Arithmetic wraps to 32 bits, and division by zero
+    /// throws a `RangeError`. Words it calls are translated in. Throws if
+    /// `name`, or a word it calls, isn't a colon definition, if it uses
+    /// anything but literals, `+ - * / MOD /MOD 1+ 1- 2* 2/ CELL+ CELLS
+    /// CHAR+ CHARS WITHIN`, `DUP DROP SWAP OVER` and words made of those,
+    /// or with 64-bit cells. `: AREA * ;` becomes, for instance,
+    /// `function area(a0, a1) { const v0 = Math.imul(a0, a1); return v0; }`
+    /// on separate lines.
+    #[wasm_bindgen(js_name = toJsFunction)]
+    pub fn to_js_function(&self, name: &str) -> Result<String, JsValue> {
+        let forth = self.forth.borrow();
+        let mut body = Vec::new();
+        flatten(&forth, name, 0, &mut body).map_err(|e| JsValue::from_str(&e))?;
+        transpile(&body, name).map_err(|e| JsValue::from_str(&e))
+    }
+}
@@ -0,0 +1,174 @@
+//! Words that talk to servers over WebSockets, with the `websocket`
+//! feature.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+use js_sys::{ArrayBuffer, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+use forth_core::{Error, Forth, ForthResult, Stack, Value};
+
+use super::js_error;
+
+/// A connection, with the messages received but not yet taken by WS-RECV?
+/// and those sent before it opened.
+struct Socket {
+    ws: WebSocket,
+    inbox: Rc<RefCell<VecDeque<Vec<u8>>>>,
+    outbox: Rc<RefCell<Vec<Vec<u8>>>>,
+    _on_open: Closure<dyn FnMut()>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+}
+
+impl Socket {
+    fn connect(url: &str) -> Result<Socket, Error> {
+        let ws = WebSocket::new(url).map_err(js_error)?;
+        ws.set_binary_type(BinaryType::Arraybuffer);
+        let inbox = Rc::new(RefCell::new(VecDeque::new()));
+        let outbox: Rc<RefCell<Vec<Vec<u8>>>> = Rc::new(RefCell::new(Vec::new()));
+
+        let (socket, queued) = (ws.clone(), outbox.clone());
+        let on_open = Closure::wrap(Box::new(move || {
+            for message in queued.borrow_mut().drain(..) {
+                let _ = send(&socket, &message);
+            }
+        }) as Box<dyn FnMut()>);
+        ws.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+
+        let received = inbox.clone();
+        let on_message = Closure::wrap(Box::new(move |event: MessageEvent| {
+            let data = event.data();
+            let bytes = match data.as_string() {
+                Some(text) => text.into_bytes(),
+                None => match data.dyn_into::<ArrayBuffer>() {
+                    Ok(buffer) => Uint8Array::new(&buffer).to_vec(),
+                    Err(_) => return,
+                },
+            };
+            received.borrow_mut().push_back(bytes);
+        }) as Box<dyn FnMut(MessageEvent)>);
+        ws.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        Ok(Socket { ws, inbox, outbox, _on_open: on_open, _on_message: on_message })
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        // The browser mustn't call the closures once they are freed.
+        self.ws.set_onopen(None);
+        self.ws.set_onmessage(None);
+        let _ = self.ws.close();
+    }
+}
+
+/// Sends `message` as text if it is UTF-8, or else as binary.
+fn send(ws: &WebSocket, message: &[u8]) -> Result<(), JsValue> {
+    match std::str::from_utf8(message) {
+        Ok(text) => ws.send_with_str(text),
+        Err(_) => ws.send_with_u8_array(message),
+    }
+}
+
+/// The connections WS-CONNECT made, by handle less one, with `None` for
+/// those closed.
+type Sockets = Rc<RefCell<Vec<Option<Socket>>>>;
+
+fn pop(stack: &mut Stack) -> Result<Value, Error> {
+    stack.pop().ok_or(Error::StackUnderflow)
+}
+
+/// Takes an address and length off `stack`, length on top, and returns the
+/// part of `memory` they describe.
+fn pop_buffer<'a>(stack: &mut Stack, memory: &'a mut [u8]) -> Result<&'a mut [u8], Error> {
+    let len = usize::try_from(pop(stack)?).map_err(|_| Error::InvalidAddress)?;
+    let addr = usize::try_from(pop(stack)?).map_err(|_| Error::InvalidAddress)?;
+    let end = addr.checked_add(len).ok_or(Error::InvalidAddress)?;
+    memory.get_mut(addr..end).ok_or(Error::InvalidAddress)
+}
+
+/// Defines `name` as a word running `f` on the open connection whose
+/// handle is on top of the stack, below which are the word's other
+/// operands.
+fn define<F>(forth: &mut Forth, sockets: &Sockets, name: &str, f: F)
+    where F: Fn(&Socket, &mut Stack, &mut [u8]) -> ForthResult + 'static
+{
+    let sockets = sockets.clone();
+    forth.define_native_with_memory(name, move |stack, memory| {
+        let handle = pop(stack)?;
+        let sockets = sockets.borrow();
+        let socket = usize::try_from(handle).ok()
+            .and_then(|handle| handle.checked_sub(1))
+            .and_then(|i| sockets.get(i))
+            .and_then(Option::as_ref)
+            .ok_or_else(|| Error::Native(format!("no open websocket {}", handle)))?;
+        f(socket, stack, memory)
+    });
+}
+
+/// Defines the WebSocket words:
+///
+/// - `WS-CONNECT` ( c-addr u -- ws ) opens a connection to the URL, such as
+///   `wss://example.com/chat`, returning its handle.
+/// - `WS-SEND` ( c-addr u ws -- ) sends a message, as text if it is UTF-8.
+///   Messages sent before the connection opens wait until it does.
+/// - `WS-RECV?` ( c-addr u ws -- n true | false ) takes the earliest message
+///   received, copying as much as fits in the buffer and returning the
+///   number of bytes copied, or returns false if none is waiting.
+/// - `WS-CLOSE` ( ws -- ) closes the connection, dropping any messages
+///   waiting.
+///
+/// Messages arrive between evaluations, so a word waiting for one should
+/// run again from a timer or animation frame rather than loop. A handle
+/// that isn't open, or a URL the browser refuses, fails with
+/// `Error::Native`.
+pub fn attach(forth: &mut Forth) {
+    let sockets: Sockets = Rc::new(RefCell::new(Vec::new()));
+
+    let connections = sockets.clone();
+    forth.define_native_with_memory("WS-CONNECT", move |stack, memory| {
+        let url = String::from_utf8_lossy(pop_buffer(stack, memory)?).into_owned();
+        let socket = Socket::connect(&url)?;
+        let mut connections = connections.borrow_mut();
+        connections.push(Some(socket));
+        stack.push(connections.len() as Value);
+        Ok(())
+    });
+    define(forth, &sockets, "WS-SEND", |socket, stack, memory| {
+        let message = pop_buffer(stack, memory)?;
+        match socket.ws.ready_state() {
+            WebSocket::CONNECTING => socket.outbox.borrow_mut().push(message.to_vec()),
+            _ => send(&socket.ws, message).map_err(js_error)?,
+        }
+        Ok(())
+    });
+    define(forth, &sockets, "WS-RECV?", |socket, stack, memory| {
+        let buffer = pop_buffer(stack, memory)?;
+        match socket.inbox.borrow_mut().pop_front() {
+            Some(message) => {
+                let len = message.len().min(buffer.len());
+                buffer[..len].copy_from_slice(&message[..len]);
+                stack.push(len as Value);
+                stack.push(-1);
+            }
+            None => stack.push(0),
+        }
+        Ok(())
+    });
+    let connections = sockets.clone();
+    forth.define_native("WS-CLOSE", move |stack| {
+        let handle = pop(stack)?;
+        let socket = usize::try_from(handle).ok()
+            .and_then(|handle| handle.checked_sub(1))
+            .and_then(|i| connections.borrow_mut().get_mut(i).and_then(Option::take));
+        match socket {
+            Some(_) => Ok(()),
+            None => Err(Error::Native(format!("no open websocket {}", handle))),
+        }
+    });
+}
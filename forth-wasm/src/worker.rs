@@ -0,0 +1,343 @@
+//! Running an interpreter in a Web Worker, so that long programs don't
+//! hold up the page.
+//!
+//! The worker's script calls `serveWorker`, and the page wraps the worker
+//! in a `ForthWorker`, whose methods mirror those of `Forth` but return
+//! promises. They talk in messages: the page posts `{ id, method, args }`
+//! for each call, and the worker answers with `{ id, output }` for each
+//! piece of program output as it is produced, then `{ id, result }`, or
+//! `{ id, error }` with a message if the call threw.
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use super::{events, field, CellArray, Interpreter, JsForthOptions};
+
+#[wasm_bindgen]
+extern "C" {
+    /// A Web Worker, or anything else that posts and receives messages
+    /// the same way.
+    #[wasm_bindgen(typescript_type = "Worker")]
+    pub type JsWorker;
+    #[wasm_bindgen(method, js_name = postMessage)]
+    fn post_message(this: &JsWorker, message: &JsValue);
+    #[wasm_bindgen(method, setter)]
+    fn set_onmessage(this: &JsWorker, handler: &JsValue);
+    #[wasm_bindgen(method)]
+    fn terminate(this: &JsWorker);
+
+    /// Posts `message` from the worker to the page.
+    #[wasm_bindgen(js_name = postMessage)]
+    fn post_to_page(message: &JsValue);
+
+    #[wasm_bindgen(typescript_type = "Promise<TraceResult>")]
+    pub type JsTraceResultPromise;
+    #[wasm_bindgen(typescript_type = "Promise<Cell[]>")]
+    pub type JsCellsPromise;
+    #[wasm_bindgen(typescript_type = "Promise<WordInfo[]>")]
+    pub type JsWordInfosPromise;
+    #[wasm_bindgen(typescript_type = "Promise<EvalError[]>")]
+    pub type JsEvalErrorsPromise;
+    #[wasm_bindgen(typescript_type = "Promise<string[]>")]
+    pub type JsStringsPromise;
+    #[wasm_bindgen(typescript_type = "Promise<string>")]
+    pub type JsStringPromise;
+    #[wasm_bindgen(typescript_type = "Promise<number>")]
+    pub type JsNumberPromise;
+    #[wasm_bindgen(typescript_type = "Promise<boolean>")]
+    pub type JsBooleanPromise;
+    #[wasm_bindgen(typescript_type = "Promise<void>")]
+    pub type JsVoidPromise;
+}
+
+/// A message `{ id, <name>: value }`.
+fn message(id: f64, name: &str, value: &JsValue) -> JsValue {
+    let message = Object::new();
+    Reflect::set(&message, &JsValue::from_str("id"), &JsValue::from_f64(id)).unwrap();
+    Reflect::set(&message, &JsValue::from_str(name), value).unwrap();
+    message.into()
+}
+
+/// Calls `method` of `forth` with `args` as the page asked.
+fn dispatch(forth: &mut Interpreter, method: &str, args: &Array) -> Result<JsValue, JsValue> {
+    let string = |i: u32| args.get(i).as_string().unwrap_or_default();
+    let result: JsValue = match method {
+        "evalResult" => forth.eval_result(&string(0)).into(),
+        "evalTransactional" => forth.eval_transactional(&string(0)).into(),
+        "trace" => {
+            let options = Some(args.get(1)).filter(|options| !options.is_undefined());
+            forth.trace(&string(0), options.map(JsCast::unchecked_into))?.into()
+        }
+        "resume" => forth.resume().into(),
+        "setFuel" => {
+            forth.set_fuel(args.get(0).as_f64().map(|fuel| fuel as u32));
+            JsValue::UNDEFINED
+        }
+        "stack" => CellArray::from(&forth.stack()[..]).into(),
+        "reset" => {
+            forth.reset();
+            JsValue::UNDEFINED
+        }
+        "words" => forth.words().into(),
+        "check" => forth.check(&string(0)).into(),
+        "complete" => forth.complete(&string(0)).into_iter().map(JsValue::from).collect::<Array>().into(),
+        "snapshot" => forth.snapshot().into(),
+        "restore" => {
+            forth.restore(&string(0))?;
+            JsValue::UNDEFINED
+        }
+        "pushKey" => match events::key_named(&string(0)) {
+            Some(key) => {
+                forth.forth.borrow_mut().push_key(key);
+                JsValue::TRUE
+            }
+            None => JsValue::FALSE,
+        },
+        "setPointer" => {
+            let number = |i: u32| args.get(i).as_f64().unwrap_or(0.0);
+            forth.set_pointer(number(0), number(1), number(2) as u32);
+            JsValue::UNDEFINED
+        }
+        "dataSpaceSize" => forth.data_space_size().into(),
+        "shareDataSpace" => {
+            forth.share_data_space(args.get(0).dyn_into()?)?;
+            JsValue::UNDEFINED
+        }
+        "unshareDataSpace" => {
+            forth.unshare_data_space();
+            JsValue::UNDEFINED
+        }
+        _ => return Err(JsValue::from_str(&format!("unknown method {}", method))),
+    };
+    Ok(result)
+}
+
+/// Serves the `ForthWorker` of the page that started this worker, with an
+/// interpreter created from `options` as `new Forth(options)` would.
+/// Call it from the worker's script once the module has loaded. Throws
+/// as the `Forth` constructor does.
+#[wasm_bindgen(js_name = serveWorker)]
+pub fn serve_worker(options: Option<JsForthOptions>) -> Result<(), JsValue> {
+    let mut forth = Interpreter::new(options)?;
+    // The request being answered, which output belongs to.
+    let current = Rc::new(Cell::new(0.0));
+    let id = current.clone();
+    forth.forth.borrow_mut().set_output_sink(move |text| {
+        post_to_page(&message(id.get(), "output", &JsValue::from_str(text)));
+    });
+    let handler = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+        let request = field(&event, "data");
+        let id = field(&request, "id").as_f64().unwrap_or(0.0);
+        let method = field(&request, "method").as_string().unwrap_or_default();
+        let args: Array = field(&request, "args").dyn_into().unwrap_or_default();
+        current.set(id);
+        let reply = match dispatch(&mut forth, &method, &args) {
+            Ok(result) => message(id, "result", &result),
+            Err(e) => {
+                let text = e.dyn_ref::<js_sys::Error>().map_or_else(|| e.as_string(), |e| e.message().as_string());
+                message(id, "error", &JsValue::from_str(&text.unwrap_or_default()))
+            }
+        };
+        post_to_page(&reply);
+    });
+    Reflect::set(&js_sys::global(), &JsValue::from_str("onmessage"), handler.as_ref())?;
+    // The worker serves for as long as it lives.
+    handler.forget();
+    Ok(())
+}
+
+/// A call waiting for the worker's answer.
+struct Pending {
+    resolve: Function,
+    reject: Function,
+    on_output: Option<Function>,
+    /// Output so far, which the worker sends ahead of the result.
+    output: String,
+}
+
+/// An interpreter running in a Web Worker that serves it with
+/// `serveWorker`, exported to JavaScript as `ForthWorker`. Each method
+/// answers as the `Forth` method of the same name would, but with a
+/// promise. Calls are answered in the order they are made.
+#[wasm_bindgen(js_name = ForthWorker)]
+pub struct InterpreterWorker {
+    worker: JsWorker,
+    pending: Rc<RefCell<BTreeMap<u32, Pending>>>,
+    next_id: u32,
+    /// Called with each piece of output as the worker produces it.
+    on_output: Option<Function>,
+    _on_message: Closure<dyn FnMut(JsValue)>,
+}
+
+#[wasm_bindgen(js_class = ForthWorker)]
+impl InterpreterWorker {
+    /// Talks to the interpreter `worker` serves, calling `onOutput`, if
+    /// given, with program output as it is produced, as well as including
+    /// it in results. Replaces the worker's `onmessage` handler.
+    #[wasm_bindgen(constructor)]
+    pub fn new(worker: JsWorker, on_output: Option<Function>) -> InterpreterWorker {
+        super::utils::set_panic_hook();
+        let pending: Rc<RefCell<BTreeMap<u32, Pending>>> = Rc::default();
+        let answers = pending.clone();
+        let on_message = Closure::<dyn FnMut(JsValue)>::new(move |event: JsValue| {
+            let data = field(&event, "data");
+            let id = field(&data, "id").as_f64().unwrap_or(-1.0) as u32;
+            let mut answers = answers.borrow_mut();
+            let output = field(&data, "output");
+            if let Some(text) = output.as_string() {
+                if let Some(call) = answers.get_mut(&id) {
+                    if let Some(ref on_output) = call.on_output {
+                        let _ = on_output.call1(&JsValue::NULL, &output);
+                    }
+                    call.output.push_str(&text);
+                }
+                return;
+            }
+            let call = match answers.remove(&id) {
+                Some(call) => call,
+                None => return,
+            };
+            drop(answers);
+            let error = field(&data, "error");
+            if let Some(text) = error.as_string() {
+                let _ = call.reject.call1(&JsValue::NULL, &js_sys::Error::new(&text));
+                return;
+            }
+            let result = field(&data, "result");
+            // Output went to the worker's sink rather than the result.
+            if result.is_object() && field(&result, "output").as_string().is_some() {
+                let _ = Reflect::set(&result, &JsValue::from_str("output"), &JsValue::from_str(&call.output));
+            }
+            let _ = call.resolve.call1(&JsValue::NULL, &result);
+        });
+        worker.set_onmessage(on_message.as_ref());
+        InterpreterWorker { worker, pending, next_id: 0, on_output, _on_message: on_message }
+    }
+
+    /// Posts a call of `method` with `args` to the worker, returning the
+    /// promise of its answer.
+    fn call(&mut self, method: &str, args: &[JsValue]) -> Promise {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        let request = message(f64::from(id), "method", &JsValue::from_str(method));
+        let args: Array = args.iter().collect();
+        Reflect::set(&request, &JsValue::from_str("args"), &args).unwrap();
+        let pending = self.pending.clone();
+        let on_output = self.on_output.clone();
+        let promise = Promise::new(&mut |resolve, reject| {
+            pending.borrow_mut().insert(id, Pending { resolve, reject, on_output: on_output.clone(), output: String::new() });
+        });
+        self.worker.post_message(&request);
+        promise
+    }
+
+    #[wasm_bindgen(js_name = evalResult)]
+    pub fn eval_result(&mut self, code: &str) -> super::JsEvalResultPromise {
+        self.call("evalResult", &[JsValue::from_str(code)]).unchecked_into()
+    }
+
+    #[wasm_bindgen(js_name = evalTransactional)]
+    pub fn eval_transactional(&mut self, code: &str) -> super::JsEvalResultPromise {
+        self.call("evalTransactional", &[JsValue::from_str(code)]).unchecked_into()
+    }
+
+    pub fn trace(&mut self, code: &str, options: Option<super::JsTraceOptions>) -> JsTraceResultPromise {
+        let options = options.map_or(JsValue::UNDEFINED, JsValue::from);
+        self.call("trace", &[JsValue::from_str(code), options]).unchecked_into()
+    }
+
+    pub fn resume(&mut self) -> super::JsEvalResultPromise {
+        self.call("resume", &[]).unchecked_into()
+    }
+
+    #[wasm_bindgen(js_name = setFuel)]
+    pub fn set_fuel(&mut self, fuel: Option<u32>) -> JsVoidPromise {
+        let fuel = fuel.map_or(JsValue::UNDEFINED, JsValue::from);
+        self.call("setFuel", &[fuel]).unchecked_into()
+    }
+
+    pub fn stack(&mut self) -> JsCellsPromise {
+        self.call("stack", &[]).unchecked_into()
+    }
+
+    pub fn reset(&mut self) -> JsVoidPromise {
+        self.call("reset", &[]).unchecked_into()
+    }
+
+    pub fn words(&mut self) -> JsWordInfosPromise {
+        self.call("words", &[]).unchecked_into()
+    }
+
+    pub fn check(&mut self, code: &str) -> JsEvalErrorsPromise {
+        self.call("check", &[JsValue::from_str(code)]).unchecked_into()
+    }
+
+    pub fn complete(&mut self, prefix: &str) -> JsStringsPromise {
+        self.call("complete", &[JsValue::from_str(prefix)]).unchecked_into()
+    }
+
+    pub fn snapshot(&mut self) -> JsStringPromise {
+        self.call("snapshot", &[]).unchecked_into()
+    }
+
+    pub fn restore(&mut self, json: &str) -> JsVoidPromise {
+        self.call("restore", &[JsValue::from_str(json)]).unchecked_into()
+    }
+
+    #[wasm_bindgen(js_name = dataSpaceSize)]
+    pub fn data_space_size(&mut self) -> JsNumberPromise {
+        self.call("dataSpaceSize", &[]).unchecked_into()
+    }
+
+    /// Has the worker copy its interpreter's data space to `buffer`, as
+    /// `Forth.shareDataSpace` does, where the page can read it as Forth
+    /// code changes it.
+    #[wasm_bindgen(js_name = shareDataSpace)]
+    pub fn share_data_space(&mut self, buffer: js_sys::SharedArrayBuffer) -> JsVoidPromise {
+        self.call("shareDataSpace", &[buffer.into()]).unchecked_into()
+    }
+
+    #[wasm_bindgen(js_name = unshareDataSpace)]
+    pub fn unshare_data_space(&mut self) -> JsVoidPromise {
+        self.call("unshareDataSpace", &[]).unchecked_into()
+    }
+
+    /// Passes the key pressed by a `keydown` event to the worker, as
+    /// `Forth.keyEvent` does. Resolves to whether it was queued; the page
+    /// has to decide on `preventDefault` before then, knowing
+    /// `Forth.keyEvent` leaves shortcuts and keys it has no use for alone.
+    #[wasm_bindgen(js_name = keyEvent)]
+    pub fn key_event(&mut self, event: &JsValue) -> JsBooleanPromise {
+        match events::event_key(event) {
+            Some(_) => self.call("pushKey", &[field(event, "key")]).unchecked_into(),
+            None => Promise::resolve(&JsValue::FALSE).unchecked_into(),
+        }
+    }
+
+    /// Passes the pointer of a mouse or pointer event to the worker, as
+    /// `Forth.pointerEvent` does.
+    #[wasm_bindgen(js_name = pointerEvent)]
+    pub fn pointer_event(&mut self, event: &JsValue) -> JsVoidPromise {
+        let (x, y, buttons) = events::event_pointer(event);
+        self.set_pointer(x, y, buttons)
+    }
+
+    #[wasm_bindgen(js_name = setPointer)]
+    pub fn set_pointer(&mut self, x: f64, y: f64, buttons: u32) -> JsVoidPromise {
+        let args = [JsValue::from_f64(x), JsValue::from_f64(y), JsValue::from(buttons)];
+        self.call("setPointer", &args).unchecked_into()
+    }
+
+    /// Stops the worker, rejecting the calls it hasn't answered.
+    pub fn terminate(&mut self) {
+        self.worker.terminate();
+        for (_, call) in std::mem::take(&mut *self.pending.borrow_mut()) {
+            let _ = call.reject.call1(&JsValue::NULL, &js_sys::Error::new("worker terminated"));
+        }
+    }
+}
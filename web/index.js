@@ -1,12 +1,104 @@
-import * as wasm from "forth";
+import * as wasm from "forth-wasm";
 
 //wasm.greet();
 
 //console.log(wasm.interpret("DUP"));
 
+const repl = new wasm.Forth({ echoInput: true, okPrompt: true, terminal: { columns: 40, rows: 12 } });
+const SESSION_KEY = "forth-session";
+
+try {
+    const saved = localStorage.getItem(SESSION_KEY);
+    if (saved) {
+        repl.restore(saved);
+    }
+} catch (e) {
+    localStorage.removeItem(SESSION_KEY);
+}
+
+// The 16 colours of the ANSI palette, which FG and BG choose from.
+const PALETTE = [
+    "#000000", "#cd3131", "#0dbc79", "#e5e510", "#2472c8", "#bc3fbc", "#11a8cd", "#e5e5e5",
+    "#666666", "#f14c4c", "#23d18b", "#f5f543", "#3b8eea", "#d670d6", "#29b8db", "#ffffff",
+];
+
+function showTerminal(grid) {
+    var view = $("#terminal").empty();
+    grid.cells.forEach(function(row, y) {
+        row.forEach(function(cell, x) {
+            var cursor = x === grid.cursorX && y === grid.cursorY;
+            view.append($("<span>").text(cell.ch).css({
+                color: PALETTE[cursor ? cell.bg : cell.fg],
+                background: PALETTE[cursor ? cell.fg : cell.bg],
+                "font-weight": cell.bold ? "bold" : "normal",
+            }));
+        });
+        view.append("\n");
+    });
+}
+
+function showWords() {
+    var list = $("#words").empty();
+    repl.words().reverse().forEach(function(word) {
+        var item = $("<li>").text(word.name);
+        if (word.doc) {
+            item.attr("title", word.doc);
+        }
+        if (word.user_defined) {
+            item.css("font-weight", "bold");
+        }
+        if (word.immediate) {
+            item.append(" (immediate)");
+        }
+        list.append(item);
+    });
+}
+
 $(document).ready(function(){
+    showWords();
+    showTerminal(repl.terminal());
+    repl.startFrames({ onTerminal: showTerminal });
+
     $('#code').bind('input propertychange', function() {
         var code = $("#code").val();
-        $("#result").html(wasm.interpret(code));
+        $("#highlight").html(repl.highlight(code));
+        var result = wasm.interpret(code);
+        var view = $("#result").empty();
+        if (result.output) {
+            view.append($("<pre>").text(result.output));
+        }
+        if (result.error) {
+            var error = result.error;
+            var where = error.word ? " (at \"" + error.word + "\", offset " + error.start + ")" : "";
+            view.append($("<div>").css("color", "red").text("Error: " + error.message + where));
+        } else {
+            result.stack.slice().reverse().forEach(function(v) {
+                view.append($("<div>").text(v));
+            });
+        }
+        var stats = result.stats;
+        view.append($("<div>").css("color", "gray").text(
+            stats.operations + " operations, stack depth " + stats.maxStackDepth
+            + ", " + stats.wordsDefined + " words, " + stats.dataSpaceUsed + " bytes"));
+    });
+
+    $('#repl').keydown(function(e) {
+        if (e.key !== "Enter") {
+            return;
+        }
+        var line = $("#repl").val();
+        $("#repl").val("");
+        $("#transcript").append($("<pre>").text(repl.evalResult(line).output));
+        showTerminal(repl.terminal());
+        localStorage.setItem(SESSION_KEY, repl.snapshot());
+        showWords();
+    });
+
+    $('#reset').click(function() {
+        repl.reset();
+        localStorage.removeItem(SESSION_KEY);
+        $("#transcript").empty();
+        showTerminal(repl.terminal());
+        showWords();
     });
 });
@@ -2,10 +2,15 @@ const CopyWebpackPlugin = require("copy-webpack-plugin");
 const path = require('path');
 
 module.exports = {
-  entry: "./bootstrap.js",
+  entry: {
+    bootstrap: "./bootstrap.js",
+    worker: "./worker.js",
+  },
   output: {
     path: path.resolve(__dirname, "dist"),
-    filename: "bootstrap.js",
+    filename: "[name].js",
+    // Chunks are loaded from the worker too, which has no `window`.
+    globalObject: "self",
   },
   mode: "development",
   plugins: [
@@ -0,0 +1,11 @@
+// Entry point of a Web Worker running Forth off the main thread. The page
+// talks to it through `new ForthWorker(new Worker("./worker.js"))`.
+// Requests that arrive while the wasm module loads are kept until it can
+// answer them.
+const early = [];
+self.onmessage = event => early.push(event);
+
+import("forth-wasm").then(wasm => {
+    wasm.serveWorker();
+    early.forEach(event => self.onmessage(event));
+});